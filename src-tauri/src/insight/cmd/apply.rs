@@ -7,6 +7,7 @@ use regex::Regex;
 use smallvec::SmallVec;
 
 use crate::io::csv::{config::CsvConfigBuilder, options::CsvOptions};
+use crate::io::writer::Finish;
 
 #[macro_export]
 macro_rules! regex_oncelock {
@@ -341,8 +342,8 @@ async fn apply_perform<P: AsRef<Path> + Send + Sync>(
 
   let mut opts = CsvOptions::new(&path);
   opts.set_skiprows(skiprows);
-  let (sep, reader) = opts.skiprows_and_delimiter()?;
-  let sep_char = sep as char;
+  let (dialect, reader) = opts.skiprows_and_dialect()?;
+  let sep_char = dialect.delimiter as char;
   let output_path = opts.output_path(Some("apply"), None)?;
 
   let force_new_column = mode == "dynfmt" || mode == "calcconv";
@@ -370,7 +371,7 @@ async fn apply_perform<P: AsRef<Path> + Send + Sync>(
 
   let config = CsvConfigBuilder::new()
     .flexible(flexible)
-    .delimiter(sep)
+    .dialect(&dialect)
     .quoting(quoting)
     .build();
 
@@ -551,7 +552,7 @@ async fn apply_perform<P: AsRef<Path> + Send + Sync>(
     wtr.write_record(&record)?;
   }
 
-  Ok(wtr.flush()?)
+  wtr.finish()
 }
 
 #[tauri::command]
@@ -1,13 +1,19 @@
-use std::{collections::{HashMap, HashSet}, fs::File, io::BufWriter, path::Path, time::Instant};
+use std::{
+  collections::{HashMap, HashSet},
+  path::Path,
+  time::Instant,
+};
 
 use anyhow::{Result, anyhow};
-use csv::{ByteRecord, ReaderBuilder, WriterBuilder};
+use csv::ByteRecord;
 use indexmap::IndexSet;
 
 use crate::{
   cmd::convert::excel_to_csv::{self, get_sheetname_by_filename},
-  io::csv::options::CsvOptions,
-  utils::WTR_BUFFER_SIZE,
+  io::{
+    csv::{config::CsvConfigBuilder, options::CsvOptions},
+    writer::{Finish, OutputOptions},
+  },
 };
 
 pub async fn cat_with_csv(
@@ -15,6 +21,7 @@ pub async fn cat_with_csv(
   output_path: String,
   quoting: bool,
   skiprows: usize,
+  output: OutputOptions,
 ) -> Result<()> {
   let mut all_columns: IndexSet<Box<[u8]>> = IndexSet::with_capacity(16);
   let mut first_sep = None;
@@ -23,16 +30,17 @@ pub async fn cat_with_csv(
   for (i, p) in paths.iter().enumerate() {
     let mut opts = CsvOptions::new(p);
     opts.set_skiprows(skiprows);
-    let (sep, reader) = opts.skiprows_and_delimiter()?;
+    let (dialect, reader) = opts.skiprows_and_dialect()?;
 
     if i == 0 {
-      first_sep = Some(sep);
+      first_sep = Some(dialect.delimiter);
     }
 
-    let mut rdr = ReaderBuilder::new()
-      .delimiter(sep)
+    let mut rdr = CsvConfigBuilder::new()
+      .dialect(&dialect)
       .quoting(quoting)
-      .from_reader(reader);
+      .build()
+      .build_reader(reader);
 
     for field in rdr.byte_headers()? {
       let fi = field.to_vec().into_boxed_slice();
@@ -40,10 +48,11 @@ pub async fn cat_with_csv(
     }
   }
 
-  let buf_wtr = BufWriter::with_capacity(WTR_BUFFER_SIZE, File::create(output_path)?);
-  let mut wtr = WriterBuilder::new()
-    .delimiter(first_sep.unwrap_or(b'|'))
-    .from_writer(buf_wtr);
+  let mut wtr = CsvConfigBuilder::new()
+    .write_delimiter(first_sep.unwrap_or(b'|'))
+    .output(output)
+    .build()
+    .build_writer(output_path)?;
 
   for c in &all_columns {
     wtr.write_field(c)?;
@@ -53,11 +62,12 @@ pub async fn cat_with_csv(
   for p in paths.iter() {
     let mut opts = CsvOptions::new(p);
     opts.set_skiprows(skiprows);
-    let (sep, reader) = opts.skiprows_and_delimiter()?;
-    let mut rdr = ReaderBuilder::new()
-      .delimiter(sep)
+    let (dialect, reader) = opts.skiprows_and_dialect()?;
+    let mut rdr = CsvConfigBuilder::new()
+      .dialect(&dialect)
       .quoting(quoting)
-      .from_reader(reader);
+      .build()
+      .build_reader(reader);
 
     let h = rdr.byte_headers()?;
 
@@ -93,7 +103,7 @@ pub async fn cat_with_csv(
     }
   }
 
-  Ok(wtr.flush()?)
+  wtr.finish()
 }
 
 fn sanitize_name(s: &str) -> String {
@@ -113,6 +123,7 @@ pub async fn cat_with_excel(
   quoting: bool,
   sheet_mapping: Vec<HashMap<String, String>>,
   all_sheets: bool,
+  output: OutputOptions,
 ) -> Result<()> {
   let temp_dir = tempfile::TempDir::new()?;
   let mut csv_paths = Vec::new();
@@ -186,7 +197,7 @@ pub async fn cat_with_excel(
     .join("|");
 
   // excel_to_csv已处理skiprows
-  cat_with_csv(csv_path_str, output_path, quoting, 0).await?;
+  cat_with_csv(csv_path_str, output_path, quoting, 0, output).await?;
 
   Ok(())
 }
@@ -197,9 +208,11 @@ pub async fn cat_csv(
   output_path: String,
   quoting: bool,
   skiprows: usize,
+  output_compression: Option<String>,
 ) -> Result<String, String> {
   let start_time = Instant::now();
-  match cat_with_csv(path, output_path, quoting, skiprows).await {
+  let output = OutputOptions::from_arg(output_compression.as_deref()).map_err(|e| e.to_string())?;
+  match cat_with_csv(path, output_path, quoting, skiprows, output).await {
     Ok(()) => {
       let elapsed = Instant::now().duration_since(start_time).as_secs_f64();
       Ok(format!("{elapsed:.0}"))
@@ -216,8 +229,10 @@ pub async fn cat_excel(
   skiprows: usize,
   sheet_mapping: Vec<HashMap<String, String>>,
   all_sheets: bool,
+  output_compression: Option<String>,
 ) -> Result<String, String> {
   let start_time = Instant::now();
+  let output = OutputOptions::from_arg(output_compression.as_deref()).map_err(|e| e.to_string())?;
   match cat_with_excel(
     path,
     output_path,
@@ -225,6 +240,7 @@ pub async fn cat_excel(
     quoting,
     sheet_mapping,
     all_sheets,
+    output,
   )
   .await
   {
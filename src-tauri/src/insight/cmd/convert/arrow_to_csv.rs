@@ -0,0 +1,43 @@
+use std::{fs::File, io::BufReader};
+
+use anyhow::Result;
+use arrow::{csv::WriterBuilder, ipc::reader::FileReader, record_batch::RecordBatch};
+
+use crate::{
+  io::{csv::options::CsvOptions, writer},
+  utils::RDR_BUFFER_SIZE,
+};
+
+/// Convert an Arrow IPC file (Feather v2) to CSV batch by batch, writes `<stem>_arrow.csv`
+pub async fn arrow_to_csv(path: &str, wtr_sep: String) -> Result<usize> {
+  let sep = if wtr_sep == "\\t" {
+    b'\t'
+  } else {
+    wtr_sep.as_bytes().first().copied().unwrap_or(b',')
+  };
+  let opts = CsvOptions::new(path);
+  let output_path = opts.output_path(Some("arrow"), None)?;
+
+  let file = BufReader::with_capacity(RDR_BUFFER_SIZE, File::open(path)?);
+  let batches = FileReader::try_new(file, None)?;
+  let schema = batches.schema();
+
+  let mut wtr = WriterBuilder::new()
+    .with_delimiter(sep)
+    .build(writer::create(&output_path)?);
+  let mut rows = 0;
+  let mut empty = true;
+  for batch in batches {
+    let batch = batch?;
+    rows += batch.num_rows();
+    empty = false;
+    wtr.write(&batch)?;
+  }
+  // 没有 batch 时仍写出表头
+  if empty {
+    wtr.write(&RecordBatch::new_empty(schema))?;
+  }
+  wtr.into_inner().finish()?;
+
+  Ok(rows)
+}
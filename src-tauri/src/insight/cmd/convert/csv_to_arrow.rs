@@ -0,0 +1,103 @@
+use std::{path::Path, sync::Arc};
+
+use anyhow::Result;
+use arrow::{
+  csv::{ReaderBuilder, reader::Format},
+  datatypes::{DataType, Field, Schema},
+  ipc::writer::FileWriter,
+};
+use serde::Serialize;
+
+use crate::io::{csv::options::CsvOptions, writer};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ArrowColumn {
+  pub name: String,
+  pub data_type: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ArrowReport {
+  pub rows: usize,
+  pub batches: usize,
+  pub columns: Vec<ArrowColumn>,
+}
+
+/// Convert CSV to an Arrow IPC file (Feather v2), writes `<stem>_arrow.arrow`
+///
+/// - `batch_size`: rows per record batch
+/// - `infer`: infer Int64, Float64, Boolean, Date and Timestamp columns,
+///   otherwise every column is Utf8 (keeps leading zeros of codes and ids)
+/// - `infer_rows`: rows read to infer the schema, 0 reads every row
+pub async fn csv_to_arrow<P>(
+  path: P,
+  skiprows: usize,
+  batch_size: usize,
+  infer: bool,
+  infer_rows: usize,
+) -> Result<ArrowReport>
+where
+  P: AsRef<Path> + Send + Sync,
+{
+  let mut opts = CsvOptions::new(&path);
+  opts.set_skiprows(skiprows);
+  let output_path = opts.output_path(Some("arrow"), Some("arrow"))?;
+
+  let (dialect, reader) = opts.skiprows_and_dialect()?;
+  let mut format = Format::default()
+    .with_header(true)
+    .with_delimiter(dialect.delimiter)
+    .with_quote(dialect.quote);
+  if let Some(escape) = dialect.escape {
+    format = format.with_escape(escape);
+  }
+  if let Some(comment) = dialect.comment {
+    format = format.with_comment(comment);
+  }
+
+  let schema = if infer {
+    let max_records = (infer_rows > 0).then_some(infer_rows);
+    format.infer_schema(reader, max_records)?.0
+  } else {
+    let (schema, _) = format.infer_schema(reader, Some(0))?;
+    Schema::new(
+      schema
+        .fields()
+        .iter()
+        .map(|f| Field::new(f.name(), DataType::Utf8, true))
+        .collect::<Vec<_>>(),
+    )
+  };
+  let schema = Arc::new(schema);
+
+  let (_, reader) = opts.skiprows_and_dialect()?;
+  let batches = ReaderBuilder::new(schema.clone())
+    .with_format(format)
+    .with_batch_size(batch_size.max(1))
+    .build(reader)?;
+
+  let mut wtr = FileWriter::try_new(writer::create(&output_path)?, &schema)?;
+  let mut rows = 0;
+  let mut count = 0;
+  for batch in batches {
+    let batch = batch?;
+    rows += batch.num_rows();
+    count += 1;
+    wtr.write(&batch)?;
+  }
+  wtr.finish()?;
+  wtr.into_inner()?.finish()?;
+
+  Ok(ArrowReport {
+    rows,
+    batches: count,
+    columns: schema
+      .fields()
+      .iter()
+      .map(|f| ArrowColumn {
+        name: f.name().to_string(),
+        data_type: f.data_type().to_string(),
+      })
+      .collect(),
+  })
+}
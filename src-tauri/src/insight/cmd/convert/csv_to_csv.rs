@@ -1,7 +1,5 @@
 use std::{
-  collections::HashMap,
-  fs::File,
-  io::{BufRead, BufReader, BufWriter},
+  io::{BufReader, Cursor, Read},
   path::Path,
   sync::{
     Arc,
@@ -11,15 +9,19 @@ use std::{
 };
 
 use anyhow::Result;
-use csv::{ByteRecord, ReaderBuilder, WriterBuilder};
+use csv::ByteRecord;
 use encoding_rs_io::DecodeReaderBytesBuilder;
 use tokio::sync::oneshot;
 
 use crate::{
-  io::csv::{
-    config::CsvConfigBuilder,
-    encoding::{detect_encoding, encoding_from_name},
-    options::CsvOptions,
+  io::{
+    csv::{
+      config::{CsvConfigBuilder, parse_byte, parse_trim},
+      encoding::{detect_encoding, encoding_from_name},
+      options::CsvOptions,
+      sniff,
+    },
+    writer::Finish,
   },
   utils::EventEmitter,
 };
@@ -35,6 +37,11 @@ pub async fn csv_to_csv<E, P>(
   progress: bool,
   skiprows: usize,
   flexible: bool,
+  line_ending: &str,
+  trim: &str,
+  comment: Option<&str>,
+  escape: Option<&str>,
+  terminator: Option<&str>,
   emitter: E,
 ) -> Result<()>
 where
@@ -43,7 +50,7 @@ where
 {
   let mut opts = CsvOptions::new(path);
   opts.set_skiprows(skiprows);
-  let (rdr_sep, reader) = opts.skiprows_and_delimiter()?;
+  let (dialect, reader) = opts.skiprows_and_dialect()?;
   let output_path = opts.output_path(Some("fmt"), None)?;
   let sep = if wtr_sep == "\\t" {
     b'\t'
@@ -66,13 +73,29 @@ where
     .emit_total_msg(&format!("{filename}|{total_rows}"))
     .await?;
 
+  // 未指定时沿用 sniff 出的注释和转义规则
+  let comment = match comment {
+    Some(c) => parse_byte(Some(c)),
+    None => dialect.comment,
+  };
+  let (escape, double_quote) = match parse_byte(escape) {
+    Some(e) => (Some(e), false),
+    None => (dialect.escape, dialect.double_quote),
+  };
+
   let config = CsvConfigBuilder::new()
+    .dialect(&dialect)
     .flexible(flexible)
-    .read_delimiter(rdr_sep)
     .quoting(quoting)
+    .comment(comment)
+    .escape(escape)
+    .double_quote(double_quote)
+    .terminator(parse_byte(terminator))
+    .trim(parse_trim(trim))
     .write_delimiter(sep)
     .quote(quote)
     .quote_style(quote_style)
+    .line_ending(line_ending.into())
     .build();
 
   let mut rdr = config.build_reader(reader);
@@ -121,7 +144,7 @@ where
 
     let final_rows = rows.load(Ordering::Relaxed);
     let _ = done_tx.send(final_rows);
-    Ok::<_, anyhow::Error>(wtr.flush()?)
+    wtr.finish()
   });
 
   counter_task.await??;
@@ -165,47 +188,28 @@ where
     encoding_from_name(&encoding_result.encoding)
   };
 
-  // 检测分隔符
-  let separator = {
-    let file = File::open(&path)?;
-    let decoder = DecodeReaderBytesBuilder::new()
-      .encoding(encoding)
-      .build(file);
-    let mut lines = BufReader::new(decoder).lines();
-
-    let candidates = [b',', b';', b'\t', b'|', b'^'];
-    let mut counts: HashMap<u8, usize> = candidates.iter().map(|&c| (c, 0)).collect();
-
-    if let Some(Ok(line)) = lines.next() {
-      for &b in line.as_bytes() {
-        if counts.contains_key(&b) {
-          *counts.get_mut(&b).unwrap() += 1;
-        }
-      }
-    }
-
-    let (&best, &max) = counts
-      .iter()
-      .max_by_key(|&(_, v)| v)
-      .expect("Candidate separators map is empty");
-    if max > 0 { best } else { b',' }
-  };
-  log::info!("Separator: {:?}", separator as char);
-
-  let file = File::open(&path)?;
+  let file = CsvOptions::new(&path).open_reader()?;
   let decoder = DecodeReaderBytesBuilder::new()
     .encoding(encoding)
     .build(file);
-  let buf_reader = BufReader::new(decoder);
+  let mut buf_reader = BufReader::new(decoder);
+
+  // 在解码后的内容上识别 dialect
+  let mut sample = Vec::with_capacity(sniff::SAMPLE_BYTES);
+  (&mut buf_reader)
+    .take(sniff::SAMPLE_BYTES as u64)
+    .read_to_end(&mut sample)?;
+  let dialect = sniff::sniff(&sample, sample.len() < sniff::SAMPLE_BYTES);
+  log::info!("Separator: {:?}", dialect.delimiter as char);
 
-  let mut rdr = ReaderBuilder::new()
-    .delimiter(separator)
+  let config = CsvConfigBuilder::new()
+    .dialect(&dialect)
     .quoting(quoting)
-    .from_reader(buf_reader);
+    .build();
+  let mut rdr = config.build_reader(Cursor::new(sample).chain(buf_reader));
 
   let output_path = opts.output_path(Some("utf8"), None)?;
-  let wtr = BufWriter::new(File::create(&output_path)?);
-  let mut wtr = WriterBuilder::new().delimiter(separator).from_writer(wtr);
+  let mut wtr = config.build_writer(&output_path)?;
 
   if let Ok(headers) = rdr.headers() {
     wtr.write_record(headers)?;
@@ -216,5 +220,5 @@ where
     wtr.write_record(&record)?;
   }
 
-  Ok(wtr.flush()?)
+  wtr.finish()
 }
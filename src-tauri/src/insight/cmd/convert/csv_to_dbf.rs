@@ -0,0 +1,459 @@
+use std::{collections::HashMap, fmt, path::Path, str::FromStr};
+
+use anyhow::{Result, anyhow, bail};
+use chrono::{Datelike, NaiveTime};
+use dbase::{FieldName, FieldValue, TableWriterBuilder, encoding::EncodingRs};
+use encoding_rs::{Encoding, UTF_8};
+use serde::Serialize;
+
+use crate::{
+  cmd::datefmt::parse_to_naive_datetime,
+  io::{
+    csv::{config::CsvConfigBuilder, encoding::encoding_from_name, options::CsvOptions},
+    writer,
+  },
+};
+
+/// dBase 字段名最长 10 字节
+const MAX_NAME_LEN: usize = 10;
+const MAX_CHAR_LEN: usize = 254;
+/// FoxPro N 字段最长 20 位 (含符号和小数点)
+const MAX_NUMERIC_LEN: usize = 20;
+const MAX_DECIMALS: usize = 15;
+
+/// dBase field type.
+/// `C` and `N` without a size (stored as 0) take their size from the data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DbfType {
+  /// `C(length)`
+  Character(u8),
+  /// `N(length,decimals)`
+  Numeric(u8, u8),
+  /// `D`, stored as YYYYMMDD
+  Date,
+  /// `L`
+  Logical,
+}
+
+impl fmt::Display for DbfType {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      DbfType::Character(len) => write!(f, "C({len})"),
+      DbfType::Numeric(len, 0) => write!(f, "N({len})"),
+      DbfType::Numeric(len, dec) => write!(f, "N({len},{dec})"),
+      DbfType::Date => write!(f, "D"),
+      DbfType::Logical => write!(f, "L"),
+    }
+  }
+}
+
+impl FromStr for DbfType {
+  type Err = anyhow::Error;
+
+  /// `C`, `C(20)`, `N`, `N(12)`, `N(12,2)`, `D`, `L`
+  fn from_str(s: &str) -> Result<Self> {
+    let spec = s.trim().to_ascii_uppercase();
+    let (kind, args) = match spec.split_once('(') {
+      Some((kind, rest)) => (
+        kind.trim(),
+        rest
+          .strip_suffix(')')
+          .ok_or_else(|| anyhow!("invalid dbf field type: {s}"))?,
+      ),
+      None => (spec.as_str(), ""),
+    };
+    let sizes = args
+      .split(',')
+      .map(str::trim)
+      .filter(|n| !n.is_empty())
+      .map(|n| n.parse::<usize>())
+      .collect::<Result<Vec<_>, _>>()
+      .map_err(|_| anyhow!("invalid dbf field type: {s}"))?;
+
+    Ok(match (kind, sizes.as_slice()) {
+      ("C", []) => DbfType::Character(0),
+      ("C", [len]) if *len <= MAX_CHAR_LEN => DbfType::Character(*len as u8),
+      ("N", []) => DbfType::Numeric(0, 0),
+      ("N", [len]) if *len <= MAX_NUMERIC_LEN => DbfType::Numeric(*len as u8, 0),
+      ("N", [len, dec]) if *len <= MAX_NUMERIC_LEN && *dec <= MAX_DECIMALS && dec < len => {
+        DbfType::Numeric(*len as u8, *dec as u8)
+      }
+      ("D", []) => DbfType::Date,
+      ("L", []) => DbfType::Logical,
+      _ => bail!("invalid dbf field type: {s}"),
+    })
+  }
+}
+
+/// What to do with values that do not fit their field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overflow {
+  /// Cut characters to the field length and round extra decimals
+  Truncate,
+  /// Abort, nothing is written
+  Error,
+}
+
+impl FromStr for Overflow {
+  type Err = anyhow::Error;
+
+  fn from_str(policy: &str) -> Result<Self> {
+    match policy {
+      "truncate" => Ok(Overflow::Truncate),
+      "error" => Ok(Overflow::Error),
+      _ => bail!("unknown overflow policy `{policy}`, use truncate or error"),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DbfField {
+  /// CSV header
+  pub column: String,
+  /// dBase field name, at most 10 bytes and unique
+  pub name: String,
+  #[serde(serialize_with = "as_display")]
+  pub kind: DbfType,
+}
+
+fn as_display<S: serde::Serializer>(kind: &DbfType, s: S) -> Result<S::Ok, S::Error> {
+  s.collect_str(kind)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DbfReport {
+  pub rows: usize,
+  pub codepage: String,
+  pub fields: Vec<DbfField>,
+  /// Number of cells cut or rounded by `Overflow::Truncate`
+  pub truncated: usize,
+}
+
+/// `column:type` pairs separated by `|`, e.g. `name:C(20)|amount:N(12,2)|birthday:D`
+pub fn parse_field_types(spec: &str) -> Result<HashMap<String, DbfType>> {
+  spec
+    .split('|')
+    .filter(|s| !s.trim().is_empty())
+    .map(|s| {
+      let (column, kind) = s
+        .rsplit_once(':')
+        .ok_or_else(|| anyhow!("invalid field type `{s}`, expected column:type"))?;
+      Ok((column.to_string(), kind.parse()?))
+    })
+    .collect()
+}
+
+fn parse_logical(value: &str) -> Option<bool> {
+  match value.to_ascii_lowercase().as_str() {
+    "t" | "true" | "y" | "yes" => Some(true),
+    "f" | "false" | "n" | "no" => Some(false),
+    _ => None,
+  }
+}
+
+/// 整数部分长度 (含符号) 和小数位数
+fn numeric_shape(value: &str) -> Option<(usize, usize)> {
+  let digits = value.strip_prefix(['-', '+']).unwrap_or(value);
+  let sign = value.starts_with('-') as usize;
+  let (int, frac) = digits.split_once('.').unwrap_or((digits, ""));
+  if (int.is_empty() && frac.is_empty())
+    || !int.bytes().all(|b| b.is_ascii_digit())
+    || !frac.bytes().all(|b| b.is_ascii_digit())
+  {
+    return None;
+  }
+  Some((sign + int.len().max(1), frac.len()))
+}
+
+fn numeric_len(int_len: usize, decimals: usize) -> usize {
+  match decimals {
+    0 => int_len,
+    d => int_len + d + 1,
+  }
+}
+
+fn encoded_len(value: &str, encoding: &'static Encoding) -> usize {
+  if encoding == UTF_8 {
+    value.len()
+  } else {
+    encoding.encode(value).0.len()
+  }
+}
+
+/// 按目标编码的字节数截断, 不拆分字符
+fn truncate_encoded<'a>(value: &'a str, max: usize, encoding: &'static Encoding) -> &'a str {
+  let mut len = 0;
+  let mut buf = [0u8; 4];
+  for (idx, c) in value.char_indices() {
+    len += encoded_len(c.encode_utf8(&mut buf), encoding);
+    if len > max {
+      return &value[..idx];
+    }
+  }
+  value
+}
+
+#[derive(Debug, Default)]
+struct ColumnStats {
+  filled: usize,
+  logical: usize,
+  numeric: usize,
+  date: usize,
+  int_len: usize,
+  decimals: usize,
+  bytes: usize,
+}
+
+impl ColumnStats {
+  fn update(&mut self, value: &str, encoding: &'static Encoding) {
+    if value.is_empty() {
+      return;
+    }
+    // 只有此前的值都符合时才继续尝试该类型
+    let filled = self.filled;
+    self.filled += 1;
+    self.bytes = self.bytes.max(encoded_len(value, encoding));
+
+    if self.logical == filled && parse_logical(value).is_some() {
+      self.logical += 1;
+    }
+    if self.numeric == filled
+      && let Some((int_len, decimals)) = numeric_shape(value)
+    {
+      self.numeric += 1;
+      self.int_len = self.int_len.max(int_len);
+      self.decimals = self.decimals.max(decimals);
+    }
+    if self.date == filled
+      && parse_to_naive_datetime(value).is_some_and(|dt| dt.time() == NaiveTime::MIN)
+    {
+      self.date += 1;
+    }
+  }
+
+  fn char_type(&self) -> DbfType {
+    DbfType::Character(self.bytes.clamp(1, MAX_CHAR_LEN) as u8)
+  }
+
+  fn numeric_type(&self) -> DbfType {
+    let decimals = self.decimals.min(MAX_DECIMALS);
+    let len = numeric_len(self.int_len.max(1), decimals).min(MAX_NUMERIC_LEN);
+    DbfType::Numeric(len as u8, decimals.min(len.saturating_sub(2)) as u8)
+  }
+
+  fn infer(&self) -> DbfType {
+    if self.filled == 0 {
+      self.char_type()
+    } else if self.logical == self.filled {
+      DbfType::Logical
+    } else if self.numeric == self.filled
+      && numeric_len(self.int_len, self.decimals) <= MAX_NUMERIC_LEN
+    {
+      self.numeric_type()
+    } else if self.date == self.filled {
+      DbfType::Date
+    } else {
+      self.char_type()
+    }
+  }
+
+  fn resolve(&self, spec: Option<DbfType>) -> DbfType {
+    match spec {
+      None => self.infer(),
+      Some(DbfType::Character(0)) => self.char_type(),
+      Some(DbfType::Numeric(0, _)) => self.numeric_type(),
+      Some(kind) => kind,
+    }
+  }
+}
+
+/// 截断并去重字段名, 空表头使用 `FIELD<n>`
+fn field_names<'a>(headers: impl Iterator<Item = &'a str>) -> Vec<String> {
+  let mut names: Vec<String> = Vec::new();
+  for (idx, header) in headers.enumerate() {
+    let base = match truncate_encoded(header.trim(), MAX_NAME_LEN, UTF_8) {
+      "" => format!("FIELD{}", idx + 1),
+      name => name.to_string(),
+    };
+    let mut name = base.clone();
+    let mut n = 1;
+    while names.contains(&name) {
+      let suffix = format!("_{n}");
+      let prefix = truncate_encoded(&base, MAX_NAME_LEN - suffix.len(), UTF_8);
+      name = format!("{prefix}{suffix}");
+      n += 1;
+    }
+    names.push(name);
+  }
+  names
+}
+
+impl DbfField {
+  fn value(
+    &self,
+    value: &str,
+    overflow: Overflow,
+    encoding: &'static Encoding,
+    truncated: &mut usize,
+  ) -> Result<FieldValue> {
+    if value.is_empty() {
+      return Ok(match self.kind {
+        DbfType::Character(_) => FieldValue::Character(None),
+        DbfType::Numeric(..) => FieldValue::Numeric(None),
+        DbfType::Date => FieldValue::Date(None),
+        DbfType::Logical => FieldValue::Logical(None),
+      });
+    }
+
+    match self.kind {
+      DbfType::Character(len) => {
+        let len = len as usize;
+        if encoded_len(value, encoding) <= len {
+          return Ok(FieldValue::Character(Some(value.to_string())));
+        }
+        if overflow == Overflow::Error {
+          bail!("`{value}` is longer than {}", self.kind);
+        }
+        *truncated += 1;
+        let value = truncate_encoded(value, len, encoding);
+        Ok(FieldValue::Character(Some(value.to_string())))
+      }
+      DbfType::Numeric(len, dec) => {
+        let (int_len, decimals) =
+          numeric_shape(value).ok_or_else(|| anyhow!("`{value}` is not a number"))?;
+        let number: f64 = value.parse()?;
+        if numeric_len(int_len, dec as usize) > len as usize {
+          bail!("`{value}` does not fit {}", self.kind);
+        }
+        if decimals > dec as usize {
+          if overflow == Overflow::Error {
+            bail!("`{value}` has more than {dec} decimals");
+          }
+          *truncated += 1;
+        }
+        let scale = 10f64.powi(dec as i32);
+        Ok(FieldValue::Numeric(Some((number * scale).round() / scale)))
+      }
+      DbfType::Date => {
+        let dt =
+          parse_to_naive_datetime(value).ok_or_else(|| anyhow!("`{value}` is not a date"))?;
+        Ok(FieldValue::Date(Some(dbase::Date::new(
+          dt.day(),
+          dt.month(),
+          dt.year() as u32,
+        ))))
+      }
+      DbfType::Logical => {
+        let flag = parse_logical(value).ok_or_else(|| anyhow!("`{value}` is not a logical"))?;
+        Ok(FieldValue::Logical(Some(flag)))
+      }
+    }
+  }
+}
+
+/// Convert CSV to dBase, writes `<stem>_csv2dbf.dbf`
+///
+/// - `field_types`: overrides, see `parse_field_types`. Other columns are inferred
+///   as L, N, D then C from all values
+/// - `codepage`: encoding of character fields, e.g. `gbk`, `windows-1252`
+pub async fn csv_to_dbf<P>(
+  path: P,
+  skiprows: usize,
+  field_types: &str,
+  overflow: Overflow,
+  codepage: &str,
+) -> Result<DbfReport>
+where
+  P: AsRef<Path> + Send + Sync,
+{
+  let encoding =
+    encoding_from_name(codepage).ok_or_else(|| anyhow!("unknown codepage: {codepage}"))?;
+  let overrides = parse_field_types(field_types)?;
+
+  let mut opts = CsvOptions::new(&path);
+  opts.set_skiprows(skiprows);
+  let output_path = opts.output_path(Some("csv2dbf"), Some("dbf"))?;
+
+  // 1.推断字段类型
+  let (dialect, reader) = opts.skiprows_and_dialect()?;
+  let config = CsvConfigBuilder::new()
+    .dialect(&dialect)
+    .trim(csv::Trim::All)
+    .build();
+  let mut rdr = config.build_reader(reader);
+  let headers = rdr.headers()?.clone();
+  if let Some(column) = overrides.keys().find(|c| !headers.iter().any(|h| h == *c)) {
+    bail!("column `{column}` not found");
+  }
+
+  let mut stats: Vec<ColumnStats> = headers.iter().map(|_| ColumnStats::default()).collect();
+  let mut record = csv::StringRecord::new();
+  while rdr.read_record(&mut record)? {
+    for (stat, value) in stats.iter_mut().zip(record.iter()) {
+      stat.update(value, encoding);
+    }
+  }
+
+  let fields: Vec<DbfField> = headers
+    .iter()
+    .zip(field_names(headers.iter()))
+    .zip(&stats)
+    .map(|((column, name), stat)| DbfField {
+      column: column.to_string(),
+      name,
+      kind: stat.resolve(overrides.get(column).copied()),
+    })
+    .collect();
+
+  // 2.写入 dbf
+  let mut builder = TableWriterBuilder::with_encoding(EncodingRs::from(encoding));
+  for field in &fields {
+    let name = FieldName::try_from(field.name.as_str())
+      .map_err(|e| anyhow!("invalid field name `{}`: {e}", field.name))?;
+    builder = match field.kind {
+      DbfType::Character(len) => builder.add_character_field(name, len),
+      DbfType::Numeric(len, dec) => builder.add_numeric_field(name, len, dec),
+      DbfType::Date => builder.add_date_field(name),
+      DbfType::Logical => builder.add_logical_field(name),
+    };
+  }
+
+  // dbase 需要可 seek 的文件, 先写临时文件再重命名
+  let temp = writer::temp_file(&output_path)?.into_temp_path();
+  let mut wtr = builder.build_with_file_dest(&temp)?;
+
+  let (_, reader) = opts.skiprows_and_dialect()?;
+  let mut rdr = config.build_reader(reader);
+  let mut rows = 0;
+  let mut truncated = 0;
+  while rdr.read_record(&mut record)? {
+    rows += 1;
+    let mut row = dbase::Record::default();
+    for (idx, field) in fields.iter().enumerate() {
+      let value = field
+        .value(
+          record.get(idx).unwrap_or_default(),
+          overflow,
+          encoding,
+          &mut truncated,
+        )
+        .map_err(|e| anyhow!("row {rows}, column `{}`: {e}", field.column))?;
+      row.insert(field.name.clone(), value);
+    }
+    wtr.write_record(&row)?;
+  }
+
+  // 写回记录数和文件结束符, drop 时也会写但会丢掉错误
+  wtr
+    .finalize()
+    .map_err(|e| anyhow!("failed to finish dbf file: {e}"))?;
+  drop(wtr);
+  temp.persist(&output_path)?;
+
+  Ok(DbfReport {
+    rows,
+    codepage: encoding.name().to_string(),
+    fields,
+    truncated,
+  })
+}
@@ -1,10 +1,12 @@
 use std::path::Path;
 
 use anyhow::{Result, anyhow};
-use csv::ReaderBuilder;
 
 use crate::io::excel::xlsx_writer::XlsxWriter;
-use crate::{io::csv::options::CsvOptions, utils::EXCEL_MAX_ROW};
+use crate::{
+  io::csv::{config::CsvConfigBuilder, options::CsvOptions},
+  utils::EXCEL_MAX_ROW,
+};
 
 /// convert csv to xlsx
 pub async fn csv_to_xlsx<P: AsRef<Path> + Send + Sync>(
@@ -17,12 +19,13 @@ pub async fn csv_to_xlsx<P: AsRef<Path> + Send + Sync>(
   let dest = path.as_ref().with_extension("xlsx");
   let mut opts = CsvOptions::new(&path);
   opts.set_skiprows(skiprows);
-  let (sep, reader) = opts.skiprows_and_delimiter()?;
+  let (dialect, reader) = opts.skiprows_and_dialect()?;
 
-  let rdr = ReaderBuilder::new()
-    .delimiter(sep)
+  let rdr = CsvConfigBuilder::new()
+    .dialect(&dialect)
     .quoting(quoting)
-    .from_reader(reader);
+    .build()
+    .build_reader(reader);
 
   if multi {
     let row_count = opts.count_lines()?;
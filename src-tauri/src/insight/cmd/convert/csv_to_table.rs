@@ -0,0 +1,200 @@
+use std::{fmt::Write as _, io::Write, path::Path};
+
+use anyhow::Result;
+
+use crate::io::{
+  csv::{config::CsvConfigBuilder, options::CsvOptions, selection::Selection},
+  writer,
+};
+
+const HTML_STYLE: &str = "body{font-family:-apple-system,'Segoe UI',Roboto,'Microsoft YaHei',sans-serif;margin:24px;color:#24292f}
+table{border-collapse:collapse;font-size:14px}
+caption{caption-side:top;text-align:left;font-weight:600;padding:6px 0}
+th,td{border:1px solid #d0d7de;padding:6px 12px;white-space:pre-wrap}
+th{background:#f6f8fa}
+tbody tr:nth-child(even){background:#fafbfc}
+.num{text-align:right;font-variant-numeric:tabular-nums}";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableFormat {
+  /// Standalone HTML page with a styled table
+  Html,
+  /// GitHub-flavoured Markdown table
+  Markdown,
+}
+
+impl TableFormat {
+  fn extension(&self) -> &'static str {
+    match self {
+      TableFormat::Html => "html",
+      TableFormat::Markdown => "md",
+    }
+  }
+}
+
+/// `1,234.5`, `-3`, `12%` are numeric, `NaN` and `inf` are not
+fn is_numeric(value: &str) -> bool {
+  let value = value.trim();
+  let value = value.strip_suffix('%').unwrap_or(value).replace(',', "");
+  value.parse::<f64>().is_ok_and(f64::is_finite)
+}
+
+/// Right-align columns whose non-empty values are all numeric
+pub fn numeric_columns(rows: &[Vec<String>], columns: usize) -> Vec<bool> {
+  (0..columns)
+    .map(|idx| {
+      let mut values = rows
+        .iter()
+        .filter_map(|row| row.get(idx))
+        .filter(|v| !v.trim().is_empty())
+        .peekable();
+      values.peek().is_some() && values.all(|v| is_numeric(v))
+    })
+    .collect()
+}
+
+pub fn escape_html(value: &str) -> String {
+  let mut escaped = String::with_capacity(value.len());
+  for c in value.chars() {
+    match c {
+      '&' => escaped.push_str("&amp;"),
+      '<' => escaped.push_str("&lt;"),
+      '>' => escaped.push_str("&gt;"),
+      '"' => escaped.push_str("&quot;"),
+      '\'' => escaped.push_str("&#39;"),
+      _ => escaped.push(c),
+    }
+  }
+  escaped
+}
+
+/// 转义 `|` 和 `\`, 换行转为 `<br>`, 避免 `<`, `>` 被当作 HTML
+pub fn escape_markdown(value: &str) -> String {
+  let mut escaped = String::with_capacity(value.len());
+  let mut chars = value.chars().peekable();
+  while let Some(c) = chars.next() {
+    match c {
+      '|' => escaped.push_str("\\|"),
+      '\\' => escaped.push_str("\\\\"),
+      '<' => escaped.push_str("&lt;"),
+      '>' => escaped.push_str("&gt;"),
+      '\r' if chars.peek() == Some(&'\n') => {}
+      '\r' | '\n' => escaped.push_str("<br>"),
+      _ => escaped.push(c),
+    }
+  }
+  escaped
+}
+
+pub fn render_html(title: &str, headers: &[String], rows: &[Vec<String>]) -> String {
+  let numeric = numeric_columns(rows, headers.len());
+  let cell_class = |idx: usize| match numeric.get(idx) {
+    Some(true) => " class=\"num\"",
+    _ => "",
+  };
+
+  let mut html = String::new();
+  let title = escape_html(title);
+  let _ = write!(
+    html,
+    "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n<style>\n{HTML_STYLE}\n</style>\n</head>\n<body>\n<table>\n<caption>{title}</caption>\n<thead>\n<tr>"
+  );
+  for (idx, header) in headers.iter().enumerate() {
+    let _ = write!(html, "<th{}>{}</th>", cell_class(idx), escape_html(header));
+  }
+  html.push_str("</tr>\n</thead>\n<tbody>\n");
+  for row in rows {
+    html.push_str("<tr>");
+    for idx in 0..headers.len() {
+      let value = row.get(idx).map(String::as_str).unwrap_or_default();
+      let _ = write!(html, "<td{}>{}</td>", cell_class(idx), escape_html(value));
+    }
+    html.push_str("</tr>\n");
+  }
+  html.push_str("</tbody>\n</table>\n</body>\n</html>\n");
+  html
+}
+
+fn markdown_row<I, S>(md: &mut String, cells: I)
+where
+  I: Iterator<Item = S>,
+  S: AsRef<str>,
+{
+  md.push('|');
+  for cell in cells {
+    let _ = write!(md, " {} |", cell.as_ref());
+  }
+  md.push('\n');
+}
+
+pub fn render_markdown(headers: &[String], rows: &[Vec<String>]) -> String {
+  let numeric = numeric_columns(rows, headers.len());
+
+  let mut md = String::new();
+  markdown_row(&mut md, headers.iter().map(|h| escape_markdown(h)));
+  markdown_row(
+    &mut md,
+    numeric.iter().map(|n| if *n { "---:" } else { "---" }),
+  );
+  for row in rows {
+    markdown_row(
+      &mut md,
+      (0..headers.len())
+        .map(|idx| escape_markdown(row.get(idx).map(String::as_str).unwrap_or_default())),
+    );
+  }
+  md
+}
+
+/// Render the CSV (or its first `limit` rows) as `<stem>_table.html` or `<stem>_table.md`
+///
+/// - `columns`: columns to keep, separated by `|`, empty keeps all
+/// - `limit`: 0 renders every row
+pub async fn csv_to_table<P>(
+  path: P,
+  skiprows: usize,
+  columns: &str,
+  limit: usize,
+  format: TableFormat,
+) -> Result<usize>
+where
+  P: AsRef<Path> + Send + Sync,
+{
+  let mut opts = CsvOptions::new(&path);
+  opts.set_skiprows(skiprows);
+  let (dialect, reader) = opts.skiprows_and_dialect()?;
+  let output_path = opts.output_path(Some("table"), Some(format.extension()))?;
+
+  let config = CsvConfigBuilder::new()
+    .dialect(&dialect)
+    .flexible(true)
+    .build();
+  let mut rdr = config.build_reader(reader);
+
+  let cols: Vec<&str> = columns.split('|').filter(|c| !c.is_empty()).collect();
+  let sel = Selection::from_headers(rdr.byte_headers()?, &cols[..])?;
+  let select = |record: &csv::ByteRecord| -> Vec<String> {
+    sel
+      .get_indices()
+      .iter()
+      .map(|&idx| String::from_utf8_lossy(record.get(idx).unwrap_or_default()).into_owned())
+      .collect()
+  };
+
+  let headers = select(rdr.byte_headers()?);
+  let mut rows = Vec::new();
+  let mut record = csv::ByteRecord::new();
+  while (limit == 0 || rows.len() < limit) && rdr.read_byte_record(&mut record)? {
+    rows.push(select(&record));
+  }
+
+  let rendered = match format {
+    TableFormat::Html => render_html(opts.file_name()?, &headers, &rows),
+    TableFormat::Markdown => render_markdown(&headers, &rows),
+  };
+  let mut wtr = writer::create(&output_path)?;
+  wtr.write_all(rendered.as_bytes())?;
+  wtr.finish()?;
+
+  Ok(rows.len())
+}
@@ -0,0 +1,121 @@
+use std::{collections::HashSet, io::Write, path::Path};
+
+use anyhow::{Result, anyhow};
+use quick_xml::{
+  Writer,
+  events::{BytesDecl, BytesEnd, BytesStart, BytesText, Event},
+};
+
+use crate::io::{
+  csv::{config::CsvConfigBuilder, options::CsvOptions},
+  writer,
+};
+
+/// Turn a header into a valid XML name, invalid characters become `_`
+pub fn xml_name(name: &str) -> String {
+  let mut xml: String = name
+    .trim()
+    .chars()
+    .map(|c| match c {
+      c if c.is_alphanumeric() || matches!(c, '_' | '-' | '.') => c,
+      _ => '_',
+    })
+    .collect();
+  match xml.chars().next() {
+    None => xml.push('_'),
+    Some(c) if !(c.is_alphabetic() || c == '_') => xml.insert(0, '_'),
+    _ => {}
+  }
+  xml
+}
+
+/// Convert CSV to XML, writes `<stem>_xml.xml`.
+///
+/// - `root`, `row`: element names of the document and of each record
+/// - `attributes`: columns written as attributes of the row element, separated by `|`,
+///   `*` writes every column as an attribute, the rest become child elements
+pub async fn csv_to_xml<P>(
+  path: P,
+  skiprows: usize,
+  root: &str,
+  row: &str,
+  attributes: &str,
+) -> Result<usize>
+where
+  P: AsRef<Path> + Send + Sync,
+{
+  let mut opts = CsvOptions::new(&path);
+  opts.set_skiprows(skiprows);
+  let (dialect, reader) = opts.skiprows_and_dialect()?;
+  let output_path = opts.output_path(Some("xml"), Some("xml"))?;
+
+  let config = CsvConfigBuilder::new()
+    .dialect(&dialect)
+    .flexible(true)
+    .build();
+  let mut rdr = config.build_reader(reader);
+  let headers = rdr.headers()?.clone();
+
+  let attr_columns: HashSet<&str> = attributes.split('|').filter(|c| !c.is_empty()).collect();
+  if let Some(column) = attr_columns
+    .iter()
+    .find(|c| **c != "*" && !headers.iter().any(|h| h == **c))
+  {
+    return Err(anyhow!("column `{column}` not found"));
+  }
+  let all_attrs = attr_columns.contains("*");
+  // (xml 名称, 是否为属性)
+  let columns: Vec<(String, bool)> = headers
+    .iter()
+    .map(|h| (xml_name(h), all_attrs || attr_columns.contains(h)))
+    .collect();
+
+  let root = xml_name(root);
+  let row = xml_name(row);
+
+  let mut xml = Writer::new_with_indent(writer::create(&output_path)?, b' ', 2);
+  xml.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))?;
+  xml.write_event(Event::Start(BytesStart::new(root.as_str())))?;
+
+  let mut count = 0;
+  let mut record = csv::StringRecord::new();
+  while rdr.read_record(&mut record)? {
+    count += 1;
+    let mut elem = BytesStart::new(row.as_str());
+    for ((name, is_attr), value) in columns.iter().zip(record.iter()) {
+      if *is_attr {
+        elem.push_attribute((name.as_str(), value));
+      }
+    }
+
+    let children: Vec<(&str, &str)> = columns
+      .iter()
+      .zip(record.iter())
+      .filter(|((_, is_attr), _)| !is_attr)
+      .map(|((name, _), value)| (name.as_str(), value))
+      .collect();
+    if children.is_empty() {
+      xml.write_event(Event::Empty(elem))?;
+      continue;
+    }
+
+    xml.write_event(Event::Start(elem))?;
+    for (name, value) in children {
+      if value.is_empty() {
+        xml.write_event(Event::Empty(BytesStart::new(name)))?;
+      } else {
+        xml.write_event(Event::Start(BytesStart::new(name)))?;
+        xml.write_event(Event::Text(BytesText::new(value)))?;
+        xml.write_event(Event::End(BytesEnd::new(name)))?;
+      }
+    }
+    xml.write_event(Event::End(BytesEnd::new(row.as_str())))?;
+  }
+
+  xml.write_event(Event::End(BytesEnd::new(root.as_str())))?;
+  let mut wtr = xml.into_inner();
+  wtr.write_all(b"\n")?;
+  wtr.finish()?;
+
+  Ok(count)
+}
@@ -1,10 +1,10 @@
-use std::{fs::File, io::BufWriter};
-
 use anyhow::Result;
-use csv::WriterBuilder;
 use dbase::FieldValue;
 
-use crate::{io::csv::options::CsvOptions, utils::WTR_BUFFER_SIZE};
+use crate::io::{
+  csv::{config::CsvConfigBuilder, options::CsvOptions},
+  writer::Finish,
+};
 
 /// convert dbf to csv
 pub async fn dbf_to_csv(path: &str, wtr_sep: String) -> Result<()> {
@@ -24,8 +24,10 @@ pub async fn dbf_to_csv(path: &str, wtr_sep: String) -> Result<()> {
     .map(|field| field.name().to_string())
     .collect();
 
-  let buf_writer = BufWriter::with_capacity(WTR_BUFFER_SIZE, File::create(output_path)?);
-  let mut wtr = WriterBuilder::new().delimiter(sep).from_writer(buf_writer);
+  let mut wtr = CsvConfigBuilder::new()
+    .write_delimiter(sep)
+    .build()
+    .build_writer(output_path)?;
   wtr.write_record(&headers)?;
 
   for result in rdr.iter_records() {
@@ -53,5 +55,5 @@ pub async fn dbf_to_csv(path: &str, wtr_sep: String) -> Result<()> {
     wtr.write_record(&row)?;
   }
 
-  Ok(wtr.flush()?)
+  wtr.finish()
 }
@@ -6,10 +6,13 @@ use std::{
 
 use anyhow::{Result, anyhow};
 use calamine::{Data, HeaderRow, Range, Reader};
-use csv::{StringRecord, WriterBuilder};
+use csv::StringRecord;
 use rayon::{iter::ParallelIterator, slice::ParallelSlice};
 
-use crate::utils;
+use crate::{
+  io::{csv::config::CsvConfigBuilder, writer::Finish},
+  utils,
+};
 
 /// convert excel to csv
 pub async fn excel_to_csv<P: AsRef<Path>>(
@@ -19,7 +22,7 @@ pub async fn excel_to_csv<P: AsRef<Path>>(
   output_path: &PathBuf,
   threads: usize,
 ) -> Result<()> {
-  let mut wtr = WriterBuilder::new().from_path(output_path)?;
+  let mut wtr = CsvConfigBuilder::new().build().build_writer(output_path)?;
 
   let mut workbook = calamine::open_workbook_auto(&path)?;
 
@@ -153,7 +156,7 @@ pub async fn excel_to_csv<P: AsRef<Path>>(
     }
   }
 
-  Ok(wtr.flush()?)
+  wtr.finish()
 }
 
 pub fn get_sheetname_by_filename(
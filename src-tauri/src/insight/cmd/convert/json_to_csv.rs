@@ -1,10 +1,16 @@
-use std::{collections::HashSet, fs::File, io::BufReader};
+use std::{collections::HashSet, io::BufReader};
 
 use anyhow::{Result, anyhow};
-use csv::WriterBuilder;
 use json_objects_to_csv::{Json2Csv, flatten_json_object::Flattener};
 
-use crate::{io::csv::options::CsvOptions, utils::RDR_BUFFER_SIZE};
+use crate::{
+  io::{
+    compress,
+    csv::{config::CsvConfigBuilder, options::CsvOptions},
+    writer,
+  },
+  utils::RDR_BUFFER_SIZE,
+};
 
 pub async fn json_to_csv(path: &str, wtr_sep: String) -> Result<()> {
   let sep = if wtr_sep == "\\t" {
@@ -12,8 +18,7 @@ pub async fn json_to_csv(path: &str, wtr_sep: String) -> Result<()> {
   } else {
     wtr_sep.into_bytes()[0]
   };
-  let file = File::open(path)?;
-  let reader = BufReader::with_capacity(RDR_BUFFER_SIZE, file);
+  let reader = BufReader::with_capacity(RDR_BUFFER_SIZE, compress::open_reader(path)?);
 
   let value: serde_json::Value = serde_json::from_reader(reader)?;
   if value.is_null() {
@@ -56,15 +61,20 @@ pub async fn json_to_csv(path: &str, wtr_sep: String) -> Result<()> {
   let opts = CsvOptions::new(path);
   let output_path = opts.output_path(Some("json"), None)?;
 
-  let wtr = WriterBuilder::new()
-    .has_headers(true)
-    .delimiter(sep)
-    .from_path(output_path)?;
+  // Json2Csv 会消费 writer, 写入临时文件, 转换成功后再替换目标文件
+  let temp = writer::temp_file(&output_path)?;
+  let wtr = CsvConfigBuilder::new()
+    .write_delimiter(sep)
+    .build()
+    .build_writer_from(temp.as_file());
 
   let flattener = Flattener::new();
   Json2Csv::new(flattener)
     .preserve_key_order(true)
     .convert_from_array(&values, wtr)?;
 
+  temp.as_file().sync_all()?;
+  temp.persist(&output_path)?;
+
   Ok(())
 }
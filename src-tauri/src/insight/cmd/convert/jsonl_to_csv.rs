@@ -1,7 +1,4 @@
-use std::{
-  fs::File,
-  io::{BufRead, BufReader},
-};
+use std::io::{BufRead, BufReader};
 
 use anyhow::{Result, anyhow};
 use csv::StringRecord;
@@ -11,7 +8,14 @@ use rayon::{
 };
 use serde_json::Value;
 
-use crate::{io::csv::options::CsvOptions, utils};
+use crate::{
+  io::{
+    compress,
+    csv::{config::CsvConfigBuilder, options::CsvOptions},
+    writer::Finish,
+  },
+  utils,
+};
 
 fn recurse_to_infer_headers(value: &Value, headers: &mut Vec<Vec<String>>, path: &[String]) {
   match value {
@@ -111,11 +115,12 @@ pub async fn jsonl_to_csv(path: &str, wtr_sep: &str, ignore_err: bool) -> Result
   let mut opts = CsvOptions::new(path);
   opts.set_decrease(false);
   let output_path = opts.output_path(Some("jsonl"), None)?;
-  let mut wtr = csv::WriterBuilder::new()
-    .delimiter(sep)
-    .from_path(output_path)?;
+  let mut wtr = CsvConfigBuilder::new()
+    .write_delimiter(sep)
+    .build()
+    .build_writer(output_path)?;
 
-  let mut rdr = BufReader::with_capacity(utils::RDR_BUFFER_SIZE, File::open(path)?);
+  let mut rdr = BufReader::with_capacity(utils::RDR_BUFFER_SIZE, compress::open_reader(path)?);
 
   let mut headers: Vec<Vec<String>> = Vec::new();
   let mut headers_emitted: bool = false;
@@ -206,5 +211,5 @@ pub async fn jsonl_to_csv(path: &str, wtr_sep: &str, ignore_err: bool) -> Result
     batch.clear();
   } // end batch loop
 
-  Ok(wtr.flush()?)
+  wtr.finish()
 }
@@ -1,7 +1,15 @@
+pub mod arrow_to_csv;
+pub mod csv_to_arrow;
 pub mod csv_to_csv;
+pub mod csv_to_dbf;
 pub mod csv_to_excel;
+pub mod csv_to_table;
+pub mod csv_to_xml;
 pub mod dbf_to_csv;
 pub mod excel_to_csv;
 pub mod json_to_csv;
 pub mod jsonl_to_csv;
+pub mod mojibake;
 pub mod perform;
+pub mod transcode;
+pub mod xml_to_csv;
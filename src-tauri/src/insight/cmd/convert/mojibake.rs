@@ -0,0 +1,248 @@
+use std::path::Path;
+
+use anyhow::Result;
+use encoding_rs::{EncoderResult, GBK, WINDOWS_1252};
+use serde::Serialize;
+
+use crate::io::{
+  csv::{
+    config::CsvConfigBuilder,
+    encoding::{count_gbk_pattern, count_utf8_chinese_pattern},
+    options::CsvOptions,
+  },
+  writer::Finish,
+};
+
+/// How a cell was garbled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MojibakeKind {
+  /// UTF-8 bytes decoded as Latin-1/Windows-1252, e.g. `æ±¤å§†`
+  Utf8AsLatin1,
+  /// GBK bytes decoded as Latin-1/Windows-1252, e.g. `ÌÀÄ·`
+  GbkAsLatin1,
+  /// UTF-8 bytes decoded as GBK, e.g. `浣犲ソ`
+  Utf8AsGbk,
+}
+
+impl MojibakeKind {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      MojibakeKind::Utf8AsLatin1 => "utf8_as_latin1",
+      MojibakeKind::GbkAsLatin1 => "gbk_as_latin1",
+      MojibakeKind::Utf8AsGbk => "utf8_as_gbk",
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Repair {
+  pub kind: MojibakeKind,
+  pub confidence: f64,
+  pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ColumnRepairs {
+  pub column: String,
+  pub cells: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MojibakeReport {
+  pub rows: usize,
+  pub repaired_cells: usize,
+  pub repaired_rows: usize,
+  pub columns: Vec<ColumnRepairs>,
+}
+
+fn is_cjk(c: char) -> bool {
+  matches!(c as u32, 0x3000..=0x303F | 0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xFF00..=0xFFEF)
+}
+
+/// 还原被当作 Latin-1/Windows-1252 解码的原始字节
+fn latin1_bytes(cell: &str) -> Option<Vec<u8>> {
+  let mut encoder = WINDOWS_1252.new_encoder();
+  let mut bytes = Vec::with_capacity(cell.len());
+  let mut buf = [0u8; 4];
+  for c in cell.chars() {
+    if (c as u32) < 0x100 {
+      bytes.push(c as u8);
+      continue;
+    }
+    // 0x80-0x9F 在 Windows-1252 中显示为 `€`, `†` 等
+    let mut out = [0u8; 4];
+    let (result, _, written) =
+      encoder.encode_from_utf8_without_replacement(c.encode_utf8(&mut buf), &mut out, false);
+    if result != EncoderResult::InputEmpty || written != 1 {
+      return None;
+    }
+    bytes.push(out[0]);
+  }
+  Some(bytes)
+}
+
+/// 还原被当作 GBK 解码的原始字节
+fn gbk_bytes(cell: &str) -> Option<Vec<u8>> {
+  let mut encoder = GBK.new_encoder();
+  let mut bytes = vec![0u8; cell.len() * 2 + 8];
+  let (result, _, written) = encoder.encode_from_utf8_without_replacement(cell, &mut bytes, true);
+  if result != EncoderResult::InputEmpty {
+    return None;
+  }
+  bytes.truncate(written);
+  Some(bytes)
+}
+
+fn repair_latin1(cell: &str) -> Option<Repair> {
+  let bytes = latin1_bytes(cell)?;
+
+  if let Ok(text) = std::str::from_utf8(&bytes) {
+    let multibyte = text.chars().filter(|c| !c.is_ascii()).count();
+    if multibyte == 0 {
+      return None;
+    }
+    let cjk = count_utf8_chinese_pattern(&bytes);
+    return Some(Repair {
+      kind: MojibakeKind::Utf8AsLatin1,
+      confidence: (0.6 + 0.1 * multibyte.max(cjk).min(4) as f64).min(1.0),
+      text: text.to_string(),
+    });
+  }
+
+  // 所有高位字节都必须组成 GBK 双字节字符
+  let pattern = count_gbk_pattern(&bytes);
+  let high_bytes = bytes.iter().filter(|b| **b >= 0x80).count();
+  if pattern.count * 2 != high_bytes {
+    return None;
+  }
+  let (text, had_errors) = GBK.decode_without_bom_handling(&bytes);
+  if had_errors || !text.chars().any(is_cjk) {
+    return None;
+  }
+  // 尾字节为 ASCII 的字符对更可能是巧合 (如 `Ña`), 不计入置信度
+  let pairs = bytes
+    .windows(2)
+    .filter(|w| w[0] >= 0xA1 && w[1] >= 0xA1)
+    .count()
+    .min(pattern.count);
+  Some(Repair {
+    kind: MojibakeKind::GbkAsLatin1,
+    confidence: 0.45 + 0.15 * pairs.min(4) as f64,
+    text: text.into_owned(),
+  })
+}
+
+fn repair_gbk(cell: &str) -> Option<Repair> {
+  let bytes = gbk_bytes(cell)?;
+  let text = std::str::from_utf8(&bytes).ok()?;
+  let cjk = count_utf8_chinese_pattern(&bytes);
+  if cjk == 0 || text == cell {
+    return None;
+  }
+  Some(Repair {
+    kind: MojibakeKind::Utf8AsGbk,
+    confidence: 0.5 + 0.125 * cjk.min(4) as f64,
+    text: text.to_string(),
+  })
+}
+
+/// Detect a double-encoded cell and try to round-trip it back to the original text
+pub fn repair_cell(cell: &str) -> Option<Repair> {
+  if cell.is_ascii() {
+    return None;
+  }
+
+  if cell.chars().any(is_cjk) {
+    repair_gbk(cell)
+  } else {
+    repair_latin1(cell)
+  }
+}
+
+/// Repair mojibake cells, writes `<stem>_mojibake.csv`
+/// and a `<stem>_mojibake_report.csv` listing every repaired cell
+pub async fn fix_mojibake<P>(path: P, skiprows: usize, threshold: f64) -> Result<MojibakeReport>
+where
+  P: AsRef<Path> + Send + Sync,
+{
+  let mut opts = CsvOptions::new(&path);
+  opts.set_skiprows(skiprows);
+  let (dialect, reader) = opts.skiprows_and_dialect()?;
+  let output_path = opts.output_path(Some("mojibake"), None)?;
+  let report_path = opts.output_path(Some("mojibake_report"), None)?;
+
+  let config = CsvConfigBuilder::new().dialect(&dialect).build();
+  let mut rdr = config.build_reader(reader);
+  let mut wtr = config.build_writer(&output_path)?;
+  let mut report_wtr = CsvConfigBuilder::new().build().build_writer(&report_path)?;
+  report_wtr.write_record([
+    "row",
+    "column",
+    "kind",
+    "confidence",
+    "original",
+    "repaired",
+  ])?;
+
+  let headers = rdr.headers()?.clone();
+  wtr.write_record(&headers)?;
+
+  let mut column_counts = vec![0usize; headers.len()];
+  let mut rows = 0;
+  let mut repaired_rows = 0;
+  let mut record = csv::StringRecord::new();
+  let mut output = csv::StringRecord::new();
+
+  while rdr.read_record(&mut record)? {
+    rows += 1;
+    output.clear();
+    let mut repaired = false;
+
+    for (idx, cell) in record.iter().enumerate() {
+      match repair_cell(cell).filter(|r| r.confidence >= threshold) {
+        Some(repair) => {
+          repaired = true;
+          if let Some(count) = column_counts.get_mut(idx) {
+            *count += 1;
+          }
+          report_wtr.write_record([
+            rows.to_string().as_str(),
+            headers.get(idx).unwrap_or_default(),
+            repair.kind.as_str(),
+            format!("{:.2}", repair.confidence).as_str(),
+            cell,
+            repair.text.as_str(),
+          ])?;
+          output.push_field(&repair.text);
+        }
+        None => output.push_field(cell),
+      }
+    }
+
+    if repaired {
+      repaired_rows += 1;
+    }
+    wtr.write_record(&output)?;
+  }
+
+  wtr.finish()?;
+  report_wtr.finish()?;
+
+  let columns: Vec<ColumnRepairs> = headers
+    .iter()
+    .zip(column_counts)
+    .filter(|(_, cells)| *cells > 0)
+    .map(|(column, cells)| ColumnRepairs {
+      column: column.to_string(),
+      cells,
+    })
+    .collect();
+
+  Ok(MojibakeReport {
+    rows,
+    repaired_cells: columns.iter().map(|c| c.cells).sum(),
+    repaired_rows,
+    columns,
+  })
+}
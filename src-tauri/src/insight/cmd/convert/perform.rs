@@ -22,6 +22,11 @@ pub async fn csv2csv(
   progress: bool,
   skiprows: usize,
   flexible: bool,
+  line_ending: Option<String>,
+  trim: Option<String>,
+  comment: Option<String>,
+  escape: Option<String>,
+  terminator: Option<String>,
   emitter: AppHandle,
 ) -> Result<String, String> {
   let start_time = Instant::now();
@@ -46,6 +51,11 @@ pub async fn csv2csv(
       progress,
       skiprows,
       flexible,
+      line_ending.as_deref().unwrap_or("lf"),
+      trim.as_deref().unwrap_or("none"),
+      comment.as_deref(),
+      escape.as_deref(),
+      terminator.as_deref(),
       emitter.clone(),
     )
     .await
@@ -97,6 +107,123 @@ pub async fn encoding2utf8(
   }
 }
 
+#[derive(serde::Serialize)]
+struct TranscodeResult {
+  reports: Vec<convert::transcode::TranscodeReport>,
+  elapsed_seconds: f64,
+}
+
+#[tauri::command]
+pub async fn transcode(
+  path: String,
+  from_encoding: Option<String>,
+  to_encoding: String,
+  bom: bool,
+  unmappable: String,
+  emitter: AppHandle,
+) -> Result<String, String> {
+  let start_time = Instant::now();
+  let mut reports = Vec::new();
+  let unmappable: convert::transcode::Unmappable = unmappable
+    .parse()
+    .map_err(|e: anyhow::Error| e.to_string())?;
+
+  let paths: Vec<&str> = path.split('|').collect();
+  for file in paths.iter() {
+    let opts = CsvOptions::new(file);
+    let filename = opts
+      .file_name()
+      .map_err(|e| format!("opts.file_name failed: {e}"))?;
+    emitter
+      .emit_info(filename)
+      .await
+      .map_err(|e| e.to_string())?;
+    match convert::transcode::transcode(
+      file,
+      from_encoding.as_deref(),
+      &to_encoding,
+      bom,
+      unmappable,
+    )
+    .await
+    {
+      Ok(report) => {
+        emitter
+          .emit_success(&format!("{filename}|{}", report.lossy))
+          .await
+          .map_err(|e| e.to_string())?;
+        reports.push(report);
+      }
+      Err(err) => {
+        emitter
+          .emit_err(&format!("{filename}|{err}"))
+          .await
+          .map_err(|e| e.to_string())?;
+        continue;
+      }
+    }
+  }
+
+  let elapsed_seconds = Instant::now().duration_since(start_time).as_secs_f64();
+  serde_json::to_string(&TranscodeResult {
+    reports,
+    elapsed_seconds,
+  })
+  .map_err(|e| e.to_string())
+}
+
+#[derive(serde::Serialize)]
+struct MojibakeResult {
+  reports: Vec<convert::mojibake::MojibakeReport>,
+  elapsed_seconds: f64,
+}
+
+#[tauri::command]
+pub async fn fix_mojibake(
+  path: String,
+  skiprows: usize,
+  threshold: f64,
+  emitter: AppHandle,
+) -> Result<String, String> {
+  let start_time = Instant::now();
+  let mut reports = Vec::new();
+
+  let paths: Vec<&str> = path.split('|').collect();
+  for file in paths.iter() {
+    let opts = CsvOptions::new(file);
+    let filename = opts
+      .file_name()
+      .map_err(|e| format!("opts.file_name failed: {e}"))?;
+    emitter
+      .emit_info(filename)
+      .await
+      .map_err(|e| e.to_string())?;
+    match convert::mojibake::fix_mojibake(file, skiprows, threshold).await {
+      Ok(report) => {
+        emitter
+          .emit_success(&format!("{filename}|{}", report.repaired_cells))
+          .await
+          .map_err(|e| e.to_string())?;
+        reports.push(report);
+      }
+      Err(err) => {
+        emitter
+          .emit_err(&format!("{filename}|{err}"))
+          .await
+          .map_err(|e| e.to_string())?;
+        continue;
+      }
+    }
+  }
+
+  let elapsed_seconds = Instant::now().duration_since(start_time).as_secs_f64();
+  serde_json::to_string(&MojibakeResult {
+    reports,
+    elapsed_seconds,
+  })
+  .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn csv2xlsx(
   path: String,
@@ -179,6 +306,128 @@ pub async fn dbf2csv(path: String, wtr_sep: String, emitter: AppHandle) -> Resul
   Ok(format!("{:.2}", elapsed_time))
 }
 
+async fn csv_to_table(
+  path: String,
+  skiprows: usize,
+  columns: String,
+  limit: usize,
+  format: convert::csv_to_table::TableFormat,
+  emitter: AppHandle,
+) -> Result<String, String> {
+  let start_time = Instant::now();
+
+  let paths: Vec<&str> = path.split('|').collect();
+  for file in paths.iter() {
+    let opts = CsvOptions::new(file);
+    let filename = opts
+      .file_name()
+      .map_err(|e| format!("opts.file_name failed: {e}"))?;
+    emitter
+      .emit_info(filename)
+      .await
+      .map_err(|e| e.to_string())?;
+    match convert::csv_to_table::csv_to_table(file, skiprows, &columns, limit, format).await {
+      Ok(rows) => {
+        emitter
+          .emit_success(&format!("{filename}|{rows}"))
+          .await
+          .map_err(|e| e.to_string())?;
+      }
+      Err(err) => {
+        emitter
+          .emit_err(&format!("{filename}|{err}"))
+          .await
+          .map_err(|e| e.to_string())?;
+        continue;
+      }
+    }
+  }
+
+  let end_time = Instant::now();
+  let elapsed_time = end_time.duration_since(start_time).as_secs_f64();
+  Ok(format!("{elapsed_time:.2}"))
+}
+
+#[tauri::command]
+pub async fn csv2html(
+  path: String,
+  skiprows: usize,
+  columns: String,
+  limit: usize,
+  emitter: AppHandle,
+) -> Result<String, String> {
+  let format = convert::csv_to_table::TableFormat::Html;
+  csv_to_table(path, skiprows, columns, limit, format, emitter).await
+}
+
+#[tauri::command]
+pub async fn csv2md(
+  path: String,
+  skiprows: usize,
+  columns: String,
+  limit: usize,
+  emitter: AppHandle,
+) -> Result<String, String> {
+  let format = convert::csv_to_table::TableFormat::Markdown;
+  csv_to_table(path, skiprows, columns, limit, format, emitter).await
+}
+
+#[derive(serde::Serialize)]
+struct DbfResult {
+  reports: Vec<convert::csv_to_dbf::DbfReport>,
+  elapsed_seconds: f64,
+}
+
+#[tauri::command]
+pub async fn csv2dbf(
+  path: String,
+  skiprows: usize,
+  field_types: String,
+  overflow: String,
+  codepage: String,
+  emitter: AppHandle,
+) -> Result<String, String> {
+  let start_time = Instant::now();
+  let mut reports = Vec::new();
+  let overflow: convert::csv_to_dbf::Overflow =
+    overflow.parse().map_err(|e: anyhow::Error| e.to_string())?;
+
+  let paths: Vec<&str> = path.split('|').collect();
+  for file in paths.iter() {
+    let opts = CsvOptions::new(file);
+    let filename = opts
+      .file_name()
+      .map_err(|e| format!("opts.file_name failed: {e}"))?;
+    emitter
+      .emit_info(filename)
+      .await
+      .map_err(|e| e.to_string())?;
+    match convert::csv_to_dbf::csv_to_dbf(file, skiprows, &field_types, overflow, &codepage).await {
+      Ok(report) => {
+        emitter
+          .emit_success(&format!("{filename}|{}", report.truncated))
+          .await
+          .map_err(|e| e.to_string())?;
+        reports.push(report);
+      }
+      Err(err) => {
+        emitter
+          .emit_err(&format!("{filename}|{err}"))
+          .await
+          .map_err(|e| e.to_string())?;
+        continue;
+      }
+    }
+  }
+
+  let elapsed_seconds = Instant::now().duration_since(start_time).as_secs_f64();
+  serde_json::to_string(&DbfResult {
+    reports,
+    elapsed_seconds,
+  })
+  .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn excel2csv(
   path: String,
@@ -317,6 +566,184 @@ pub async fn json2csv(path: String, wtr_sep: String, emitter: AppHandle) -> Resu
   Ok(format!("{:.2}", elapsed_time))
 }
 
+#[derive(serde::Serialize)]
+struct ArrowResult {
+  reports: Vec<convert::csv_to_arrow::ArrowReport>,
+  elapsed_seconds: f64,
+}
+
+#[tauri::command]
+pub async fn csv2arrow(
+  path: String,
+  skiprows: usize,
+  batch_size: usize,
+  infer: bool,
+  infer_rows: usize,
+  emitter: AppHandle,
+) -> Result<String, String> {
+  let start_time = Instant::now();
+  let mut reports = Vec::new();
+
+  let paths: Vec<&str> = path.split('|').collect();
+  for file in paths.iter() {
+    let opts = CsvOptions::new(file);
+    let filename = opts
+      .file_name()
+      .map_err(|e| format!("opts.file_name failed: {e}"))?;
+    emitter
+      .emit_info(filename)
+      .await
+      .map_err(|e| e.to_string())?;
+    match convert::csv_to_arrow::csv_to_arrow(file, skiprows, batch_size, infer, infer_rows).await {
+      Ok(report) => {
+        emitter
+          .emit_success(&format!("{filename}|{}", report.rows))
+          .await
+          .map_err(|e| e.to_string())?;
+        reports.push(report);
+      }
+      Err(err) => {
+        emitter
+          .emit_err(&format!("{filename}|{err}"))
+          .await
+          .map_err(|e| e.to_string())?;
+        continue;
+      }
+    }
+  }
+
+  let elapsed_seconds = Instant::now().duration_since(start_time).as_secs_f64();
+  serde_json::to_string(&ArrowResult {
+    reports,
+    elapsed_seconds,
+  })
+  .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn arrow2csv(
+  path: String,
+  wtr_sep: String,
+  emitter: AppHandle,
+) -> Result<String, String> {
+  let start_time = Instant::now();
+
+  let paths: Vec<&str> = path.split('|').collect();
+  for file in paths.iter() {
+    let opts = CsvOptions::new(file);
+    let filename = opts
+      .file_name()
+      .map_err(|e| format!("opts.file_name failed: {e}"))?;
+    emitter
+      .emit_info(filename)
+      .await
+      .map_err(|e| e.to_string())?;
+    match convert::arrow_to_csv::arrow_to_csv(file, wtr_sep.clone()).await {
+      Ok(rows) => {
+        emitter
+          .emit_success(&format!("{filename}|{rows}"))
+          .await
+          .map_err(|e| e.to_string())?;
+      }
+      Err(err) => {
+        emitter
+          .emit_err(&format!("{filename}|{err}"))
+          .await
+          .map_err(|e| e.to_string())?;
+        continue;
+      }
+    }
+  }
+
+  let end_time = Instant::now();
+  let elapsed_time = end_time.duration_since(start_time).as_secs_f64();
+  Ok(format!("{elapsed_time:.2}"))
+}
+
+#[tauri::command]
+pub async fn xml2csv(
+  path: String,
+  record_path: String,
+  wtr_sep: String,
+  emitter: AppHandle,
+) -> Result<String, String> {
+  let start_time = Instant::now();
+
+  let paths: Vec<&str> = path.split('|').collect();
+  for file in paths.iter() {
+    let opts = CsvOptions::new(file);
+    let filename = opts
+      .file_name()
+      .map_err(|e| format!("opts.file_name failed: {e}"))?;
+    emitter
+      .emit_info(filename)
+      .await
+      .map_err(|e| e.to_string())?;
+    match convert::xml_to_csv::xml_to_csv(file, &record_path, wtr_sep.clone()).await {
+      Ok(rows) => {
+        emitter
+          .emit_success(&format!("{filename}|{rows}"))
+          .await
+          .map_err(|e| e.to_string())?;
+      }
+      Err(err) => {
+        emitter
+          .emit_err(&format!("{filename}|{err}"))
+          .await
+          .map_err(|e| e.to_string())?;
+        continue;
+      }
+    }
+  }
+
+  let end_time = Instant::now();
+  let elapsed_time = end_time.duration_since(start_time).as_secs_f64();
+  Ok(format!("{elapsed_time:.2}"))
+}
+
+#[tauri::command]
+pub async fn csv2xml(
+  path: String,
+  skiprows: usize,
+  root: String,
+  row: String,
+  attributes: String,
+  emitter: AppHandle,
+) -> Result<String, String> {
+  let start_time = Instant::now();
+
+  let paths: Vec<&str> = path.split('|').collect();
+  for file in paths.iter() {
+    let opts = CsvOptions::new(file);
+    let filename = opts
+      .file_name()
+      .map_err(|e| format!("opts.file_name failed: {e}"))?;
+    emitter
+      .emit_info(filename)
+      .await
+      .map_err(|e| e.to_string())?;
+    match convert::csv_to_xml::csv_to_xml(file, skiprows, &root, &row, &attributes).await {
+      Ok(rows) => {
+        emitter
+          .emit_success(&format!("{filename}|{rows}"))
+          .await
+          .map_err(|e| e.to_string())?;
+      }
+      Err(err) => {
+        emitter
+          .emit_err(&format!("{filename}|{err}"))
+          .await
+          .map_err(|e| e.to_string())?;
+        continue;
+      }
+    }
+  }
+
+  let end_time = Instant::now();
+  let elapsed_time = end_time.duration_since(start_time).as_secs_f64();
+  Ok(format!("{elapsed_time:.2}"))
+}
+
 #[tauri::command]
 pub async fn jsonl2csv(
   path: String,
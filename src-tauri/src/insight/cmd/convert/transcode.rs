@@ -0,0 +1,221 @@
+use std::{
+  io::{BufRead, BufReader, Write},
+  path::Path,
+  str::FromStr,
+};
+
+use anyhow::{Result, anyhow};
+use encoding_rs::{Encoder, EncoderResult, Encoding, UTF_8, UTF_16BE, UTF_16LE};
+use encoding_rs_io::DecodeReaderBytesBuilder;
+use serde::Serialize;
+
+use crate::io::{
+  csv::{
+    encoding::{detect_encoding, encoding_from_name},
+    options::CsvOptions,
+  },
+  writer,
+};
+
+/// What to do with characters the target encoding cannot represent
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unmappable {
+  /// Abort, nothing is written
+  Error,
+  /// Write `?`
+  Replace,
+  /// Write an HTML numeric character reference, e.g. `&#128512;`
+  HtmlEscape,
+}
+
+impl FromStr for Unmappable {
+  type Err = anyhow::Error;
+
+  fn from_str(policy: &str) -> Result<Self> {
+    match policy {
+      "error" => Ok(Unmappable::Error),
+      "replace" => Ok(Unmappable::Replace),
+      "html" | "html_escape" => Ok(Unmappable::HtmlEscape),
+      _ => Err(anyhow!(
+        "unknown unmappable policy `{policy}`, use error, replace or html_escape"
+      )),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TranscodeReport {
+  pub from: String,
+  pub to: String,
+  pub lines: usize,
+  /// Number of characters that were replaced or escaped
+  pub lossy: usize,
+}
+
+enum Target {
+  Utf8,
+  Utf16 { big_endian: bool },
+  Legacy(Encoder),
+}
+
+impl Target {
+  fn new(encoding: &'static Encoding) -> Result<Self> {
+    if encoding == UTF_8 {
+      Ok(Target::Utf8)
+    } else if encoding == UTF_16LE || encoding == UTF_16BE {
+      // encoding_rs 不支持编码为 UTF-16, 手动处理
+      Ok(Target::Utf16 {
+        big_endian: encoding == UTF_16BE,
+      })
+    } else if encoding.output_encoding() != encoding {
+      Err(anyhow!("cannot encode to {}", encoding.name()))
+    } else {
+      Ok(Target::Legacy(encoding.new_encoder()))
+    }
+  }
+
+  fn bom(&self) -> &'static [u8] {
+    match self {
+      Target::Utf8 => b"\xEF\xBB\xBF",
+      Target::Utf16 { big_endian: false } => b"\xFF\xFE",
+      Target::Utf16 { big_endian: true } => b"\xFE\xFF",
+      Target::Legacy(_) => b"",
+    }
+  }
+
+  /// Encode `src` into `out`, returns the number of lossy characters
+  /// or the first unmappable character when the policy is `Error`
+  fn encode(
+    &mut self,
+    src: &str,
+    last: bool,
+    policy: Unmappable,
+    out: &mut Vec<u8>,
+  ) -> Result<usize, char> {
+    match self {
+      Target::Utf8 => {
+        out.extend_from_slice(src.as_bytes());
+        Ok(0)
+      }
+      Target::Utf16 { big_endian } => {
+        for unit in src.encode_utf16() {
+          if *big_endian {
+            out.extend_from_slice(&unit.to_be_bytes());
+          } else {
+            out.extend_from_slice(&unit.to_le_bytes());
+          }
+        }
+        Ok(0)
+      }
+      Target::Legacy(encoder) => {
+        let mut lossy = 0;
+        let mut src = src;
+        let mut buf = [0u8; 4096];
+        loop {
+          let (result, read, written) =
+            encoder.encode_from_utf8_without_replacement(src, &mut buf, last);
+          out.extend_from_slice(&buf[..written]);
+          src = &src[read..];
+          match result {
+            EncoderResult::InputEmpty => return Ok(lossy),
+            EncoderResult::OutputFull => {}
+            EncoderResult::Unmappable(c) => {
+              lossy += 1;
+              match policy {
+                Unmappable::Error => return Err(c),
+                Unmappable::Replace => out.push(b'?'),
+                Unmappable::HtmlEscape => {
+                  let _ = write!(out, "&#{};", c as u32);
+                }
+              }
+            }
+          }
+        }
+      }
+    }
+  }
+}
+
+/// Convert a file from its (detected or given) encoding to `to_encoding`
+pub async fn transcode<P>(
+  path: P,
+  from_encoding: Option<&str>,
+  to_encoding: &str,
+  bom: bool,
+  policy: Unmappable,
+) -> Result<TranscodeReport>
+where
+  P: AsRef<Path> + Send + Sync,
+{
+  let target_encoding = encoding_from_name(to_encoding)
+    .ok_or_else(|| anyhow!("unknown target encoding `{to_encoding}`"))?;
+  let mut target = Target::new(target_encoding)?;
+
+  let source_encoding = match from_encoding.filter(|name| !name.is_empty()) {
+    Some(name) => {
+      encoding_from_name(name).ok_or_else(|| anyhow!("unknown source encoding `{name}`"))?
+    }
+    None => {
+      let encoding_result = detect_encoding(&path.as_ref().to_string_lossy(), true)?;
+      log::info!(
+        "自动检测编码:{} (置信度:{})",
+        encoding_result.encoding,
+        encoding_result.confidence
+      );
+      encoding_from_name(&encoding_result.encoding).unwrap_or(UTF_8)
+    }
+  };
+
+  let opts = CsvOptions::new(&path);
+  let decoder = DecodeReaderBytesBuilder::new()
+    .encoding(Some(source_encoding))
+    .build(opts.open_reader()?);
+  let mut rdr = BufReader::new(decoder);
+
+  let suffix = target_encoding.name().to_ascii_lowercase();
+  let output_path = opts.output_path(Some(suffix.as_str()), None)?;
+  let mut wtr = writer::create(&output_path)?;
+
+  if bom {
+    wtr.write_all(target.bom())?;
+  }
+
+  let mut line = String::new();
+  let mut buf = Vec::new();
+  let mut lines = 0;
+  let mut lossy = 0;
+  loop {
+    line.clear();
+    buf.clear();
+    let n = rdr.read_line(&mut line)?;
+    let last = n == 0;
+    if !last {
+      lines += 1;
+    }
+
+    match target.encode(&line, last, policy, &mut buf) {
+      Ok(count) => lossy += count,
+      Err(c) => {
+        return Err(anyhow!(
+          "line {lines}: character {c:?} (U+{:04X}) cannot be encoded in {}",
+          c as u32,
+          target_encoding.name()
+        ));
+      }
+    }
+    wtr.write_all(&buf)?;
+
+    if last {
+      break;
+    }
+  }
+
+  wtr.finish()?;
+
+  Ok(TranscodeReport {
+    from: source_encoding.name().to_string(),
+    to: target_encoding.name().to_string(),
+    lines,
+    lossy,
+  })
+}
@@ -0,0 +1,287 @@
+use std::{
+  collections::HashMap,
+  io::{BufRead, BufReader, Read},
+};
+
+use anyhow::{Result, anyhow};
+use indexmap::{IndexMap, IndexSet};
+use quick_xml::{
+  Reader,
+  encoding::Decoder,
+  escape::resolve_predefined_entity,
+  events::{BytesStart, Event},
+};
+
+use crate::{
+  io::{
+    compress,
+    csv::{config::CsvConfigBuilder, options::CsvOptions},
+    writer::Finish,
+  },
+  utils::RDR_BUFFER_SIZE,
+};
+
+type Fields = IndexMap<String, String>;
+
+/// 当前记录内的状态
+struct RecordState {
+  /// 记录元素下的相对路径, 重复的兄弟元素带序号, 如 `Item_2`
+  path: Vec<String>,
+  /// 每层已出现的子元素计数
+  siblings: Vec<HashMap<String, usize>>,
+  fields: Fields,
+}
+
+impl RecordState {
+  fn column(&self) -> String {
+    self.path.join("/")
+  }
+
+  fn push_text(&mut self, column: String, text: &str) {
+    let value = self.fields.entry(column).or_default();
+    value.push_str(text);
+  }
+
+  fn push_attributes(&mut self, e: &BytesStart, decoder: Decoder) -> Result<()> {
+    let prefix = self.column();
+    for attr in e.attributes() {
+      let attr = attr?;
+      let key = String::from_utf8_lossy(attr.key.local_name().as_ref()).into_owned();
+      let column = match prefix.as_str() {
+        "" => format!("@{key}"),
+        p => format!("{p}/@{key}"),
+      };
+      self.fields.insert(
+        column,
+        attr.decode_and_unescape_value(decoder)?.into_owned(),
+      );
+    }
+    Ok(())
+  }
+
+  fn open(&mut self, name: String) {
+    let count = self
+      .siblings
+      .last_mut()
+      .map(|s| {
+        let n = s.entry(name.clone()).or_default();
+        *n += 1;
+        *n
+      })
+      .unwrap_or(1);
+    self.path.push(match count {
+      1 => name,
+      n => format!("{name}_{n}"),
+    });
+    self.siblings.push(HashMap::new());
+  }
+
+  fn close(&mut self) {
+    self.path.pop();
+    self.siblings.pop();
+  }
+}
+
+/// `Invoices/Invoice` -> ["Invoices", "Invoice"], a leading `/` anchors the path at the root
+fn parse_record_path(record_path: &str) -> Result<(bool, Vec<String>)> {
+  let absolute = record_path.starts_with('/');
+  let segments: Vec<String> = record_path
+    .split('/')
+    .filter(|s| !s.is_empty())
+    .map(String::from)
+    .collect();
+  if segments.is_empty() {
+    return Err(anyhow!("record path is empty"));
+  }
+  Ok((absolute, segments))
+}
+
+fn local_name(e: &BytesStart) -> String {
+  String::from_utf8_lossy(e.local_name().as_ref()).into_owned()
+}
+
+/// Stream every element matching `record_path` and flatten it into `column -> value`.
+///
+/// Child elements become `Parent/Child`, attributes `@attr` or `Child/@attr`,
+/// repeated siblings get a suffix (`Item`, `Item_2`, ...) and nested records are flattened.
+pub fn for_each_record<R, F>(reader: R, record_path: &str, mut f: F) -> Result<usize>
+where
+  R: BufRead,
+  F: FnMut(Fields) -> Result<()>,
+{
+  let (absolute, segments) = parse_record_path(record_path)?;
+  let is_record = |stack: &[String]| {
+    if absolute {
+      stack == segments.as_slice()
+    } else {
+      stack.ends_with(&segments)
+    }
+  };
+
+  let mut rdr = Reader::from_reader(reader);
+
+  let mut buf = Vec::new();
+  let mut stack: Vec<String> = Vec::new();
+  let mut record: Option<RecordState> = None;
+  let mut count = 0;
+
+  loop {
+    match rdr.read_event_into(&mut buf)? {
+      Event::Start(e) => match record.as_mut() {
+        Some(state) => {
+          state.open(local_name(&e));
+          state.push_attributes(&e, rdr.decoder())?;
+        }
+        None => {
+          stack.push(local_name(&e));
+          if is_record(&stack) {
+            let mut state = RecordState {
+              path: Vec::new(),
+              siblings: vec![HashMap::new()],
+              fields: Fields::new(),
+            };
+            state.push_attributes(&e, rdr.decoder())?;
+            record = Some(state);
+          }
+        }
+      },
+      Event::Empty(e) => match record.as_mut() {
+        Some(state) => {
+          state.open(local_name(&e));
+          state.push_attributes(&e, rdr.decoder())?;
+          // 没有属性的空元素也保留一列
+          if e.attributes().next().is_none() {
+            state.fields.entry(state.column()).or_default();
+          }
+          state.close();
+        }
+        None => {
+          stack.push(local_name(&e));
+          if is_record(&stack) {
+            let mut state = RecordState {
+              path: Vec::new(),
+              siblings: Vec::new(),
+              fields: Fields::new(),
+            };
+            state.push_attributes(&e, rdr.decoder())?;
+            count += 1;
+            f(state.fields)?;
+          }
+          stack.pop();
+        }
+      },
+      Event::Text(e) => {
+        if let Some(state) = record.as_mut() {
+          // 跳过元素之间的缩进
+          let text = e.xml_content()?;
+          if !text.trim().is_empty() {
+            state.push_text(text_column(state, &stack), &text);
+          }
+        }
+      }
+      Event::CData(e) => {
+        if let Some(state) = record.as_mut() {
+          let text = e.decode()?;
+          state.push_text(text_column(state, &stack), &text);
+        }
+      }
+      Event::GeneralRef(e) => {
+        if let Some(state) = record.as_mut() {
+          let text = match e.resolve_char_ref()? {
+            Some(c) => c.to_string(),
+            None => {
+              let name = e.decode()?;
+              resolve_predefined_entity(&name)
+                .map(String::from)
+                .unwrap_or_else(|| format!("&{name};"))
+            }
+          };
+          state.push_text(text_column(state, &stack), &text);
+        }
+      }
+      Event::End(_) => match record.as_mut() {
+        Some(state) if !state.path.is_empty() => state.close(),
+        Some(_) => {
+          if let Some(mut state) = record.take() {
+            count += 1;
+            for value in state.fields.values_mut() {
+              let trimmed = value.trim();
+              if trimmed.len() != value.len() {
+                *value = trimmed.to_string();
+              }
+            }
+            f(state.fields)?;
+          }
+          stack.pop();
+        }
+        None => {
+          stack.pop();
+        }
+      },
+      Event::Eof => break,
+      _ => {}
+    }
+    buf.clear();
+  }
+
+  Ok(count)
+}
+
+/// 记录元素自身的文本使用元素名作为列名
+fn text_column(state: &RecordState, stack: &[String]) -> String {
+  match state.path.is_empty() {
+    true => stack.last().cloned().unwrap_or_default(),
+    false => state.column(),
+  }
+}
+
+/// Convert XML to CSV, writes `<stem>_xml.csv`.
+///
+/// The file is read twice: once to collect the columns, once to write the rows,
+/// so memory does not grow with the input size.
+pub async fn xml_to_csv(path: &str, record_path: &str, wtr_sep: String) -> Result<usize> {
+  let sep = if wtr_sep == "\\t" {
+    b'\t'
+  } else {
+    wtr_sep.as_bytes().first().copied().unwrap_or(b',')
+  };
+  let open = || -> Result<BufReader<Box<dyn Read + Send>>> {
+    Ok(BufReader::with_capacity(
+      RDR_BUFFER_SIZE,
+      compress::open_reader(path)?,
+    ))
+  };
+
+  // 1.收集所有列 (按首次出现的顺序)
+  let mut columns: IndexSet<String> = IndexSet::new();
+  let count = for_each_record(open()?, record_path, |fields| {
+    for column in fields.into_keys() {
+      columns.insert(column);
+    }
+    Ok(())
+  })?;
+  if count == 0 {
+    return Err(anyhow!("no element matches `{record_path}`"));
+  }
+
+  // 2.写入记录
+  let opts = CsvOptions::new(path);
+  let output_path = opts.output_path(Some("xml"), None)?;
+  let mut wtr = CsvConfigBuilder::new()
+    .write_delimiter(sep)
+    .build()
+    .build_writer(output_path)?;
+  wtr.write_record(&columns)?;
+
+  for_each_record(open()?, record_path, |fields| {
+    wtr.write_record(
+      columns
+        .iter()
+        .map(|c| fields.get(c).map(String::as_str).unwrap_or_default()),
+    )?;
+    Ok(())
+  })?;
+  wtr.finish()?;
+
+  Ok(count)
+}
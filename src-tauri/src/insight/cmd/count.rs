@@ -1,19 +1,25 @@
 use std::{path::Path, time::Instant};
 
 use anyhow::Result;
-use csv::{ByteRecord, ReaderBuilder};
+use csv::ByteRecord;
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use tauri::{Emitter, Window};
 
-use crate::io::csv::options::CsvOptions;
+use crate::io::csv::{config::CsvConfigBuilder, options::CsvOptions, parallel::ParallelReader};
 
 pub async fn count_rows<P: AsRef<Path> + Send + Sync>(path: P, skiprows: usize) -> Result<u64> {
   let mut opts = CsvOptions::new(&path);
   opts.set_skiprows(skiprows);
 
+  opts.ensure_index()?;
   let count = match opts.indexed()? {
     Some(idx) => idx.count(),
-    None => count_record(true, &opts).await?,
+    // 压缩文件只能流式读取, 普通文件按块并行计数
+    None if opts.is_compressed() => count_record(true, &opts).await?,
+    None => {
+      let config = CsvConfigBuilder::new().dialect(&opts.dialect()?).build();
+      ParallelReader::from_options(&opts, &config)?.count_records(0)?
+    }
   };
 
   Ok(count)
@@ -36,12 +42,13 @@ async fn count_record<P: AsRef<Path> + Send + Sync>(
   quoting: bool,
   opts: &CsvOptions<P>,
 ) -> Result<u64> {
-  let (sep, reader) = opts.skiprows_and_delimiter()?;
+  let (dialect, reader) = opts.skiprows_and_dialect()?;
 
-  let mut rdr = ReaderBuilder::new()
-    .delimiter(sep)
+  let mut rdr = CsvConfigBuilder::new()
+    .dialect(&dialect)
     .quoting(quoting)
-    .from_reader(reader);
+    .build()
+    .build_reader(reader);
 
   let mut record = ByteRecord::new();
   let mut count: u64 = 0;
@@ -11,6 +11,7 @@ use tauri::AppHandle;
 use tokio::sync::oneshot;
 
 use crate::io::csv::{config::CsvConfigBuilder, options::CsvOptions, selection::Selection};
+use crate::io::writer::Finish;
 use crate::utils::EventEmitter;
 
 #[derive(Deserialize, Serialize)]
@@ -83,7 +84,7 @@ const DATE_FORMATS: &[&str] = &[
 ];
 
 /// 尝试将字符串解析为 NaiveDateTime (支持日期,日期时间)
-fn parse_to_naive_datetime(s: &str) -> Option<NaiveDateTime> {
+pub(crate) fn parse_to_naive_datetime(s: &str) -> Option<NaiveDateTime> {
   // 1.parse datetime
   for fmt in DATE_FORMATS {
     if let Ok(dt) = NaiveDateTime::parse_from_str(s, fmt) {
@@ -101,6 +102,13 @@ fn parse_to_naive_datetime(s: &str) -> Option<NaiveDateTime> {
   None
 }
 
+/// 按指定格式解析为 NaiveDateTime, 只有日期时时间为 00:00:00
+pub(crate) fn parse_with_format(s: &str, fmt: &str) -> Option<NaiveDateTime> {
+  NaiveDateTime::parse_from_str(s, fmt)
+    .ok()
+    .or_else(|| NaiveDate::parse_from_str(s, fmt).ok()?.and_hms_opt(0, 0, 0))
+}
+
 /// 转换 CSV 中指定列的日期格式
 ///
 /// - `path`: 输入CSV路径
@@ -123,7 +131,7 @@ where
 {
   let mut opts = CsvOptions::new(path);
   opts.set_skiprows(skiprows);
-  let (sep, reader) = opts.skiprows_and_delimiter()?;
+  let (dialect, reader) = opts.skiprows_and_dialect()?;
   let output_path = opts.output_path(Some("date"), None)?;
   let error_output_path = opts.output_path(Some("date_errors"), None)?;
 
@@ -135,7 +143,7 @@ where
 
   let config = CsvConfigBuilder::new()
     .flexible(flexible)
-    .delimiter(sep)
+    .dialect(&dialect)
     .quoting(quoting)
     .build();
 
@@ -215,14 +223,7 @@ where
 
         let parsed = if let Some(fmt) = input_formats.get(&idx) {
           // 手动格式
-          if let Ok(ndt) = NaiveDateTime::parse_from_str(cell, fmt) {
-            Some(ndt)
-          } else if let Ok(nd) = NaiveDate::parse_from_str(cell, fmt) {
-            nd.and_hms_opt(0, 0, 0)
-          } else {
-            has_error = true; // 标记该行有错误
-            None
-          }
+          parse_with_format(cell, fmt)
         } else {
           // 自动检测
           parse_to_naive_datetime(cell)
@@ -249,8 +250,8 @@ where
 
     let final_rows = rows.load(Ordering::Relaxed);
     let _ = done_tx.send(final_rows);
-    wtr.flush()?;
-    error_wtr.flush()?;
+    wtr.finish()?;
+    error_wtr.finish()?;
     Ok::<_, anyhow::Error>(())
   });
 
@@ -1,9 +1,33 @@
-use std::{collections::HashMap, time::Instant};
+use std::{
+  cmp::{self, Reverse},
+  collections::{BinaryHeap, HashMap, HashSet, hash_map::Entry},
+  fs::File,
+  hash::{BuildHasher, RandomState},
+  io::{Read, Seek, SeekFrom, Write},
+  path::Path,
+  sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+  },
+  time::{Duration, Instant},
+};
 
-use anyhow::Result;
+use anyhow::{Result, anyhow};
 use csv::{ByteRecord, Reader, Writer};
+use tauri::AppHandle;
+use tokio::sync::oneshot;
 
-use crate::io::csv::{config::CsvConfigBuilder, options::CsvOptions, selection::Selection};
+use crate::cmd::datefmt::parse_to_naive_datetime;
+use crate::io::csv::{config::CsvConfigBuilder, options::CsvOptions, selection::Selection, spill};
+use crate::io::writer::Finish;
+use crate::similarity::{Normalize, jaro_winkler};
+use crate::utils::{self, EventEmitter, MemoryPlan};
+
+/// 分区数上限, 每个分区同时打开一个临时文件
+const MAX_PARTITIONS: usize = 256;
+
+/// 一个 block 内不同的值 -> 第一次出现的行, 以及这些值的字符与行号
+type Block = (HashMap<Vec<u8>, usize>, Vec<(Vec<char>, usize)>);
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum DedupMode {
@@ -11,19 +35,148 @@ pub enum DedupMode {
   KeepLast,
   KeepDuplicates,
   Unique,
+  /// Keep the best row of every key, see `KeepBy`
+  KeepBy(KeepBy),
+}
+
+impl DedupMode {
+  /// Whether `candidate` replaces the row kept so far for the same key
+  fn replaces(&self, candidate: &ByteRecord, kept: &ByteRecord) -> bool {
+    match self {
+      DedupMode::KeepLast => true,
+      DedupMode::KeepBy(keep_by) => keep_by.better(candidate, kept),
+      _ => false,
+    }
+  }
+}
+
+/// How the values of a keep-by column are compared
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KeepValue {
+  Numeric,
+  Lexical,
+  /// Any format `datefmt` recognizes
+  Date,
+}
+
+/// A keep-by rule naming its column, resolved into `KeepBy` once the headers are read
+#[derive(Debug, Clone)]
+pub struct KeepByColumn {
+  pub column: String,
+  pub max: bool,
+  pub value: KeepValue,
+}
+
+/// Keep the row with the max (or min) value of `column` among the rows sharing a key.
+///
+/// Values that do not parse never win, ties keep the earlier row.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeepBy {
+  pub column: usize,
+  pub max: bool,
+  pub value: KeepValue,
+}
+
+#[derive(PartialEq, PartialOrd)]
+enum Value<'a> {
+  Num(f64),
+  Text(&'a [u8]),
+  Date(chrono::NaiveDateTime),
+}
+
+impl KeepBy {
+  /// Resolve `column` against `headers`
+  pub fn from_headers(
+    headers: &ByteRecord,
+    column: &str,
+    max: bool,
+    value: KeepValue,
+  ) -> Result<Self> {
+    Ok(KeepBy {
+      column: Selection::from_headers(headers, &[column])?.first_indices()?,
+      max,
+      value,
+    })
+  }
+
+  fn value<'a>(&self, record: &'a ByteRecord) -> Option<Value<'a>> {
+    let field = record.get(self.column)?;
+    match self.value {
+      KeepValue::Lexical => Some(Value::Text(field)),
+      KeepValue::Numeric => std::str::from_utf8(field)
+        .ok()?
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|f| !f.is_nan())
+        .map(Value::Num),
+      KeepValue::Date => {
+        parse_to_naive_datetime(std::str::from_utf8(field).ok()?.trim()).map(Value::Date)
+      }
+    }
+  }
+
+  fn better(&self, candidate: &ByteRecord, kept: &ByteRecord) -> bool {
+    match (self.value(candidate), self.value(kept)) {
+      (Some(a), Some(b)) => {
+        let want = match self.max {
+          true => cmp::Ordering::Greater,
+          false => cmp::Ordering::Less,
+        };
+        a.partial_cmp(&b) == Some(want)
+      }
+      (Some(_), None) => true,
+      _ => false,
+    }
+  }
+}
+
+/// Options of `cluster_ids`
+#[derive(Debug, Clone)]
+pub struct FuzzyOptions {
+  /// Column compared with Jaro-Winkler
+  pub column: String,
+  /// Minimum similarity for two rows to join the same cluster
+  pub threshold: f64,
+  /// Leading chars of every block column that must be equal, 0 compares whole values
+  pub block_prefix: usize,
+}
+
+/// Options of `dedup_csv`
+#[derive(Debug, Clone)]
+pub struct DedupOptions {
+  pub mode: DedupMode,
+  /// Duplicates are adjacent, nothing is held in memory
+  pub sorted: bool,
+  pub normalize: Normalize,
+  /// Keep the row with the max/min of a column, replaces `mode`
+  pub keep_by: Option<KeepByColumn>,
+  /// Write the dropped rows to `<stem>_dedup_discarded.csv`
+  pub audit: bool,
+  /// Bytes an unsorted dedup may hold, the available memory when not set
+  pub memory_budget: Option<usize>,
+  /// Report the rows partitioned
+  pub progress: bool,
 }
 
+/// Dedup the rows of `rdr` by the (normalised) `selection` key, returns the rows written.
+///
+/// `discarded` receives the rows dropped by KeepFirst, KeepLast and KeepBy,
+/// in their input order, with the same headers.
 pub fn dedup_csv_rows<R: std::io::Read, W: std::io::Write>(
   rdr: &mut Reader<R>,
   wtr: &mut Writer<W>,
   selection: &Selection,
+  normalize: &Normalize,
   mode: DedupMode,
   sorted: bool,
+  mut discarded: Option<&mut Writer<W>>,
 ) -> Result<usize> {
   let headers = rdr.byte_headers()?.clone();
   if mode != DedupMode::Unique {
     wtr.write_byte_record(&headers)?;
   }
+  audit_headers(&mut discarded, mode, &headers)?;
   let mut output_rows = 0;
 
   match (sorted, mode) {
@@ -31,11 +184,13 @@ pub fn dedup_csv_rows<R: std::io::Read, W: std::io::Write>(
       let mut current_key: Option<Vec<u8>> = None;
       for result in rdr.byte_records() {
         let record = result?;
-        let key = selection.get_composite_key(&record);
+        let key = normalize.composite_key(selection, &record);
         if current_key.as_ref() != Some(&key) {
           wtr.write_byte_record(&record)?;
           output_rows += 1;
           current_key = Some(key);
+        } else if let Some(d) = discarded.as_mut() {
+          d.write_byte_record(&record)?;
         }
       }
     }
@@ -46,7 +201,7 @@ pub fn dedup_csv_rows<R: std::io::Read, W: std::io::Write>(
 
       for result in rdr.byte_records() {
         let record = result?;
-        let key = selection.get_composite_key(&record);
+        let key = normalize.composite_key(selection, &record);
 
         if let Some(ref ck) = current_key {
           if ck != &key {
@@ -54,6 +209,8 @@ pub fn dedup_csv_rows<R: std::io::Read, W: std::io::Write>(
               wtr.write_byte_record(&r)?;
               output_rows += 1;
             }
+          } else if let (Some(d), Some(r)) = (discarded.as_mut(), current_record.take()) {
+            d.write_byte_record(&r)?;
           }
         }
 
@@ -67,6 +224,46 @@ pub fn dedup_csv_rows<R: std::io::Read, W: std::io::Write>(
       }
     }
 
+    (true, DedupMode::KeepBy(keep_by)) => {
+      let mut current_key: Option<Vec<u8>> = None;
+      let mut kept: Option<(usize, ByteRecord)> = None;
+      // 当前 key 被丢弃的行
+      let mut dropped: Vec<(usize, ByteRecord)> = Vec::new();
+
+      for (row, result) in rdr.byte_records().enumerate() {
+        let record = result?;
+        let key = normalize.composite_key(selection, &record);
+
+        match kept.take() {
+          Some((best_row, best)) if current_key.as_ref() == Some(&key) => {
+            let (keep, drop) = match keep_by.better(&record, &best) {
+              true => ((row, record), (best_row, best)),
+              false => ((best_row, best), (row, record)),
+            };
+            kept = Some(keep);
+            if discarded.is_some() {
+              dropped.push(drop);
+            }
+          }
+          previous => {
+            if let Some((_, r)) = previous {
+              wtr.write_byte_record(&r)?;
+              output_rows += 1;
+            }
+            write_dropped(&mut discarded, &mut dropped)?;
+            current_key = Some(key);
+            kept = Some((row, record));
+          }
+        }
+      }
+
+      if let Some((_, r)) = kept {
+        wtr.write_byte_record(&r)?;
+        output_rows += 1;
+      }
+      write_dropped(&mut discarded, &mut dropped)?;
+    }
+
     (true, DedupMode::KeepDuplicates) => {
       let mut prev_key: Option<Vec<u8>> = None;
       let mut prev_record: Option<ByteRecord> = None;
@@ -74,7 +271,7 @@ pub fn dedup_csv_rows<R: std::io::Read, W: std::io::Write>(
 
       for result in rdr.byte_records() {
         let record = result?;
-        let key = selection.get_composite_key(&record);
+        let key = normalize.composite_key(selection, &record);
 
         if let Some(ref pk) = prev_key {
           if pk == &key {
@@ -101,25 +298,45 @@ pub fn dedup_csv_rows<R: std::io::Read, W: std::io::Write>(
       let mut seen = std::collections::HashSet::new();
       for result in rdr.byte_records() {
         let record = result?;
-        let key = selection.get_composite_key(&record);
+        let key = normalize.composite_key(selection, &record);
         if seen.insert(key) {
           wtr.write_byte_record(&record)?;
           output_rows += 1;
+        } else if let Some(d) = discarded.as_mut() {
+          d.write_byte_record(&record)?;
         }
       }
     }
 
-    (false, DedupMode::KeepLast) => {
-      let mut map: HashMap<Vec<u8>, ByteRecord> = HashMap::new();
-      for result in rdr.byte_records() {
+    (false, DedupMode::KeepLast | DedupMode::KeepBy(_)) => {
+      let mut map: HashMap<Vec<u8>, (usize, ByteRecord)> = HashMap::new();
+      let mut dropped: Vec<(usize, ByteRecord)> = Vec::new();
+      for (row, result) in rdr.byte_records().enumerate() {
         let record = result?;
-        let key = selection.get_composite_key(&record);
-        map.insert(key, record);
+        let key = normalize.composite_key(selection, &record);
+        match map.entry(key) {
+          Entry::Occupied(mut e) => {
+            let drop = match mode.replaces(&record, &e.get().1) {
+              true => e.insert((row, record)),
+              false => (row, record),
+            };
+            if discarded.is_some() {
+              dropped.push(drop);
+            }
+          }
+          Entry::Vacant(e) => {
+            e.insert((row, record));
+          }
+        }
       }
-      for record in map.into_values() {
+      // 按保留行在原文件中的位置输出
+      let mut kept: Vec<_> = map.into_values().collect();
+      kept.sort_unstable_by_key(|(row, _)| *row);
+      for (_, record) in kept {
         wtr.write_byte_record(&record)?;
         output_rows += 1;
       }
+      write_dropped(&mut discarded, &mut dropped)?;
     }
 
     (false, DedupMode::KeepDuplicates) => {
@@ -129,7 +346,7 @@ pub fn dedup_csv_rows<R: std::io::Read, W: std::io::Write>(
 
       for result in rdr.byte_records() {
         let record = result?;
-        let key = selection.get_composite_key(&record);
+        let key = normalize.composite_key(selection, &record);
         *key_count.entry(key.clone()).or_insert(0) += 1;
         all_keys.push(key);
         all_records.push(record);
@@ -154,7 +371,7 @@ pub fn dedup_csv_rows<R: std::io::Read, W: std::io::Write>(
       let mut seen = std::collections::HashSet::new();
       for result in rdr.byte_records() {
         let record = result?;
-        let key = selection.get_composite_key(&record);
+        let key = normalize.composite_key(selection, &record);
         if seen.insert(key) {
           let unique_record: ByteRecord = selection
             .get_indices()
@@ -169,61 +386,684 @@ pub fn dedup_csv_rows<R: std::io::Read, W: std::io::Write>(
   }
 
   wtr.flush()?;
+  if let Some(d) = discarded {
+    d.flush()?;
+  }
   Ok(output_rows)
 }
 
-#[derive(serde::Serialize)]
-struct DedupResult {
-  output_rows: usize,
-  elapsed_seconds: f64,
-  mode: String,
+/// Only the modes keeping one row per key can be audited
+fn audit_headers<W: Write>(
+  discarded: &mut Option<&mut Writer<W>>,
+  mode: DedupMode,
+  headers: &ByteRecord,
+) -> Result<()> {
+  if let Some(d) = discarded.as_mut() {
+    if matches!(mode, DedupMode::KeepDuplicates | DedupMode::Unique) {
+      return Err(anyhow!(
+        "discarded rows are only written when keeping one row per key"
+      ));
+    }
+    d.write_byte_record(headers)?;
+  }
+  Ok(())
 }
 
-#[tauri::command]
-pub async fn dedup(
-  path: String,
-  columns: Vec<String>,
-  mode: String,
+/// Write the dropped rows in input order
+fn write_dropped<W: Write>(
+  discarded: &mut Option<&mut Writer<W>>,
+  dropped: &mut Vec<(usize, ByteRecord)>,
+) -> Result<()> {
+  if let Some(d) = discarded.as_mut() {
+    dropped.sort_unstable_by_key(|(row, _)| *row);
+    for (_, record) in dropped.iter() {
+      d.write_byte_record(record)?;
+    }
+  }
+  dropped.clear();
+  Ok(())
+}
+
+/// Number of partitions that keeps one partition of a `size` bytes file within `budget`
+pub fn partition_count(size: u64, budget: usize) -> usize {
+  let bytes = size.saturating_mul(utils::MEMORY_OVERHEAD);
+  (bytes.div_ceil(budget.max(1) as u64) as usize).clamp(2, MAX_PARTITIONS)
+}
+
+/// Same output as `dedup_csv_rows` with `sorted == false`, without holding every key in memory.
+///
+/// Records tagged with their row number are hash-partitioned by key into temp files,
+/// each partition is deduplicated on its own, then the kept rows are merged back by row number,
+/// so the output keeps the input order. `rows` counts the records read.
+pub fn partitioned_dedup<R: Read, W: Write>(
+  rdr: &mut Reader<R>,
+  wtr: &mut Writer<W>,
+  selection: &Selection,
+  normalize: &Normalize,
+  mode: DedupMode,
+  partitions: usize,
+  rows: &AtomicUsize,
+  mut discarded: Option<&mut Writer<W>>,
+) -> Result<usize> {
+  let headers = rdr.byte_headers()?.clone();
+  match mode {
+    DedupMode::Unique => wtr.write_byte_record(&project(&headers, selection, 0))?,
+    _ => wtr.write_byte_record(&headers)?,
+  }
+  audit_headers(&mut discarded, mode, &headers)?;
+
+  // 1.按 key 的哈希分区, 每条记录前加上行号
+  let partitions = partitions.max(1);
+  let hasher = RandomState::new();
+  let mut writers = (0..partitions)
+    .map(|_| spill::run_writer())
+    .collect::<Result<Vec<_>>>()?;
+  let mut record = ByteRecord::new();
+  let mut tagged = ByteRecord::new();
+  let mut row: u64 = 0;
+  while rdr.read_byte_record(&mut record)? {
+    let key = normalize.composite_key(selection, &record);
+    let p = (hasher.hash_one(&key) % partitions as u64) as usize;
+    tagged.clear();
+    tagged.push_field(row.to_string().as_bytes());
+    tagged.extend(record.iter());
+    writers[p].write_byte_record(&tagged)?;
+    row += 1;
+    rows.fetch_add(1, Ordering::Relaxed);
+  }
+  let parts = writers
+    .into_iter()
+    .map(spill::finish_run)
+    .collect::<Result<Vec<_>>>()?;
+
+  // 2.逐个分区去重, 保留与丢弃的记录仍按行号递增
+  let mut kept = Vec::with_capacity(parts.len());
+  let mut dropped = Vec::new();
+  for part in parts {
+    let (k, d) = dedup_partition(part, selection, normalize, mode, discarded.is_some())?;
+    kept.push(k);
+    dropped.extend(d);
+  }
+
+  // 3.按行号归并
+  let output_rows = merge_by_row(kept, wtr)?;
+  if let Some(d) = discarded {
+    merge_by_row(dropped, d)?;
+    d.flush()?;
+  }
+
+  wtr.flush()?;
+  Ok(output_rows)
+}
+
+/// Merge runs of tagged records sorted by row number, the tags are removed
+fn merge_by_row<W: Write>(runs: Vec<File>, wtr: &mut Writer<W>) -> Result<usize> {
+  let mut readers: Vec<_> = runs.into_iter().map(spill::read_run).collect();
+  let mut heads = vec![ByteRecord::new(); readers.len()];
+  let mut heap = BinaryHeap::new();
+  for (i, rdr) in readers.iter_mut().enumerate() {
+    if rdr.read_byte_record(&mut heads[i])? {
+      heap.push(Reverse((row_number(&heads[i])?, i)));
+    }
+  }
+
+  let mut record = ByteRecord::new();
+  let mut count = 0;
+  while let Some(Reverse((_, i))) = heap.pop() {
+    record.clear();
+    record.extend(heads[i].iter().skip(1));
+    wtr.write_byte_record(&record)?;
+    count += 1;
+    if readers[i].read_byte_record(&mut heads[i])? {
+      heap.push(Reverse((row_number(&heads[i])?, i)));
+    }
+  }
+  Ok(count)
+}
+
+/// Dedup one partition, the kept (and with `audit` the dropped) records
+/// are still tagged with their row number
+fn dedup_partition(
+  part: File,
+  selection: &Selection,
+  normalize: &Normalize,
+  mode: DedupMode,
+  audit: bool,
+) -> Result<(File, Option<File>)> {
+  let mut rdr = spill::read_run(part);
+  let mut wtr = spill::run_writer()?;
+  let mut dropped = match audit {
+    true => Some(spill::run_writer()?),
+    false => None,
+  };
+  let mut tagged = ByteRecord::new();
+
+  match mode {
+    DedupMode::KeepFirst | DedupMode::Unique => {
+      let mut seen = HashSet::new();
+      while rdr.read_byte_record(&mut tagged)? {
+        if seen.insert(normalize.composite_key(selection, &untag(&tagged))) {
+          match mode {
+            DedupMode::Unique => wtr.write_byte_record(&project(&tagged, selection, 1))?,
+            _ => wtr.write_byte_record(&tagged)?,
+          }
+        } else if let Some(d) = dropped.as_mut() {
+          d.write_byte_record(&tagged)?;
+        }
+      }
+    }
+    DedupMode::KeepLast | DedupMode::KeepBy(_) => {
+      let mut best: HashMap<Vec<u8>, (ByteRecord, ByteRecord)> = HashMap::new();
+      let mut drops = Vec::new();
+      while rdr.read_byte_record(&mut tagged)? {
+        let record = untag(&tagged);
+        match best.entry(normalize.composite_key(selection, &record)) {
+          Entry::Occupied(mut e) => {
+            let drop = match mode.replaces(&record, &e.get().1) {
+              true => e.insert((tagged.clone(), record)).0,
+              false => tagged.clone(),
+            };
+            if audit {
+              drops.push(drop);
+            }
+          }
+          Entry::Vacant(e) => {
+            e.insert((tagged.clone(), record));
+          }
+        }
+      }
+      let mut kept = best
+        .into_values()
+        .map(|(t, _)| Ok((row_number(&t)?, t)))
+        .collect::<Result<Vec<_>>>()?;
+      kept.sort_unstable_by_key(|(row, _)| *row);
+      for (_, t) in &kept {
+        wtr.write_byte_record(t)?;
+      }
+      if let Some(d) = dropped.as_mut() {
+        let mut drops = drops
+          .into_iter()
+          .map(|t| Ok((row_number(&t)?, t)))
+          .collect::<Result<Vec<_>>>()?;
+        drops.sort_unstable_by_key(|(row, _)| *row);
+        for (_, t) in &drops {
+          d.write_byte_record(t)?;
+        }
+      }
+    }
+    DedupMode::KeepDuplicates => {
+      let mut counts: HashMap<Vec<u8>, usize> = HashMap::new();
+      while rdr.read_byte_record(&mut tagged)? {
+        *counts
+          .entry(normalize.composite_key(selection, &untag(&tagged)))
+          .or_insert(0) += 1;
+      }
+      let mut part = rdr.into_inner();
+      part.seek(SeekFrom::Start(0))?;
+      let mut rdr = spill::read_run(part);
+      while rdr.read_byte_record(&mut tagged)? {
+        if counts[&normalize.composite_key(selection, &untag(&tagged))] > 1 {
+          wtr.write_byte_record(&tagged)?;
+        }
+      }
+    }
+  }
+
+  Ok((
+    spill::finish_run(wtr)?,
+    dropped.map(spill::finish_run).transpose()?,
+  ))
+}
+
+fn row_number(tagged: &ByteRecord) -> Result<u64> {
+  std::str::from_utf8(tagged.get(0).unwrap_or_default())?
+    .parse()
+    .map_err(|e| anyhow!("invalid row number in dedup partition: {e}"))
+}
+
+fn untag(tagged: &ByteRecord) -> ByteRecord {
+  tagged.iter().skip(1).collect()
+}
+
+/// Keep the tag fields (the first `tags`) and the selected columns
+fn project(record: &ByteRecord, selection: &Selection, tags: usize) -> ByteRecord {
+  let mut projected: ByteRecord = record.iter().take(tags).collect();
+  for &i in selection.get_indices() {
+    projected.push_field(record.get(i + tags).unwrap_or_default());
+  }
+  projected
+}
+
+/// Most distinct values of one block, every pair of them is compared
+pub const MAX_BLOCK_VALUES: usize = 10_000;
+
+/// Estimated heap size of a distinct (block, value) besides its bytes
+const CLUSTER_ENTRY_OVERHEAD: usize = 96;
+
+/// Chars compared with Jaro-Winkler, bytes that are not UTF-8 are compared one by one
+fn value_chars(value: &[u8]) -> Vec<char> {
+  match std::str::from_utf8(value) {
+    Ok(s) => s.chars().collect(),
+    Err(_) => value.iter().map(|&b| b as char).collect(),
+  }
+}
+
+/// Cluster near-duplicate rows instead of dropping them, returns the cluster id of every row.
+///
+/// Rows are only compared within a block sharing the (normalised, prefix-truncated) values of
+/// `block`, two rows join the same cluster when the Jaro-Winkler similarity of their normalised
+/// `fuzzy.column` values reaches `fuzzy.threshold`. Empty values are never clustered.
+/// Cluster ids start at 1 and follow the first row of each cluster.
+///
+/// Only the distinct (block, value) pairs and two ids per row are held in memory, more than
+/// `budget` bytes of them or a block over `MAX_BLOCK_VALUES` values is an error.
+pub fn cluster_ids<R: Read>(
+  rdr: &mut Reader<R>,
+  block: &Selection,
+  normalize: &Normalize,
+  fuzzy: &FuzzyOptions,
+  budget: Option<usize>,
+) -> Result<Vec<usize>> {
+  let headers = rdr.byte_headers()?.clone();
+  let column = Selection::from_headers(&headers, &[fuzzy.column.as_str()])?.first_indices()?;
+  let budget = budget.unwrap_or(usize::MAX);
+
+  let mut blocks: HashMap<Vec<u8>, Block> = HashMap::new();
+  let mut parent: Vec<usize> = Vec::new();
+  let mut bytes = 0;
+  let mut record = ByteRecord::new();
+  while rdr.read_byte_record(&mut record)? {
+    let row = parent.len();
+    parent.push(row);
+    bytes += 2 * std::mem::size_of::<usize>();
+    let value = normalize.apply(record.get(column).unwrap_or_default());
+    if value.is_empty() {
+      continue;
+    }
+    let mut key = Vec::new();
+    for (i, &idx) in block.get_indices().iter().enumerate() {
+      if i > 0 {
+        key.push(b'\0');
+      }
+      let v = normalize.apply(record.get(idx).unwrap_or_default());
+      match (fuzzy.block_prefix, std::str::from_utf8(&v)) {
+        (0, _) => key.extend_from_slice(&v),
+        (n, Ok(s)) => key.extend(s.chars().take(n).collect::<String>().bytes()),
+        (n, Err(_)) => key.extend(v.iter().take(n)),
+      }
+    }
+
+    let key_len = key.len();
+    let (seen, values) = blocks.entry(key).or_default();
+    match seen.get(value.as_ref()) {
+      Some(&first) => union(&mut parent, first, row),
+      None => {
+        if values.len() >= MAX_BLOCK_VALUES {
+          return Err(anyhow!(
+            "a block has more than {MAX_BLOCK_VALUES} distinct values at row {}, \
+             add a block column or a longer block prefix",
+            row + 1
+          ));
+        }
+        bytes += key_len + value.len() * 5 + CLUSTER_ENTRY_OVERHEAD;
+        values.push((value_chars(&value), row));
+        seen.insert(value.into_owned(), row);
+      }
+    }
+    if bytes > budget {
+      return Err(anyhow!(
+        "the values to cluster do not fit in memory at row {}, \
+         add a block column or a longer block prefix",
+        row + 1
+      ));
+    }
+  }
+
+  for (_, values) in blocks.values() {
+    for (i, (a, row_a)) in values.iter().enumerate() {
+      for (b, row_b) in &values[i + 1..] {
+        if jaro_winkler(a, b) >= fuzzy.threshold {
+          union(&mut parent, *row_a, *row_b);
+        }
+      }
+    }
+  }
+  drop(blocks);
+
+  // 根节点是簇内最小的行号, 按行顺序编号
+  let mut ids = vec![0; parent.len()];
+  let mut next_id = 0;
+  for row in 0..parent.len() {
+    let root = find(&mut parent, row);
+    if root == row {
+      next_id += 1;
+      ids[row] = next_id;
+    }
+    ids[row] = ids[root];
+  }
+  Ok(ids)
+}
+
+fn find(parent: &mut [usize], mut i: usize) -> usize {
+  while parent[i] != i {
+    parent[i] = parent[parent[i]];
+    i = parent[i];
+  }
+  i
+}
+
+/// 以较小的行号作为根
+fn union(parent: &mut [usize], a: usize, b: usize) {
+  let (ra, rb) = (find(parent, a), find(parent, b));
+  if ra != rb {
+    parent[ra.max(rb)] = ra.min(rb);
+  }
+}
+
+/// Cluster the rows of `path` with `cluster_ids`, writes `<stem>_dedup.csv` with a
+/// `cluster_id` column. `columns` are the block columns, at least one is needed.
+///
+/// The file is read twice, the first pass only keeps what `cluster_ids` needs.
+pub async fn cluster_csv<P: AsRef<Path> + Send + Sync>(
+  path: P,
+  columns: &[String],
+  normalize: Normalize,
+  fuzzy: &FuzzyOptions,
   skiprows: usize,
-  sorted: bool,
   flexible: bool,
   quoting: bool,
-) -> Result<String, String> {
-  let start_time = Instant::now();
-
+) -> Result<usize> {
+  if columns.is_empty() {
+    return Err(anyhow!(
+      "cluster mode needs at least one block column, comparing every pair of rows is too slow"
+    ));
+  }
   let mut opts = CsvOptions::new(&path);
   opts.set_skiprows(skiprows);
-  let (sep, reader) = opts.skiprows_and_delimiter().map_err(|e| e.to_string())?;
-  let output_path = opts
-    .output_path(Some("dedup"), None)
-    .map_err(|e| e.to_string())?;
+  let (dialect, reader) = opts.skiprows_and_dialect()?;
+  let output_path = opts.output_path(Some("dedup"), None)?;
 
   let config = CsvConfigBuilder::new()
     .flexible(flexible)
-    .delimiter(sep)
+    .dialect(&dialect)
     .quoting(quoting)
     .build();
 
   let mut rdr = config.build_reader(reader);
-  let mut wtr = config
-    .build_writer(&output_path)
-    .map_err(|e| e.to_string())?;
+  let column_strs: Vec<&str> = columns.iter().map(|s| s.as_str()).collect();
+  let block = Selection::from_headers(rdr.byte_headers()?, &column_strs)?;
+  let budget = match utils::memory_plan(&opts)? {
+    MemoryPlan::InMemory => None,
+    MemoryPlan::OnDisk(budget) => Some(budget),
+  };
+  let ids = cluster_ids(&mut rdr, &block, &normalize, fuzzy, budget)?;
 
-  let headers = rdr.byte_headers().map_err(|e| e.to_string())?.clone();
+  let (_, reader) = opts.skiprows_and_dialect()?;
+  let mut rdr = config.build_reader(reader);
+  let mut wtr = config.build_writer(&output_path)?;
+  let mut headers = rdr.byte_headers()?.clone();
+  headers.push_field(b"cluster_id");
+  wtr.write_byte_record(&headers)?;
+  let mut record = ByteRecord::new();
+  for id in &ids {
+    if !rdr.read_byte_record(&mut record)? {
+      return Err(anyhow!("{} changed while clustering", opts.file_name()?));
+    }
+    record.push_field(id.to_string().as_bytes());
+    wtr.write_byte_record(&record)?;
+  }
+  wtr.finish()?;
 
+  Ok(ids.len())
+}
+
+/// Dedup `path`, writes `<stem>_dedup.csv`.
+///
+/// An unsorted dedup that would not fit in the memory budget is partitioned on disk,
+/// see `DedupOptions`.
+pub async fn dedup_csv<E, P>(
+  path: P,
+  columns: &[String],
+  options: &DedupOptions,
+  skiprows: usize,
+  flexible: bool,
+  quoting: bool,
+  emitter: E,
+) -> Result<usize>
+where
+  E: EventEmitter + Send + Sync + 'static,
+  P: AsRef<Path> + Send + Sync,
+{
+  let mut opts = CsvOptions::new(&path);
+  opts.set_skiprows(skiprows);
+  let (dialect, reader) = opts.skiprows_and_dialect()?;
+  let output_path = opts.output_path(Some("dedup"), None)?;
+
+  let config = CsvConfigBuilder::new()
+    .flexible(flexible)
+    .dialect(&dialect)
+    .quoting(quoting)
+    .build();
+
+  let mut rdr = config.build_reader(reader);
+  let mut wtr = config.build_writer(&output_path)?;
+  let mut discarded = match options.audit {
+    true => Some(config.build_writer(opts.output_path(Some("dedup_discarded"), None)?)?),
+    false => None,
+  };
+
+  let headers = rdr.byte_headers()?.clone();
   let column_strs: Vec<&str> = columns.iter().map(|s| s.as_str()).collect();
-  let selection = Selection::from_headers(&headers, &column_strs).map_err(|e| e.to_string())?;
-
-  let dedup_mode = match mode.as_str() {
-    "keep_first" => DedupMode::KeepFirst,
-    "keep_last" => DedupMode::KeepLast,
-    "keep_duplicates" => DedupMode::KeepDuplicates,
-    "unique" => DedupMode::Unique,
-    _ => return Err("Invalid dedup mode".into()),
+  let selection = Selection::from_headers(&headers, &column_strs)?;
+  let mode = match &options.keep_by {
+    Some(k) => DedupMode::KeepBy(KeepBy::from_headers(&headers, &k.column, k.max, k.value)?),
+    None => options.mode,
+  };
+  let (sorted, normalize, progress) = (options.sorted, options.normalize, options.progress);
+
+  let partitions = match (sorted, options.memory_budget) {
+    (true, _) => None,
+    (false, Some(budget)) => {
+      let size = utils::uncompressed_size(&opts)?;
+      (size.saturating_mul(utils::MEMORY_OVERHEAD) > budget as u64)
+        .then(|| partition_count(size, budget))
+    }
+    (false, None) => match utils::memory_plan(&opts)? {
+      MemoryPlan::InMemory => None,
+      MemoryPlan::OnDisk(budget) => Some(partition_count(utils::uncompressed_size(&opts)?, budget)),
+    },
+  };
+
+  let total_rows = match progress && partitions.is_some() {
+    true => opts.idx_count_rows().await?,
+    false => 0,
   };
+  emitter.emit_total_rows(total_rows).await?;
+
+  let rows = Arc::new(AtomicUsize::new(0));
+  let (stop_tx, mut stop_rx) = oneshot::channel::<()>();
+  let (done_tx, mut done_rx) = oneshot::channel::<usize>();
 
-  let output_rows = dedup_csv_rows(&mut rdr, &mut wtr, &selection, dedup_mode, sorted)
-    .map_err(|e| e.to_string())?;
+  let timer_task = if progress && partitions.is_some() {
+    let rows_clone = Arc::clone(&rows);
+
+    Some(tokio::spawn(async move {
+      let mut interval = tokio::time::interval(Duration::from_millis(500));
+      loop {
+        tokio::select! {
+          _ = interval.tick() => {
+            let current_rows = rows_clone.load(Ordering::Relaxed);
+            if let Err(err) = emitter.emit_update_rows(current_rows).await {
+              let _ = emitter.emit_err(&format!("failed to emit current rows: {err}")).await;
+            }
+          },
+          Ok(final_rows) = (&mut done_rx) => {
+            if let Err(err) = emitter.emit_update_rows(final_rows).await {
+              let _ = emitter.emit_err(&format!("failed to emit final rows: {err}")).await;
+            }
+            break;
+          },
+          _ = (&mut stop_rx) => { break; }
+        }
+      }
+    }))
+  } else {
+    None
+  };
+
+  let rows_clone = Arc::clone(&rows);
+  let dedup_task = tokio::task::spawn_blocking(move || {
+    let output_rows = match partitions {
+      Some(n) => partitioned_dedup(
+        &mut rdr,
+        &mut wtr,
+        &selection,
+        &normalize,
+        mode,
+        n,
+        &rows_clone,
+        discarded.as_mut(),
+      )?,
+      None => dedup_csv_rows(
+        &mut rdr,
+        &mut wtr,
+        &selection,
+        &normalize,
+        mode,
+        sorted,
+        discarded.as_mut(),
+      )?,
+    };
+    wtr.finish()?;
+    if let Some(d) = discarded {
+      d.finish()?;
+    }
+    Ok::<_, anyhow::Error>(output_rows)
+  });
+
+  let result = dedup_task.await?;
+  match &result {
+    Ok(_) => {
+      let _ = done_tx.send(rows.load(Ordering::Relaxed));
+    }
+    Err(_) => {
+      let _ = stop_tx.send(());
+    }
+  }
+  if let Some(task) = timer_task {
+    task.await?;
+  }
+
+  result
+}
+
+#[derive(serde::Serialize)]
+struct DedupResult {
+  output_rows: usize,
+  elapsed_seconds: f64,
+  mode: String,
+}
+
+/// Arguments of the `dedup` command besides the path, columns, mode and skiprows
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(default)]
+pub struct DedupArgs {
+  sorted: bool,
+  flexible: bool,
+  quoting: bool,
+  /// MB
+  memory_budget: Option<usize>,
+  progress: bool,
+  normalize: Option<String>,
+  /// `cluster` 模式
+  fuzzy_column: Option<String>,
+  threshold: Option<f64>,
+  block_prefix: Option<usize>,
+  /// `keep_max` / `keep_min` 模式
+  keep_column: Option<String>,
+  keep_value: Option<String>,
+  audit: bool,
+}
+
+#[tauri::command]
+pub async fn dedup(
+  path: String,
+  columns: Vec<String>,
+  mode: String,
+  skiprows: usize,
+  args: DedupArgs,
+  app_handle: AppHandle,
+) -> Result<String, String> {
+  let start_time = Instant::now();
+
+  let normalize =
+    Normalize::parse(args.normalize.as_deref().unwrap_or_default()).map_err(|e| e.to_string())?;
+
+  let output_rows = if mode == "cluster" {
+    let fuzzy = FuzzyOptions {
+      column: args
+        .fuzzy_column
+        .ok_or("cluster mode needs a fuzzy column")?,
+      threshold: args.threshold.unwrap_or(0.9),
+      block_prefix: args.block_prefix.unwrap_or(0),
+    };
+    cluster_csv(
+      path,
+      &columns,
+      normalize,
+      &fuzzy,
+      skiprows,
+      args.flexible,
+      args.quoting,
+    )
+    .await
+    .map_err(|e| e.to_string())?
+  } else {
+    let dedup_mode = match mode.as_str() {
+      "keep_first" | "keep_max" | "keep_min" => DedupMode::KeepFirst,
+      "keep_last" => DedupMode::KeepLast,
+      "keep_duplicates" => DedupMode::KeepDuplicates,
+      "unique" => DedupMode::Unique,
+      _ => return Err("Invalid dedup mode".into()),
+    };
+    let keep_by = match mode.as_str() {
+      "keep_max" | "keep_min" => Some(KeepByColumn {
+        column: args
+          .keep_column
+          .ok_or("keep_max/keep_min needs a keep column")?,
+        max: mode == "keep_max",
+        value: match args.keep_value.as_deref().unwrap_or("numeric") {
+          "numeric" => KeepValue::Numeric,
+          "lexical" => KeepValue::Lexical,
+          "date" => KeepValue::Date,
+          v => return Err(format!("Invalid keep value `{v}`")),
+        },
+      }),
+      _ => None,
+    };
+
+    let options = DedupOptions {
+      mode: dedup_mode,
+      sorted: args.sorted,
+      normalize,
+      keep_by,
+      audit: args.audit,
+      // memory_budget 单位为 MB
+      memory_budget: args.memory_budget.map(|mb| mb * 1024 * 1024),
+      progress: args.progress,
+    };
+    dedup_csv(
+      path,
+      &columns,
+      &options,
+      skiprows,
+      args.flexible,
+      args.quoting,
+      app_handle,
+    )
+    .await
+    .map_err(|e| e.to_string())?
+  };
 
   let elapsed = Instant::now().duration_since(start_time).as_secs_f64();
 
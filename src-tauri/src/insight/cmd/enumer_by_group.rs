@@ -11,6 +11,7 @@ use tokio::sync::oneshot;
 use crate::io::csv::config::CsvConfigBuilder;
 use crate::io::csv::options::CsvOptions;
 use crate::io::csv::selection::Selection;
+use crate::io::writer::Finish;
 use crate::utils::EventEmitter;
 
 async fn enumerate_by_group<E, P>(
@@ -29,7 +30,7 @@ where
 {
   let mut opts = CsvOptions::new(&path);
   opts.set_skiprows(skiprows);
-  let (sep, reader) = opts.skiprows_and_delimiter()?;
+  let (dialect, reader) = opts.skiprows_and_dialect()?;
   let output_path = opts.output_path(Some("group_enum"), None)?;
 
   let total_rows = if progress {
@@ -41,7 +42,7 @@ where
 
   let config = CsvConfigBuilder::new()
     .flexible(flexible)
-    .delimiter(sep)
+    .dialect(&dialect)
     .quoting(quoting)
     .build();
 
@@ -107,7 +108,7 @@ where
 
     let final_count = rows_done.load(Ordering::Relaxed);
     let _ = done_tx.send(final_count);
-    wtr.flush()?;
+    wtr.finish()?;
     Ok::<_, anyhow::Error>(())
   });
 
@@ -133,12 +134,12 @@ where
 {
   let mut opts = CsvOptions::new(&path);
   opts.set_skiprows(skiprows);
-  let (sep, reader) = opts.skiprows_and_delimiter()?;
+  let (dialect, reader) = opts.skiprows_and_dialect()?;
   let output_path = opts.output_path(Some("group_enum"), None)?;
 
   let config = CsvConfigBuilder::new()
     .flexible(flexible)
-    .delimiter(sep)
+    .dialect(&dialect)
     .quoting(quoting)
     .build();
 
@@ -183,8 +184,7 @@ where
     wtr.write_byte_record(&out_record)?;
   }
 
-  wtr.flush()?;
-  Ok(())
+  wtr.finish()
 }
 
 #[tauri::command]
@@ -6,10 +6,11 @@ use std::{
 };
 
 use anyhow::{Result, anyhow};
-use csv::{ByteRecord, ReaderBuilder, WriterBuilder};
+use csv::ByteRecord;
 use ext_sort::{ExternalSorter, ExternalSorterBuilder, LimitedBufferBuilder};
 
-use crate::io::csv::{options::CsvOptions, selection::Selection};
+use crate::io::csv::{config::CsvConfigBuilder, options::CsvOptions, selection::Selection};
+use crate::io::writer::Finish;
 use crate::utils;
 
 const RW_BUFFER_CAPACITY: usize = 1_000_000; // 1 MB
@@ -24,8 +25,13 @@ pub async fn sort_csv(
   quoting: bool,
 ) -> Result<()> {
   let opts = CsvOptions::new(&path);
-  let (sep, reader) = opts.skiprows_and_delimiter()?;
+  let (dialect, reader) = opts.skiprows_and_dialect()?;
+  let config = CsvConfigBuilder::new()
+    .dialect(&dialect)
+    .quoting(quoting)
+    .build();
 
+  opts.ensure_index()?;
   let mut idxfile = match opts.indexed() {
     Ok(idx) => {
       if idx.is_none() {
@@ -38,10 +44,7 @@ pub async fn sort_csv(
     }
   };
 
-  let mut input_rdr = ReaderBuilder::new()
-    .delimiter(sep)
-    .quoting(quoting)
-    .from_reader(reader);
+  let mut input_rdr = config.build_reader(reader);
 
   let linewtr_tfile = tempfile::NamedTempFile::new_in(tmp_dir)?;
   let mut line_wtr = BufWriter::with_capacity(RW_BUFFER_CAPACITY, linewtr_tfile.as_file());
@@ -115,7 +118,7 @@ pub async fn sort_csv(
 
   let output_path = opts.output_path(Some("extsort"), None)?;
 
-  let mut sorted_csv_wtr = WriterBuilder::new().delimiter(sep).from_path(output_path)?;
+  let mut sorted_csv_wtr = config.build_writer(output_path)?;
 
   sorted_csv_wtr.write_byte_record(&headers)?;
 
@@ -135,7 +138,7 @@ pub async fn sort_csv(
     idxfile.read_byte_record(&mut record_wrk)?;
     sorted_csv_wtr.write_byte_record(&record_wrk)?;
   }
-  sorted_csv_wtr.flush()?;
+  sorted_csv_wtr.finish()?;
   drop(sorted_line_wtr);
   sorted_tfile.close()?;
 
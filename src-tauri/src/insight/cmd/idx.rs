@@ -1,18 +1,198 @@
 use std::{
-  fs::File,
-  io::BufWriter,
-  path::{Path, PathBuf},
+  fs::{self, File},
+  io::{self, BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+  path::Path,
   time::Instant,
 };
 
-use anyhow::Result;
-use csv::ReaderBuilder;
-use csv_index::RandomAccessSimple;
+use anyhow::{Result, anyhow};
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use serde::Serialize;
 use tauri::{Emitter, Window};
 
-use crate::{io::csv::options::CsvOptions, utils::WTR_BUFFER_SIZE};
+use crate::{
+  index::{self, INDEX_VERSION, IndexMeta, IndexStatus},
+  io::{
+    csv::{config::CsvConfigBuilder, options::CsvOptions},
+    writer,
+  },
+  utils::{RDR_BUFFER_SIZE, WTR_BUFFER_SIZE},
+};
+
+/// Byte offset of the first row after `skiprows` lines
+fn skiprows_offset(file: &mut File, skiprows: usize) -> Result<u64> {
+  file.seek(SeekFrom::Start(0))?;
+  let mut reader = BufReader::with_capacity(RDR_BUFFER_SIZE, &mut *file);
+  let mut offset = 0;
+  let mut line = Vec::new();
+  for i in 0..skiprows {
+    line.clear();
+    let n = reader.read_until(b'\n', &mut line)?;
+    if n == 0 {
+      return Err(anyhow!(
+        "File ended at line {i} while skipping {skiprows} rows"
+      ));
+    }
+    offset += n as u64;
+  }
+  Ok(offset)
+}
+
+/// Write the big-endian start offset of every record (header included) read by `rdr`,
+/// `base` is the position of `rdr` in the file. Returns the number of records.
+fn write_offsets<R: Read, W: Write>(
+  rdr: &mut csv::Reader<R>,
+  base: u64,
+  wtr: &mut W,
+) -> Result<u64> {
+  let mut record = csv::ByteRecord::new();
+  let mut records = 0;
+  while rdr.read_byte_record(&mut record)? {
+    let pos = record.position().map_or(0, |p| p.byte());
+    wtr.write_all(&(base + pos).to_be_bytes())?;
+    records += 1;
+  }
+  Ok(records)
+}
+
+/// Build `<file>.idx` and its `.idx.meta`, record offsets are absolute positions in the file
+pub fn build_index<P: AsRef<Path> + Send + Sync>(
+  opts: &CsvOptions<P>,
+  quoting: bool,
+  flexible: bool,
+) -> Result<IndexMeta> {
+  if opts.is_compressed() {
+    return Err(anyhow!(
+      "cannot create an index for a compressed file, decompress it first"
+    ));
+  }
+  let dialect = opts.dialect()?;
+  let skiprows = opts.get_skip_rows();
+
+  let mut file = File::open(opts.file_path()?)?;
+  let size = file.metadata()?.len();
+  let (mtime, head_hash, tail_hash) = index::fingerprint(&mut file, size)?;
+  let samples = index::sample_hashes(&mut file, size)?;
+
+  let offset = skiprows_offset(&mut file, skiprows)?;
+  file.seek(SeekFrom::Start(offset))?;
+  let mut rdr = CsvConfigBuilder::new()
+    .dialect(&dialect)
+    .quoting(quoting)
+    .flexible(flexible)
+    .has_headers(false)
+    .build()
+    .build_reader(BufReader::with_capacity(RDR_BUFFER_SIZE, file));
+
+  // 写入临时文件, 完成后替换旧的 .idx
+  let idx_path = opts.idx_path();
+  let (idx_file, temp) = writer::temp_file(&idx_path)?.into_parts();
+  let mut wtr = BufWriter::with_capacity(WTR_BUFFER_SIZE, idx_file);
+  let records = write_offsets(&mut rdr, offset, &mut wtr)?;
+  wtr.write_all(&records.to_be_bytes())?;
+  wtr.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+  remove_meta(&idx_path)?;
+  temp.persist(&idx_path)?;
+
+  let meta = IndexMeta {
+    version: INDEX_VERSION,
+    size,
+    mtime,
+    head_hash,
+    tail_hash,
+    samples,
+    skiprows,
+    delimiter: dialect.delimiter as char,
+    quote: dialect.quote as char,
+    escape: dialect.escape.map(char::from),
+    comment: dialect.comment.map(char::from),
+    quoting,
+    flexible,
+    records,
+  };
+  meta.write(&idx_path)?;
+
+  Ok(meta)
+}
+
+/// 替换 .idx 前删除旧的 meta, 中断时留下的索引不会被当作有效
+fn remove_meta(idx_path: &Path) -> Result<()> {
+  match fs::remove_file(index::meta_path(idx_path)) {
+    Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+    _ => Ok(()),
+  }
+}
+
+/// Extend the index of a file that only grew since `meta` was written.
+///
+/// The last indexed record is parsed again, it may have been incomplete when indexed.
+/// `IndexMeta::status` must have reported `Appended`, the index is rebuilt
+/// when a sampled block of the indexed part changed.
+pub fn append_index<P: AsRef<Path> + Send + Sync>(
+  opts: &CsvOptions<P>,
+  meta: &IndexMeta,
+) -> Result<IndexMeta> {
+  if meta.records == 0 {
+    return build_index(opts, meta.quoting, meta.flexible);
+  }
+
+  let mut file = File::open(opts.file_path()?)?;
+  if !meta.verify_samples(&mut file)? {
+    log::info!(
+      "rebuild index of {}: file content changed",
+      opts.file_name()?
+    );
+    return build_index(opts, meta.quoting, meta.flexible);
+  }
+  let size = file.metadata()?.len();
+  let (mtime, head_hash, tail_hash) = index::fingerprint(&mut file, size)?;
+  let samples = index::sample_hashes(&mut file, size)?;
+
+  // 在 .idx 的副本上扩展, 完成后替换, 出错时原索引不变
+  let idx_path = opts.idx_path();
+  let mut idx_file = writer::temp_file(&idx_path)?;
+  io::copy(&mut File::open(&idx_path)?, &mut idx_file)?;
+  let (mut idx_file, temp) = idx_file.into_parts();
+  let last = (meta.records - 1) * 8;
+  let mut buf = [0u8; 8];
+  idx_file.seek(SeekFrom::Start(last))?;
+  idx_file.read_exact(&mut buf)?;
+  let offset = u64::from_be_bytes(buf);
+  // 去掉最后一条记录的偏移和记录数, 从最后一条记录重新解析
+  idx_file.set_len(last)?;
+  idx_file.seek(SeekFrom::End(0))?;
 
+  file.seek(SeekFrom::Start(offset))?;
+  let mut rdr = CsvConfigBuilder::new()
+    .dialect(&opts.dialect()?)
+    .quoting(meta.quoting)
+    .flexible(meta.flexible)
+    .has_headers(false)
+    .build()
+    .build_reader(BufReader::with_capacity(RDR_BUFFER_SIZE, file));
+
+  let mut wtr = BufWriter::with_capacity(WTR_BUFFER_SIZE, idx_file);
+  let records = meta.records - 1 + write_offsets(&mut rdr, offset, &mut wtr)?;
+  wtr.write_all(&records.to_be_bytes())?;
+  wtr.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+  remove_meta(&idx_path)?;
+  temp.persist(&idx_path)?;
+
+  let meta = IndexMeta {
+    size,
+    mtime,
+    head_hash,
+    tail_hash,
+    samples,
+    records,
+    ..meta.clone()
+  };
+  meta.write(&idx_path)?;
+
+  Ok(meta)
+}
+
+/// Create `<file>.idx`, an index of a file that only grew is extended instead of rebuilt
 pub async fn create_index<P: AsRef<Path> + Send + Sync>(
   path: P,
   quoting: bool,
@@ -21,22 +201,50 @@ pub async fn create_index<P: AsRef<Path> + Send + Sync>(
 ) -> Result<()> {
   let mut opts = CsvOptions::new(&path);
   opts.set_skiprows(skiprows);
-  let (sep, reader) = opts.skiprows_and_delimiter()?;
-  let file_name = opts.file_name()?;
-  let mut output_path = PathBuf::from(opts.parent_path()?);
-  output_path.push(format!("{file_name}.idx"));
+  if !opts.is_compressed() {
+    let health = index_health(&opts)?;
+    if let (IndexStatus::Appended(_), Some(meta)) = (&health.status, &health.meta)
+      && meta.quoting == quoting
+      && meta.flexible == flexible
+    {
+      append_index(&opts, meta)?;
+      return Ok(());
+    }
+  }
+  build_index(&opts, quoting, flexible)?;
 
-  let mut rdr = ReaderBuilder::new()
-    .delimiter(sep)
-    .quoting(quoting)
-    .flexible(flexible)
-    .from_reader(reader);
+  Ok(())
+}
 
-  let mut wtr = BufWriter::with_capacity(WTR_BUFFER_SIZE, File::create(output_path)?);
+#[derive(Debug, Serialize)]
+pub struct IndexHealth {
+  pub file: String,
+  #[serde(flatten)]
+  pub status: IndexStatus,
+  pub meta: Option<IndexMeta>,
+}
 
-  RandomAccessSimple::create(&mut rdr, &mut wtr)?;
+/// Check whether `<file>.idx` can be used with `skiprows`, without rebuilding it
+pub fn index_health<P: AsRef<Path> + Send + Sync>(opts: &CsvOptions<P>) -> Result<IndexHealth> {
+  let file = opts.file_name()?.to_string();
+  if !opts.idx_path().exists() {
+    return Ok(IndexHealth {
+      file,
+      status: IndexStatus::Missing,
+      meta: None,
+    });
+  }
+  let meta = IndexMeta::read(&opts.idx_path())?;
+  let status = match &meta {
+    None => IndexStatus::Unversioned,
+    Some(meta) => meta.status(
+      Path::new(opts.file_path()?),
+      opts.get_skip_rows(),
+      &opts.dialect()?,
+    )?,
+  };
 
-  Ok(())
+  Ok(IndexHealth { file, status, meta })
 }
 
 async fn single_process(
@@ -51,7 +259,7 @@ async fn single_process(
 
   window.emit("info", filename).map_err(|e| e.to_string())?;
 
-  match create_index(file, quoting, false, skiprows,).await {
+  match create_index(file, quoting, false, skiprows).await {
     Ok(_) => {
       let end_time = Instant::now();
       let elapsed_time = end_time.duration_since(start_time).as_secs_f64();
@@ -133,3 +341,16 @@ pub async fn csv_idx(
   let elapsed_time = end_time.duration_since(start_time).as_secs_f64();
   Ok(format!("{elapsed_time:.0}"))
 }
+
+/// Inspect the index of every file in `path` (separated by `|`), returns a JSON array
+#[tauri::command]
+pub async fn idx_health(path: String, skiprows: usize) -> Result<String, String> {
+  let mut reports = Vec::new();
+  for file in path.split('|').filter(|p| !p.is_empty()) {
+    let mut opts = CsvOptions::new(file);
+    opts.set_skiprows(skiprows);
+    reports.push(index_health(&opts).map_err(|e| format!("{file}|{e}"))?);
+  }
+
+  serde_json::to_string(&reports).map_err(|e| e.to_string())
+}
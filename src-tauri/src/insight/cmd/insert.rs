@@ -14,6 +14,7 @@ use tauri::AppHandle;
 use tokio::sync::oneshot;
 
 use crate::io::csv::{config::CsvConfigBuilder, options::CsvOptions, selection::Selection};
+use crate::io::writer::Finish;
 use crate::utils::EventEmitter;
 
 fn is_relative_position(pos: &str) -> bool {
@@ -40,7 +41,7 @@ where
 {
   let mut opts = CsvOptions::new(path);
   opts.set_skiprows(skiprows);
-  let (sep, reader) = opts.skiprows_and_delimiter()?;
+  let (dialect, reader) = opts.skiprows_and_dialect()?;
   let output_path = opts.output_path(Some("insert"), None)?;
 
   let total_rows = if progress {
@@ -52,7 +53,7 @@ where
 
   let config = CsvConfigBuilder::new()
     .flexible(flexible)
-    .delimiter(sep)
+    .dialect(&dialect)
     .quoting(quoting)
     .build();
 
@@ -186,7 +187,7 @@ where
     }
     let final_rows = rows.load(Ordering::Relaxed);
     let _ = done_tx.send(final_rows);
-    Ok::<_, anyhow::Error>(wtr.flush()?)
+    wtr.finish()
   });
 
   counter_task.await??;
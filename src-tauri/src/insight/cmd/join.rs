@@ -9,12 +9,12 @@ use std::{
   time::Instant,
 };
 
-use anyhow::Result;
+use anyhow::{Result, anyhow};
 use byteorder::{BigEndian, WriteBytesExt};
-use csv::{ReaderBuilder, WriterBuilder};
 
 use crate::index::Indexed;
-use crate::io::csv::{options::CsvOptions, selection::Selection};
+use crate::io::csv::{config::CsvConfigBuilder, options::CsvOptions, selection::Selection};
+use crate::io::writer::{Finish, OutputWriter};
 
 type ByteString = Vec<u8>;
 
@@ -27,7 +27,7 @@ struct IoState<R, W: Write> {
   nulls: bool,
 }
 
-impl<R: Read + Seek, W: Write> IoState<R, W> {
+impl<R: Read + Seek> IoState<R, OutputWriter> {
   fn write_headers(&mut self, extend: bool) -> Result<()> {
     if extend {
       let mut headers = self.rdr1.byte_headers()?.clone();
@@ -60,7 +60,7 @@ impl<R: Read + Seek, W: Write> IoState<R, W> {
         }
       }
     }
-    Ok(())
+    self.wtr.finish()
   }
 
   fn outer_join(mut self, right: bool) -> Result<()> {
@@ -97,7 +97,7 @@ impl<R: Read + Seek, W: Write> IoState<R, W> {
         }
       }
     }
-    Ok(())
+    self.wtr.finish()
   }
 
   fn left_join(mut self, anti: bool) -> Result<()> {
@@ -115,9 +115,8 @@ impl<R: Read + Seek, W: Write> IoState<R, W> {
         self.wtr.write_record(&row)?;
       }
     }
-    self.wtr.flush()?;
 
-    Ok(())
+    self.wtr.finish()
   }
 
   fn full_outer_join(mut self) -> Result<()> {
@@ -155,7 +154,7 @@ impl<R: Read + Seek, W: Write> IoState<R, W> {
         self.wtr.write_record(pad1.iter().chain(&scratch))?;
       }
     }
-    Ok(())
+    self.wtr.finish()
   }
 
   fn cross_join(mut self) -> Result<()> {
@@ -174,7 +173,7 @@ impl<R: Read + Seek, W: Write> IoState<R, W> {
         self.wtr.write_record(row1.iter().chain(&row2))?;
       }
     }
-    Ok(())
+    self.wtr.finish()
   }
 
   fn get_padding(&mut self) -> Result<(csv::ByteRecord, csv::ByteRecord)> {
@@ -194,27 +193,31 @@ fn new_io_state<P: AsRef<Path> + Send + Sync>(
   sel2: String,
   nulls: bool,
   quoting: bool,
-) -> Result<IoState<File, Box<dyn Write + 'static>>> {
+) -> Result<IoState<File, OutputWriter>> {
   let opts1 = CsvOptions::new(&path1);
-  let sep1 = opts1.detect_separator()?;
+  let dialect1 = opts1.dialect()?;
   let opts2 = CsvOptions::new(&path2);
-  let sep2 = opts2.detect_separator()?;
+  let dialect2 = opts2.dialect()?;
   let output_path = opts1.output_path(Some("join"), None)?;
+  // join需要随机读取(Seek), 压缩文件不支持
+  if opts1.is_compressed() || opts2.is_compressed() {
+    return Err(anyhow!(
+      "join does not support compressed input, decompress it first"
+    ));
+  }
 
-  let mut rdr1 = ReaderBuilder::new()
-    .delimiter(sep1)
+  let config1 = CsvConfigBuilder::new()
+    .dialect(&dialect1)
     .quoting(quoting)
-    .from_reader(File::open(&path1)?);
-  let mut rdr2 = ReaderBuilder::new()
-    .delimiter(sep2)
+    .build();
+  let mut rdr1 = config1.build_reader(File::open(&path1)?);
+  let mut rdr2 = CsvConfigBuilder::new()
+    .dialect(&dialect2)
     .quoting(quoting)
-    .from_reader(File::open(&path2)?);
+    .build()
+    .build_reader(File::open(&path2)?);
 
-  let boxed_writer: Box<dyn Write> = Box::new(File::create(output_path)?);
-
-  let wtr = WriterBuilder::new()
-    .delimiter(sep1)
-    .from_writer(boxed_writer);
+  let wtr = config1.build_writer(output_path)?;
 
   let sel1 = Selection::from_headers(rdr1.byte_headers()?, &[sel1.as_str()][..])?;
   let sel2 = Selection::from_headers(rdr2.byte_headers()?, &[sel2.as_str()][..])?;
@@ -378,7 +381,7 @@ pub async fn join(
   sel2: String,
   join_type: String,
   nulls: bool,
-  quoting: bool
+  quoting: bool,
 ) -> Result<String, String> {
   let start_time = Instant::now();
 
@@ -14,7 +14,10 @@ use tauri::AppHandle;
 use tokio::sync::oneshot;
 
 use crate::{
-  io::csv::{config::CsvConfigBuilder, options::CsvOptions, selection::Selection},
+  io::{
+    csv::{config::CsvConfigBuilder, options::CsvOptions, selection::Selection},
+    writer::Finish,
+  },
   utils::EventEmitter,
 };
 
@@ -40,7 +43,7 @@ where
 {
   let mut opts = CsvOptions::new(&path);
   opts.set_skiprows(skiprows);
-  let (sep, reader) = opts.skiprows_and_delimiter()?;
+  let (dialect, reader) = opts.skiprows_and_dialect()?;
   let output_path = opts.output_path(Some("pinyin"), None)?;
 
   let total_rows = match progress {
@@ -51,7 +54,7 @@ where
 
   let config = CsvConfigBuilder::new()
     .flexible(flexible)
-    .delimiter(sep)
+    .dialect(&dialect)
     .quoting(quoting)
     .build();
 
@@ -137,7 +140,7 @@ where
 
     let final_rows = rows.load(Ordering::Relaxed);
     let _ = done_tx.send(final_rows);
-    Ok::<_, anyhow::Error>(wtr.flush()?)
+    wtr.finish()
   });
 
   counter_task.await??;
@@ -13,7 +13,10 @@ use tauri::AppHandle;
 use tokio::sync::oneshot;
 
 use crate::{
-  io::csv::{config::CsvConfigBuilder, options::CsvOptions},
+  io::{
+    csv::{config::CsvConfigBuilder, options::CsvOptions},
+    writer::Finish,
+  },
   utils::EventEmitter,
 };
 
@@ -32,7 +35,7 @@ where
 {
   let mut opts = CsvOptions::new(&path);
   opts.set_skiprows(skiprows);
-  let (sep, reader) = opts.skiprows_and_delimiter()?;
+  let (dialect, reader) = opts.skiprows_and_dialect()?;
   let output_path = opts.output_path(Some("rename"), None)?;
 
   let total_rows = match progress {
@@ -43,7 +46,7 @@ where
 
   let config = CsvConfigBuilder::new()
     .flexible(flexible)
-    .delimiter(sep)
+    .dialect(&dialect)
     .quoting(quoting)
     .build();
 
@@ -96,7 +99,7 @@ where
 
     let final_rows = rows.load(Ordering::Relaxed);
     let _ = done_tx.send(final_rows);
-    Ok::<_, anyhow::Error>(wtr.flush()?)
+    wtr.finish()
   });
 
   counter_task.await??;
@@ -1,8 +1,6 @@
 use std::{
   borrow::Cow,
-  fs::File,
-  io::{BufReader, BufWriter, Cursor, Read},
-  path::PathBuf,
+  io::{BufReader, Read},
   sync::{
     Arc,
     atomic::{AtomicUsize, Ordering},
@@ -11,24 +9,24 @@ use std::{
 };
 
 use anyhow::Result;
-use csv::{ByteRecord, ReaderBuilder, WriterBuilder};
-use rayon::{
-  ThreadPoolBuilder,
-  iter::{IntoParallelIterator, ParallelIterator},
-};
+use csv::ByteRecord;
 use regex::bytes::RegexBuilder;
 use tauri::AppHandle;
-use tempfile::TempDir;
 use tokio::sync::oneshot;
 
 use crate::{
-  io::csv::{config::CsvConfigBuilder, options::CsvOptions, selection::Selection},
-  utils::{self, EventEmitter, MmapOffsets},
+  io::{
+    csv::{
+      config::CsvConfigBuilder, options::CsvOptions, parallel::ParallelReader, selection::Selection,
+    },
+    writer::{Finish, OutputWriter},
+  },
+  utils::EventEmitter,
 };
 
 pub async fn regex_replace<E>(
   mut rdr: csv::Reader<BufReader<Box<dyn Read + Send>>>,
-  mut wtr: csv::Writer<BufWriter<File>>,
+  mut wtr: csv::Writer<OutputWriter>,
   opts: CsvOptions<String>,
   column: String,
   regex_pattern: String,
@@ -111,7 +109,7 @@ where
 
     let final_rows = rows.load(Ordering::Relaxed);
     let _ = done_tx.send(final_rows);
-    Ok::<_, anyhow::Error>(wtr.flush()?)
+    wtr.finish()
   });
 
   counter_task.await??;
@@ -125,7 +123,7 @@ where
 }
 
 pub fn regex_replace_parallel(
-  mut wtr: csv::Writer<BufWriter<File>>,
+  mut wtr: csv::Writer<OutputWriter>,
   opts: CsvOptions<String>,
   column: String,
   pattern: String,
@@ -133,178 +131,56 @@ pub fn regex_replace_parallel(
   threads: usize,
 ) -> Result<String> {
   let pattern = RegexBuilder::new(&pattern).build()?;
-  let sep = opts.get_delimiter()?;
-  let total_data_rows = opts
-    .indexed()?
-    .ok_or_else(|| anyhow::anyhow!("No indexed file, create index first"))?
-    .count() as usize;
-  if total_data_rows == 0 {
-    return Ok("0".to_string());
-  }
+  let config = CsvConfigBuilder::new().dialect(&opts.dialect()?).build();
+  let rdr = ParallelReader::from_options(&opts, &config)?;
 
-  let offsets = Arc::new(MmapOffsets::from_file(opts.idx_path())?);
-  let total_records = offsets.len();
-  if total_records != total_data_rows + 1 {
-    anyhow::bail!(
-      "Index inconsistency: total_records={} vs data_rows={}",
-      total_records,
-      total_data_rows
-    );
-  }
-
-  let csv_file = File::open(opts.file_path()?)?;
-  let csv_mmap = unsafe { memmap2::Mmap::map(&csv_file)? };
-  let csv_mmap = Arc::new(csv_mmap);
-
-  // Parse header
-  let header_start = offsets.get(0) as usize;
-  let header_end = offsets.get(1) as usize;
-  if header_start > header_end || header_end > csv_mmap.len() {
-    anyhow::bail!(
-      "Invalid header range: [{}..{}) in file of size {}",
-      header_start,
-      header_end,
-      csv_mmap.len()
-    );
-  }
-
-  let header_slice = &csv_mmap[header_start..header_end];
-  let mut header_reader = ReaderBuilder::new()
-    .has_headers(false)
-    .delimiter(sep)
-    .from_reader(Cursor::new(header_slice));
-  let raw_header = header_reader
-    .byte_records()
-    .next()
-    .ok_or_else(|| anyhow::anyhow!("Failed to parse header"))??;
-  let true_header = utils::clean_header(&raw_header);
-  let sel = Selection::from_headers(&true_header, &[column.as_str()])?;
+  let headers = rdr.byte_headers();
+  let sel = Selection::from_headers(headers, &[column.as_str()])?;
   let field_index = sel.first_indices()?;
 
   // Write header to output
-  wtr.write_byte_record(&true_header)?;
-
-  let njobs = utils::njobs(Some(threads));
-  let chunk_size_bytes = 256 * 1024 * 1024; // 256MB
-  let file_size = csv_mmap.len();
-  let num_chunks = (file_size + chunk_size_bytes - 1) / chunk_size_bytes;
-
-  let temp_dir = TempDir::new()?;
-
-  let pool = ThreadPoolBuilder::new()
-    .num_threads(njobs)
-    .build()
-    .map_err(|e| anyhow::anyhow!("Failed to create thread pool: {}", e))?;
-
-  let results: Result<Vec<(PathBuf, usize)>> = pool.install(|| {
-    (0..num_chunks)
-      .into_par_iter()
-      .map(|chunk_id| -> Result<(PathBuf, usize)> {
-        let start_byte = chunk_id * chunk_size_bytes;
-        if start_byte >= file_size {
-          return Ok((PathBuf::new(), 0));
-        }
-
-        // Find physical row indices for this chunk
-        let mut phys_start_idx = 1; // skip header
-        if offsets.len() <= 1 {
-          return Ok((PathBuf::new(), 0));
-        }
-        for i in 1..offsets.len() {
-          if offsets.get(i) as usize >= start_byte {
-            phys_start_idx = i;
-            break;
-          }
-        }
-
-        let next_chunk_start = (chunk_id + 1) * chunk_size_bytes;
-        let mut phys_end_idx = offsets.len();
-        for i in phys_start_idx..offsets.len() {
-          if offsets.get(i) as usize >= next_chunk_start {
-            phys_end_idx = i;
-            break;
-          }
-        }
-
-        if phys_start_idx >= phys_end_idx {
-          return Ok((PathBuf::new(), 0));
-        }
-
-        let chunk_start = offsets.get(phys_start_idx) as usize;
-        let chunk_end = if phys_end_idx < offsets.len() {
-          offsets.get(phys_end_idx) as usize
-        } else {
-          file_size
-        };
+  wtr.write_byte_record(headers)?;
 
-        if chunk_start >= file_size || chunk_start >= chunk_end {
-          return Ok((PathBuf::new(), 0));
-        }
-
-        let slice = &csv_mmap[chunk_start..chunk_end];
-        let reader = ReaderBuilder::new()
-          .has_headers(false)
-          .delimiter(sep)
-          .from_reader(Cursor::new(slice));
-
-        let out_path = temp_dir.path().join(format!("part_{}.csv", chunk_id));
-        let mut local_wtr = WriterBuilder::new().delimiter(sep).from_path(&out_path)?;
-
-        let mut match_count = 0;
-        for record_result in reader.into_byte_records() {
-          let record = record_result?;
-
-          let mut new_fields: Vec<Vec<u8>> = Vec::with_capacity(record.len());
-          let mut matched = false;
-
-          for (idx, field) in record.iter().enumerate() {
-            if idx == field_index {
-              if pattern.is_match(field) {
-                matched = true;
-                let replaced = pattern.replace_all(field, replacement.as_bytes());
-                new_fields.push(replaced.into_owned());
-              } else {
-                new_fields.push(field.to_vec());
-              }
-            } else {
-              new_fields.push(field.to_vec());
-            }
-          }
-
-          if matched {
+  let mut total_matches = 0;
+  rdr.for_each_ordered(
+    threads,
+    |chunk| {
+      let mut records = Vec::new();
+      let mut match_count = 0;
+      let mut record = ByteRecord::new();
+      while chunk.read_byte_record(&mut record)? {
+        match record.get(field_index) {
+          Some(field) if pattern.is_match(field) => {
             match_count += 1;
+            let replaced = pattern.replace_all(field, replacement.as_bytes());
+            let new_record: ByteRecord = record
+              .iter()
+              .enumerate()
+              .map(|(idx, f)| {
+                if idx == field_index {
+                  replaced.as_ref()
+                } else {
+                  f
+                }
+              })
+              .collect();
+            records.push(new_record);
           }
-
-          let new_record = ByteRecord::from(new_fields);
-          local_wtr.write_byte_record(&new_record)?;
+          _ => records.push(record.clone()),
         }
+      }
+      Ok((records, match_count))
+    },
+    |(records, match_count)| {
+      total_matches += match_count;
+      for record in &records {
+        wtr.write_byte_record(record)?;
+      }
+      Ok(())
+    },
+  )?;
 
-        local_wtr.flush()?;
-        Ok((out_path, match_count))
-      })
-      .collect()
-  });
-
-  let mut total_matches = 0;
-  for (path, count) in results? {
-    if path.as_os_str().is_empty() {
-      continue;
-    }
-    total_matches += count;
-
-    let mut part_file = File::open(&path)?;
-    let mut part_reader = ReaderBuilder::new()
-      .delimiter(sep)
-      .has_headers(false)
-      .from_reader(&mut part_file);
-
-    for record_result in part_reader.byte_records() {
-      let record = record_result?;
-      wtr.write_byte_record(&record)?;
-    }
-  }
-
-  wtr.flush()?;
+  wtr.finish()?;
   Ok(total_matches.to_string())
 }
 
@@ -324,13 +200,13 @@ pub async fn replace(
   let start_time = Instant::now();
   let mut opts = CsvOptions::new(path.clone());
   opts.set_skiprows(skiprows);
-  let (sep, reader) = opts.skiprows_and_delimiter().map_err(|e| format!("{e}"))?;
+  let (dialect, reader) = opts.skiprows_and_dialect().map_err(|e| format!("{e}"))?;
   let output_path = opts
     .output_path(Some("replace"), None)
     .map_err(|e| format!("create output falied: {e}"))?;
   let config = CsvConfigBuilder::new()
     .flexible(flexible)
-    .delimiter(sep)
+    .dialect(&dialect)
     .quoting(quoting)
     .build();
   let rdr = config.build_reader(reader);
@@ -338,6 +214,8 @@ pub async fn replace(
     .build_writer(&output_path)
     .map_err(|e| format!("build writer failed: {e}"))?;
 
+  // 压缩文件无法内存映射, 只能单线程流式读取
+  let threads = if opts.is_compressed() { 1 } else { threads };
   let replaced_rows = match threads {
     1 => regex_replace(
       rdr,
@@ -1,30 +1,48 @@
-use std::{path::Path, time::Instant};
+use std::{
+  io::{Read, Write},
+  path::Path,
+  time::Instant,
+};
 
 use anyhow::Result;
 use csv::ByteRecord;
 
-use crate::io::csv::{config::CsvConfigBuilder, options::CsvOptions};
+use crate::io::csv::{config::CsvConfigBuilder, options::CsvOptions, spill};
+use crate::io::writer::Finish;
+use crate::utils::{self, MemoryPlan};
 
 pub async fn reverse_csv<P: AsRef<Path> + Send + Sync>(
   path: P,
   quoting: bool,
   skiprows: usize,
   flexible: bool,
+) -> Result<()> {
+  reverse_csv_with(path, quoting, skiprows, flexible, None).await
+}
+
+/// Same as `reverse_csv`, `plan` overrides the memory planner when there is no index
+pub async fn reverse_csv_with<P: AsRef<Path> + Send + Sync>(
+  path: P,
+  quoting: bool,
+  skiprows: usize,
+  flexible: bool,
+  plan: Option<MemoryPlan>,
 ) -> Result<()> {
   let mut opts = CsvOptions::new(path);
   opts.set_skiprows(skiprows);
-  let (sep, reader) = opts.skiprows_and_delimiter()?;
+  let (dialect, reader) = opts.skiprows_and_dialect()?;
   let output_path = opts.output_path(Some("reverse"), None)?;
 
   let config = CsvConfigBuilder::new()
     .flexible(flexible)
-    .delimiter(sep)
+    .dialect(&dialect)
     .quoting(quoting)
     .build();
 
   let mut rdr = config.build_reader(reader);
   let mut wtr = config.build_writer(&output_path)?;
 
+  opts.ensure_index()?;
   if let Some(mut idx_file) = opts.indexed()? {
     // we have an index, no need to check avail mem,
     // we're reading the file in reverse streaming
@@ -41,18 +59,61 @@ pub async fn reverse_csv<P: AsRef<Path> + Send + Sync>(
       pos -= 1;
     }
   } else {
-    // we don't have an index, we need to read the entire file into memory
-    // we're loading the entire file into memory, we need to check avail mem
-    let mut all = rdr.byte_records().collect::<Result<Vec<_>, _>>()?;
-    all.reverse();
-
+    let plan = match plan {
+      Some(plan) => plan,
+      None => utils::memory_plan(&opts)?,
+    };
     wtr.write_record(rdr.byte_headers()?)?;
-    for r in all {
-      wtr.write_byte_record(&r)?;
+
+    match plan {
+      MemoryPlan::InMemory => {
+        let mut all = rdr.byte_records().collect::<Result<Vec<_>, _>>()?;
+        all.reverse();
+        for r in all {
+          wtr.write_byte_record(&r)?;
+        }
+      }
+      MemoryPlan::OnDisk(budget) => reverse_on_disk(&mut rdr, &mut wtr, budget)?,
+    }
+  }
+
+  wtr.finish()
+}
+
+/// Cut the records into reversed chunks of about `budget` bytes kept in temp files,
+/// then write the chunks back last to first
+fn reverse_on_disk<R: Read, W: Write>(
+  rdr: &mut csv::Reader<R>,
+  wtr: &mut csv::Writer<W>,
+  budget: usize,
+) -> Result<()> {
+  let mut runs = Vec::new();
+  let mut chunk = Vec::new();
+  let mut bytes = 0;
+  for record in rdr.byte_records() {
+    let record = record?;
+    bytes += spill::record_size(&record);
+    chunk.push(record);
+    if bytes >= budget {
+      runs.push(spill::write_run(chunk.iter().rev())?);
+      chunk.clear();
+      bytes = 0;
+    }
+  }
+
+  // 最后一块还在内存中, 最先输出
+  for r in chunk.iter().rev() {
+    wtr.write_byte_record(r)?;
+  }
+  let mut record = ByteRecord::new();
+  for run in runs.into_iter().rev() {
+    let mut run = spill::read_run(run);
+    while run.read_byte_record(&mut record)? {
+      wtr.write_byte_record(&record)?;
     }
   }
 
-  Ok(wtr.flush()?)
+  Ok(())
 }
 
 #[tauri::command]
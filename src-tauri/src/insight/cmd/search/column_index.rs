@@ -0,0 +1,643 @@
+use std::{
+  fs::{self, File},
+  io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+  path::{Path, PathBuf},
+  time::Instant,
+};
+
+use anyhow::{Result, anyhow};
+use csv::ByteRecord;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  cmd::{
+    idx,
+    search::{
+      generic::{MatchSink, SearchOutput},
+      matcher::base_mode,
+    },
+  },
+  index::{self, INDEX_VERSION, IndexMeta, IndexStatus, Indexed},
+  io::{
+    csv::{config::CsvConfigBuilder, options::CsvOptions, selection::Selection, spill},
+    writer,
+  },
+  utils::{self, MemoryPlan, WTR_BUFFER_SIZE},
+};
+
+/// Estimated heap size of a buffered (value, row) besides the value
+const ENTRY_OVERHEAD: usize = 48;
+
+/// `<file>.cidx/<column>.cidx.meta`, what a column index was built from.
+///
+/// A `.cidx` holds the distinct values of one column sorted by bytes, each entry is a
+/// big-endian u32 value length, the value and the u64 data rows (0-based, the records of
+/// `.idx` after the header) having it. A table of u64 entry positions and the number of
+/// entries end the file, the rows of an entry end where the next entry starts.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ColumnIndexMeta {
+  pub column: String,
+  /// Distinct values
+  pub values: u64,
+  #[serde(flatten)]
+  pub file: IndexMeta,
+}
+
+impl ColumnIndexMeta {
+  fn read(index_path: &Path) -> Result<Option<Self>> {
+    match fs::read(index::meta_path(index_path)) {
+      Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+      Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+      Err(e) => Err(e.into()),
+    }
+  }
+
+  fn write(&self, index_path: &Path) -> Result<()> {
+    fs::write(
+      index::meta_path(index_path),
+      serde_json::to_vec_pretty(self)?,
+    )?;
+    Ok(())
+  }
+}
+
+/// `<file>.cidx`, the folder with the column indexes of a file
+pub fn column_index_dir<P: AsRef<Path> + Send + Sync>(opts: &CsvOptions<P>) -> PathBuf {
+  let mut p = opts.idx_path();
+  p.set_extension("cidx");
+  p
+}
+
+/// Column names are not always valid file names, a hash keeps similar names apart
+fn column_index_path<P: AsRef<Path> + Send + Sync>(opts: &CsvOptions<P>, column: &str) -> PathBuf {
+  let name: String = column
+    .chars()
+    .map(|c| match c.is_alphanumeric() || c == '-' || c == '_' {
+      true => c,
+      false => '_',
+    })
+    .collect();
+  let hash = index::fnv1a(column.as_bytes()) as u32;
+  column_index_dir(opts).join(format!("{name}-{hash:08x}.cidx"))
+}
+
+/// Writes the entries of a `.cidx`, (value, row) must come sorted by value then row
+struct EntryWriter {
+  wtr: BufWriter<File>,
+  /// 条目位置先写入临时文件, 结束时接在条目之后
+  positions: BufWriter<File>,
+  pos: u64,
+  current: Option<Vec<u8>>,
+  values: u64,
+}
+
+impl EntryWriter {
+  fn new(file: File) -> Result<Self> {
+    Ok(EntryWriter {
+      wtr: BufWriter::with_capacity(WTR_BUFFER_SIZE, file),
+      positions: BufWriter::new(tempfile::tempfile()?),
+      pos: 0,
+      current: None,
+      values: 0,
+    })
+  }
+
+  fn push(&mut self, value: &[u8], row: u64) -> Result<()> {
+    if self.current.as_deref() != Some(value) {
+      self.positions.write_all(&self.pos.to_be_bytes())?;
+      self.wtr.write_all(&(value.len() as u32).to_be_bytes())?;
+      self.wtr.write_all(value)?;
+      self.pos += 4 + value.len() as u64;
+      self.current = Some(value.to_vec());
+      self.values += 1;
+    }
+    self.wtr.write_all(&row.to_be_bytes())?;
+    self.pos += 8;
+    Ok(())
+  }
+
+  fn finish(mut self) -> Result<u64> {
+    let mut positions = self.positions.into_inner().map_err(|e| e.into_error())?;
+    positions.seek(SeekFrom::Start(0))?;
+    io::copy(&mut positions, &mut self.wtr)?;
+    self.wtr.write_all(&self.values.to_be_bytes())?;
+    self
+      .wtr
+      .into_inner()
+      .map_err(|e| e.into_error())?
+      .sync_all()?;
+    Ok(self.values)
+  }
+}
+
+/// Sort a chunk by value, rows keep their order, and write it to a temp file
+fn spill_chunk(chunk: &mut Vec<(Vec<u8>, u64)>) -> Result<File> {
+  chunk.sort_by(|a, b| a.0.cmp(&b.0));
+  let mut wtr = spill::run_writer()?;
+  for (value, row) in chunk.drain(..) {
+    wtr.write_byte_record(&ByteRecord::from(vec![
+      &value[..],
+      row.to_string().as_bytes(),
+    ]))?;
+  }
+  spill::finish_run(wtr)
+}
+
+fn read_entry(rdr: &mut csv::Reader<File>) -> Result<Option<(Vec<u8>, u64)>> {
+  let mut record = ByteRecord::new();
+  if !rdr.read_byte_record(&mut record)? {
+    return Ok(None);
+  }
+  let value = record.get(0).unwrap_or_default().to_vec();
+  let row = std::str::from_utf8(record.get(1).unwrap_or_default())?.parse()?;
+  Ok(Some((value, row)))
+}
+
+/// Build the index of `column`, `plan` overrides the memory planner.
+///
+/// Values are kept in memory while they fit in the plan, otherwise sorted runs are
+/// spilled to temp files and merged.
+pub fn build_column_index<P: AsRef<Path> + Send + Sync>(
+  opts: &CsvOptions<P>,
+  column: &str,
+  quoting: bool,
+  flexible: bool,
+  plan: Option<MemoryPlan>,
+) -> Result<ColumnIndexMeta> {
+  if opts.is_compressed() {
+    return Err(anyhow!(
+      "cannot create an index for a compressed file, decompress it first"
+    ));
+  }
+  let dialect = opts.dialect()?;
+  let mut file = File::open(opts.file_path()?)?;
+  let size = file.metadata()?.len();
+  let (mtime, head_hash, tail_hash) = index::fingerprint(&mut file, size)?;
+  let samples = index::sample_hashes(&mut file, size)?;
+
+  let (_, reader) = opts.skiprows_and_dialect()?;
+  let mut rdr = CsvConfigBuilder::new()
+    .dialect(&dialect)
+    .quoting(quoting)
+    .flexible(flexible)
+    .build()
+    .build_reader(reader);
+  let headers = rdr.byte_headers()?.clone();
+  let idx = Selection::from_headers(&headers, &[column][..])?.first_indices()?;
+  let budget = match plan {
+    Some(plan) => plan,
+    None => utils::memory_plan(opts)?,
+  };
+  let budget = match budget {
+    MemoryPlan::InMemory => usize::MAX,
+    MemoryPlan::OnDisk(budget) => budget,
+  };
+
+  let mut runs = Vec::new();
+  let mut chunk = Vec::new();
+  let (mut bytes, mut rows) = (0, 0);
+  let mut record = ByteRecord::new();
+  while rdr.read_byte_record(&mut record)? {
+    let value = record.get(idx).unwrap_or_default();
+    bytes += value.len() + ENTRY_OVERHEAD;
+    chunk.push((value.to_vec(), rows));
+    rows += 1;
+    if bytes >= budget {
+      runs.push(spill_chunk(&mut chunk)?);
+      bytes = 0;
+    }
+  }
+
+  let index_path = column_index_path(opts, column);
+  fs::create_dir_all(column_index_dir(opts))?;
+  // 旧的 meta 先删除, 写入中断时不会把旧 meta 配给新的 .cidx
+  match fs::remove_file(index::meta_path(&index_path)) {
+    Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e.into()),
+    _ => {}
+  }
+  let (file, temp) = writer::temp_file(&index_path)?.into_parts();
+  let mut wtr = EntryWriter::new(file)?;
+  if runs.is_empty() {
+    chunk.sort_by(|a, b| a.0.cmp(&b.0));
+    for (value, row) in &chunk {
+      wtr.push(value, *row)?;
+    }
+  } else {
+    if !chunk.is_empty() {
+      runs.push(spill_chunk(&mut chunk)?);
+    }
+    drop(chunk);
+
+    // k路归并, 值相同时先取前面的 run, 行号保持递增
+    let mut readers: Vec<_> = runs.into_iter().map(spill::read_run).collect();
+    let mut heads = Vec::with_capacity(readers.len());
+    for rdr in readers.iter_mut() {
+      heads.push(read_entry(rdr)?);
+    }
+    loop {
+      let mut min: Option<usize> = None;
+      for (i, head) in heads.iter().enumerate() {
+        if let Some((value, _)) = head
+          && min.is_none_or(|m| heads[m].as_ref().is_some_and(|(v, _)| value < v))
+        {
+          min = Some(i);
+        }
+      }
+      let Some(i) = min else { break };
+      let Some((value, row)) = heads[i].take() else {
+        break;
+      };
+      wtr.push(&value, row)?;
+      heads[i] = read_entry(&mut readers[i])?;
+    }
+  }
+  let values = wtr.finish()?;
+  temp.persist(&index_path)?;
+
+  let meta = ColumnIndexMeta {
+    column: column.to_string(),
+    values,
+    file: IndexMeta {
+      version: INDEX_VERSION,
+      size,
+      mtime,
+      head_hash,
+      tail_hash,
+      samples,
+      skiprows: opts.get_skip_rows(),
+      delimiter: dialect.delimiter as char,
+      quote: dialect.quote as char,
+      escape: dialect.escape.map(char::from),
+      comment: dialect.comment.map(char::from),
+      quoting,
+      flexible,
+      records: rows + 1,
+    },
+  };
+  meta.write(&index_path)?;
+
+  Ok(meta)
+}
+
+/// Create the index of every column in `columns`, and the `.idx` used to read the rows back
+pub async fn create_column_index<P: AsRef<Path> + Send + Sync>(
+  path: P,
+  columns: &[&str],
+  quoting: bool,
+  flexible: bool,
+  skiprows: usize,
+  plan: Option<MemoryPlan>,
+) -> Result<Vec<ColumnIndexMeta>> {
+  idx::create_index(&path, quoting, flexible, skiprows).await?;
+  let mut opts = CsvOptions::new(&path);
+  opts.set_skiprows(skiprows);
+  columns
+    .iter()
+    .map(|column| build_column_index(&opts, column, quoting, flexible, plan))
+    .collect()
+}
+
+/// Queries a column index answers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IndexQuery {
+  Equal(Vec<String>),
+  StartsWith(Vec<String>),
+}
+
+impl IndexQuery {
+  /// `equal` and `starts_with` (or their `_multi` lists) on a condition or a lookup list,
+  /// other modes scan the file
+  pub fn new(mode: &str, values: Vec<String>) -> Option<Self> {
+    match base_mode(mode) {
+      "equal" => Some(IndexQuery::Equal(values)),
+      "starts_with" => Some(IndexQuery::StartsWith(values)),
+      _ => None,
+    }
+  }
+
+  /// Whether a column index can answer `mode`
+  pub fn indexable(mode: &str) -> bool {
+    matches!(base_mode(mode), "equal" | "starts_with")
+  }
+
+  /// The values or prefixes given back when the index cannot answer the query
+  pub fn into_values(self) -> Vec<String> {
+    match self {
+      IndexQuery::Equal(values) | IndexQuery::StartsWith(values) => values,
+    }
+  }
+}
+
+/// An open `.cidx`
+pub struct ColumnIndex {
+  rdr: BufReader<File>,
+  /// 条目位置表的起点
+  table: u64,
+  values: u64,
+}
+
+impl ColumnIndex {
+  fn open_file(path: &Path) -> Result<Self> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    if len < 8 {
+      return Err(anyhow!("{} is not a column index", path.display()));
+    }
+    let mut buf = [0u8; 8];
+    file.seek(SeekFrom::Start(len - 8))?;
+    file.read_exact(&mut buf)?;
+    let values = u64::from_be_bytes(buf);
+    let table = values
+      .checked_mul(8)
+      .and_then(|n| (len - 8).checked_sub(n))
+      .ok_or(anyhow!("{} is not a column index", path.display()))?;
+    Ok(ColumnIndex {
+      rdr: BufReader::new(file),
+      table,
+      values,
+    })
+  }
+
+  /// The index of `column` if it matches the file, `skiprows`, dialect, `quoting` and `flexible`
+  pub fn open<P: AsRef<Path> + Send + Sync>(
+    opts: &CsvOptions<P>,
+    column: &str,
+    quoting: bool,
+    flexible: bool,
+  ) -> Result<Option<Self>> {
+    if opts.is_compressed() {
+      return Ok(None);
+    }
+    let index_path = column_index_path(opts, column);
+    let Some(meta) = ColumnIndexMeta::read(&index_path)? else {
+      return Ok(None);
+    };
+    if meta.column != column || !index_path.exists() {
+      return Ok(None);
+    }
+    // quoting 不同时引号内的分隔符会切出不同的值
+    if meta.file.quoting != quoting || meta.file.flexible != flexible {
+      log::info!("index of column {column} not used: built with other quoting or flexible");
+      return Ok(None);
+    }
+    match meta.file.status(
+      Path::new(opts.file_path()?),
+      opts.get_skip_rows(),
+      &opts.dialect()?,
+    )? {
+      IndexStatus::Fresh => Ok(Some(ColumnIndex::open_file(&index_path)?)),
+      status => {
+        log::info!("index of column {column} not used: {status:?}");
+        Ok(None)
+      }
+    }
+  }
+
+  fn read_u64(&mut self) -> Result<u64> {
+    let mut buf = [0u8; 8];
+    self.rdr.read_exact(&mut buf)?;
+    Ok(u64::from_be_bytes(buf))
+  }
+
+  /// Start of entry `i`, the table start for `i == values`
+  fn position(&mut self, i: u64) -> Result<u64> {
+    if i == self.values {
+      return Ok(self.table);
+    }
+    self.rdr.seek(SeekFrom::Start(self.table + i * 8))?;
+    self.read_u64()
+  }
+
+  fn value(&mut self, i: u64) -> Result<Vec<u8>> {
+    let pos = self.position(i)?;
+    self.rdr.seek(SeekFrom::Start(pos))?;
+    let mut len = [0u8; 4];
+    self.rdr.read_exact(&mut len)?;
+    let mut value = vec![0; u32::from_be_bytes(len) as usize];
+    self.rdr.read_exact(&mut value)?;
+    Ok(value)
+  }
+
+  fn rows(&mut self, i: u64, rows: &mut Vec<u64>) -> Result<()> {
+    let end = self.position(i + 1)?;
+    let value = self.value(i)?;
+    let start = self.position(i)? + 4 + value.len() as u64;
+    self.rdr.seek(SeekFrom::Start(start))?;
+    for _ in 0..(end - start) / 8 {
+      rows.push(self.read_u64()?);
+    }
+    Ok(())
+  }
+
+  /// First entry whose value is not less than `key`
+  fn lower_bound(&mut self, key: &[u8]) -> Result<u64> {
+    let (mut lo, mut hi) = (0, self.values);
+    while lo < hi {
+      let mid = lo + (hi - lo) / 2;
+      if self.value(mid)?.as_slice() < key {
+        lo = mid + 1;
+      } else {
+        hi = mid;
+      }
+    }
+    Ok(lo)
+  }
+
+  /// Sorted and distinct data rows matching `query`
+  pub fn query(&mut self, query: &IndexQuery) -> Result<Vec<u64>> {
+    let mut rows = Vec::new();
+    match query {
+      IndexQuery::Equal(values) => {
+        for value in values {
+          let i = self.lower_bound(value.as_bytes())?;
+          if i < self.values && self.value(i)? == value.as_bytes() {
+            self.rows(i, &mut rows)?;
+          }
+        }
+      }
+      IndexQuery::StartsWith(prefixes) => {
+        for prefix in prefixes {
+          let mut i = self.lower_bound(prefix.as_bytes())?;
+          while i < self.values && self.value(i)?.starts_with(prefix.as_bytes()) {
+            self.rows(i, &mut rows)?;
+            i += 1;
+          }
+        }
+      }
+    }
+    rows.sort_unstable();
+    rows.dedup();
+    Ok(rows)
+  }
+}
+
+/// Answer `query` with the index of `column` and the `.idx`.
+///
+/// Returns `None` when there is no usable index, or when the output needs the unmatched
+/// rows, then the file has to be scanned.
+pub fn search_indexed<P: AsRef<Path> + Send + Sync>(
+  opts: &CsvOptions<P>,
+  column: &str,
+  query: &IndexQuery,
+  quoting: bool,
+  flexible: bool,
+  output: SearchOutput,
+) -> Result<Option<String>> {
+  if output == SearchOutput::Split {
+    return Ok(None);
+  }
+  let Some(mut column_index) = ColumnIndex::open(opts, column, quoting, flexible)? else {
+    return Ok(None);
+  };
+  let Some((_, idx_file)) = opts.index_files()? else {
+    return Ok(None);
+  };
+  let rows = column_index.query(query)?;
+
+  let (dialect, reader) = opts.skiprows_and_dialect()?;
+  let config = CsvConfigBuilder::new()
+    .dialect(&dialect)
+    .quoting(quoting)
+    .flexible(flexible)
+    .build();
+  let headers = config.build_reader(reader).byte_headers()?.clone();
+  let mut sink = MatchSink::for_search(opts, &config, output)?;
+  sink.write_headers(&headers)?;
+
+  let mut rdr = Indexed::open(
+    config.build_reader(File::open(opts.file_path()?)?),
+    idx_file,
+  )?;
+  let mut record = ByteRecord::new();
+  for &row in &rows {
+    // 行号和计数不需要读取记录
+    if output == SearchOutput::Rows {
+      rdr.seek(row)?;
+      if !rdr.read_byte_record(&mut record)? {
+        return Err(anyhow!("row {row} of the column index is not in the file"));
+      }
+    }
+    sink.write(row as usize + 1, &record, true)?;
+  }
+  sink.finish()?;
+
+  Ok(Some(rows.len().to_string()))
+}
+
+#[derive(Debug, Serialize)]
+pub struct ColumnIndexHealth {
+  /// `.cidx` file name
+  pub file: String,
+  #[serde(flatten)]
+  pub status: IndexStatus,
+  pub meta: Option<ColumnIndexMeta>,
+}
+
+/// Check every column index of the file against the file, `skiprows` and dialect
+pub fn column_index_health<P: AsRef<Path> + Send + Sync>(
+  opts: &CsvOptions<P>,
+) -> Result<Vec<ColumnIndexHealth>> {
+  let dir = column_index_dir(opts);
+  if !dir.is_dir() {
+    return Ok(Vec::new());
+  }
+  let mut paths: Vec<PathBuf> = fs::read_dir(&dir)?
+    .map(|entry| entry.map(|e| e.path()))
+    .collect::<io::Result<_>>()?;
+  paths.retain(|p| p.extension().is_some_and(|ext| ext == "cidx"));
+  paths.sort();
+
+  paths
+    .into_iter()
+    .map(|path| {
+      let file = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+      let meta = ColumnIndexMeta::read(&path)?;
+      let status = match &meta {
+        None => IndexStatus::Unversioned,
+        Some(meta) => meta.file.status(
+          Path::new(opts.file_path()?),
+          opts.get_skip_rows(),
+          &opts.dialect()?,
+        )?,
+      };
+      Ok(ColumnIndexHealth { file, status, meta })
+    })
+    .collect()
+}
+
+/// Remove the index of `column`, or every column index when `None`. Returns the number removed.
+pub fn drop_column_index<P: AsRef<Path> + Send + Sync>(
+  opts: &CsvOptions<P>,
+  column: Option<&str>,
+) -> Result<usize> {
+  let dir = column_index_dir(opts);
+  if !dir.is_dir() {
+    return Ok(0);
+  }
+  let Some(column) = column else {
+    let count = column_index_health(opts)?.len();
+    fs::remove_dir_all(&dir)?;
+    return Ok(count);
+  };
+
+  let index_path = column_index_path(opts, column);
+  if !index_path.exists() {
+    return Ok(0);
+  }
+  fs::remove_file(&index_path)?;
+  let meta_path = index::meta_path(&index_path);
+  if meta_path.exists() {
+    fs::remove_file(meta_path)?;
+  }
+  if fs::read_dir(&dir)?.next().is_none() {
+    fs::remove_dir(&dir)?;
+  }
+  Ok(1)
+}
+
+/// Build the index of every column in `columns` (separated by `|`)
+#[tauri::command]
+pub async fn column_idx(
+  path: String,
+  columns: String,
+  quoting: bool,
+  flexible: bool,
+  skiprows: usize,
+) -> Result<String, String> {
+  let start_time = Instant::now();
+  let columns: Vec<&str> = columns.split('|').filter(|c| !c.is_empty()).collect();
+  if columns.is_empty() {
+    return Err("No column to index".to_string());
+  }
+
+  match create_column_index(&path, &columns, quoting, flexible, skiprows, None).await {
+    Ok(_) => {
+      let elapsed_time = start_time.elapsed().as_secs_f64();
+      Ok(format!("{elapsed_time:.0}"))
+    }
+    Err(err) => Err(format!("{err}")),
+  }
+}
+
+/// Inspect the column indexes of a file, returns a JSON array
+#[tauri::command]
+pub async fn column_idx_health(path: String, skiprows: usize) -> Result<String, String> {
+  let mut opts = CsvOptions::new(&path);
+  opts.set_skiprows(skiprows);
+  let reports = column_index_health(&opts).map_err(|e| format!("{path}|{e}"))?;
+
+  serde_json::to_string(&reports).map_err(|e| e.to_string())
+}
+
+/// Remove the index of `column`, or all column indexes of the file
+#[tauri::command]
+pub async fn drop_column_idx(path: String, column: Option<String>) -> Result<String, String> {
+  let opts = CsvOptions::new(&path);
+  match drop_column_index(&opts, column.as_deref()) {
+    Ok(count) => Ok(count.to_string()),
+    Err(err) => Err(format!("{err}")),
+  }
+}
@@ -0,0 +1,128 @@
+use anyhow::{Result, anyhow};
+use chrono::{Datelike, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime};
+
+use crate::cmd::datefmt::{parse_to_naive_datetime, parse_with_format};
+
+/// How cells that are not dates are treated
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Unparsable {
+  /// 不匹配 (默认)
+  #[default]
+  Exclude,
+  /// 视为匹配
+  Include,
+}
+
+impl From<&str> for Unparsable {
+  fn from(s: &str) -> Self {
+    match s {
+      "include" => Unparsable::Include,
+      _ => Unparsable::Exclude,
+    }
+  }
+}
+
+#[derive(Debug)]
+enum DateMode {
+  Before(NaiveDateTime),
+  After(NaiveDateTime),
+  /// 上限只有日期时包含当天
+  Between(NaiveDateTime, NaiveDateTime),
+  LastDays(i64),
+  SameMonth(NaiveDate),
+  SameQuarter(NaiveDate),
+  SameYear(i32),
+}
+
+/// Compares the dates of a column with a condition.
+///
+/// Cells are parsed with `format` if given, otherwise with the detection of `datefmt`,
+/// a date without time is at 00:00:00.
+pub struct DateMatcher {
+  mode: DateMode,
+  format: Option<String>,
+  unparsable: Unparsable,
+  today: NaiveDate,
+}
+
+fn quarter(date: &NaiveDate) -> u32 {
+  date.month0() / 3
+}
+
+impl DateMatcher {
+  /// `mode` is one of date_before, date_after, date_between (`start|end`),
+  /// date_last_days (`N`), date_same_month, date_same_quarter, date_same_year
+  pub fn new(
+    mode: &str,
+    condition: &str,
+    format: Option<&str>,
+    unparsable: Unparsable,
+  ) -> Result<Self> {
+    let format = format.map(str::trim).filter(|f| !f.is_empty());
+    let parse = |s: &str| {
+      let s = s.trim();
+      format
+        .and_then(|fmt| parse_with_format(s, fmt))
+        .or_else(|| parse_to_naive_datetime(s))
+        .ok_or(anyhow!("Invalid date: {s}"))
+    };
+
+    let mode = match mode {
+      "date_before" => DateMode::Before(parse(condition)?),
+      "date_after" => DateMode::After(parse(condition)?),
+      "date_between" => {
+        let bounds: Vec<&str> = condition.split('|').collect();
+        if bounds.len() != 2 {
+          return Err(anyhow!(
+            "Exactly two dates required for date_between: start and end"
+          ));
+        }
+        let (a, b) = (parse(bounds[0])?, parse(bounds[1])?);
+        DateMode::Between(a.min(b), a.max(b))
+      }
+      "date_last_days" => DateMode::LastDays(
+        condition
+          .trim()
+          .parse::<u32>()
+          .map_err(|_| anyhow!("Invalid number of days: {condition}"))?
+          .into(),
+      ),
+      "date_same_month" => DateMode::SameMonth(parse(condition)?.date()),
+      "date_same_quarter" => DateMode::SameQuarter(parse(condition)?.date()),
+      "date_same_year" => DateMode::SameYear(parse(condition)?.year()),
+      _ => return Err(anyhow!("Unsupported date mode: {mode}")),
+    };
+
+    Ok(DateMatcher {
+      mode,
+      format: format.map(String::from),
+      unparsable,
+      today: Local::now().date_naive(),
+    })
+  }
+
+  fn parse(&self, value: &str) -> Option<NaiveDateTime> {
+    let value = value.trim();
+    match &self.format {
+      Some(fmt) => parse_with_format(value, fmt),
+      None => parse_to_naive_datetime(value),
+    }
+  }
+
+  pub fn is_match(&self, value: &str) -> bool {
+    let Some(dt) = self.parse(value) else {
+      return self.unparsable == Unparsable::Include;
+    };
+    let date = dt.date();
+    match &self.mode {
+      DateMode::Before(t) => dt < *t,
+      DateMode::After(t) => dt > *t,
+      DateMode::Between(lo, hi) if hi.time() == NaiveTime::MIN => dt >= *lo && date <= hi.date(),
+      DateMode::Between(lo, hi) => dt >= *lo && dt <= *hi,
+      DateMode::LastDays(n) => date <= self.today && date >= self.today - Duration::days(*n),
+      DateMode::SameMonth(d) => date.year() == d.year() && date.month() == d.month(),
+      DateMode::SameQuarter(d) => date.year() == d.year() && quarter(&date) == quarter(d),
+      DateMode::SameYear(y) => date.year() == *y,
+    }
+  }
+}
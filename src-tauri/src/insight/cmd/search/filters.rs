@@ -1,25 +1,25 @@
 use std::{
-  collections::HashSet,
-  fs::File,
-  io::{BufRead, BufReader, BufWriter, Read, Write},
+  io::{BufRead, BufReader, Read, Write},
   path::PathBuf,
 };
 
-use anyhow::{Result, anyhow};
-use regex::bytes::RegexBuilder;
+use anyhow::Result;
 
 use crate::{
-  cmd::search::generic::{generic_parallel_search, generic_search},
-  index::Indexed,
-  io::csv::options::CsvOptions,
-  utils::{EventEmitter, WTR_BUFFER_SIZE},
+  cmd::search::{
+    date::DateMatcher,
+    generic::{MatchSink, SearchInput, generic_parallel_search, generic_search},
+    lookup::LookupMatcher,
+    matcher::Matcher,
+  },
+  io::writer,
+  utils::EventEmitter,
 };
 
 pub async fn equal<E>(
   rdr: csv::Reader<BufReader<Box<dyn Read + Send>>>,
-  wtr: csv::Writer<BufWriter<File>>,
-  opts: CsvOptions<String>,
-  idx: Option<Indexed<File, File>>,
+  wtr: impl Into<MatchSink>,
+  opts: impl Into<SearchInput>,
   column: String,
   conditions: Vec<String>,
   progress: bool,
@@ -28,23 +28,16 @@ pub async fn equal<E>(
 ) -> Result<String>
 where
   E: EventEmitter + Send + Sync + 'static,
-{  
+{
+  let matcher = Matcher::from_conditions("equal", conditions)?;
+  let wtr = wtr.into();
+  let input = opts.into();
   let jobs = threads.unwrap_or(1);
-  let cond_set: HashSet<String> = conditions.into_iter().collect();
-  let match_fn = move |value: &str, _: &[String]| cond_set.contains(value);
-
+  let match_fn = move |value: &str, _: &[String]| matcher.is_match(value);
   match jobs {
     1 => generic_search(rdr, wtr, column, vec![], progress, match_fn, emitter).await,
     _ => tokio::task::spawn_blocking(move || {
-      generic_parallel_search(
-        opts,
-        &mut idx.unwrap(),
-        wtr,
-        column,
-        vec![],
-        jobs,
-        match_fn,
-      )
+      generic_parallel_search(input, wtr, column, vec![], jobs, match_fn)
     })
     .await
     .map_err(|e| anyhow::anyhow!("Task join error: {}", e))?,
@@ -53,9 +46,8 @@ where
 
 pub async fn not_equal<E>(
   rdr: csv::Reader<BufReader<Box<dyn Read + Send>>>,
-  wtr: csv::Writer<BufWriter<File>>,
-  opts: CsvOptions<String>,
-  idx: Option<Indexed<File, File>>,
+  wtr: impl Into<MatchSink>,
+  opts: impl Into<SearchInput>,
   column: String,
   conditions: Vec<String>,
   progress: bool,
@@ -64,23 +56,16 @@ pub async fn not_equal<E>(
 ) -> Result<String>
 where
   E: EventEmitter + Send + Sync + 'static,
-{  
+{
+  let matcher = Matcher::from_conditions("not_equal", conditions)?;
+  let wtr = wtr.into();
+  let input = opts.into();
   let jobs = threads.unwrap_or(1);
-  let cond_set: HashSet<String> = conditions.into_iter().collect();
-  let match_fn = move |value: &str, _: &[String]| !cond_set.contains(value);
-
+  let match_fn = move |value: &str, _: &[String]| matcher.is_match(value);
   match jobs {
     1 => generic_search(rdr, wtr, column, vec![], progress, match_fn, emitter).await,
     _ => tokio::task::spawn_blocking(move || {
-      generic_parallel_search(
-        opts,
-        &mut idx.unwrap(),
-        wtr,
-        column,
-        vec![],
-        jobs,
-        match_fn,
-      )
+      generic_parallel_search(input, wtr, column, vec![], jobs, match_fn)
     })
     .await
     .map_err(|e| anyhow::anyhow!("Task join error: {}", e))?,
@@ -89,9 +74,8 @@ where
 
 pub async fn contains<E>(
   rdr: csv::Reader<BufReader<Box<dyn Read + Send>>>,
-  wtr: csv::Writer<BufWriter<File>>,
-  opts: CsvOptions<String>,
-  idx: Option<Indexed<File, File>>,
+  wtr: impl Into<MatchSink>,
+  opts: impl Into<SearchInput>,
   column: String,
   conditions: Vec<String>,
   progress: bool,
@@ -101,21 +85,15 @@ pub async fn contains<E>(
 where
   E: EventEmitter + Send + Sync + 'static,
 {
+  let matcher = Matcher::from_conditions("contains", conditions.clone())?;
+  let wtr = wtr.into();
+  let input = opts.into();
   let jobs = threads.unwrap_or(1);
-  let conditions_clone = conditions.clone();
-  let match_fn = move |value: &str, _: &[String]| conditions_clone.iter().any(|cond| value.contains(cond));
+  let match_fn = move |value: &str, _: &[String]| matcher.is_match(value);
   match jobs {
     1 => generic_search(rdr, wtr, column, conditions, progress, match_fn, emitter).await,
     _ => tokio::task::spawn_blocking(move || {
-      generic_parallel_search(
-        opts,
-        &mut idx.unwrap(),
-        wtr,
-        column,
-        conditions,
-        jobs,
-        match_fn,
-      )
+      generic_parallel_search(input, wtr, column, conditions, jobs, match_fn)
     })
     .await
     .map_err(|e| anyhow::anyhow!("Task join error: {}", e))?,
@@ -124,9 +102,8 @@ where
 
 pub async fn not_contains<E>(
   rdr: csv::Reader<BufReader<Box<dyn Read + Send>>>,
-  wtr: csv::Writer<BufWriter<File>>,
-  opts: CsvOptions<String>,
-  idx: Option<Indexed<File, File>>,
+  wtr: impl Into<MatchSink>,
+  opts: impl Into<SearchInput>,
   column: String,
   conditions: Vec<String>,
   progress: bool,
@@ -136,21 +113,15 @@ pub async fn not_contains<E>(
 where
   E: EventEmitter + Send + Sync + 'static,
 {
+  let matcher = Matcher::from_conditions("not_contains", conditions.clone())?;
+  let wtr = wtr.into();
+  let input = opts.into();
   let jobs = threads.unwrap_or(1);
-  let conditions_clone = conditions.clone();
-  let match_fn = move |value: &str, _: &[String]| !conditions_clone.iter().any(|cond| value.contains(cond));
+  let match_fn = move |value: &str, _: &[String]| matcher.is_match(value);
   match jobs {
     1 => generic_search(rdr, wtr, column, conditions, progress, match_fn, emitter).await,
     _ => tokio::task::spawn_blocking(move || {
-      generic_parallel_search(
-        opts,
-        &mut idx.unwrap(),
-        wtr,
-        column,
-        conditions,
-        jobs,
-        match_fn,
-      )
+      generic_parallel_search(input, wtr, column, conditions, jobs, match_fn)
     })
     .await
     .map_err(|e| anyhow::anyhow!("Task join error: {}", e))?,
@@ -159,9 +130,8 @@ where
 
 pub async fn starts_with<E>(
   rdr: csv::Reader<BufReader<Box<dyn Read + Send>>>,
-  wtr: csv::Writer<BufWriter<File>>,
-  opts: CsvOptions<String>,
-  idx: Option<Indexed<File, File>>,
+  wtr: impl Into<MatchSink>,
+  opts: impl Into<SearchInput>,
   column: String,
   conditions: Vec<String>,
   progress: bool,
@@ -171,21 +141,15 @@ pub async fn starts_with<E>(
 where
   E: EventEmitter + Send + Sync + 'static,
 {
+  let matcher = Matcher::from_conditions("starts_with", conditions.clone())?;
+  let wtr = wtr.into();
+  let input = opts.into();
   let jobs = threads.unwrap_or(1);
-  let conditions_clone = conditions.clone();
-  let match_fn = move |value: &str, _: &[String]| conditions_clone.iter().any(|cond| value.starts_with(cond));
+  let match_fn = move |value: &str, _: &[String]| matcher.is_match(value);
   match jobs {
     1 => generic_search(rdr, wtr, column, conditions, progress, match_fn, emitter).await,
     _ => tokio::task::spawn_blocking(move || {
-      generic_parallel_search(
-        opts,
-        &mut idx.unwrap(),
-        wtr,
-        column,
-        conditions,
-        jobs,
-        match_fn,
-      )
+      generic_parallel_search(input, wtr, column, conditions, jobs, match_fn)
     })
     .await
     .map_err(|e| anyhow::anyhow!("Task join error: {}", e))?,
@@ -194,9 +158,8 @@ where
 
 pub async fn not_starts_with<E>(
   rdr: csv::Reader<BufReader<Box<dyn Read + Send>>>,
-  wtr: csv::Writer<BufWriter<File>>,
-  opts: CsvOptions<String>,
-  idx: Option<Indexed<File, File>>,
+  wtr: impl Into<MatchSink>,
+  opts: impl Into<SearchInput>,
   column: String,
   conditions: Vec<String>,
   progress: bool,
@@ -206,21 +169,15 @@ pub async fn not_starts_with<E>(
 where
   E: EventEmitter + Send + Sync + 'static,
 {
+  let matcher = Matcher::from_conditions("not_starts_with", conditions.clone())?;
+  let wtr = wtr.into();
+  let input = opts.into();
   let jobs = threads.unwrap_or(1);
-  let conditions_clone = conditions.clone();
-  let match_fn = move |value: &str, _: &[String]| !conditions_clone.iter().any(|cond| value.starts_with(cond));
+  let match_fn = move |value: &str, _: &[String]| matcher.is_match(value);
   match jobs {
     1 => generic_search(rdr, wtr, column, conditions, progress, match_fn, emitter).await,
     _ => tokio::task::spawn_blocking(move || {
-      generic_parallel_search(
-        opts,
-        &mut idx.unwrap(),
-        wtr,
-        column,
-        conditions,
-        jobs,
-        match_fn,
-      )
+      generic_parallel_search(input, wtr, column, conditions, jobs, match_fn)
     })
     .await
     .map_err(|e| anyhow::anyhow!("Task join error: {}", e))?,
@@ -229,9 +186,8 @@ where
 
 pub async fn ends_with<E>(
   rdr: csv::Reader<BufReader<Box<dyn Read + Send>>>,
-  wtr: csv::Writer<BufWriter<File>>,
-  opts: CsvOptions<String>,
-  idx: Option<Indexed<File, File>>,
+  wtr: impl Into<MatchSink>,
+  opts: impl Into<SearchInput>,
   column: String,
   conditions: Vec<String>,
   progress: bool,
@@ -241,21 +197,15 @@ pub async fn ends_with<E>(
 where
   E: EventEmitter + Send + Sync + 'static,
 {
+  let matcher = Matcher::from_conditions("ends_with", conditions.clone())?;
+  let wtr = wtr.into();
+  let input = opts.into();
   let jobs = threads.unwrap_or(1);
-  let conditions_clone = conditions.clone();
-  let match_fn = move |value: &str, _: &[String]| conditions_clone.iter().any(|cond| value.ends_with(cond));
+  let match_fn = move |value: &str, _: &[String]| matcher.is_match(value);
   match jobs {
     1 => generic_search(rdr, wtr, column, conditions, progress, match_fn, emitter).await,
     _ => tokio::task::spawn_blocking(move || {
-      generic_parallel_search(
-        opts,
-        &mut idx.unwrap(),
-        wtr,
-        column,
-        conditions,
-        jobs,
-        match_fn,
-      )
+      generic_parallel_search(input, wtr, column, conditions, jobs, match_fn)
     })
     .await
     .map_err(|e| anyhow::anyhow!("Task join error: {}", e))?,
@@ -264,9 +214,8 @@ where
 
 pub async fn not_ends_with<E>(
   rdr: csv::Reader<BufReader<Box<dyn Read + Send>>>,
-  wtr: csv::Writer<BufWriter<File>>,
-  opts: CsvOptions<String>,
-  idx: Option<Indexed<File, File>>,
+  wtr: impl Into<MatchSink>,
+  opts: impl Into<SearchInput>,
   column: String,
   conditions: Vec<String>,
   progress: bool,
@@ -276,21 +225,15 @@ pub async fn not_ends_with<E>(
 where
   E: EventEmitter + Send + Sync + 'static,
 {
+  let matcher = Matcher::from_conditions("not_ends_with", conditions.clone())?;
+  let wtr = wtr.into();
+  let input = opts.into();
   let jobs = threads.unwrap_or(1);
-  let conditions_clone = conditions.clone();
-  let match_fn = move |value: &str, _: &[String]| !conditions_clone.iter().any(|cond| value.ends_with(cond));
+  let match_fn = move |value: &str, _: &[String]| matcher.is_match(value);
   match jobs {
     1 => generic_search(rdr, wtr, column, conditions, progress, match_fn, emitter).await,
     _ => tokio::task::spawn_blocking(move || {
-      generic_parallel_search(
-        opts,
-        &mut idx.unwrap(),
-        wtr,
-        column,
-        conditions,
-        jobs,
-        match_fn,
-      )
+      generic_parallel_search(input, wtr, column, conditions, jobs, match_fn)
     })
     .await
     .map_err(|e| anyhow::anyhow!("Task join error: {}", e))?,
@@ -299,9 +242,8 @@ where
 
 pub async fn regex_search<E>(
   rdr: csv::Reader<BufReader<Box<dyn Read + Send>>>,
-  wtr: csv::Writer<BufWriter<File>>,
-  opts: CsvOptions<String>,
-  idx: Option<Indexed<File, File>>,
+  wtr: impl Into<MatchSink>,
+  opts: impl Into<SearchInput>,
   column: String,
   regex_char: String,
   progress: bool,
@@ -311,9 +253,11 @@ pub async fn regex_search<E>(
 where
   E: EventEmitter + Send + Sync + 'static,
 {
-  let pattern = RegexBuilder::new(&regex_char).build()?;
+  let matcher = Matcher::new("regex", &regex_char)?;
+  let wtr = wtr.into();
+  let input = opts.into();
   let jobs = threads.unwrap_or(1);
-  let match_fn = move |value: &str, _: &[String]| pattern.is_match(value.as_bytes());
+  let match_fn = move |value: &str, _: &[String]| matcher.is_match(value);
   match jobs {
     1 => {
       generic_search(
@@ -328,15 +272,7 @@ where
       .await
     }
     _ => tokio::task::spawn_blocking(move || {
-      generic_parallel_search(
-        opts,
-        &mut idx.unwrap(),
-        wtr,
-        column,
-        vec![regex_char],
-        jobs,
-        match_fn,
-      )
+      generic_parallel_search(input, wtr, column, vec![regex_char], jobs, match_fn)
     })
     .await
     .map_err(|e| anyhow::anyhow!("Task join error: {}", e))?,
@@ -345,9 +281,8 @@ where
 
 pub async fn is_null<E>(
   rdr: csv::Reader<BufReader<Box<dyn Read + Send>>>,
-  wtr: csv::Writer<BufWriter<File>>,
-  opts: CsvOptions<String>,
-  idx: Option<Indexed<File, File>>,
+  wtr: impl Into<MatchSink>,
+  opts: impl Into<SearchInput>,
   column: String,
   conditions: Vec<String>,
   progress: bool,
@@ -357,20 +292,15 @@ pub async fn is_null<E>(
 where
   E: EventEmitter + Send + Sync + 'static,
 {
+  let matcher = Matcher::IsNull;
+  let wtr = wtr.into();
+  let input = opts.into();
   let jobs = threads.unwrap_or(1);
-  let match_fn = |value: &str, _: &[String]| value.trim().is_empty();
+  let match_fn = move |value: &str, _: &[String]| matcher.is_match(value);
   match jobs {
     1 => generic_search(rdr, wtr, column, conditions, progress, match_fn, emitter).await,
     _ => tokio::task::spawn_blocking(move || {
-      generic_parallel_search(
-        opts,
-        &mut idx.unwrap(),
-        wtr,
-        column,
-        conditions,
-        jobs,
-        match_fn,
-      )
+      generic_parallel_search(input, wtr, column, conditions, jobs, match_fn)
     })
     .await
     .map_err(|e| anyhow::anyhow!("Task join error: {}", e))?,
@@ -379,9 +309,8 @@ where
 
 pub async fn is_not_null<E>(
   rdr: csv::Reader<BufReader<Box<dyn Read + Send>>>,
-  wtr: csv::Writer<BufWriter<File>>,
-  opts: CsvOptions<String>,
-  idx: Option<Indexed<File, File>>,
+  wtr: impl Into<MatchSink>,
+  opts: impl Into<SearchInput>,
   column: String,
   conditions: Vec<String>,
   progress: bool,
@@ -391,20 +320,15 @@ pub async fn is_not_null<E>(
 where
   E: EventEmitter + Send + Sync + 'static,
 {
+  let matcher = Matcher::IsNotNull;
+  let wtr = wtr.into();
+  let input = opts.into();
   let jobs = threads.unwrap_or(1);
-  let match_fn = |value: &str, _: &[String]| !value.trim().is_empty();
+  let match_fn = move |value: &str, _: &[String]| matcher.is_match(value);
   match jobs {
     1 => generic_search(rdr, wtr, column, conditions, progress, match_fn, emitter).await,
     _ => tokio::task::spawn_blocking(move || {
-      generic_parallel_search(
-        opts,
-        &mut idx.unwrap(),
-        wtr,
-        column,
-        conditions,
-        jobs,
-        match_fn,
-      )
+      generic_parallel_search(input, wtr, column, conditions, jobs, match_fn)
     })
     .await
     .map_err(|e| anyhow::anyhow!("Task join error: {}", e))?,
@@ -413,9 +337,8 @@ where
 
 pub async fn greater_than<E>(
   rdr: csv::Reader<BufReader<Box<dyn Read + Send>>>,
-  wtr: csv::Writer<BufWriter<File>>,
-  opts: CsvOptions<String>,
-  idx: Option<Indexed<File, File>>,
+  wtr: impl Into<MatchSink>,
+  opts: impl Into<SearchInput>,
   column: String,
   conditions: String,
   progress: bool,
@@ -425,16 +348,11 @@ pub async fn greater_than<E>(
 where
   E: EventEmitter + Send + Sync + 'static,
 {
-  let threshold_value = conditions
-    .parse::<f64>()
-    .map_err(|_| anyhow!("Condition must be a valid number"))?;
+  let matcher = Matcher::new("gt", &conditions)?;
+  let wtr = wtr.into();
+  let input = opts.into();
   let jobs = threads.unwrap_or(1);
-  let match_fn = move |value: &str, _: &[String]| {
-    value
-      .parse::<f64>()
-      .map(|v| v > threshold_value)
-      .unwrap_or(false)
-  };
+  let match_fn = move |value: &str, _: &[String]| matcher.is_match(value);
   match jobs {
     1 => {
       generic_search(
@@ -449,15 +367,7 @@ where
       .await
     }
     _ => tokio::task::spawn_blocking(move || {
-      generic_parallel_search(
-        opts,
-        &mut idx.unwrap(),
-        wtr,
-        column,
-        vec![conditions],
-        jobs,
-        match_fn,
-      )
+      generic_parallel_search(input, wtr, column, vec![conditions], jobs, match_fn)
     })
     .await
     .map_err(|e| anyhow::anyhow!("Task join error: {}", e))?,
@@ -466,9 +376,8 @@ where
 
 pub async fn greater_than_or_equal<E>(
   rdr: csv::Reader<BufReader<Box<dyn Read + Send>>>,
-  wtr: csv::Writer<BufWriter<File>>,
-  opts: CsvOptions<String>,
-  idx: Option<Indexed<File, File>>,
+  wtr: impl Into<MatchSink>,
+  opts: impl Into<SearchInput>,
   column: String,
   conditions: String,
   progress: bool,
@@ -478,16 +387,11 @@ pub async fn greater_than_or_equal<E>(
 where
   E: EventEmitter + Send + Sync + 'static,
 {
-  let threshold_value = conditions
-    .parse::<f64>()
-    .map_err(|_| anyhow!("Condition must be a valid number"))?;
+  let matcher = Matcher::new("ge", &conditions)?;
+  let wtr = wtr.into();
+  let input = opts.into();
   let jobs = threads.unwrap_or(1);
-  let match_fn = move |value: &str, _: &[String]| {
-    value
-      .parse::<f64>()
-      .map(|v| v >= threshold_value)
-      .unwrap_or(false)
-  };
+  let match_fn = move |value: &str, _: &[String]| matcher.is_match(value);
   match jobs {
     1 => {
       generic_search(
@@ -502,15 +406,7 @@ where
       .await
     }
     _ => tokio::task::spawn_blocking(move || {
-      generic_parallel_search(
-        opts,
-        &mut idx.unwrap(),
-        wtr,
-        column,
-        vec![conditions],
-        jobs,
-        match_fn,
-      )
+      generic_parallel_search(input, wtr, column, vec![conditions], jobs, match_fn)
     })
     .await
     .map_err(|e| anyhow::anyhow!("Task join error: {}", e))?,
@@ -519,9 +415,8 @@ where
 
 pub async fn less_than<E>(
   rdr: csv::Reader<BufReader<Box<dyn Read + Send>>>,
-  wtr: csv::Writer<BufWriter<File>>,
-  opts: CsvOptions<String>,
-  idx: Option<Indexed<File, File>>,
+  wtr: impl Into<MatchSink>,
+  opts: impl Into<SearchInput>,
   column: String,
   conditions: String,
   progress: bool,
@@ -531,16 +426,11 @@ pub async fn less_than<E>(
 where
   E: EventEmitter + Send + Sync + 'static,
 {
-  let threshold_value = conditions
-    .parse::<f64>()
-    .map_err(|_| anyhow!("Invalid number: {conditions}"))?;
+  let matcher = Matcher::new("lt", &conditions)?;
+  let wtr = wtr.into();
+  let input = opts.into();
   let jobs = threads.unwrap_or(1);
-  let match_fn = move |value: &str, _: &[String]| {
-    value
-      .parse::<f64>()
-      .map(|v| v < threshold_value)
-      .unwrap_or(false)
-  };
+  let match_fn = move |value: &str, _: &[String]| matcher.is_match(value);
   match jobs {
     1 => {
       generic_search(
@@ -555,15 +445,7 @@ where
       .await
     }
     _ => tokio::task::spawn_blocking(move || {
-      generic_parallel_search(
-        opts,
-        &mut idx.unwrap(),
-        wtr,
-        column,
-        vec![conditions],
-        jobs,
-        match_fn,
-      )
+      generic_parallel_search(input, wtr, column, vec![conditions], jobs, match_fn)
     })
     .await
     .map_err(|e| anyhow::anyhow!("Task join error: {}", e))?,
@@ -572,9 +454,8 @@ where
 
 pub async fn less_than_or_equal<E>(
   rdr: csv::Reader<BufReader<Box<dyn Read + Send>>>,
-  wtr: csv::Writer<BufWriter<File>>,
-  opts: CsvOptions<String>,
-  idx: Option<Indexed<File, File>>,
+  wtr: impl Into<MatchSink>,
+  opts: impl Into<SearchInput>,
   column: String,
   conditions: String,
   progress: bool,
@@ -584,16 +465,11 @@ pub async fn less_than_or_equal<E>(
 where
   E: EventEmitter + Send + Sync + 'static,
 {
-  let threshold_value = conditions
-    .parse::<f64>()
-    .map_err(|_| anyhow!("Condition must be a valid number"))?;
+  let matcher = Matcher::new("le", &conditions)?;
+  let wtr = wtr.into();
+  let input = opts.into();
   let jobs = threads.unwrap_or(1);
-  let match_fn = move |value: &str, _: &[String]| {
-    value
-      .parse::<f64>()
-      .map(|v| v <= threshold_value)
-      .unwrap_or(false)
-  };
+  let match_fn = move |value: &str, _: &[String]| matcher.is_match(value);
   match jobs {
     1 => {
       generic_search(
@@ -608,15 +484,7 @@ where
       .await
     }
     _ => tokio::task::spawn_blocking(move || {
-      generic_parallel_search(
-        opts,
-        &mut idx.unwrap(),
-        wtr,
-        column,
-        vec![conditions],
-        jobs,
-        match_fn,
-      )
+      generic_parallel_search(input, wtr, column, vec![conditions], jobs, match_fn)
     })
     .await
     .map_err(|e| anyhow::anyhow!("Task join error: {}", e))?,
@@ -625,9 +493,8 @@ where
 
 pub async fn between<E>(
   rdr: csv::Reader<BufReader<Box<dyn Read + Send>>>,
-  wtr: csv::Writer<BufWriter<File>>,
-  opts: CsvOptions<String>,
-  idx: Option<Indexed<File, File>>,
+  wtr: impl Into<MatchSink>,
+  opts: impl Into<SearchInput>,
   column: String,
   conditions: Vec<String>,
   progress: bool,
@@ -637,44 +504,69 @@ pub async fn between<E>(
 where
   E: EventEmitter + Send + Sync + 'static,
 {
-  if conditions.len() != 2 {
-    return Err(anyhow!(
-      "Exactly two values required for between: min and max"
-    ));
+  let matcher = Matcher::from_conditions("between", conditions.clone())?;
+  let wtr = wtr.into();
+  let input = opts.into();
+  let jobs = threads.unwrap_or(1);
+  let match_fn = move |value: &str, _: &[String]| matcher.is_match(value);
+  match jobs {
+    1 => generic_search(rdr, wtr, column, conditions, progress, match_fn, emitter).await,
+    _ => tokio::task::spawn_blocking(move || {
+      generic_parallel_search(input, wtr, column, conditions, jobs, match_fn)
+    })
+    .await
+    .map_err(|e| anyhow::anyhow!("Task join error: {}", e))?,
   }
+}
 
-  let val1 = conditions[0]
-    .parse::<f64>()
-    .map_err(|_| anyhow!("Invalid number: {}", conditions[0]))?;
-
-  let val2 = conditions[1]
-    .parse::<f64>()
-    .map_err(|_| anyhow!("Invalid number: {}", conditions[1]))?;
+pub async fn lookup<E>(
+  rdr: csv::Reader<BufReader<Box<dyn Read + Send>>>,
+  wtr: impl Into<MatchSink>,
+  opts: impl Into<SearchInput>,
+  column: String,
+  matcher: LookupMatcher,
+  progress: bool,
+  threads: Option<usize>,
+  emitter: E,
+) -> Result<String>
+where
+  E: EventEmitter + Send + Sync + 'static,
+{
+  let wtr = wtr.into();
+  let input = opts.into();
+  let jobs = threads.unwrap_or(1);
+  let match_fn = move |value: &str, _: &[String]| matcher.is_match(value);
+  match jobs {
+    1 => generic_search(rdr, wtr, column, vec![], progress, match_fn, emitter).await,
+    _ => tokio::task::spawn_blocking(move || {
+      generic_parallel_search(input, wtr, column, vec![], jobs, match_fn)
+    })
+    .await
+    .map_err(|e| anyhow::anyhow!("Task join error: {}", e))?,
+  }
+}
 
-  let (min_value, max_value) = if val1 <= val2 {
-    (val1, val2)
-  } else {
-    (val2, val1)
-  };
+pub async fn date_search<E>(
+  rdr: csv::Reader<BufReader<Box<dyn Read + Send>>>,
+  wtr: impl Into<MatchSink>,
+  opts: impl Into<SearchInput>,
+  column: String,
+  matcher: DateMatcher,
+  progress: bool,
+  threads: Option<usize>,
+  emitter: E,
+) -> Result<String>
+where
+  E: EventEmitter + Send + Sync + 'static,
+{
+  let wtr = wtr.into();
+  let input = opts.into();
   let jobs = threads.unwrap_or(1);
-  let match_fn = move |value: &str, _: &[String]| {
-    value
-      .parse::<f64>()
-      .map(|v| v >= min_value && v <= max_value)
-      .unwrap_or(false)
-  };
+  let match_fn = move |value: &str, _: &[String]| matcher.is_match(value);
   match jobs {
-    1 => generic_search(rdr, wtr, column, conditions, progress, match_fn, emitter).await,
+    1 => generic_search(rdr, wtr, column, vec![], progress, match_fn, emitter).await,
     _ => tokio::task::spawn_blocking(move || {
-      generic_parallel_search(
-        opts,
-        &mut idx.unwrap(),
-        wtr,
-        column,
-        conditions,
-        jobs,
-        match_fn,
-      )
+      generic_parallel_search(input, wtr, column, vec![], jobs, match_fn)
     })
     .await
     .map_err(|e| anyhow::anyhow!("Task join error: {}", e))?,
@@ -686,7 +578,7 @@ pub async fn irregular_with_regex(
   output_path: PathBuf,
   pattern: String,
 ) -> Result<String> {
-  let mut wtr = BufWriter::with_capacity(WTR_BUFFER_SIZE, File::create(output_path)?);
+  let mut wtr = writer::create(output_path)?;
   let re = regex::Regex::new(&pattern)?;
 
   let mut total = 0;
@@ -697,7 +589,7 @@ pub async fn irregular_with_regex(
       total += 1;
     }
   }
-  wtr.flush()?;
+  wtr.finish()?;
 
   Ok(total.to_string())
 }
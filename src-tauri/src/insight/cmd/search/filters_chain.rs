@@ -1,10 +1,15 @@
-use std::{fs::File, path::Path};
+use std::path::Path;
 
 use anyhow::{Result, anyhow};
 
 use crate::{
-  cmd::search::{generic, perform::ColumnConfig},
-  index::Indexed,
+  cmd::search::{
+    date::{DateMatcher, Unparsable},
+    fuzzy::FuzzyMatcher,
+    generic::{self, MatchSink, SearchInput, SearchOutput},
+    matcher::Matcher,
+    perform::{ColumnConfig, ValueMatcher},
+  },
   io::csv::{config::CsvConfigBuilder, options::CsvOptions},
   utils::EventEmitter,
 };
@@ -18,148 +23,111 @@ pub async fn search_with_chain<E, P>(
   flexible: bool,
   progress: bool,
   threads: usize,
+  output: SearchOutput,
   emitter: E,
 ) -> Result<String>
 where
   E: EventEmitter + Send + Sync + 'static,
   P: AsRef<Path> + Send + Sync,
 {
-  if configs.is_empty() {
-    return Err(anyhow!("No filters added"));
-  }
-  if logics.len() != configs.len().saturating_sub(1) {
-    return Err(anyhow!("logics length must be configs.len() - 1"));
-  }
-
   let columns: Vec<String> = configs.iter().map(|c| c.column.clone()).collect();
+  let match_fn = chain_match_fn(configs, logics)?;
 
   let mut opts = CsvOptions::new(path.as_ref().to_string_lossy().to_string());
   opts.set_skiprows(skiprows);
-  let (sep, reader) = opts.skiprows_and_delimiter()?;
-  let output_path = opts.output_path(Some("search"), None)?;
+  let (dialect, reader) = opts.skiprows_and_dialect()?;
   let config = CsvConfigBuilder::new()
     .flexible(flexible)
-    .delimiter(sep)
+    .dialect(&dialect)
     .quoting(quoting)
     .build();
   let rdr = config.build_reader(reader);
-  let wtr = config.build_writer(&output_path)?;
+  let wtr = MatchSink::for_search(&opts, &config, output)?;
 
-  let mut idx: Option<Indexed<File, File>> = None;
-  match threads {
-    1 => {
-      let total_rows = if progress {
-        opts.idx_count_rows().await?
-      } else {
-        0
+  // 压缩文件无法内存映射, 只能单线程流式读取
+  let threads = if opts.is_compressed() { 1 } else { threads };
+  if threads == 1 {
+    let total_rows = if progress {
+      opts.idx_count_rows().await?
+    } else {
+      0
+    };
+    emitter.emit_total_search_rows(total_rows).await?;
+  }
+
+  let match_count = match threads {
+    1 => generic::generic_search_chain(rdr, wtr, columns, progress, match_fn, emitter).await?,
+    _ => tokio::task::spawn_blocking(move || {
+      let input = SearchInput {
+        opts,
+        quoting,
+        flexible,
       };
-      emitter.emit_total_search_rows(total_rows).await?;
-    }
-    _ => {
-      idx = Some(
-        opts
-          .indexed()?
-          .ok_or_else(|| anyhow!("No indexed file, create index first"))?,
-      );
+      generic::generic_parallel_search_chain(input, wtr, columns, threads, match_fn)
+    })
+    .await
+    .map_err(|e| anyhow::anyhow!("Task join error: {e}"))??,
+  };
+
+  Ok(match_count)
+}
+
+/// The predicate of one column of a chain
+enum ColumnMatcher {
+  Value(ValueMatcher),
+  Plain(Matcher),
+}
+
+impl ColumnMatcher {
+  fn is_match(&self, value: &str) -> bool {
+    match self {
+      ColumnMatcher::Value(matcher) => matcher.is_match(value),
+      ColumnMatcher::Plain(matcher) => matcher.is_match(value),
     }
   }
+}
+
+/// Build the predicate of a chain, it takes the values of the configured columns in order
+pub(crate) fn chain_match_fn(
+  configs: Vec<ColumnConfig>,
+  logics: Vec<String>,
+) -> Result<impl Fn(&[&str]) -> bool + Send + Sync + 'static> {
+  if configs.is_empty() {
+    return Err(anyhow!("No filters added"));
+  }
+  if logics.len() != configs.len().saturating_sub(1) {
+    return Err(anyhow!("logics length must be configs.len() - 1"));
+  }
 
-  // 预解析每个 condition
-  let parsed_configs: Vec<(String, Vec<String>)> = configs
+  // 每列预先构建一次匹配器, 与单列搜索共用 `Matcher`
+  let matchers: Vec<ColumnMatcher> = configs
     .into_iter()
     .map(|cfg| {
-      let sub_conds = cfg
-        .condition
-        .split('|')
-        .map(|s| s.trim().to_string())
-        .collect::<Vec<_>>();
-      (cfg.mode, sub_conds)
+      Ok(if cfg.mode.starts_with("date_") {
+        ColumnMatcher::Value(ValueMatcher::Date(DateMatcher::new(
+          &cfg.mode,
+          &cfg.condition,
+          cfg.date_format.as_deref(),
+          Unparsable::from(cfg.unparsable.as_deref().unwrap_or("exclude")),
+        )?))
+      } else if cfg.mode == "fuzzy" {
+        ColumnMatcher::Value(ValueMatcher::Fuzzy {
+          matcher: FuzzyMatcher::new(&cfg.condition, cfg.fuzzy)?,
+          score: false,
+        })
+      } else {
+        ColumnMatcher::Plain(Matcher::new(&cfg.mode, &cfg.condition)?)
+      })
     })
-    .collect();
+    .collect::<Result<_>>()?;
 
   // 构造 match_fn
   let match_fn = move |values: &[&str]| -> bool {
     // 每列独立判断
     let col_results: Vec<bool> = values
       .iter()
-      .enumerate()
-      .map(|(i, &value)| {
-        let (mode, sub_conds) = &parsed_configs[i];
-        match mode.as_str() {
-          "equal" => sub_conds.iter().any(|c| value == c),
-          "not_equal" => sub_conds.iter().all(|c| value != c),
-          "contains" => sub_conds.iter().any(|c| value.contains(c)),
-          "not_contains" => sub_conds.iter().all(|c| !value.contains(c)),
-          "starts_with" => sub_conds.iter().any(|c| value.starts_with(c)),
-          "not_starts_with" => sub_conds.iter().all(|c| !value.starts_with(c)),
-          "ends_with" => sub_conds.iter().any(|c| value.ends_with(c)),
-          "not_ends_with" => sub_conds.iter().all(|c| !value.ends_with(c)),
-          "regex" => sub_conds.iter().any(|pattern| {
-            regex::Regex::new(pattern)
-              .ok()
-              .map_or(false, |re| re.is_match(value))
-          }),
-          "is_null" => value.is_empty(),
-          "is_not_null" => !value.is_empty(),
-          "gt" => {
-            if let (Some(threshold), Ok(val)) = (sub_conds.get(0), value.parse::<f64>()) {
-              if let Ok(t) = threshold.parse::<f64>() {
-                val > t
-              } else {
-                false
-              }
-            } else {
-              false
-            }
-          }
-          "ge" => {
-            if let (Some(threshold), Ok(val)) = (sub_conds.get(0), value.parse::<f64>()) {
-              if let Ok(t) = threshold.parse::<f64>() {
-                val >= t
-              } else {
-                false
-              }
-            } else {
-              false
-            }
-          }
-          "lt" => {
-            if let (Some(threshold), Ok(val)) = (sub_conds.get(0), value.parse::<f64>()) {
-              if let Ok(t) = threshold.parse::<f64>() {
-                val < t
-              } else {
-                false
-              }
-            } else {
-              false
-            }
-          }
-          "le" => {
-            if let (Some(threshold), Ok(val)) = (sub_conds.get(0), value.parse::<f64>()) {
-              if let Ok(t) = threshold.parse::<f64>() {
-                val <= t
-              } else {
-                false
-              }
-            } else {
-              false
-            }
-          }
-          "between" => {
-            if sub_conds.len() == 2 {
-              if let (Ok(low), Ok(high)) =
-                (sub_conds[0].parse::<f64>(), sub_conds[1].parse::<f64>())
-              {
-                if let Ok(val) = value.parse::<f64>() {
-                  return val >= low && val <= high;
-                }
-              }
-            }
-            false
-          }
-          _ => false,
-        }
-      })
+      .zip(&matchers)
+      .map(|(&value, matcher)| matcher.is_match(value))
       .collect();
 
     // 链式组合
@@ -174,21 +142,5 @@ where
     result
   };
 
-  let match_count = match threads {
-    1 => generic::generic_search_chain(rdr, wtr, columns, progress, match_fn, emitter).await?,
-    _ => tokio::task::spawn_blocking(move || {
-      generic::generic_parallel_search_chain(
-        opts,
-        &mut idx.unwrap(),
-        wtr,
-        columns,
-        threads,
-        match_fn,
-      )
-    })
-    .await
-    .map_err(|e| anyhow::anyhow!("Task join error: {e}"))??,
-  };
-
-  Ok(match_count)
+  Ok(match_fn)
 }
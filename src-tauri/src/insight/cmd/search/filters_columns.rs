@@ -0,0 +1,148 @@
+use std::path::Path;
+
+use anyhow::{Result, anyhow};
+use csv::ByteRecord;
+
+use crate::{
+  cmd::search::{
+    generic::{SearchOutput, generic_search_records},
+    matcher::{self, Matcher},
+  },
+  utils::EventEmitter,
+};
+
+/// Header of the column naming the matched columns
+pub const MATCHED_COLUMNS: &str = "_matched_columns";
+
+/// Search every column of a row instead of one named column
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnScope {
+  /// 任一列匹配即可
+  Any,
+  /// 所有列都要匹配
+  All,
+}
+
+impl ColumnScope {
+  /// `column` (the default) searches the named column only
+  pub fn new(scope: &str) -> Result<Option<Self>> {
+    match scope {
+      "column" => Ok(None),
+      "any" => Ok(Some(ColumnScope::Any)),
+      "all" => Ok(Some(ColumnScope::All)),
+      _ => Err(anyhow!("Unsupported column scope: {scope}")),
+    }
+  }
+}
+
+/// A `Matcher` applied to every column of a row
+pub struct ColumnsMatcher {
+  matcher: Matcher,
+  scope: ColumnScope,
+}
+
+impl ColumnsMatcher {
+  pub fn new(mode: &str, condition: &str, scope: ColumnScope) -> Result<Self> {
+    if !matches!(
+      matcher::base_mode(mode),
+      "equal" | "contains" | "starts_with" | "regex"
+    ) {
+      return Err(anyhow!(
+        "Searching every column supports equal, contains, starts_with and regex, not {mode}"
+      ));
+    }
+    Ok(ColumnsMatcher {
+      matcher: Matcher::new(mode, condition)?,
+      scope,
+    })
+  }
+
+  fn is_field_match(&self, field: &[u8]) -> bool {
+    self.matcher.is_match(&String::from_utf8_lossy(field))
+  }
+
+  pub fn is_match(&self, record: &ByteRecord) -> bool {
+    match self.scope {
+      ColumnScope::Any => record.iter().any(|f| self.is_field_match(f)),
+      ColumnScope::All => !record.is_empty() && record.iter().all(|f| self.is_field_match(f)),
+    }
+  }
+
+  /// Indices of the matching columns, `None` when the row does not match
+  pub fn matched_columns(&self, record: &ByteRecord) -> Option<Vec<usize>> {
+    let mut matched = Vec::new();
+    for (i, field) in record.iter().enumerate() {
+      if self.is_field_match(field) {
+        matched.push(i);
+      } else if self.scope == ColumnScope::All {
+        return None;
+      }
+    }
+    (!matched.is_empty()).then_some(matched)
+  }
+
+  /// Whether the row matches, with the row to write when `label` adds `_matched_columns`
+  fn evaluate(
+    &self,
+    headers: &ByteRecord,
+    label: bool,
+    record: &ByteRecord,
+  ) -> (bool, Option<ByteRecord>) {
+    if !label {
+      return (self.is_match(record), None);
+    }
+
+    let matched = self.matched_columns(record);
+    let names = matched
+      .as_ref()
+      .map(|indices| {
+        indices
+          .iter()
+          .filter_map(|&i| headers.get(i))
+          .map(|name| String::from_utf8_lossy(name))
+          .collect::<Vec<_>>()
+          .join("|")
+      })
+      .unwrap_or_default();
+    let mut labeled = record.clone();
+    labeled.push_field(names.as_bytes());
+    (matched.is_some(), Some(labeled))
+  }
+}
+
+pub async fn search_columns<E, P>(
+  path: P,
+  mode: &str,
+  condition: &str,
+  scope: ColumnScope,
+  label: bool,
+  skiprows: usize,
+  quoting: bool,
+  flexible: bool,
+  progress: bool,
+  threads: usize,
+  output: SearchOutput,
+  emitter: E,
+) -> Result<String>
+where
+  E: EventEmitter + Send + Sync + 'static,
+  P: AsRef<Path> + Send + Sync,
+{
+  let matcher = ColumnsMatcher::new(mode, condition, scope)?;
+  generic_search_records(
+    path,
+    label.then_some(MATCHED_COLUMNS),
+    skiprows,
+    quoting,
+    flexible,
+    progress,
+    threads,
+    output,
+    emitter,
+    move |headers: &ByteRecord| {
+      let headers = headers.clone();
+      Ok(move |record: &ByteRecord| matcher.evaluate(&headers, label, record))
+    },
+  )
+  .await
+}
@@ -3,7 +3,7 @@ use std::path::Path;
 use anyhow::Result;
 
 use crate::{
-  cmd::search::generic::{generic_multi_search, generic_multi_search_unique},
+  cmd::search::generic::{SearchOutput, generic_multi_search, generic_multi_search_unique},
   utils::EventEmitter,
 };
 
@@ -15,6 +15,7 @@ pub async fn equal_multi<E, P>(
   quoting: bool,
   progress: bool,
   unique: bool,
+  output: SearchOutput,
   emitter: E,
 ) -> Result<String>
 where
@@ -23,10 +24,13 @@ where
 {
   let match_fn = |value: &str, condition: &String| value == condition;
   if unique {
-    generic_multi_search_unique(path, column, skiprows, quoting, progress, match_fn, emitter).await
+    generic_multi_search_unique(
+      path, column, skiprows, quoting, progress, output, match_fn, emitter,
+    )
+    .await
   } else {
     generic_multi_search(
-      path, column, conditions, skiprows, quoting, progress, match_fn, emitter,
+      path, column, conditions, skiprows, quoting, progress, output, match_fn, emitter,
     )
     .await
   }
@@ -40,6 +44,7 @@ pub async fn contains_multi<E, P>(
   quoting: bool,
   progress: bool,
   unique: bool,
+  output: SearchOutput,
   emitter: E,
 ) -> Result<String>
 where
@@ -48,10 +53,13 @@ where
 {
   let match_fn = |value: &str, condition: &String| value.contains(condition);
   if unique {
-    generic_multi_search_unique(path, column, skiprows, quoting, progress, match_fn, emitter).await
+    generic_multi_search_unique(
+      path, column, skiprows, quoting, progress, output, match_fn, emitter,
+    )
+    .await
   } else {
     generic_multi_search(
-      path, column, conditions, skiprows, quoting, progress, match_fn, emitter,
+      path, column, conditions, skiprows, quoting, progress, output, match_fn, emitter,
     )
     .await
   }
@@ -65,6 +73,7 @@ pub async fn starts_with_multi<E, P>(
   quoting: bool,
   progress: bool,
   unique: bool,
+  output: SearchOutput,
   emitter: E,
 ) -> Result<String>
 where
@@ -73,10 +82,13 @@ where
 {
   let match_fn = |value: &str, condition: &String| value.starts_with(condition);
   if unique {
-    generic_multi_search_unique(path, column, skiprows, quoting, progress, match_fn, emitter).await
+    generic_multi_search_unique(
+      path, column, skiprows, quoting, progress, output, match_fn, emitter,
+    )
+    .await
   } else {
     generic_multi_search(
-      path, column, conditions, skiprows, quoting, progress, match_fn, emitter,
+      path, column, conditions, skiprows, quoting, progress, output, match_fn, emitter,
     )
     .await
   }
@@ -90,6 +102,7 @@ pub async fn ends_with_multi<E, P>(
   quoting: bool,
   progress: bool,
   unique: bool,
+  output: SearchOutput,
   emitter: E,
 ) -> Result<String>
 where
@@ -98,10 +111,13 @@ where
 {
   let match_fn = |value: &str, conds: &String| value.ends_with(conds);
   if unique {
-    generic_multi_search_unique(path, column, skiprows, quoting, progress, match_fn, emitter).await
+    generic_multi_search_unique(
+      path, column, skiprows, quoting, progress, output, match_fn, emitter,
+    )
+    .await
   } else {
     generic_multi_search(
-      path, column, conditions, skiprows, quoting, progress, match_fn, emitter,
+      path, column, conditions, skiprows, quoting, progress, output, match_fn, emitter,
     )
     .await
   }
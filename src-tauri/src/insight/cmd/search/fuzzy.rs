@@ -0,0 +1,185 @@
+use std::path::Path;
+
+use anyhow::{Result, anyhow};
+use csv::ByteRecord;
+use pinyin::ToPinyin;
+
+use crate::{
+  cmd::search::generic::{SearchOutput, generic_search_records},
+  io::csv::selection::Selection,
+  similarity::{damerau_levenshtein, jaro_winkler, levenshtein},
+  utils::EventEmitter,
+};
+
+/// Header of the column with the similarity score
+pub const FUZZY_SCORE: &str = "_fuzzy_score";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FuzzyMetric {
+  /// 编辑距离
+  #[default]
+  Levenshtein,
+  /// 编辑距离, 相邻字符互换算一次编辑
+  Damerau,
+  /// Jaro-Winkler 相似度
+  JaroWinkler,
+}
+
+/// Options of the `fuzzy` search mode
+#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize)]
+#[serde(default)]
+pub struct FuzzyOptions {
+  pub metric: FuzzyMetric,
+  /// 编辑距离上限 (levenshtein, damerau)
+  pub max_distance: usize,
+  /// 相似度下限 (jaro_winkler)
+  pub threshold: f64,
+  /// 汉字先转为拼音再比较
+  pub pinyin: bool,
+  pub ignore_case: bool,
+}
+
+impl Default for FuzzyOptions {
+  fn default() -> Self {
+    FuzzyOptions {
+      metric: FuzzyMetric::default(),
+      max_distance: 1,
+      threshold: 0.9,
+      pinyin: false,
+      ignore_case: false,
+    }
+  }
+}
+
+/// Matches values close to one of the `|` separated conditions.
+///
+/// The score is in `[0, 1]`: the Jaro-Winkler similarity, or `1 - distance / longer length`
+/// for the edit distances.
+pub struct FuzzyMatcher {
+  targets: Vec<Vec<char>>,
+  options: FuzzyOptions,
+}
+
+impl FuzzyMatcher {
+  pub fn new(condition: &str, options: FuzzyOptions) -> Result<Self> {
+    if !(0.0..=1.0).contains(&options.threshold) {
+      return Err(anyhow!(
+        "Similarity threshold must be between 0 and 1, not {}",
+        options.threshold
+      ));
+    }
+    let mut matcher = FuzzyMatcher {
+      targets: Vec::new(),
+      options,
+    };
+    matcher.targets = condition
+      .split('|')
+      .map(str::trim)
+      .filter(|s| !s.is_empty())
+      .map(|s| matcher.chars(s))
+      .collect();
+    if matcher.targets.is_empty() {
+      return Err(anyhow!("No condition to fuzzy search"));
+    }
+    Ok(matcher)
+  }
+
+  fn chars(&self, value: &str) -> Vec<char> {
+    let value = value.trim();
+    let value = if self.options.pinyin {
+      value
+        .chars()
+        .map(|c| {
+          c.to_pinyin()
+            .map_or_else(|| c.into(), |py| py.plain().to_string())
+        })
+        .collect::<String>()
+    } else {
+      value.to_string()
+    };
+    if self.options.ignore_case {
+      value.to_lowercase().chars().collect()
+    } else {
+      value.chars().collect()
+    }
+  }
+
+  /// Whether `value` matches one of the conditions, with the best score
+  pub fn evaluate(&self, value: &str) -> (bool, f64) {
+    let value = self.chars(value);
+    let mut best = (false, 0.0);
+    for target in &self.targets {
+      let result = match self.options.metric {
+        FuzzyMetric::JaroWinkler => {
+          let score = jaro_winkler(&value, target);
+          (score >= self.options.threshold, score)
+        }
+        metric => {
+          let distance = match metric {
+            FuzzyMetric::Damerau => damerau_levenshtein(&value, target),
+            _ => levenshtein(&value, target),
+          };
+          let longer = value.len().max(target.len()).max(1);
+          (
+            distance <= self.options.max_distance,
+            1.0 - distance as f64 / longer as f64,
+          )
+        }
+      };
+      if result > best {
+        best = result;
+      }
+    }
+    best
+  }
+
+  pub fn is_match(&self, value: &str) -> bool {
+    self.evaluate(value).0
+  }
+}
+
+/// Fuzzy search of `column`, `score` appends `_fuzzy_score` with the best score of the row
+pub async fn search_fuzzy<E, P>(
+  path: P,
+  column: String,
+  matcher: FuzzyMatcher,
+  score: bool,
+  skiprows: usize,
+  quoting: bool,
+  flexible: bool,
+  progress: bool,
+  threads: usize,
+  output: SearchOutput,
+  emitter: E,
+) -> Result<String>
+where
+  E: EventEmitter + Send + Sync + 'static,
+  P: AsRef<Path> + Send + Sync,
+{
+  generic_search_records(
+    path,
+    score.then_some(FUZZY_SCORE),
+    skiprows,
+    quoting,
+    flexible,
+    progress,
+    threads,
+    output,
+    emitter,
+    move |headers: &ByteRecord| {
+      let idx = Selection::from_headers(headers, &[column.as_str()][..])?.first_indices()?;
+      Ok(move |record: &ByteRecord| {
+        let value = String::from_utf8_lossy(record.get(idx).unwrap_or_default());
+        let (matched, best) = matcher.evaluate(&value);
+        let scored = score.then(|| {
+          let mut scored = record.clone();
+          scored.push_field(format!("{best:.4}").as_bytes());
+          scored
+        });
+        (matched, scored)
+      })
+    },
+  )
+  .await
+}
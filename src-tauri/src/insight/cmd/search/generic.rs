@@ -1,8 +1,7 @@
 use std::{
   collections::{HashMap, HashSet},
-  fs::File,
-  io::{BufReader, BufWriter, Cursor, Read},
-  path::{Path, PathBuf},
+  io::{BufReader, Read},
+  path::Path,
   sync::{
     Arc,
     atomic::{AtomicUsize, Ordering},
@@ -10,21 +9,173 @@ use std::{
   time::Duration,
 };
 
-use anyhow::Result;
-use csv::{ReaderBuilder, Writer, WriterBuilder};
-use rayon::{
-  ThreadPoolBuilder,
-  iter::{IntoParallelIterator, ParallelIterator},
-};
-use tempfile::TempDir;
+use anyhow::{Result, anyhow};
+use csv::{ByteRecord, Writer};
 use tokio::sync::oneshot;
 
 use crate::{
-  index::Indexed,
-  io::csv::{options::CsvOptions, selection::Selection},
-  utils::{self, EventEmitter, MmapOffsets},
+  io::{
+    csv::{
+      config::{CsvConfig, CsvConfigBuilder},
+      options::CsvOptions,
+      parallel::ParallelReader,
+      selection::Selection,
+    },
+    writer::{Finish, OutputWriter},
+  },
+  utils::EventEmitter,
 };
 
+/// What a search writes besides the number of matches
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SearchOutput {
+  /// 匹配行写入 `_search.csv`
+  #[default]
+  Rows,
+  /// 只统计匹配行数, 不写文件
+  Count,
+  /// 匹配行的行号 (从 1 开始, 不含表头) 写入 `_search_rows.csv`
+  RowNumbers,
+  /// 匹配行写入 `_search.csv`, 其余行写入 `_search_unmatched.csv`
+  Split,
+}
+
+impl From<&str> for SearchOutput {
+  fn from(output: &str) -> Self {
+    match output {
+      "count" => SearchOutput::Count,
+      "row_numbers" => SearchOutput::RowNumbers,
+      "split" => SearchOutput::Split,
+      _ => SearchOutput::Rows,
+    }
+  }
+}
+
+/// Writes the outcome of every searched row as its `SearchOutput` asks
+pub struct MatchSink {
+  matched: Option<Writer<OutputWriter>>,
+  unmatched: Option<Writer<OutputWriter>>,
+  row_numbers: bool,
+}
+
+impl MatchSink {
+  /// `prefix` is the output path without `.csv`, e.g. `{parent}/{stem}_search`
+  pub fn new(config: &CsvConfig, output: SearchOutput, prefix: &str) -> Result<Self> {
+    let writer = |suffix: &str| config.build_writer(format!("{prefix}{suffix}.csv"));
+    Ok(match output {
+      SearchOutput::Rows => MatchSink::from(writer("")?),
+      SearchOutput::Count => MatchSink {
+        matched: None,
+        unmatched: None,
+        row_numbers: false,
+      },
+      SearchOutput::RowNumbers => MatchSink {
+        matched: Some(writer("_rows")?),
+        unmatched: None,
+        row_numbers: true,
+      },
+      SearchOutput::Split => MatchSink {
+        matched: Some(writer("")?),
+        unmatched: Some(writer("_unmatched")?),
+        row_numbers: false,
+      },
+    })
+  }
+
+  /// Names of the outputs a search on a file with `stem` may write, the combined
+  /// multi-file output included, so they are not searched as inputs
+  pub fn output_names(stem: &str) -> [String; 4] {
+    ["", "_rows", "_unmatched", "_combined"].map(|suffix| format!("{stem}_search{suffix}.csv"))
+  }
+
+  /// Sink of a search on `opts`, writing `{stem}_search*.csv`
+  pub fn for_search<P>(
+    opts: &CsvOptions<P>,
+    config: &CsvConfig,
+    output: SearchOutput,
+  ) -> Result<Self>
+  where
+    P: AsRef<Path> + Send + Sync,
+  {
+    let prefix = format!("{}/{}_search", opts.parent_path()?, opts.file_stem()?);
+    MatchSink::new(config, output, &prefix)
+  }
+
+  /// Whether (matched, unmatched) rows are written
+  pub(crate) fn wants(&self) -> (bool, bool) {
+    (self.matched.is_some(), self.unmatched.is_some())
+  }
+
+  pub(crate) fn write_headers(&mut self, headers: &ByteRecord) -> Result<()> {
+    if let Some(wtr) = &mut self.matched {
+      match self.row_numbers {
+        true => wtr.write_record(["row"])?,
+        false => wtr.write_byte_record(headers)?,
+      }
+    }
+    if let Some(wtr) = &mut self.unmatched {
+      wtr.write_byte_record(headers)?;
+    }
+    Ok(())
+  }
+
+  /// `row` is the 1-based data row of `record`
+  pub(crate) fn write(&mut self, row: usize, record: &ByteRecord, matched: bool) -> Result<()> {
+    match (matched, &mut self.matched, &mut self.unmatched) {
+      (true, Some(wtr), _) if self.row_numbers => wtr.write_record([row.to_string()])?,
+      (true, Some(wtr), _) => wtr.write_byte_record(record)?,
+      (false, _, Some(wtr)) => wtr.write_byte_record(record)?,
+      _ => {}
+    }
+    Ok(())
+  }
+
+  /// Write the rows of a parallel chunk that starts after `offset` rows
+  pub(crate) fn write_chunk(&mut self, offset: usize, chunk: ChunkMatches) -> Result<()> {
+    for (i, matched, record) in chunk.records {
+      self.write(offset + i + 1, &record, matched)?;
+    }
+    Ok(())
+  }
+
+  pub(crate) fn finish(self) -> Result<()> {
+    for wtr in [self.matched, self.unmatched].into_iter().flatten() {
+      wtr.finish()?;
+    }
+    Ok(())
+  }
+}
+
+impl From<Writer<OutputWriter>> for MatchSink {
+  fn from(wtr: Writer<OutputWriter>) -> Self {
+    MatchSink {
+      matched: Some(wtr),
+      unmatched: None,
+      row_numbers: false,
+    }
+  }
+}
+
+/// Rows of one parallel chunk, only those the sink writes keep their record
+#[derive(Default)]
+pub(crate) struct ChunkMatches {
+  pub(crate) rows: usize,
+  pub(crate) matches: usize,
+  records: Vec<(usize, bool, ByteRecord)>,
+}
+
+impl ChunkMatches {
+  pub(crate) fn push(&mut self, record: &ByteRecord, matched: bool, wants: (bool, bool)) {
+    if matched {
+      self.matches += 1;
+    }
+    if (matched && wants.0) || (!matched && wants.1) {
+      self.records.push((self.rows, matched, record.clone()));
+    }
+    self.rows += 1;
+  }
+}
+
 fn sanitize_condition(condition: &str) -> String {
   condition
     .chars()
@@ -35,9 +186,142 @@ fn sanitize_condition(condition: &str) -> String {
     .collect()
 }
 
+/// Search the rows of `path` with a predicate built from its headers.
+///
+/// The predicate returns whether the row matches and, when `extra_header` is set,
+/// the row to write with the extra column appended.
+pub(crate) async fn generic_search_records<E, P, B, F>(
+  path: P,
+  extra_header: Option<&str>,
+  skiprows: usize,
+  quoting: bool,
+  flexible: bool,
+  progress: bool,
+  threads: usize,
+  output: SearchOutput,
+  emitter: E,
+  build: B,
+) -> Result<String>
+where
+  E: EventEmitter + Send + Sync + 'static,
+  P: AsRef<Path> + Send + Sync,
+  B: FnOnce(&ByteRecord) -> Result<F>,
+  F: Fn(&ByteRecord) -> (bool, Option<ByteRecord>) + Send + Sync + 'static,
+{
+  let mut opts = CsvOptions::new(path.as_ref().to_string_lossy().to_string());
+  opts.set_skiprows(skiprows);
+  let (dialect, reader) = opts.skiprows_and_dialect()?;
+  let config = CsvConfigBuilder::new()
+    .flexible(flexible)
+    .dialect(&dialect)
+    .quoting(quoting)
+    .build();
+  let mut rdr = config.build_reader(reader);
+  let mut sink = MatchSink::for_search(&opts, &config, output)?;
+
+  let headers = rdr.byte_headers()?.clone();
+  let evaluate = build(&headers)?;
+  let mut output_headers = headers;
+  if let Some(extra) = extra_header {
+    output_headers.push_field(extra.as_bytes());
+  }
+  sink.write_headers(&output_headers)?;
+
+  // 压缩文件无法内存映射, 只能单线程流式读取
+  let threads = if opts.is_compressed() { 1 } else { threads };
+  if threads != 1 {
+    return tokio::task::spawn_blocking(move || {
+      let rdr = ParallelReader::from_options(&opts, &config)?;
+      let wants = sink.wants();
+      let (mut total, mut rows) = (0, 0);
+      rdr.for_each_ordered(
+        threads,
+        |chunk| {
+          let mut matches = ChunkMatches::default();
+          let mut record = ByteRecord::new();
+          while chunk.read_byte_record(&mut record)? {
+            let (matched, extended) = evaluate(&record);
+            matches.push(extended.as_ref().unwrap_or(&record), matched, wants);
+          }
+          Ok(matches)
+        },
+        |matches| {
+          total += matches.matches;
+          let offset = rows;
+          rows += matches.rows;
+          sink.write_chunk(offset, matches)
+        },
+      )?;
+      sink.finish()?;
+      Ok(total.to_string())
+    })
+    .await
+    .map_err(|e| anyhow!("Task join error: {e}"))?;
+  }
+
+  let total_rows = if progress {
+    opts.idx_count_rows().await?
+  } else {
+    0
+  };
+  emitter.emit_total_search_rows(total_rows).await?;
+
+  let rows = Arc::new(AtomicUsize::new(0));
+  let match_rows = Arc::new(AtomicUsize::new(0));
+  let match_rows_clone = Arc::clone(&match_rows);
+  let (stop_tx, mut stop_rx) = oneshot::channel::<()>();
+  let (done_tx, mut done_rx) = oneshot::channel::<usize>();
+
+  let timer_task = if progress {
+    let rows_clone = Arc::clone(&rows);
+    Some(tokio::spawn(async move {
+      let mut interval = tokio::time::interval(Duration::from_millis(500));
+      loop {
+        tokio::select! {
+          _ = interval.tick() => {
+            let r = rows_clone.load(Ordering::Relaxed);
+            let _ = emitter.emit_update_search_rows(r).await;
+          },
+          Ok(final_rows) = (&mut done_rx) => {
+            let _ = emitter.emit_update_search_rows(final_rows).await;
+            break;
+          },
+          _ = (&mut stop_rx) => break,
+        }
+      }
+    }))
+  } else {
+    None
+  };
+
+  let counter_task = tokio::task::spawn_blocking(move || {
+    let mut record = ByteRecord::new();
+    while rdr.read_byte_record(&mut record)? {
+      let row = rows.fetch_add(1, Ordering::Relaxed) + 1;
+      let (matched, extended) = evaluate(&record);
+      if matched {
+        match_rows.fetch_add(1, Ordering::Relaxed);
+      }
+      sink.write(row, extended.as_ref().unwrap_or(&record), matched)?;
+    }
+
+    let final_rows = rows.load(Ordering::Relaxed);
+    let _ = done_tx.send(final_rows);
+    sink.finish()
+  });
+
+  counter_task.await??;
+  let _ = stop_tx.send(());
+  if let Some(task) = timer_task {
+    task.await?;
+  }
+
+  Ok(match_rows_clone.load(Ordering::Relaxed).to_string())
+}
+
 pub(crate) async fn generic_search<E, F>(
   mut rdr: csv::Reader<BufReader<Box<dyn Read + Send>>>,
-  mut wtr: csv::Writer<BufWriter<File>>,
+  mut sink: MatchSink,
   column: String,
   conditions: Vec<String>,
   progress: bool,
@@ -52,7 +336,7 @@ where
   // 缓存列索引,避免每次循环都计算
   let field_index = sel.first_indices()?;
 
-  wtr.write_record(rdr.headers()?)?;
+  sink.write_headers(rdr.byte_headers()?)?;
 
   let rows = Arc::new(AtomicUsize::new(0));
   let match_rows = Arc::new(AtomicUsize::new(0));
@@ -90,19 +374,19 @@ where
   let counter_task = tokio::task::spawn_blocking(move || {
     for result in rdr.records() {
       let record = result?;
-      if let Some(value) = record.get(field_index) {
-        if match_fn(value, &conditions) {
-          wtr.write_record(&record)?;
-
-          match_rows.fetch_add(1, Ordering::Relaxed);
-        }
+      let row = rows.fetch_add(1, Ordering::Relaxed) + 1;
+      let matched = record
+        .get(field_index)
+        .is_some_and(|value| match_fn(value, &conditions));
+      if matched {
+        match_rows.fetch_add(1, Ordering::Relaxed);
       }
-      rows.fetch_add(1, Ordering::Relaxed);
+      sink.write(row, record.as_byte_record(), matched)?;
     }
 
     let final_rows = rows.load(Ordering::Relaxed);
     let _ = done_tx.send(final_rows);
-    Ok::<_, anyhow::Error>(wtr.flush()?)
+    sink.finish()
   });
 
   counter_task.await??;
@@ -122,6 +406,7 @@ pub(crate) async fn generic_multi_search<E, F, P>(
   skiprows: usize,
   quoting: bool,
   progress: bool,
+  output: SearchOutput,
   match_fn: F,
   emitter: E,
 ) -> Result<String>
@@ -139,7 +424,11 @@ where
   let match_fn = Arc::new(match_fn);
   let mut opts = CsvOptions::new(&path);
   opts.set_skiprows(skiprows);
-  let (sep, reader) = opts.skiprows_and_delimiter()?;
+  let (dialect, reader) = opts.skiprows_and_dialect()?;
+  let config = CsvConfigBuilder::new()
+    .dialect(&dialect)
+    .quoting(quoting)
+    .build();
 
   let total_rows = match progress {
     true => opts.idx_count_rows().await?,
@@ -154,10 +443,18 @@ where
     .iter()
     .map(|cond| {
       let sanitized = sanitize_condition(cond);
-      let path = format!("{parent_path}/{file_stem}_{sanitized}.csv");
+      let path = format!("{parent_path}/{file_stem}_{sanitized}");
       (cond.clone(), path)
     })
     .collect();
+  // split: 每个条件一个匹配文件, 不匹配任何条件的行写入一个文件
+  let (output, unmatched_path) = match output {
+    SearchOutput::Split => (
+      SearchOutput::Rows,
+      Some(format!("{parent_path}/{file_stem}_search_unmatched.csv")),
+    ),
+    _ => (output, None),
+  };
 
   let rows = Arc::new(AtomicUsize::new(0));
   let match_rows = Arc::new(AtomicUsize::new(0));
@@ -193,51 +490,58 @@ where
   };
 
   let counter_task = tokio::task::spawn_blocking(move || {
-    let mut writers: HashMap<String, Writer<std::fs::File>> = HashMap::new();
+    let mut sinks: HashMap<String, MatchSink> = HashMap::new();
 
     for (cond, path) in &output_paths {
-      let file = File::create(path)?;
-      writers.insert(
-        cond.clone(),
-        WriterBuilder::new().delimiter(sep).from_writer(file),
-      );
+      sinks.insert(cond.clone(), MatchSink::new(&config, output, path)?);
     }
+    let mut unmatched = unmatched_path
+      .map(|path| config.build_writer(path))
+      .transpose()?;
 
-    let mut rdr = ReaderBuilder::new()
-      .delimiter(sep)
-      .quoting(quoting)
-      .from_reader(reader);
-    let headers = rdr.headers()?.clone();
+    let mut rdr = config.build_reader(reader);
+    let headers = rdr.byte_headers()?.clone();
 
-    for wtr in writers.values_mut() {
-      wtr.write_record(&headers)?;
+    for sink in sinks.values_mut() {
+      sink.write_headers(&headers)?;
+    }
+    if let Some(wtr) = &mut unmatched {
+      wtr.write_byte_record(&headers)?;
     }
 
     let sel = Selection::from_headers(rdr.byte_headers()?, &[column.as_str()][..])?;
-  // 缓存列索引,避免每次循环都计算
-  let field_index = sel.first_indices()?;
+    // 缓存列索引,避免每次循环都计算
+    let field_index = sel.first_indices()?;
 
-  for result in rdr.records() {
-    let record = result?;
-    if let Some(value) = record.get(field_index) {
-      for condition in conditions.iter() {
-        if match_fn(value, condition) {
-          if let Some(wtr) = writers.get_mut(condition) {
-            wtr.write_record(&record)?;
+    for result in rdr.records() {
+      let record = result?;
+      let row = rows.fetch_add(1, Ordering::Relaxed) + 1;
+      let mut any_matched = false;
+      if let Some(value) = record.get(field_index) {
+        for condition in conditions.iter() {
+          if match_fn(value, condition) {
+            if let Some(sink) = sinks.get_mut(condition) {
+              sink.write(row, record.as_byte_record(), true)?;
 
-            match_rows.fetch_add(1, Ordering::Relaxed);
+              match_rows.fetch_add(1, Ordering::Relaxed);
+              any_matched = true;
+            }
           }
         }
       }
+      if !any_matched && let Some(wtr) = &mut unmatched {
+        wtr.write_byte_record(record.as_byte_record())?;
+      }
     }
-    rows.fetch_add(1, Ordering::Relaxed);
-  }
     let final_rows = rows.load(Ordering::Relaxed);
     let _ = done_tx.send(final_rows);
 
-    // flush all writers
-    for wtr in writers.values_mut() {
-      wtr.flush()?;
+    // finish all writers
+    for (_, sink) in sinks {
+      sink.finish()?;
+    }
+    if let Some(wtr) = unmatched {
+      wtr.finish()?;
     }
     Ok::<_, anyhow::Error>(())
   });
@@ -258,6 +562,7 @@ pub(crate) async fn generic_multi_search_unique<E, F, P>(
   skiprows: usize,
   quoting: bool,
   progress: bool,
+  output: SearchOutput,
   match_fn: F,
   emitter: E,
 ) -> Result<String>
@@ -268,13 +573,14 @@ where
 {
   let mut opts = CsvOptions::new(&path);
   opts.set_skiprows(skiprows);
-  let (sep, reader) = opts.skiprows_and_delimiter()?;
+  let (dialect, reader) = opts.skiprows_and_dialect()?;
 
   // 读取 column 的所有唯一值
-  let mut rdr = ReaderBuilder::new()
-    .delimiter(sep)
+  let mut rdr = CsvConfigBuilder::new()
+    .dialect(&dialect)
     .quoting(quoting)
-    .from_reader(reader);
+    .build()
+    .build_reader(reader);
 
   let sel = Selection::from_headers(rdr.byte_headers()?, &[column.as_str()][..])?;
 
@@ -291,15 +597,45 @@ where
   let conditions: Vec<String> = condition_set.into_iter().collect();
 
   generic_multi_search(
-    path, column, conditions, skiprows, quoting, progress, match_fn, emitter,
+    path, column, conditions, skiprows, quoting, progress, output, match_fn, emitter,
   )
   .await
 }
 
+/// The file of a parallel search with the reader settings of the caller,
+/// so the result does not depend on the number of threads
+pub struct SearchInput {
+  pub opts: CsvOptions<String>,
+  pub quoting: bool,
+  pub flexible: bool,
+}
+
+impl SearchInput {
+  fn config(&self) -> Result<CsvConfig> {
+    Ok(
+      CsvConfigBuilder::new()
+        .dialect(&self.opts.dialect()?)
+        .quoting(self.quoting)
+        .flexible(self.flexible)
+        .build(),
+    )
+  }
+}
+
+/// The defaults of `CsvConfig`, quoting on and flexible off
+impl From<CsvOptions<String>> for SearchInput {
+  fn from(opts: CsvOptions<String>) -> Self {
+    SearchInput {
+      opts,
+      quoting: true,
+      flexible: false,
+    }
+  }
+}
+
 pub(crate) fn generic_parallel_search<F>(
-  opts: CsvOptions<String>,
-  idx: &mut Indexed<File, File>,
-  mut wtr: Writer<BufWriter<File>>,
+  input: SearchInput,
+  mut sink: MatchSink,
   column: String,
   conditions: Vec<String>,
   jobs: usize,
@@ -308,177 +644,46 @@ pub(crate) fn generic_parallel_search<F>(
 where
   F: Fn(&str, &[String]) -> bool + Send + Sync + 'static,
 {
-  let total_data_rows = idx.count() as usize;
-  if total_data_rows == 0 {
-    return Ok("0".to_string());
-  }
-
-  let offsets = Arc::new(MmapOffsets::from_file(opts.idx_path())?);
-  let total_records = offsets.len();
-  let sep = opts.get_delimiter()?;
-
-  if total_records != total_data_rows + 1 {
-    return Err(anyhow::anyhow!(
-      "Index inconsistency: total_records={} vs data_rows={}",
-      total_records,
-      total_data_rows
-    ));
-  }
-
-  let csv_path = opts.file_path()?;
-  let csv_file = File::open(&csv_path)?;
-  let csv_mmap = unsafe { memmap2::Mmap::map(&csv_file)? };
-  let csv_mmap = Arc::new(csv_mmap);
-
-  // Parse header
-  let header_start = offsets.get(0) as usize;
-  let header_end = offsets.get(1) as usize;
-  if header_start > header_end || header_end > csv_mmap.len() {
-    return Err(anyhow::anyhow!(
-      "Invalid header range: [{}..{}) in file of size {}",
-      header_start,
-      header_end,
-      csv_mmap.len()
-    ));
-  }
+  let config = input.config()?;
+  let rdr = ParallelReader::from_options(&input.opts, &config)?;
 
-  let header_slice = &csv_mmap[header_start..header_end];
-  let mut header_reader = ReaderBuilder::new()
-    .has_headers(false)
-    .delimiter(sep)
-    .from_reader(Cursor::new(header_slice));
-
-  let raw_header = header_reader
-    .byte_records()
-    .next()
-    .ok_or_else(|| anyhow::anyhow!("Failed to parse header"))??;
-
-  let true_header = utils::clean_header(&raw_header);
-
-  let sel = Selection::from_headers(&true_header, &[column.as_str()])?;
+  let headers = rdr.byte_headers();
+  let sel = Selection::from_headers(headers, &[column.as_str()])?;
   let field_index = sel.first_indices()?;
 
-  wtr.write_byte_record(&true_header)?;
-
-  // Configure thread count
-  let njobs = utils::njobs(Some(jobs));
-  let chunk_size_bytes = 256 * 1024 * 1024; // 256MB
-  let file_size = csv_mmap.len();
-  let num_chunks = (file_size + chunk_size_bytes - 1) / chunk_size_bytes;
-
-  let temp_dir = TempDir::new()?;
-
-  const BUFFER_SIZE: usize = 1024 * 1024; // 1MB缓冲区
-
-  let pool = ThreadPoolBuilder::new()
-    .num_threads(njobs)
-    .build()
-    .map_err(|e| anyhow::anyhow!("Failed to create thread pool: {}", e))?;
-
-  let results: Result<Vec<(PathBuf, usize)>> = pool.install(|| {
-    (0..num_chunks)
-      .into_par_iter()
-      .map(|chunk_id| -> Result<(PathBuf, usize)> {
-        let start_byte = chunk_id * chunk_size_bytes;
-        if start_byte >= file_size {
-          return Ok((PathBuf::new(), 0));
-        }
-
-        // Skip header: data rows start at index 1
-        let mut phys_start_idx = 1;
-        if offsets.len() <= 1 {
-          return Ok((PathBuf::new(), 0)); // no data rows
-        }
-        for i in 1..offsets.len() {
-          if offsets.get(i) as usize >= start_byte {
-            phys_start_idx = i;
-            break;
-          }
-        }
-
-        let next_chunk_start = (chunk_id + 1) * chunk_size_bytes;
-        let mut phys_end_idx = offsets.len();
-        for i in phys_start_idx..offsets.len() {
-          if offsets.get(i) as usize >= next_chunk_start {
-            phys_end_idx = i;
-            break;
-          }
-        }
-
-        if phys_start_idx >= phys_end_idx {
-          return Ok((PathBuf::new(), 0));
-        }
-
-        let chunk_start = offsets.get(phys_start_idx) as usize;
-        let chunk_end = if phys_end_idx < offsets.len() {
-          offsets.get(phys_end_idx) as usize
-        } else {
-          file_size
-        };
-
-        if chunk_start >= file_size || chunk_start >= chunk_end {
-          return Ok((PathBuf::new(), 0));
-        }
-
-        let slice = &csv_mmap[chunk_start..chunk_end];
-        let reader = ReaderBuilder::new()
-          .has_headers(false)
-          .delimiter(sep)
-          .from_reader(Cursor::new(slice));
-
-        let out_path = temp_dir.path().join(format!("part_{}.csv", chunk_id));
-
-        let file = File::create(&out_path)?;
-        let buf_writer = BufWriter::with_capacity(BUFFER_SIZE, file);
-        let mut local_wtr = WriterBuilder::new()
-          .delimiter(sep)
-          .from_writer(buf_writer);
-
-        let mut count = 0;
-        for record_result in reader.into_byte_records() {
-          let record = record_result?;
-          if let Some(value) = record.get(field_index) {
-            if let Ok(s) = std::str::from_utf8(value) {
-              if match_fn(s, &conditions) {
-                local_wtr.write_byte_record(&record)?;
-                count += 1;
-              }
-            }
-          }
-        }
-        local_wtr.flush()?;
-        Ok((out_path, count))
-      })
-      .collect::<Result<Vec<_>, _>>()
-  });
-
-  let mut total = 0;
-  for (path, count) in results? {
-    if count == 0 || path.as_os_str().is_empty() {
-      continue;
-    }
-
-    let file = File::open(&path)?;
-    let buf_reader = BufReader::with_capacity(BUFFER_SIZE, file);
-    let part_reader = ReaderBuilder::new()
-      .delimiter(sep)
-      .has_headers(false) // temp files have no header
-      .from_reader(buf_reader);
-
-    for record_result in part_reader.into_byte_records() {
-      let record = record_result?;
-      wtr.write_byte_record(&record)?;
-      total += 1;
-    }
-  }
+  sink.write_headers(headers)?;
+  let wants = sink.wants();
+
+  let (mut total, mut rows) = (0, 0);
+  rdr.for_each_ordered(
+    jobs,
+    |chunk| {
+      let mut matches = ChunkMatches::default();
+      let mut record = ByteRecord::new();
+      while chunk.read_byte_record(&mut record)? {
+        let matched = record
+          .get(field_index)
+          .and_then(|value| std::str::from_utf8(value).ok())
+          .is_some_and(|s| match_fn(s, &conditions));
+        matches.push(&record, matched, wants);
+      }
+      Ok(matches)
+    },
+    |matches| {
+      total += matches.matches;
+      let offset = rows;
+      rows += matches.rows;
+      sink.write_chunk(offset, matches)
+    },
+  )?;
 
-  wtr.flush()?;
+  sink.finish()?;
   Ok(total.to_string())
 }
 
 pub(crate) async fn generic_search_chain<E, F>(
   mut rdr: csv::Reader<BufReader<Box<dyn Read + Send>>>,
-  mut wtr: csv::Writer<BufWriter<File>>,
+  mut sink: MatchSink,
   columns: Vec<String>,
   progress: bool,
   match_fn: F,
@@ -497,7 +702,7 @@ where
     &columns.iter().map(|s| s.as_str()).collect::<Vec<_>>(),
   )?;
 
-  wtr.write_record(rdr.headers()?)?;
+  sink.write_headers(rdr.byte_headers()?)?;
 
   let rows = Arc::new(AtomicUsize::new(0));
   let match_rows = Arc::new(AtomicUsize::new(0));
@@ -530,22 +735,23 @@ where
   let counter_task = tokio::task::spawn_blocking(move || {
     for result in rdr.records() {
       let record = result?;
+      let row = rows.fetch_add(1, Ordering::Relaxed) + 1;
       let values: Vec<&str> = sel
         .get_indices()
         .iter()
         .map(|&idx| record.get(idx).unwrap_or(""))
         .collect();
 
-      if match_fn(&values) {
-        wtr.write_record(&record)?;
+      let matched = match_fn(&values);
+      if matched {
         match_rows.fetch_add(1, Ordering::Relaxed);
       }
-      rows.fetch_add(1, Ordering::Relaxed);
+      sink.write(row, record.as_byte_record(), matched)?;
     }
 
     let final_rows = rows.load(Ordering::Relaxed);
     let _ = done_tx.send(final_rows);
-    Ok::<_, anyhow::Error>(wtr.flush()?)
+    sink.finish()
   });
 
   counter_task.await??;
@@ -558,9 +764,8 @@ where
 }
 
 pub(crate) fn generic_parallel_search_chain<F>(
-  opts: CsvOptions<String>,
-  idx: &mut Indexed<File, File>,
-  mut wtr: csv::Writer<BufWriter<File>>,
+  input: SearchInput,
+  mut sink: MatchSink,
   columns: Vec<String>,
   threads: usize,
   match_fn: F,
@@ -572,172 +777,49 @@ where
     return Err(anyhow::anyhow!("At least one column must be specified"));
   }
 
-  let total_data_rows = idx.count() as usize;
-  if total_data_rows == 0 {
-    return Ok("0".to_string());
-  }
-
-  let offsets = Arc::new(MmapOffsets::from_file(opts.idx_path())?);
-  let total_records = offsets.len();
-  let sep = opts.get_delimiter()?;
-
-  if total_records != total_data_rows + 1 {
-    return Err(anyhow::anyhow!(
-      "Index inconsistency: total_records={} vs data_rows={}",
-      total_records,
-      total_data_rows
-    ));
-  }
-
-  let csv_path = opts.file_path()?;
-  let csv_file = std::fs::File::open(&csv_path)?;
-  let csv_mmap = unsafe { memmap2::Mmap::map(&csv_file)? };
-  let csv_mmap = Arc::new(csv_mmap);
-
-  // Parse header (first record)
-  let header_start = offsets.get(0) as usize;
-  let header_end = offsets.get(1) as usize;
-  if header_start >= header_end || header_end > csv_mmap.len() {
-    return Err(anyhow::anyhow!(
-      "Invalid header range: [{}..{}) in file of size {}",
-      header_start,
-      header_end,
-      csv_mmap.len()
-    ));
-  }
+  let config = input.config()?;
+  let rdr = ParallelReader::from_options(&input.opts, &config)?;
 
-  let header_slice = &csv_mmap[header_start..header_end];
-  let mut header_reader = ReaderBuilder::new()
-    .has_headers(false)
-    .delimiter(sep)
-    .from_reader(Cursor::new(header_slice));
-
-  let raw_header = header_reader
-    .byte_records()
-    .next()
-    .ok_or_else(|| anyhow::anyhow!("Failed to parse header"))??;
-
-  let true_header = utils::clean_header(&raw_header);
+  let headers = rdr.byte_headers();
   let sel = Selection::from_headers(
-    &true_header,
+    headers,
     &columns.iter().map(|s| s.as_str()).collect::<Vec<_>>(),
   )?;
   let field_indices: Vec<usize> = sel.get_indices().to_vec();
 
-  // Write header to output
-  wtr.write_byte_record(&true_header)?;
-
-  // Parallel processing setup
-  let njobs = utils::njobs(Some(threads));
-  let chunk_size_bytes = 256 * 1024 * 1024; // 256 MB
-  let file_size = csv_mmap.len();
-  let num_chunks = (file_size.saturating_sub(header_end) + chunk_size_bytes - 1) / chunk_size_bytes;
-
-  let temp_dir = TempDir::new()?;
-
-  let pool = ThreadPoolBuilder::new()
-    .num_threads(njobs)
-    .build()
-    .map_err(|e| anyhow::anyhow!("Failed to create thread pool: {}", e))?;
-
-  let results: Result<Vec<(PathBuf, usize)>> = pool.install(|| {
-    (0..num_chunks)
-      .into_par_iter()
-      .map(|chunk_id| -> Result<(PathBuf, usize)> {
-        let data_start = header_end; // skip header
-        let chunk_start_byte = data_start + chunk_id * chunk_size_bytes;
-        if chunk_start_byte >= file_size {
-          return Ok((PathBuf::new(), 0));
-        }
-
-        // Find first record whose start >= chunk_start_byte
-        let mut phys_start_idx = 1; // index in offsets (0=header)
-        for i in 1..offsets.len() {
-          if offsets.get(i) as usize >= chunk_start_byte {
-            phys_start_idx = i;
-            break;
-          }
-        }
-
-        let next_chunk_byte = data_start + (chunk_id + 1) * chunk_size_bytes;
-        let mut phys_end_idx = offsets.len();
-        for i in phys_start_idx..offsets.len() {
-          if offsets.get(i) as usize >= next_chunk_byte {
-            phys_end_idx = i;
-            break;
-          }
-        }
-
-        if phys_start_idx >= phys_end_idx {
-          return Ok((PathBuf::new(), 0));
-        }
-
-        let slice_start = offsets.get(phys_start_idx) as usize;
-        let slice_end = if phys_end_idx < offsets.len() {
-          offsets.get(phys_end_idx) as usize
-        } else {
-          file_size
-        };
-
-        if slice_start >= file_size || slice_start >= slice_end {
-          return Ok((PathBuf::new(), 0));
-        }
-
-        let slice = &csv_mmap[slice_start..slice_end];
-        let reader = ReaderBuilder::new()
-          .has_headers(false)
-          .delimiter(sep)
-          .from_reader(Cursor::new(slice));
-
-        let out_path = temp_dir.path().join(format!("part_{}.csv", chunk_id));
-        let mut local_wtr = WriterBuilder::new().delimiter(sep).from_path(&out_path)?;
-
-        let mut count = 0;
-        for record_result in reader.into_byte_records() {
-          let record = record_result?;
-
-          // Extract values for selected columns
-          let values: Vec<&str> = field_indices
-            .iter()
-            .map(|&idx| {
-              record
-                .get(idx)
-                .and_then(|b| std::str::from_utf8(b).ok())
-                .unwrap_or("")
-            })
-            .collect();
-
-          if match_fn(&values) {
-            local_wtr.write_byte_record(&record)?;
-            count += 1;
-          }
-        }
-
-        local_wtr.flush()?;
-        Ok((out_path, count))
-      })
-      .collect::<Result<Vec<_>, _>>()
-  });
-
-  let mut total_matches = 0;
-  for (path, count) in results? {
-    if count == 0 || path.as_os_str().is_empty() {
-      continue;
-    }
-
-    let mut part_file = std::fs::File::open(&path)?;
-    let part_reader = ReaderBuilder::new()
-      .delimiter(sep)
-      .has_headers(false)
-      .from_reader(&mut part_file);
-
-    for record_result in part_reader.into_byte_records() {
-      let record = record_result?;
-      wtr.write_byte_record(&record)?;
-    }
-    total_matches += count;
-  }
+  sink.write_headers(headers)?;
+  let wants = sink.wants();
+
+  let (mut total_matches, mut rows) = (0, 0);
+  rdr.for_each_ordered(
+    threads,
+    |chunk| {
+      let mut matches = ChunkMatches::default();
+      let mut record = ByteRecord::new();
+      while chunk.read_byte_record(&mut record)? {
+        let values: Vec<&str> = field_indices
+          .iter()
+          .map(|&idx| {
+            record
+              .get(idx)
+              .and_then(|b| std::str::from_utf8(b).ok())
+              .unwrap_or("")
+          })
+          .collect();
+
+        let matched = match_fn(&values);
+        matches.push(&record, matched, wants);
+      }
+      Ok(matches)
+    },
+    |matches| {
+      total_matches += matches.matches;
+      let offset = rows;
+      rows += matches.rows;
+      sink.write_chunk(offset, matches)
+    },
+  )?;
 
-  wtr.flush()?;
+  sink.finish()?;
   Ok(total_matches.to_string())
 }
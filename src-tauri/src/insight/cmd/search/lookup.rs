@@ -0,0 +1,110 @@
+use std::{collections::HashSet, path::Path};
+
+use aho_corasick::{AhoCorasick, Anchored, Input, StartKind};
+use anyhow::{Result, anyhow};
+
+use crate::{
+  io::{
+    csv::{options::CsvOptions, selection::Selection},
+    excel::excel_reader::ExcelReader,
+  },
+  utils::clean_header,
+};
+
+const EXCEL_EXTENSIONS: [&str; 5] = ["xls", "xlsx", "xlsm", "xlsb", "ods"];
+
+/// Distinct, trimmed and non-empty values of `column` in a csv or excel file (first sheet)
+pub fn read_lookup_values<P: AsRef<Path>>(
+  path: P,
+  column: &str,
+  skiprows: usize,
+) -> Result<Vec<String>> {
+  let path = path.as_ref();
+  let ext = path
+    .extension()
+    .map(|e| e.to_string_lossy().to_lowercase())
+    .unwrap_or_default();
+
+  let mut seen = HashSet::new();
+  let mut values = Vec::new();
+  let mut push = |value: &str| {
+    let value = value.trim();
+    if !value.is_empty() && seen.insert(value.to_string()) {
+      values.push(value.to_string());
+    }
+  };
+
+  if EXCEL_EXTENSIONS.contains(&ext.as_str()) {
+    let range = ExcelReader::from_path(path)?.worksheet_range_at(0, skiprows as u32)?;
+    let mut rows = range.rows();
+    let headers = rows.next().ok_or(anyhow!("No data"))?;
+    let idx = headers
+      .iter()
+      .position(|cell| *cell == column)
+      .ok_or(anyhow!("Column '{column}' not found in headers."))?;
+    for row in rows {
+      if let Some(cell) = row.get(idx) {
+        push(&cell.to_string());
+      }
+    }
+  } else {
+    let mut opts = CsvOptions::new(path);
+    opts.set_skiprows(skiprows);
+    let mut rdr = opts.skiprows_reader()?;
+    let headers = clean_header(rdr.byte_headers()?);
+    let idx = Selection::from_headers(&headers, &[column])?.first_indices()?;
+    for record in rdr.byte_records() {
+      if let Some(value) = record?.get(idx) {
+        push(&String::from_utf8_lossy(value));
+      }
+    }
+  }
+
+  Ok(values)
+}
+
+enum Lookup {
+  Equal(HashSet<String>),
+  /// 不锚定为 contains, 锚定为 starts_with
+  Patterns(AhoCorasick, Anchored),
+}
+
+/// Matches a value against a lookup list, "in list" or "not in list"
+pub struct LookupMatcher {
+  lookup: Lookup,
+  negate: bool,
+}
+
+impl LookupMatcher {
+  /// `mode` is equal, contains or starts_with, a `not_` prefix keeps the values not in the list
+  pub fn new(mode: &str, values: Vec<String>) -> Result<Self> {
+    let (negate, kind) = match mode.strip_prefix("not_") {
+      Some(kind) => (true, kind),
+      None => (false, mode),
+    };
+    let lookup = match kind {
+      "equal" => Lookup::Equal(values.into_iter().collect()),
+      "contains" => Lookup::Patterns(AhoCorasick::new(&values)?, Anchored::No),
+      "starts_with" => Lookup::Patterns(
+        AhoCorasick::builder()
+          .start_kind(StartKind::Anchored)
+          .build(&values)?,
+        Anchored::Yes,
+      ),
+      _ => {
+        return Err(anyhow!(
+          "Lookup search supports equal, contains and starts_with, not {mode}"
+        ));
+      }
+    };
+    Ok(LookupMatcher { lookup, negate })
+  }
+
+  pub fn is_match(&self, value: &str) -> bool {
+    let found = match &self.lookup {
+      Lookup::Equal(set) => set.contains(value),
+      Lookup::Patterns(ac, anchored) => ac.is_match(Input::new(value).anchored(*anchored)),
+    };
+    found != self.negate
+  }
+}
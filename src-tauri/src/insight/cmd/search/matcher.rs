@@ -0,0 +1,106 @@
+use std::collections::HashSet;
+
+use anyhow::{Result, anyhow};
+use regex::bytes::{Regex, RegexBuilder};
+
+/// The predicate of a single-column search mode, built once from its condition.
+///
+/// Conditions are split on `|` as in `perform_search`, `filters` searches with the same predicates.
+pub enum Matcher {
+  Equal(HashSet<String>),
+  NotEqual(HashSet<String>),
+  Contains(Vec<String>),
+  NotContains(Vec<String>),
+  StartsWith(Vec<String>),
+  NotStartsWith(Vec<String>),
+  EndsWith(Vec<String>),
+  NotEndsWith(Vec<String>),
+  Regex(Regex),
+  IsNull,
+  IsNotNull,
+  GreaterThan(f64),
+  GreaterThanEqual(f64),
+  LessThan(f64),
+  LessThanEqual(f64),
+  Between(f64, f64),
+}
+
+fn parse_number(s: &str) -> Result<f64> {
+  s.trim()
+    .parse::<f64>()
+    .map_err(|_| anyhow!("Invalid number: {s}"))
+}
+
+/// `equal_multi` and the other `*_multi` modes split the output by condition in a single
+/// file search, they match with the predicate of the plain mode
+pub fn base_mode(mode: &str) -> &str {
+  mode.strip_suffix("_multi").unwrap_or(mode)
+}
+
+impl Matcher {
+  /// Parse the condition of `mode`, lists are split on `|`
+  pub fn new(mode: &str, condition: &str) -> Result<Self> {
+    match base_mode(mode) {
+      "regex" => Ok(Matcher::Regex(RegexBuilder::new(condition).build()?)),
+      "gt" => Ok(Matcher::GreaterThan(parse_number(condition)?)),
+      "ge" => Ok(Matcher::GreaterThanEqual(parse_number(condition)?)),
+      "lt" => Ok(Matcher::LessThan(parse_number(condition)?)),
+      "le" => Ok(Matcher::LessThanEqual(parse_number(condition)?)),
+      "irregular_regex" => Err(anyhow!(
+        "irregular_regex matches whole lines, not the values of a column"
+      )),
+      _ => Matcher::from_conditions(
+        mode,
+        condition.split('|').map(|s| s.trim().to_string()).collect(),
+      ),
+    }
+  }
+
+  /// The modes whose condition is a list, `conditions` are already split
+  pub fn from_conditions(mode: &str, conditions: Vec<String>) -> Result<Self> {
+    Ok(match base_mode(mode) {
+      "equal" => Matcher::Equal(conditions.into_iter().collect()),
+      "not_equal" => Matcher::NotEqual(conditions.into_iter().collect()),
+      "contains" => Matcher::Contains(conditions),
+      "not_contains" => Matcher::NotContains(conditions),
+      "starts_with" => Matcher::StartsWith(conditions),
+      "not_starts_with" => Matcher::NotStartsWith(conditions),
+      "ends_with" => Matcher::EndsWith(conditions),
+      "not_ends_with" => Matcher::NotEndsWith(conditions),
+      "is_null" => Matcher::IsNull,
+      "is_not_null" => Matcher::IsNotNull,
+      "between" => {
+        if conditions.len() != 2 {
+          return Err(anyhow!(
+            "Exactly two values required for between: min and max"
+          ));
+        }
+        let (a, b) = (parse_number(&conditions[0])?, parse_number(&conditions[1])?);
+        Matcher::Between(a.min(b), a.max(b))
+      }
+      _ => return Err(anyhow!("Unsupported search mode: {mode}")),
+    })
+  }
+
+  pub fn is_match(&self, value: &str) -> bool {
+    let number = || value.parse::<f64>().ok();
+    match self {
+      Matcher::Equal(set) => set.contains(value),
+      Matcher::NotEqual(set) => !set.contains(value),
+      Matcher::Contains(conds) => conds.iter().any(|c| value.contains(c.as_str())),
+      Matcher::NotContains(conds) => !conds.iter().any(|c| value.contains(c.as_str())),
+      Matcher::StartsWith(conds) => conds.iter().any(|c| value.starts_with(c.as_str())),
+      Matcher::NotStartsWith(conds) => !conds.iter().any(|c| value.starts_with(c.as_str())),
+      Matcher::EndsWith(conds) => conds.iter().any(|c| value.ends_with(c.as_str())),
+      Matcher::NotEndsWith(conds) => !conds.iter().any(|c| value.ends_with(c.as_str())),
+      Matcher::Regex(re) => re.is_match(value.as_bytes()),
+      Matcher::IsNull => value.trim().is_empty(),
+      Matcher::IsNotNull => !value.trim().is_empty(),
+      Matcher::GreaterThan(t) => number().is_some_and(|v| v > *t),
+      Matcher::GreaterThanEqual(t) => number().is_some_and(|v| v >= *t),
+      Matcher::LessThan(t) => number().is_some_and(|v| v < *t),
+      Matcher::LessThanEqual(t) => number().is_some_and(|v| v <= *t),
+      Matcher::Between(lo, hi) => number().is_some_and(|v| v >= *lo && v <= *hi),
+    }
+  }
+}
@@ -1,5 +1,12 @@
+pub mod column_index;
+pub mod date;
 pub mod filters;
 pub mod filters_chain;
+pub mod filters_columns;
 pub mod filters_multi;
+pub mod fuzzy;
 pub mod generic;
+pub mod lookup;
+pub mod matcher;
+pub mod multi_file;
 pub mod perform;
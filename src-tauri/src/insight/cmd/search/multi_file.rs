@@ -0,0 +1,369 @@
+use std::{
+  borrow::Cow,
+  fs::File,
+  path::{Path, PathBuf},
+  sync::Arc,
+};
+
+use anyhow::{Result, anyhow};
+use csv::ByteRecord;
+use tokio::sync::Semaphore;
+
+use crate::{
+  cmd::search::{
+    filters, filters_chain,
+    filters_columns::{ColumnScope, ColumnsMatcher},
+    generic::{MatchSink, SearchOutput},
+    matcher::Matcher,
+    perform::ColumnConfig,
+  },
+  io::{
+    csv::{
+      config::{CsvConfig, CsvConfigBuilder},
+      options::CsvOptions,
+      selection::Selection,
+      spill,
+    },
+    writer::Finish,
+  },
+  utils::{EventEmitter, clean_header, njobs},
+};
+
+/// A row predicate, built for the headers of one file
+pub type RowFilter = Box<dyn Fn(&ByteRecord) -> bool + Send + Sync>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilesOutput {
+  /// `<stem>_search*.csv` next to every input
+  PerFile(SearchOutput),
+  /// One file with a leading `_source_file` column, `row_number` adds `_source_row`
+  Combined { row_number: bool },
+}
+
+impl FilesOutput {
+  pub fn new(output: Option<&str>, row_number: bool, mode: SearchOutput) -> Result<Self> {
+    match output.unwrap_or("per_file") {
+      "per_file" => Ok(FilesOutput::PerFile(mode)),
+      "combined" if mode == SearchOutput::Rows => Ok(FilesOutput::Combined { row_number }),
+      "combined" => Err(anyhow!("Combined output only writes the matching rows")),
+      other => Err(anyhow!("Unsupported output: {other}")),
+    }
+  }
+}
+
+/// Whether `path` names several files: a `|` list, a folder or a file name pattern
+pub fn is_multi_path(path: &str) -> bool {
+  path.contains(['|', '*', '?']) || Path::new(path).is_dir()
+}
+
+/// Filter of the single column search modes
+pub fn column_filter(
+  column: String,
+  mode: &str,
+  condition: &str,
+) -> Result<impl Fn(&ByteRecord) -> Result<RowFilter> + Send + Sync + 'static> {
+  let matcher = Matcher::new(mode, condition)?;
+  Ok(value_filter(column, move |value| matcher.is_match(value)))
+}
+
+/// Filter testing the values of `column` with `is_match`
+pub fn value_filter<M>(
+  column: String,
+  is_match: M,
+) -> impl Fn(&ByteRecord) -> Result<RowFilter> + Send + Sync + 'static
+where
+  M: Fn(&str) -> bool + Send + Sync + 'static,
+{
+  let is_match = Arc::new(is_match);
+  move |headers: &ByteRecord| -> Result<RowFilter> {
+    let idx = Selection::from_headers(headers, &[column.as_str()][..])?.first_indices()?;
+    let is_match = Arc::clone(&is_match);
+    Ok(Box::new(move |record: &ByteRecord| {
+      record
+        .get(idx)
+        .is_some_and(|v| is_match(&String::from_utf8_lossy(v)))
+    }))
+  }
+}
+
+/// Filter searching every column of a row
+pub fn columns_filter(
+  mode: &str,
+  condition: &str,
+  scope: ColumnScope,
+) -> Result<impl Fn(&ByteRecord) -> Result<RowFilter> + Send + Sync + 'static> {
+  let matcher = Arc::new(ColumnsMatcher::new(mode, condition, scope)?);
+  Ok(move |_: &ByteRecord| -> Result<RowFilter> {
+    let matcher = Arc::clone(&matcher);
+    Ok(Box::new(move |record: &ByteRecord| {
+      matcher.is_match(record)
+    }))
+  })
+}
+
+/// Filter of `search_chain`
+pub fn chain_filter(
+  configs: Vec<ColumnConfig>,
+  logics: Vec<String>,
+) -> Result<impl Fn(&ByteRecord) -> Result<RowFilter> + Send + Sync + 'static> {
+  let columns: Vec<String> = configs.iter().map(|c| c.column.clone()).collect();
+  let match_fn = Arc::new(filters_chain::chain_match_fn(configs, logics)?);
+  Ok(move |headers: &ByteRecord| -> Result<RowFilter> {
+    let columns: Vec<&str> = columns.iter().map(|s| s.as_str()).collect();
+    let indices = Selection::from_headers(headers, &columns)?
+      .get_indices()
+      .clone();
+    let match_fn = Arc::clone(&match_fn);
+    Ok(Box::new(move |record: &ByteRecord| {
+      let values: Vec<Cow<str>> = indices
+        .iter()
+        .map(|&i| String::from_utf8_lossy(record.get(i).unwrap_or_default()))
+        .collect();
+      let values: Vec<&str> = values.iter().map(|v| v.as_ref()).collect();
+      match_fn(&values)
+    }))
+  })
+}
+
+/// `irregular_regex` on every file in `paths`, one file after another.
+///
+/// The matching lines of each file are written to `<stem>_search.csv`, events are the
+/// same as `search_files`. Returns the total matches.
+pub async fn search_files_lines<E>(
+  paths: Vec<PathBuf>,
+  pattern: String,
+  output: FilesOutput,
+  skiprows: usize,
+  emitter: E,
+) -> Result<String>
+where
+  E: EventEmitter + Send + Sync + 'static,
+{
+  if paths.is_empty() {
+    return Err(anyhow!("No file to search"));
+  }
+  if output != FilesOutput::PerFile(SearchOutput::Rows) {
+    return Err(anyhow!(
+      "irregular_regex only writes the matching lines of each file"
+    ));
+  }
+  regex::Regex::new(&pattern)?;
+
+  let mut total = 0;
+  for path in paths {
+    let filename = path
+      .file_name()
+      .map(|n| n.to_string_lossy().to_string())
+      .unwrap_or_else(|| path.to_string_lossy().to_string());
+    emitter.emit_info(&filename).await?;
+    let result = async {
+      let mut opts = CsvOptions::new(path.to_string_lossy().to_string());
+      opts.set_skiprows(skiprows);
+      let (_, reader) = opts.skiprows_and_delimiter()?;
+      let output_path = opts.output_path(Some("search"), None)?;
+      filters::irregular_with_regex(reader, output_path, pattern.clone()).await
+    }
+    .await;
+    match result {
+      Ok(matches) => {
+        total += matches.parse::<usize>()?;
+        emitter
+          .emit_success(&format!("{filename}|{matches}"))
+          .await?;
+      }
+      Err(err) => emitter.emit_err(&format!("{filename}|{err}")).await?,
+    }
+  }
+
+  Ok(total.to_string())
+}
+
+struct FileMatches {
+  count: usize,
+  /// 合并输出时的 (config, headers, 匹配行的临时文件)
+  run: Option<(CsvConfig, ByteRecord, File)>,
+}
+
+fn search_file<F>(
+  path: &Path,
+  make_filter: &F,
+  output: FilesOutput,
+  skiprows: usize,
+  quoting: bool,
+  flexible: bool,
+) -> Result<FileMatches>
+where
+  F: Fn(&ByteRecord) -> Result<RowFilter>,
+{
+  let mut opts = CsvOptions::new(path.to_string_lossy().to_string());
+  opts.set_skiprows(skiprows);
+  let (dialect, reader) = opts.skiprows_and_dialect()?;
+  let config = CsvConfigBuilder::new()
+    .flexible(flexible)
+    .dialect(&dialect)
+    .quoting(quoting)
+    .build();
+  let mut rdr = config.build_reader(reader);
+  let headers = clean_header(rdr.byte_headers()?);
+  let filter = make_filter(&headers)?;
+
+  let mut count = 0;
+  let mut record = ByteRecord::new();
+  match output {
+    FilesOutput::PerFile(mode) => {
+      let mut sink = MatchSink::for_search(&opts, &config, mode)?;
+      sink.write_headers(&headers)?;
+      let mut row = 0;
+      while rdr.read_byte_record(&mut record)? {
+        row += 1;
+        let matched = filter(&record);
+        if matched {
+          count += 1;
+        }
+        sink.write(row, &record, matched)?;
+      }
+      sink.finish()?;
+      Ok(FileMatches { count, run: None })
+    }
+    FilesOutput::Combined { row_number } => {
+      let source = opts.file_name()?.to_string();
+      let mut run = spill::run_writer()?;
+      let mut tagged = ByteRecord::new();
+      let mut row = 0;
+      while rdr.read_byte_record(&mut record)? {
+        row += 1;
+        if filter(&record) {
+          tagged.clear();
+          tagged.push_field(source.as_bytes());
+          if row_number {
+            tagged.push_field(row.to_string().as_bytes());
+          }
+          tagged.extend(&record);
+          run.write_byte_record(&tagged)?;
+          count += 1;
+        }
+      }
+      let run = spill::finish_run(run)?;
+      Ok(FileMatches {
+        count,
+        run: Some((config, headers, run)),
+      })
+    }
+  }
+}
+
+/// Search every file in `paths`, up to `threads` files at a time.
+///
+/// Each file emits `info` with its name when it starts, then `success` as
+/// `name|matches` or `err` as `name|error` in input order. A failed file does
+/// not stop the others. In combined output, files whose headers differ from the
+/// first searched file are reported and left out. Returns the total matches.
+pub async fn search_files<E, F>(
+  paths: Vec<PathBuf>,
+  make_filter: F,
+  output: FilesOutput,
+  skiprows: usize,
+  quoting: bool,
+  flexible: bool,
+  threads: usize,
+  emitter: E,
+) -> Result<String>
+where
+  E: EventEmitter + Send + Sync + 'static,
+  F: Fn(&ByteRecord) -> Result<RowFilter> + Send + Sync + 'static,
+{
+  if paths.is_empty() {
+    return Err(anyhow!("No file to search"));
+  }
+
+  let emitter = Arc::new(emitter);
+  let make_filter = Arc::new(make_filter);
+  let semaphore = Arc::new(Semaphore::new(njobs(Some(threads))));
+
+  let mut tasks = Vec::with_capacity(paths.len());
+  for path in &paths {
+    let path = path.clone();
+    let emitter = Arc::clone(&emitter);
+    let make_filter = Arc::clone(&make_filter);
+    let semaphore = Arc::clone(&semaphore);
+    tasks.push(tokio::spawn(async move {
+      let _permit = semaphore.acquire_owned().await?;
+      let filename = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string_lossy().to_string());
+      emitter.emit_info(&filename).await?;
+      let result = tokio::task::spawn_blocking(move || {
+        search_file(&path, &*make_filter, output, skiprows, quoting, flexible)
+      })
+      .await
+      .map_err(|e| anyhow!("Task join error: {e}"))?;
+      Ok::<_, anyhow::Error>((filename, result))
+    }));
+  }
+
+  let mut total = 0;
+  let mut runs: Vec<(CsvConfig, ByteRecord, File)> = Vec::new();
+  let mut combined_path = None;
+  for (path, task) in paths.iter().zip(tasks) {
+    let (filename, result) = task.await??;
+    let matches = match result {
+      Ok(matches) => matches,
+      Err(err) => {
+        emitter.emit_err(&format!("{filename}|{err}")).await?;
+        continue;
+      }
+    };
+    if let Some((config, headers, run)) = matches.run {
+      match runs.first() {
+        Some((_, first, _)) if *first != headers => {
+          emitter
+            .emit_err(&format!("{filename}|headers differ from the first file"))
+            .await?;
+          continue;
+        }
+        Some(_) => {}
+        None => {
+          combined_path = Some(
+            CsvOptions::new(path.to_string_lossy().to_string())
+              .output_path(Some("search_combined"), None)?,
+          )
+        }
+      }
+      runs.push((config, headers, run));
+    }
+    total += matches.count;
+    emitter
+      .emit_success(&format!("{filename}|{}", matches.count))
+      .await?;
+  }
+
+  if let (FilesOutput::Combined { row_number }, Some(output_path)) = (output, combined_path) {
+    tokio::task::spawn_blocking(move || -> Result<()> {
+      let mut runs = runs.into_iter();
+      let Some((config, headers, run)) = runs.next() else {
+        return Ok(());
+      };
+      let mut wtr = config.build_writer(&output_path)?;
+      let mut combined_headers = ByteRecord::from(vec!["_source_file"]);
+      if row_number {
+        combined_headers.push_field(b"_source_row");
+      }
+      combined_headers.extend(&headers);
+      wtr.write_byte_record(&combined_headers)?;
+
+      let mut record = ByteRecord::new();
+      for run in std::iter::once(run).chain(runs.map(|(_, _, run)| run)) {
+        let mut rdr = spill::read_run(run);
+        while rdr.read_byte_record(&mut record)? {
+          wtr.write_byte_record(&record)?;
+        }
+      }
+      wtr.finish()
+    })
+    .await
+    .map_err(|e| anyhow!("Task join error: {e}"))??;
+  }
+
+  Ok(total.to_string())
+}
@@ -1,14 +1,23 @@
-use std::{collections::HashSet, fs::File, path::Path, time::Instant};
+use std::{collections::HashSet, path::Path, time::Instant};
 
 use anyhow::{Result, anyhow};
 use smallvec::SmallVec;
 use tauri::AppHandle;
 
 use crate::{
-  cmd::search::{filters, filters_chain, filters_multi},
-  index::Indexed,
+  cmd::search::{
+    column_index::{self, IndexQuery},
+    date::{DateMatcher, Unparsable},
+    filters, filters_chain,
+    filters_columns::{self, ColumnScope, MATCHED_COLUMNS},
+    filters_multi,
+    fuzzy::{self, FUZZY_SCORE, FuzzyMatcher, FuzzyOptions},
+    generic::{MatchSink, SearchInput, SearchOutput},
+    lookup::{LookupMatcher, read_lookup_values},
+    multi_file::{self, FilesOutput},
+  },
   io::csv::{config::CsvConfigBuilder, options::CsvOptions},
-  utils::EventEmitter,
+  utils::{EventEmitter, expand_paths},
 };
 
 #[derive(Debug)]
@@ -34,6 +43,7 @@ enum SearchMode {
   LessThanEqual,
   Between,
   IrregularRegex,
+  Fuzzy,
 }
 
 impl From<&str> for SearchMode {
@@ -56,11 +66,34 @@ impl From<&str> for SearchMode {
       "le" => SearchMode::LessThanEqual,
       "between" => SearchMode::Between,
       "irregular_regex" => SearchMode::IrregularRegex,
+      "fuzzy" => SearchMode::Fuzzy,
       _ => SearchMode::Equal,
     }
   }
 }
 
+/// Matchers of the modes built from more than the condition
+pub(crate) enum ValueMatcher {
+  /// 值列表来自查找文件
+  Lookup(LookupMatcher),
+  Date(DateMatcher),
+  /// `score` 输出 `_fuzzy_score` 列
+  Fuzzy {
+    matcher: FuzzyMatcher,
+    score: bool,
+  },
+}
+
+impl ValueMatcher {
+  pub(crate) fn is_match(&self, value: &str) -> bool {
+    match self {
+      ValueMatcher::Lookup(matcher) => matcher.is_match(value),
+      ValueMatcher::Date(matcher) => matcher.is_match(value),
+      ValueMatcher::Fuzzy { matcher, .. } => matcher.is_match(value),
+    }
+  }
+}
+
 async fn perform_search<P: AsRef<Path> + Send + Sync + 'static>(
   path: P,
   column: String,
@@ -72,6 +105,8 @@ async fn perform_search<P: AsRef<Path> + Send + Sync + 'static>(
   unique: bool,
   skiprows: usize,
   threads: Option<usize>,
+  output: SearchOutput,
+  value_matcher: Option<ValueMatcher>,
   emitter: AppHandle,
 ) -> Result<String> {
   let multi_conditions = if conditions.contains('|') {
@@ -98,61 +133,101 @@ async fn perform_search<P: AsRef<Path> + Send + Sync + 'static>(
   match search_mode {
     SearchMode::EqualMulti(conditions) => {
       filters_multi::equal_multi(
-        path, column, conditions, skiprows, quoting, progress, unique, emitter,
+        path, column, conditions, skiprows, quoting, progress, unique, output, emitter,
       )
       .await
     }
     SearchMode::StartsWithMulti(conditions) => {
       filters_multi::starts_with_multi(
-        path, column, conditions, skiprows, quoting, progress, unique, emitter,
+        path, column, conditions, skiprows, quoting, progress, unique, output, emitter,
       )
       .await
     }
     SearchMode::ContainsMulti(conditions) => {
       filters_multi::contains_multi(
-        path, column, conditions, skiprows, quoting, progress, unique, emitter,
+        path, column, conditions, skiprows, quoting, progress, unique, output, emitter,
       )
       .await
     }
     SearchMode::EndsWithMulti(conditions) => {
       filters_multi::ends_with_multi(
-        path, column, conditions, skiprows, quoting, progress, unique, emitter,
+        path, column, conditions, skiprows, quoting, progress, unique, output, emitter,
       )
       .await
     }
+    SearchMode::Fuzzy => match value_matcher {
+      Some(ValueMatcher::Fuzzy { matcher, score }) => {
+        fuzzy::search_fuzzy(
+          path,
+          column,
+          matcher,
+          score,
+          skiprows,
+          quoting,
+          flexible,
+          progress,
+          threads.unwrap_or(1),
+          output,
+          emitter,
+        )
+        .await
+      }
+      _ => Err(anyhow!("Fuzzy search requires its options")),
+    },
     _ => {
       let vec_conditions = multi_conditions.to_vec();
       let mut opts = CsvOptions::new(path.as_ref().to_string_lossy().to_string());
       opts.set_skiprows(skiprows);
-      let (sep, reader) = opts.skiprows_and_delimiter()?;
+      let (dialect, reader) = opts.skiprows_and_dialect()?;
       let output_path = opts.output_path(Some("search"), None)?;
       let config = CsvConfigBuilder::new()
         .flexible(flexible)
-        .delimiter(sep)
+        .dialect(&dialect)
         .quoting(quoting)
         .build();
+      if matches!(search_mode, SearchMode::IrregularRegex) && output != SearchOutput::Rows {
+        return Err(anyhow!("irregular_regex only writes the matching lines"));
+      }
       let rdr = config.build_reader(reader);
-      let wtr = config.build_writer(&output_path)?;
+      let wtr = MatchSink::for_search(&opts, &config, output)?;
 
-      let mut idx: Option<Indexed<File, File>> = None;
+      // 压缩文件无法内存映射, 只能单线程流式读取
+      let threads = if opts.is_compressed() {
+        threads.map(|_| 1)
+      } else {
+        threads
+      };
 
-      if let Some(threads) = threads {
-        if threads == 1 {
-          // 单线程: emit_total_search_rows
-          let total_rows = if progress {
-            opts.idx_count_rows().await?
-          } else {
-            0
-          };
-          emitter.emit_total_search_rows(total_rows).await?;
+      // 单线程: emit_total_search_rows, 多线程按块并行读取, 不需要索引
+      if threads == Some(1) {
+        let total_rows = if progress {
+          opts.idx_count_rows().await?
         } else {
-          // 多线程: 初始化idx供后续使用
-          idx = Some(
-            opts
-              .indexed()?
-              .ok_or_else(|| anyhow!("No indexed file, create index first"))?,
-          );
+          0
+        };
+        emitter.emit_total_search_rows(total_rows).await?;
+      }
+
+      // 多线程时按同样的 quoting, flexible 读取
+      let input = SearchInput {
+        opts,
+        quoting,
+        flexible,
+      };
+
+      // 查找文件和日期模式的匹配器已经构建
+      match value_matcher {
+        Some(ValueMatcher::Lookup(matcher)) => {
+          return filters::lookup(rdr, wtr, input, column, matcher, progress, threads, emitter)
+            .await;
+        }
+        Some(ValueMatcher::Date(matcher)) => {
+          return filters::date_search(
+            rdr, wtr, input, column, matcher, progress, threads, emitter,
+          )
+          .await;
         }
+        _ => {}
       }
 
       match search_mode {
@@ -160,8 +235,7 @@ async fn perform_search<P: AsRef<Path> + Send + Sync + 'static>(
           filters::equal(
             rdr,
             wtr,
-            opts,
-            idx,
+            input,
             column,
             vec_conditions,
             progress,
@@ -174,8 +248,7 @@ async fn perform_search<P: AsRef<Path> + Send + Sync + 'static>(
           filters::not_equal(
             rdr,
             wtr,
-            opts,
-            idx,
+            input,
             column,
             vec_conditions,
             progress,
@@ -188,8 +261,7 @@ async fn perform_search<P: AsRef<Path> + Send + Sync + 'static>(
           filters::contains(
             rdr,
             wtr,
-            opts,
-            idx,
+            input,
             column,
             vec_conditions,
             progress,
@@ -202,8 +274,7 @@ async fn perform_search<P: AsRef<Path> + Send + Sync + 'static>(
           filters::not_contains(
             rdr,
             wtr,
-            opts,
-            idx,
+            input,
             column,
             vec_conditions,
             progress,
@@ -216,8 +287,7 @@ async fn perform_search<P: AsRef<Path> + Send + Sync + 'static>(
           filters::starts_with(
             rdr,
             wtr,
-            opts,
-            idx,
+            input,
             column,
             vec_conditions,
             progress,
@@ -230,8 +300,7 @@ async fn perform_search<P: AsRef<Path> + Send + Sync + 'static>(
           filters::not_starts_with(
             rdr,
             wtr,
-            opts,
-            idx,
+            input,
             column,
             vec_conditions,
             progress,
@@ -244,8 +313,7 @@ async fn perform_search<P: AsRef<Path> + Send + Sync + 'static>(
           filters::ends_with(
             rdr,
             wtr,
-            opts,
-            idx,
+            input,
             column,
             vec_conditions,
             progress,
@@ -258,8 +326,7 @@ async fn perform_search<P: AsRef<Path> + Send + Sync + 'static>(
           filters::not_ends_with(
             rdr,
             wtr,
-            opts,
-            idx,
+            input,
             column,
             vec_conditions,
             progress,
@@ -270,59 +337,37 @@ async fn perform_search<P: AsRef<Path> + Send + Sync + 'static>(
         }
         SearchMode::Regex => {
           filters::regex_search(
-            rdr, wtr, opts, idx, column, conditions, progress, threads, emitter,
+            rdr, wtr, input, column, conditions, progress, threads, emitter,
           )
           .await
         }
         SearchMode::IsNull => {
-          filters::is_null(
-            rdr,
-            wtr,
-            opts,
-            idx,
-            column,
-            vec![],
-            progress,
-            threads,
-            emitter,
-          )
-          .await
+          filters::is_null(rdr, wtr, input, column, vec![], progress, threads, emitter).await
         }
         SearchMode::IsNotNull => {
-          filters::is_not_null(
-            rdr,
-            wtr,
-            opts,
-            idx,
-            column,
-            vec![],
-            progress,
-            threads,
-            emitter,
-          )
-          .await
+          filters::is_not_null(rdr, wtr, input, column, vec![], progress, threads, emitter).await
         }
         SearchMode::GreaterThan => {
           filters::greater_than(
-            rdr, wtr, opts, idx, column, conditions, progress, threads, emitter,
+            rdr, wtr, input, column, conditions, progress, threads, emitter,
           )
           .await
         }
         SearchMode::GreaterThanEqual => {
           filters::greater_than_or_equal(
-            rdr, wtr, opts, idx, column, conditions, progress, threads, emitter,
+            rdr, wtr, input, column, conditions, progress, threads, emitter,
           )
           .await
         }
         SearchMode::LessThan => {
           filters::less_than(
-            rdr, wtr, opts, idx, column, conditions, progress, threads, emitter,
+            rdr, wtr, input, column, conditions, progress, threads, emitter,
           )
           .await
         }
         SearchMode::LessThanEqual => {
           filters::less_than_or_equal(
-            rdr, wtr, opts, idx, column, conditions, progress, threads, emitter,
+            rdr, wtr, input, column, conditions, progress, threads, emitter,
           )
           .await
         }
@@ -330,8 +375,7 @@ async fn perform_search<P: AsRef<Path> + Send + Sync + 'static>(
           filters::between(
             rdr,
             wtr,
-            opts,
-            idx,
+            input,
             column,
             vec_conditions,
             progress,
@@ -341,7 +385,7 @@ async fn perform_search<P: AsRef<Path> + Send + Sync + 'static>(
           .await
         }
         SearchMode::IrregularRegex => {
-          let (_, reader) = opts.skiprows_and_delimiter()?;
+          let (_, reader) = input.opts.skiprows_and_delimiter()?;
           filters::irregular_with_regex(reader, output_path, conditions).await
         }
         _ => Err(anyhow!("Unsupported search mode")),
@@ -362,25 +406,165 @@ pub async fn search(
   unique: bool,
   skiprows: usize,
   threads: usize,
+  output: Option<String>,
+  source_row: Option<bool>,
+  output_mode: Option<String>,
+  scope: Option<String>,
+  matched_columns: Option<bool>,
+  lookup_path: Option<String>,
+  lookup_column: Option<String>,
+  lookup_skiprows: Option<usize>,
+  date_format: Option<String>,
+  unparsable: Option<String>,
+  fuzzy: Option<FuzzyOptions>,
+  fuzzy_score: Option<bool>,
   app_handle: AppHandle,
 ) -> Result<(String, String), String> {
   let start_time = Instant::now();
 
-  match perform_search(
-    path,
-    column,
-    condition,
-    &mode,
-    progress,
-    quoting,
-    flexible,
-    unique,
-    skiprows,
-    Some(threads),
-    app_handle,
-  )
-  .await
-  {
+  let result = async {
+    let output_mode = SearchOutput::from(output_mode.as_deref().unwrap_or("rows"));
+    let scope = ColumnScope::new(scope.as_deref().unwrap_or("column"))?;
+    let label = matched_columns.unwrap_or(false);
+    let mut lookup_values = match &lookup_path {
+      Some(lookup_path) => {
+        let lookup_column = lookup_column.as_deref().unwrap_or(&column);
+        let lookup_skiprows = lookup_skiprows.unwrap_or(0);
+        Some(read_lookup_values(
+          lookup_path,
+          lookup_column,
+          lookup_skiprows,
+        )?)
+      }
+      None => None,
+    };
+
+    // 有列索引时 equal, starts_with 和查找列表不扫描文件
+    if scope.is_none() && IndexQuery::indexable(&mode) && !multi_file::is_multi_path(&path) {
+      let is_lookup = lookup_values.is_some();
+      let values = lookup_values
+        .take()
+        .unwrap_or_else(|| condition.split('|').map(|s| s.trim().to_string()).collect());
+      if let Some(query) = IndexQuery::new(&mode, values) {
+        let mut opts = CsvOptions::new(path.clone());
+        opts.set_skiprows(skiprows);
+        let index_column = column.clone();
+        let (indexed, query) = tokio::task::spawn_blocking(move || {
+          column_index::search_indexed(&opts, &index_column, &query, quoting, flexible, output_mode)
+            .map(|indexed| (indexed, query))
+        })
+        .await
+        .map_err(|e| anyhow!("Task join error: {e}"))??;
+        if let Some(match_rows) = indexed {
+          return Ok(match_rows);
+        }
+        // 没有可用的索引, 查找列表交还给扫描
+        if is_lookup {
+          lookup_values = Some(query.into_values());
+        }
+      }
+    }
+
+    let value_matcher = if let Some(values) = lookup_values {
+      Some(ValueMatcher::Lookup(LookupMatcher::new(&mode, values)?))
+    } else if mode.starts_with("date_") {
+      let unparsable = Unparsable::from(unparsable.as_deref().unwrap_or("exclude"));
+      Some(ValueMatcher::Date(DateMatcher::new(
+        &mode,
+        &condition,
+        date_format.as_deref(),
+        unparsable,
+      )?))
+    } else if mode == "fuzzy" {
+      Some(ValueMatcher::Fuzzy {
+        matcher: FuzzyMatcher::new(&condition, fuzzy.unwrap_or_default())?,
+        score: fuzzy_score.unwrap_or(false),
+      })
+    } else {
+      None
+    };
+    if scope.is_some() && value_matcher.is_some() {
+      return Err(anyhow!("Lookup, date and fuzzy modes search one column"));
+    }
+
+    if multi_file::is_multi_path(&path) {
+      if label {
+        return Err(anyhow!(
+          "{MATCHED_COLUMNS} is not written when searching several files"
+        ));
+      }
+      if fuzzy_score.unwrap_or(false) {
+        return Err(anyhow!(
+          "{FUZZY_SCORE} is not written when searching several files"
+        ));
+      }
+      let paths = expand_paths(&path)?;
+      let output = FilesOutput::new(output.as_deref(), source_row.unwrap_or(false), output_mode)?;
+      if mode == "irregular_regex" {
+        return multi_file::search_files_lines(paths, condition, output, skiprows, app_handle)
+          .await;
+      }
+      if let Some(matcher) = value_matcher {
+        let filter = multi_file::value_filter(column, move |value| matcher.is_match(value));
+        return multi_file::search_files(
+          paths, filter, output, skiprows, quoting, flexible, threads, app_handle,
+        )
+        .await;
+      }
+      match scope {
+        Some(scope) => {
+          let filter = multi_file::columns_filter(&mode, &condition, scope)?;
+          multi_file::search_files(
+            paths, filter, output, skiprows, quoting, flexible, threads, app_handle,
+          )
+          .await
+        }
+        None => {
+          let filter = multi_file::column_filter(column, &mode, &condition)?;
+          multi_file::search_files(
+            paths, filter, output, skiprows, quoting, flexible, threads, app_handle,
+          )
+          .await
+        }
+      }
+    } else if let Some(scope) = scope {
+      filters_columns::search_columns(
+        path,
+        &mode,
+        &condition,
+        scope,
+        label,
+        skiprows,
+        quoting,
+        flexible,
+        progress,
+        threads,
+        output_mode,
+        app_handle,
+      )
+      .await
+    } else {
+      perform_search(
+        path,
+        column,
+        condition,
+        &mode,
+        progress,
+        quoting,
+        flexible,
+        unique,
+        skiprows,
+        Some(threads),
+        output_mode,
+        value_matcher,
+        app_handle,
+      )
+      .await
+    }
+  }
+  .await;
+
+  match result {
     Ok(match_rows) => {
       let end_time = Instant::now();
       let elapsed_time = end_time.duration_since(start_time).as_secs_f64();
@@ -395,6 +579,15 @@ pub struct ColumnConfig {
   pub(crate) column: String,
   pub(crate) mode: String,
   pub(crate) condition: String,
+  /// `fuzzy` 模式的选项
+  #[serde(default)]
+  pub(crate) fuzzy: FuzzyOptions,
+  /// 日期模式的格式, 不设置时自动识别
+  #[serde(default)]
+  pub(crate) date_format: Option<String>,
+  /// 日期模式中无法解析的值: `include` 或 `exclude` (默认)
+  #[serde(default)]
+  pub(crate) unparsable: Option<String>,
 }
 
 #[tauri::command]
@@ -407,15 +600,42 @@ pub async fn search_chain(
   flexible: bool,
   skiprows: usize,
   threads: usize,
+  output: Option<String>,
+  source_row: Option<bool>,
+  output_mode: Option<String>,
   app_handle: AppHandle,
 ) -> Result<(String, String), String> {
   let start_time = Instant::now();
+  let output_mode = SearchOutput::from(output_mode.as_deref().unwrap_or("rows"));
+
+  let result = if multi_file::is_multi_path(&path) {
+    async {
+      let paths = expand_paths(&path)?;
+      let output = FilesOutput::new(output.as_deref(), source_row.unwrap_or(false), output_mode)?;
+      let filter = multi_file::chain_filter(configs, logics)?;
+      multi_file::search_files(
+        paths, filter, output, skiprows, quoting, flexible, threads, app_handle,
+      )
+      .await
+    }
+    .await
+  } else {
+    filters_chain::search_with_chain(
+      path,
+      configs,
+      logics,
+      skiprows,
+      quoting,
+      flexible,
+      progress,
+      threads,
+      output_mode,
+      app_handle,
+    )
+    .await
+  };
 
-  match filters_chain::search_with_chain(
-    path, configs, logics, skiprows, quoting, flexible, progress, threads, app_handle,
-  )
-  .await
-  {
+  match result {
     Ok(match_rows) => {
       let end_time = Instant::now();
       let elapsed_time = end_time.duration_since(start_time).as_secs_f64();
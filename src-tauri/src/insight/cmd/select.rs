@@ -14,7 +14,10 @@ use tauri::AppHandle;
 use tokio::sync::oneshot;
 
 use crate::{
-  io::csv::{config::CsvConfigBuilder, options::CsvOptions},
+  io::{
+    csv::{config::CsvConfigBuilder, options::CsvOptions},
+    writer::Finish,
+  },
   utils::EventEmitter,
 };
 
@@ -50,7 +53,7 @@ where
 {
   let mut opts = CsvOptions::new(path);
   opts.set_skiprows(skiprows);
-  let (sep, reader) = opts.skiprows_and_delimiter()?;
+  let (dialect, reader) = opts.skiprows_and_dialect()?;
   let output_path = opts.output_path(Some("select"), None)?;
   let col_names: HashSet<&str> = sel_cols.split('|').collect();
 
@@ -62,7 +65,7 @@ where
 
   let config = CsvConfigBuilder::new()
     .flexible(flexible)
-    .delimiter(sep)
+    .dialect(&dialect)
     .quoting(quoting)
     .build();
 
@@ -157,7 +160,7 @@ where
 
     let final_rows = rows.load(Ordering::Relaxed);
     let _ = done_tx.send(final_rows);
-    Ok::<_, anyhow::Error>(wtr.flush()?)
+    wtr.finish()
   });
 
   counter_task.await??;
@@ -1,41 +1,48 @@
 use std::{
-  fs::File,
-  io::{BufRead, BufWriter, Write},
+  io::{BufRead, Write},
   path::Path,
   time::Instant,
 };
 
 use anyhow::{Result, anyhow};
-use csv::{ReaderBuilder, WriterBuilder};
 
-use crate::utils::WTR_BUFFER_SIZE;
-use crate::{io::csv::options::CsvOptions, utils};
+use crate::io::writer::{Finish, OutputOptions, OutputWriter};
+use crate::{
+  io::csv::{config::CsvConfigBuilder, options::CsvOptions},
+  utils,
+};
 
 /// 将CSV文件拆分为good行和bad行
 ///
 /// # 参数
 /// - `path`: 输入CSV路径
 /// - `expected_columns`: 可手动指定期望列数:若为 None,则以第一行为准
+/// - `output`: good 和 bad 两个输出文件的压缩格式, 不能写到 stdout
 pub async fn separate_csv<P>(
   path: P,
   quoting: bool,
   expected_columns: Option<usize>,
   skiprows: usize,
+  output: OutputOptions,
 ) -> Result<()>
 where
   P: AsRef<Path> + Send + Sync,
 {
+  output.require_files()?;
   let mut opts = CsvOptions::new(&path);
   opts.set_skiprows(skiprows);
-  let (sep, reader) = opts.skiprows_and_delimiter()?;
+  let (dialect, reader) = opts.skiprows_and_dialect()?;
   let good_path = opts.output_path(Some("good"), None)?;
   let bad_path = opts.output_path(Some("bad"), None)?;
 
-  let good_buf_wtr = BufWriter::with_capacity(WTR_BUFFER_SIZE, File::create(good_path)?);
-  let mut good_wtr = WriterBuilder::new()
-    .delimiter(sep)
-    .from_writer(good_buf_wtr);
-  let mut bad_wtr = BufWriter::with_capacity(WTR_BUFFER_SIZE, File::create(bad_path)?);
+  let config = CsvConfigBuilder::new()
+    .dialect(&dialect)
+    .has_headers(false)
+    .quoting(quoting)
+    .output(output)
+    .build();
+  let mut good_wtr = config.build_writer(good_path)?;
+  let mut bad_wtr = OutputWriter::create(bad_path, output)?;
 
   let mut lines = reader.lines().enumerate();
 
@@ -45,12 +52,10 @@ where
   };
 
   // 解析 header
-  let mut header_parser = ReaderBuilder::new()
-    .has_headers(false)
+  let mut header_parser = CsvConfigBuilder::from_config(config.clone())
     .flexible(true)
-    .delimiter(sep)
-    .quoting(quoting)
-    .from_reader(header.as_bytes());
+    .build()
+    .build_reader(header.as_bytes());
 
   let inferred_columns = if let Some(Ok(record)) = header_parser.records().next() {
     // 如果expected_columns是Some(n)且 n > 0,则使用它;否则用header的列数
@@ -63,7 +68,7 @@ where
   } else {
     log::error!("Failed to parse header line. Writing to bad file.");
     writeln!(bad_wtr, "{}", header)?;
-    bad_wtr.flush()?;
+    bad_wtr.finish()?;
     return Err(anyhow!("Header line is invalid CSV; cannot proceed"));
   };
 
@@ -88,12 +93,7 @@ where
       }
     };
 
-    let mut parser = ReaderBuilder::new()
-      .has_headers(false)
-      .delimiter(sep)
-      .flexible(false)
-      .quoting(quoting)
-      .from_reader(line.as_bytes());
+    let mut parser = config.build_reader(line.as_bytes());
 
     match parser.records().next() {
       Some(Ok(record)) if record.len() == inferred_columns => {
@@ -126,8 +126,8 @@ where
     }
   }
 
-  good_wtr.flush()?;
-  bad_wtr.flush()?;
+  good_wtr.finish()?;
+  bad_wtr.finish()?;
 
   Ok(())
 }
@@ -138,14 +138,17 @@ pub async fn separate(
   quoting: bool,
   expected_columns: String,
   skiprows: usize,
+  output_compression: Option<String>,
 ) -> Result<String, String> {
   let start_time = Instant::now();
+  let output = OutputOptions::from_arg(output_compression.as_deref()).map_err(|e| e.to_string())?;
 
   match separate_csv(
     path,
     quoting,
     Some(utils::parse_usize(&expected_columns, "expected_columns").unwrap_or(0)),
     skiprows,
+    output,
   )
   .await
   {
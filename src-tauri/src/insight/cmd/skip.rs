@@ -1,6 +1,5 @@
 use std::{
-  fs::File,
-  io::{BufRead, BufReader, BufWriter, Write},
+  io::{BufRead, BufReader, Write},
   path::Path,
   time::Instant,
 };
@@ -8,8 +7,8 @@ use std::{
 use anyhow::{Result, anyhow};
 
 use crate::{
-  io::csv::options::CsvOptions,
-  utils::{self, RDR_BUFFER_SIZE, WTR_BUFFER_SIZE},
+  io::{csv::options::CsvOptions, writer},
+  utils::{self, RDR_BUFFER_SIZE},
 };
 
 pub async fn skip_csv<P>(path: P, skiprows: usize) -> Result<()>
@@ -20,11 +19,11 @@ where
     return Err(anyhow!("The skip rows must be greater than or equal to 1"));
   }
 
-  let rdr = BufReader::with_capacity(RDR_BUFFER_SIZE, File::open(&path)?);
-
   let opts = CsvOptions::new(&path);
+  let rdr = BufReader::with_capacity(RDR_BUFFER_SIZE, opts.open_reader()?);
+
   let output_path = opts.output_path(Some("skip"), None)?;
-  let mut wtr = BufWriter::with_capacity(WTR_BUFFER_SIZE, File::create(output_path)?);
+  let mut wtr = writer::create(output_path)?;
 
   let mut lines = rdr.lines();
 
@@ -36,7 +35,7 @@ where
     writeln!(wtr, "{}", line?)?;
   }
 
-  Ok(wtr.flush()?)
+  wtr.finish()
 }
 
 #[tauri::command]
@@ -1,12 +1,15 @@
-use std::{fs::File, io::BufWriter, path::Path, str::FromStr, time::Instant};
+use std::{fs::File, path::Path, str::FromStr, time::Instant};
 
 use anyhow::{Result, anyhow};
-use csv::{ByteRecord, ReaderBuilder, WriterBuilder};
+use csv::ByteRecord;
 
 use crate::{
   index::Indexed,
-  io::csv::options::CsvOptions,
-  utils::{WTR_BUFFER_SIZE, parse_usize},
+  io::{
+    csv::{config::CsvConfigBuilder, options::CsvOptions},
+    writer::{Finish, OutputOptions},
+  },
+  utils::parse_usize,
 };
 
 pub enum SliceMode {
@@ -26,20 +29,6 @@ impl FromStr for SliceMode {
   }
 }
 
-fn create_writer<P: AsRef<Path>>(
-  output_path: P,
-  sep: u8,
-  flexible: bool,
-) -> Result<csv::Writer<BufWriter<File>>> {
-  let buf_wtr = BufWriter::with_capacity(WTR_BUFFER_SIZE, File::create(output_path)?);
-  Ok(
-    WriterBuilder::new()
-      .delimiter(sep)
-      .flexible(flexible)
-      .from_writer(buf_wtr),
-  )
-}
-
 pub async fn slice_csv_by_rows<P>(
   path: P,
   quoting: bool,
@@ -47,6 +36,7 @@ pub async fn slice_csv_by_rows<P>(
   start: usize,
   end: usize,
   skiprows: usize,
+  output: OutputOptions,
 ) -> Result<()>
 where
   P: AsRef<Path> + Send + Sync,
@@ -60,16 +50,17 @@ where
 
   let mut opts = CsvOptions::new(&path);
   opts.set_skiprows(skiprows);
-  let (sep, reader) = opts.skiprows_and_delimiter()?;
+  let (dialect, reader) = opts.skiprows_and_dialect()?;
   let output_path = opts.output_path(Some("slice"), None)?;
 
-  let mut rdr = ReaderBuilder::new()
-    .delimiter(sep)
+  let config = CsvConfigBuilder::new()
+    .dialect(&dialect)
     .quoting(quoting)
     .flexible(flexible)
-    .from_reader(reader);
-
-  let mut wtr = create_writer(output_path, sep, flexible)?;
+    .output(output)
+    .build();
+  let mut rdr = config.build_reader(reader);
+  let mut wtr = config.build_writer(output_path)?;
   wtr.write_byte_record(rdr.byte_headers()?)?;
 
   let mut n = 0;
@@ -85,7 +76,7 @@ where
     wtr.write_byte_record(&record)?;
   }
 
-  Ok(wtr.flush()?)
+  wtr.finish()
 }
 
 pub async fn with_index(
@@ -94,6 +85,7 @@ pub async fn with_index(
   flexible: bool,
   start: usize,
   end: usize,
+  output: OutputOptions,
 ) -> Result<()> {
   if start < 1 {
     return Err(anyhow!("start must be at least 1"));
@@ -103,10 +95,14 @@ pub async fn with_index(
   }
 
   let opts = CsvOptions::new(path);
-  let sep = opts.get_delimiter()?;
   let output_path = opts.output_path(Some("slice"), None)?;
 
-  let mut wtr = create_writer(output_path, sep, flexible)?;
+  let mut wtr = CsvConfigBuilder::new()
+    .dialect(&opts.dialect()?)
+    .flexible(flexible)
+    .output(output)
+    .build()
+    .build_writer(output_path)?;
   wtr.write_byte_record(indexed_file.byte_headers()?)?;
 
   indexed_file.seek((start - 1) as u64)?;
@@ -116,7 +112,7 @@ pub async fn with_index(
     wtr.write_byte_record(&r?)?;
   }
 
-  Ok(wtr.flush()?)
+  wtr.finish()
 }
 
 #[tauri::command]
@@ -128,6 +124,7 @@ pub async fn slice(
   end: String,
   skiprows: usize,
   mode: String,
+  output_compression: Option<String>,
 ) -> Result<String, String> {
   let start_time = Instant::now();
 
@@ -138,18 +135,24 @@ pub async fn slice(
 
   let start = parse_usize(&start, "start")?;
   let end = parse_usize(&end, "end")?;
+  let output = OutputOptions::from_arg(output_compression.as_deref()).map_err(|e| e.to_string())?;
 
   let res = match mode {
     SliceMode::Index => {
       let opts = CsvOptions::new(&path);
+      opts
+        .ensure_index()
+        .map_err(|e| format!("failed to update index file: {}", e))?;
       let indexed_file = opts
         .indexed()
         .map_err(|e| format!("failed to open indexed file: {}", e))?
         .ok_or_else(|| "no index file found; please create an index first".to_string())?;
 
-      with_index(indexed_file, &path, flexible, start, end).await
+      with_index(indexed_file, &path, flexible, start, end, output).await
+    }
+    SliceMode::Rows => {
+      slice_csv_by_rows(path, quoting, flexible, start, end, skiprows, output).await
     }
-    SliceMode::Rows => slice_csv_by_rows(path, quoting, flexible, start, end, skiprows).await,
   };
 
   res.map_err(|e| format!("slice failed: {e}"))?;
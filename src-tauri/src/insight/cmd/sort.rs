@@ -1,9 +1,17 @@
-use std::{cmp, path::Path, time::Instant};
+use std::{
+  cmp,
+  io::{Read, Write},
+  path::Path,
+  time::Instant,
+};
 
 use anyhow::Result;
+use csv::ByteRecord;
 
 use self::Number::{Float, Int};
-use crate::io::csv::{config::CsvConfigBuilder, options::CsvOptions, selection::Selection};
+use crate::io::csv::{config::CsvConfigBuilder, options::CsvOptions, selection::Selection, spill};
+use crate::io::writer::Finish;
+use crate::utils::{self, MemoryPlan};
 
 pub async fn sort_csv<P: AsRef<Path> + Send + Sync>(
   path: P,
@@ -13,15 +21,32 @@ pub async fn sort_csv<P: AsRef<Path> + Send + Sync>(
   quoting: bool,
   flexible: bool,
   skiprows: usize,
+) -> Result<()> {
+  sort_csv_with(
+    path, column, numeric, reverse, quoting, flexible, skiprows, None,
+  )
+  .await
+}
+
+/// Same as `sort_csv`, `plan` overrides the memory planner
+pub async fn sort_csv_with<P: AsRef<Path> + Send + Sync>(
+  path: P,
+  column: String,
+  numeric: bool,
+  reverse: bool,
+  quoting: bool,
+  flexible: bool,
+  skiprows: usize,
+  plan: Option<MemoryPlan>,
 ) -> Result<()> {
   let mut opts = CsvOptions::new(&path);
   opts.set_skiprows(skiprows);
-  let (sep, reader) = opts.skiprows_and_delimiter()?;
+  let (dialect, reader) = opts.skiprows_and_dialect()?;
   let output_path = opts.output_path(Some("sort"), None)?;
 
   let config = CsvConfigBuilder::new()
     .flexible(flexible)
-    .delimiter(sep)
+    .dialect(&dialect)
     .quoting(quoting)
     .build();
 
@@ -30,44 +55,106 @@ pub async fn sort_csv<P: AsRef<Path> + Send + Sync>(
 
   let headers = rdr.byte_headers()?.clone();
   let sel = Selection::from_headers(&headers, &[column.as_str()][..])?;
-
-  let mut all = rdr.byte_records().collect::<Result<Vec<_>, _>>()?;
-  match (numeric, reverse) {
-    (false, false) => all.sort_by(|r1, r2| {
-      let a = sel.get_row_key(r1);
-      let b = sel.get_row_key(r2);
-      iter_cmp(a.iter(), b.iter())
-    }),
-    (true, false) => all.sort_by(|r1, r2| {
-      let a = sel.get_row_key(r1);
-      let b = sel.get_row_key(r2);
-      iter_cmp_num(
+  let compare = |r1: &ByteRecord, r2: &ByteRecord| {
+    let (a, b) = match reverse {
+      false => (sel.get_row_key(r1), sel.get_row_key(r2)),
+      true => (sel.get_row_key(r2), sel.get_row_key(r1)),
+    };
+    match numeric {
+      false => iter_cmp(a.iter(), b.iter()),
+      true => iter_cmp_num(
         a.iter().map(|x| x.as_slice()),
         b.iter().map(|x| x.as_slice()),
-      )
-    }),
-    (false, true) => all.sort_by(|r1, r2| {
-      let a = sel.get_row_key(r1);
-      let b = sel.get_row_key(r2);
-      iter_cmp(b.iter(), a.iter())
-    }),
-    (true, true) => all.sort_by(|r1, r2| {
-      let a = sel.get_row_key(r1);
-      let b = sel.get_row_key(r2);
-      iter_cmp_num(
-        b.iter().map(|x| x.as_slice()),
-        a.iter().map(|x| x.as_slice()),
-      )
-    }),
-  }
+      ),
+    }
+  };
 
+  let plan = match plan {
+    Some(plan) => plan,
+    None => utils::memory_plan(&opts)?,
+  };
   wtr.write_record(&headers)?;
 
-  for r in all.into_iter() {
-    wtr.write_byte_record(&r)?;
+  match plan {
+    MemoryPlan::InMemory => {
+      let mut all = rdr.byte_records().collect::<Result<Vec<_>, _>>()?;
+      all.sort_by(compare);
+      for r in all.into_iter() {
+        wtr.write_byte_record(&r)?;
+      }
+    }
+    MemoryPlan::OnDisk(budget) => external_sort(&mut rdr, &mut wtr, budget, compare)?,
+  }
+
+  wtr.finish()
+}
+
+/// Sort chunks of about `budget` bytes into runs kept in temp files,
+/// then merge the runs. Ties go to the earlier run, so the sort stays stable
+fn external_sort<R, W, F>(
+  rdr: &mut csv::Reader<R>,
+  wtr: &mut csv::Writer<W>,
+  budget: usize,
+  compare: F,
+) -> Result<()>
+where
+  R: Read,
+  W: Write,
+  F: Fn(&ByteRecord, &ByteRecord) -> cmp::Ordering,
+{
+  let mut runs = Vec::new();
+  let mut chunk = Vec::new();
+  let mut bytes = 0;
+  for record in rdr.byte_records() {
+    let record = record?;
+    bytes += spill::record_size(&record);
+    chunk.push(record);
+    if bytes >= budget {
+      chunk.sort_by(&compare);
+      runs.push(spill::write_run(&chunk)?);
+      chunk.clear();
+      bytes = 0;
+    }
+  }
+  chunk.sort_by(&compare);
+  if runs.is_empty() {
+    for r in &chunk {
+      wtr.write_byte_record(r)?;
+    }
+    return Ok(());
+  }
+  if !chunk.is_empty() {
+    runs.push(spill::write_run(&chunk)?);
+  }
+  drop(chunk);
+
+  // k路归并, 每个 run 只保留当前记录
+  let mut readers: Vec<_> = runs.into_iter().map(spill::read_run).collect();
+  let mut heads = Vec::with_capacity(readers.len());
+  for rdr in readers.iter_mut() {
+    let mut record = ByteRecord::new();
+    heads.push(rdr.read_byte_record(&mut record)?.then_some(record));
+  }
+  loop {
+    let mut min: Option<usize> = None;
+    for (i, head) in heads.iter().enumerate() {
+      if let Some(record) = head
+        && min.is_none_or(|m| compare(record, heads[m].as_ref().unwrap()) == cmp::Ordering::Less)
+      {
+        min = Some(i);
+      }
+    }
+    let Some(i) = min else { break };
+    let Some(mut record) = heads[i].take() else {
+      break;
+    };
+    wtr.write_byte_record(&record)?;
+    if readers[i].read_byte_record(&mut record)? {
+      heads[i] = Some(record);
+    }
   }
 
-  Ok(wtr.flush()?)
+  Ok(())
 }
 
 /// Order `a` and `b` lexicographically using `Ord`
@@ -1,16 +1,23 @@
 use std::{
   fs::File,
-  io::{BufRead, BufReader, BufWriter, Write},
+  io::{BufRead, BufReader, Write},
   time::Instant,
 };
 
-use anyhow::Result;
-use csv::{ByteRecord, ReaderBuilder, Writer, WriterBuilder};
+use anyhow::{Result, anyhow};
+use csv::{ByteRecord, Writer};
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 
 use crate::{
   index::Indexed,
-  io::csv::options::CsvOptions,
+  io::{
+    csv::{
+      config::{CsvConfig, CsvConfigBuilder},
+      options::CsvOptions,
+      parallel::ParallelReader,
+    },
+    writer::{Finish, OutputOptions, OutputWriter},
+  },
   utils::{RDR_BUFFER_SIZE, num_of_chunks},
 };
 
@@ -18,13 +25,11 @@ fn new_writer(
   headers: &ByteRecord,
   index: usize,
   output_path: &str,
-  sep: u8,
-) -> Result<Writer<BufWriter<File>>> {
+  config: &CsvConfig,
+) -> Result<Writer<OutputWriter>> {
   let spath = format!("{output_path}.split_{index}.csv");
 
-  let mut wtr = WriterBuilder::new()
-    .delimiter(sep)
-    .from_writer(BufWriter::new(File::create(spath)?));
+  let mut wtr = config.build_writer(spath)?;
 
   wtr.write_record(headers)?;
 
@@ -35,28 +40,77 @@ pub async fn sequential_split_rows(
   opts: CsvOptions<&str>,
   size: u32,
   output_path: &str,
+  output: OutputOptions,
 ) -> Result<()> {
-  let (sep, reader) = opts.skiprows_and_delimiter()?;
+  let (dialect, reader) = opts.skiprows_and_dialect()?;
+  let config = CsvConfigBuilder::new()
+    .dialect(&dialect)
+    .output(output)
+    .build();
 
-  let mut rdr = ReaderBuilder::new().delimiter(sep).from_reader(reader);
+  let mut rdr = config.build_reader(reader);
 
   let headers = rdr.byte_headers()?.clone();
 
-  let mut wtr = new_writer(&headers, 0, &output_path, sep)?;
+  let mut wtr = new_writer(&headers, 0, &output_path, &config)?;
   let mut i = 0;
   let mut cnt = 1;
   let mut row = ByteRecord::new();
   while rdr.read_byte_record(&mut row)? {
     if i > 0 && i % size == 0 {
-      wtr.flush()?;
-      wtr = new_writer(&headers, cnt, &output_path, sep)?;
+      wtr.finish()?;
+      wtr = new_writer(&headers, cnt, &output_path, &config)?;
       cnt += 1;
     }
     wtr.write_byte_record(&row)?;
     i += 1;
   }
 
-  Ok(wtr.flush()?)
+  wtr.finish()
+}
+
+/// Split a file without an index, chunks are parsed in parallel and written in file order
+pub async fn chunked_split_rows(
+  opts: CsvOptions<&str>,
+  size: u32,
+  output_path: &str,
+  output: OutputOptions,
+) -> Result<()> {
+  let config = CsvConfigBuilder::new()
+    .dialect(&opts.dialect()?)
+    .output(output)
+    .build();
+  let rdr = ParallelReader::from_options(&opts, &config)?;
+  let headers = rdr.byte_headers();
+
+  let mut wtr = new_writer(headers, 0, output_path, &config)?;
+  let mut i = 0;
+  let mut cnt = 1;
+  rdr.for_each_ordered(
+    0,
+    |chunk| {
+      let mut rows = Vec::new();
+      let mut row = ByteRecord::new();
+      while chunk.read_byte_record(&mut row)? {
+        rows.push(row.clone());
+      }
+      Ok(rows)
+    },
+    |rows| {
+      for row in rows {
+        if i > 0 && i % size == 0 {
+          let next = new_writer(headers, cnt, output_path, &config)?;
+          std::mem::replace(&mut wtr, next).finish()?;
+          cnt += 1;
+        }
+        wtr.write_byte_record(&row)?;
+        i += 1;
+      }
+      Ok(())
+    },
+  )?;
+
+  wtr.finish()
 }
 
 pub async fn parallel_split_rows(
@@ -64,52 +118,45 @@ pub async fn parallel_split_rows(
   opts: CsvOptions<&str>,
   chunk_size: usize,
   output_path: &str,
+  output: OutputOptions,
 ) -> Result<()> {
   let nchunks = num_of_chunks(idx.count() as usize, chunk_size);
   if nchunks == 1 {
     // there's only one chunk, we can just do a sequential split
     // which has less overhead and better error handling
-    return sequential_split_rows(opts, chunk_size.try_into()?, output_path).await;
+    return sequential_split_rows(opts, chunk_size.try_into()?, output_path, output).await;
   }
 
-  let sep = opts.detect_separator()?;
+  let config = CsvConfigBuilder::new()
+    .dialect(&opts.dialect()?)
+    .output(output)
+    .build();
 
-  // safety: we cannot use ? here because we're in a closure
-  (0..nchunks).into_par_iter().for_each(|i| {
-    // safety: safe to unwrap because we know the file is indexed
-    let mut idx = opts.indexed().unwrap().unwrap();
-    // safety: the only way this can fail is if the file first row of the chunk
-    // is not a valid CSV record, which is impossible because we're reading
-    // from a file with a valid index
-    let headers = idx.byte_headers().unwrap();
+  // 任一分块出错 (如磁盘已满, 临时文件重命名失败) 时返回错误
+  (0..nchunks).into_par_iter().try_for_each(|i| {
+    let mut idx = opts
+      .indexed()?
+      .ok_or_else(|| anyhow!("the index changed while splitting"))?;
+    let headers = idx.byte_headers()?.clone();
 
-    // safety: the only way this can fail is if we cannot create a file
-    let mut wtr = new_writer(headers, i * chunk_size, &output_path, sep).unwrap();
+    let mut wtr = new_writer(&headers, i * chunk_size, output_path, &config)?;
 
-    // safety: we know that there is more than one chunk, so we can safely
-    // seek to the start of the chunk
-    idx.seek((i * chunk_size) as u64).unwrap();
-    let mut write_row;
+    idx.seek((i * chunk_size) as u64)?;
     for row in idx.byte_records().take(chunk_size) {
-      write_row = row.unwrap();
-      wtr.write_byte_record(&write_row).unwrap();
+      wtr.write_byte_record(&row?)?;
     }
-    // safety: safe to unwrap because we know the writer is a file
-    // the only way this can fail is if we cannot write to the file
-    wtr.flush().unwrap();
-  });
-
-  Ok(())
+    wtr.finish()
+  })
 }
 
 fn new_lines_writer(
   headers: &Option<String>,
   index: usize,
   output_path: &str,
-) -> Result<BufWriter<File>> {
+  output: OutputOptions,
+) -> Result<OutputWriter> {
   let output_file = format!("{output_path}.split_{index}.csv");
-  let file = File::create(output_file)?;
-  let mut wtr = BufWriter::new(file);
+  let mut wtr = OutputWriter::create(output_file, output)?;
   if let Some(header) = headers {
     writeln!(wtr, "{}", header)?;
   }
@@ -117,32 +164,45 @@ fn new_lines_writer(
   Ok(wtr)
 }
 
-pub async fn split_lines(path: String, size: u32, output_path: &str) -> Result<()> {
-  let reader = BufReader::with_capacity(RDR_BUFFER_SIZE, File::open(path)?);
+pub async fn split_lines(
+  path: String,
+  size: u32,
+  output_path: &str,
+  output: OutputOptions,
+) -> Result<()> {
+  let opts = CsvOptions::new(&path);
+  let reader = BufReader::with_capacity(RDR_BUFFER_SIZE, opts.open_reader()?);
   let mut lines = reader.lines();
   let headers = lines.next().transpose()?;
 
-  let mut wtr = new_lines_writer(&headers, 0, &output_path)?;
+  let mut wtr = new_lines_writer(&headers, 0, output_path, output)?;
   let mut i = 0;
   let mut cnt = 1;
 
   for line in lines {
     let line = line?;
     if i > 0 && i % size == 0 {
-      wtr.flush()?;
-      wtr = new_lines_writer(&headers, cnt, &output_path)?;
+      wtr.finish()?;
+      wtr = new_lines_writer(&headers, cnt, output_path, output)?;
       cnt += 1;
     }
     writeln!(wtr, "{}", line)?;
     i += 1;
   }
 
-  Ok(wtr.flush()?)
+  wtr.finish()
 }
 
 #[tauri::command]
-pub async fn split(path: String, size: u32, mode: String) -> Result<String, String> {
+pub async fn split(
+  path: String,
+  size: u32,
+  mode: String,
+  output_compression: Option<String>,
+) -> Result<String, String> {
   let start_time = Instant::now();
+  let output = OutputOptions::from_arg(output_compression.as_deref()).map_err(|e| e.to_string())?;
+  output.require_files().map_err(|e| e.to_string())?;
 
   let opts = CsvOptions::new(path.as_str());
   let parent_path = opts
@@ -155,16 +215,22 @@ pub async fn split(path: String, size: u32, mode: String) -> Result<String, Stri
 
   match mode.as_str() {
     "rows" => {
+      // 并行任务各自打开 .idx, 需要时先在这里重建或扩展
+      opts.ensure_index().map_err(|e| e.to_string())?;
       match opts.indexed().map_err(|e| e.to_string())? {
         Some(idx) => parallel_split_rows(
           &idx,
           opts,
           size.try_into().map_err(|e| format!("invalid size: {e}"))?,
           &output_path,
+          output,
         )
         .await
         .map_err(|e| e.to_string())?,
-        None => sequential_split_rows(opts, size, &output_path)
+        None if opts.is_compressed() => sequential_split_rows(opts, size, &output_path, output)
+          .await
+          .map_err(|e| e.to_string())?,
+        None => chunked_split_rows(opts, size, &output_path, output)
           .await
           .map_err(|e| e.to_string())?,
       };
@@ -172,7 +238,7 @@ pub async fn split(path: String, size: u32, mode: String) -> Result<String, Stri
       let elapsed_time = end_time.duration_since(start_time).as_secs_f64();
       Ok(format!("{elapsed_time:.0}"))
     }
-    _ => match split_lines(path, size, &output_path).await {
+    _ => match split_lines(path, size, &output_path, output).await {
       Ok(_) => {
         let end_time = Instant::now();
         let elapsed_time = end_time.duration_since(start_time).as_secs_f64();
@@ -1,6 +1,4 @@
 use std::{
-  fs::File,
-  io::BufWriter,
   path::Path,
   sync::{
     Arc,
@@ -10,12 +8,14 @@ use std::{
 };
 
 use anyhow::{Result, anyhow};
-use csv::{ReaderBuilder, WriterBuilder};
 use tokio::sync::oneshot;
 
 use crate::{
-  io::csv::{options::CsvOptions, selection::Selection},
-  utils::{EventEmitter, WTR_BUFFER_SIZE},
+  io::{
+    csv::{config::CsvConfigBuilder, options::CsvOptions, selection::Selection},
+    writer::Finish,
+  },
+  utils::EventEmitter,
 };
 
 #[derive(Debug)]
@@ -83,7 +83,7 @@ where
 {
   let mut opts = CsvOptions::new(&path);
   opts.set_skiprows(skiprows);
-  let (sep, reader) = opts.skiprows_and_delimiter()?;
+  let (dialect, reader) = opts.skiprows_and_dialect()?;
   let output_path = opts.output_path(Some("pad"), None)?;
   let length = length.parse::<usize>()?;
 
@@ -93,14 +93,14 @@ where
   };
   emitter.emit_total_rows(total_rows).await?;
 
-  let mut rdr = ReaderBuilder::new()
-    .delimiter(sep)
+  let config = CsvConfigBuilder::new()
+    .dialect(&dialect)
     .quoting(quoting)
-    .from_reader(reader);
+    .build();
+  let mut rdr = config.build_reader(reader);
   let sel = Selection::from_headers(rdr.byte_headers()?, &[column][..])?;
 
-  let buf_writer = BufWriter::with_capacity(WTR_BUFFER_SIZE, File::create(output_path)?);
-  let mut wtr = WriterBuilder::new().delimiter(sep).from_writer(buf_writer);
+  let mut wtr = config.build_writer(output_path)?;
   wtr.write_record(rdr.headers()?)?;
 
   let rows = Arc::new(AtomicUsize::new(0));
@@ -149,7 +149,7 @@ where
 
     let final_rows = rows.load(Ordering::Relaxed);
     let _ = done_tx.send(final_rows);
-    Ok::<_, anyhow::Error>(wtr.flush()?)
+    wtr.finish()
   });
 
   counter_task.await??;
@@ -1,6 +1,5 @@
 use std::{
-  fs::File,
-  io::{BufReader, BufWriter, Read},
+  io::{BufReader, Read},
   path::Path,
   sync::{
     Arc,
@@ -10,12 +9,15 @@ use std::{
 };
 
 use anyhow::{Result, anyhow};
-use csv::{Reader, ReaderBuilder, Writer, WriterBuilder};
+use csv::{Reader, Writer};
 use tokio::sync::oneshot;
 
 use crate::{
-  io::csv::{options::CsvOptions, selection::Selection},
-  utils::{EventEmitter, RDR_BUFFER_SIZE},
+  io::{
+    csv::{config::CsvConfigBuilder, options::CsvOptions, selection::Selection},
+    writer::{Finish, OutputWriter},
+  },
+  utils::EventEmitter,
 };
 
 #[derive(Debug)]
@@ -48,7 +50,7 @@ impl SliceMode {
 
 pub async fn slice_nchar<E>(
   mut rdr: Reader<BufReader<Box<dyn Read + Send>>>,
-  mut wtr: Writer<BufWriter<File>>,
+  mut wtr: Writer<OutputWriter>,
   column: &str,
   n: usize,
   reverse: bool,
@@ -136,7 +138,7 @@ where
 
     let final_rows = rows.load(Ordering::Relaxed);
     let _ = done_tx.send(final_rows);
-    Ok::<_, anyhow::Error>(wtr.flush()?)
+    wtr.finish()
   });
 
   counter_task.await??;
@@ -150,7 +152,7 @@ where
 
 pub async fn slice<E>(
   mut rdr: Reader<BufReader<Box<dyn Read + Send>>>,
-  mut wtr: Writer<BufWriter<File>>,
+  mut wtr: Writer<OutputWriter>,
   column: &str,
   start_idx: i32,
   length: usize,
@@ -252,7 +254,7 @@ where
 
     let final_rows = rows.load(Ordering::Relaxed);
     let _ = done_tx.send(final_rows);
-    Ok::<_, anyhow::Error>(wtr.flush()?)
+    wtr.finish()
   });
 
   counter_task.await??;
@@ -292,7 +294,7 @@ where
 
   let mut opts = CsvOptions::new(&path);
   opts.set_skiprows(skiprows);
-  let (sep, reader) = opts.skiprows_and_delimiter()?;
+  let (dialect, reader) = opts.skiprows_and_dialect()?;
   let output_path = opts.output_path(Some("slice"), None)?;
 
   let total_rows = match progress {
@@ -301,13 +303,12 @@ where
   };
   emitter.emit_total_rows(total_rows).await?;
 
-  let rdr = ReaderBuilder::new()
-    .delimiter(sep)
+  let config = CsvConfigBuilder::new()
+    .dialect(&dialect)
     .quoting(quoting)
-    .from_reader(reader);
-
-  let buf_writer = BufWriter::with_capacity(RDR_BUFFER_SIZE, File::create(output_path)?);
-  let wtr = WriterBuilder::new().delimiter(sep).from_writer(buf_writer);
+    .build();
+  let rdr = config.build_reader(reader);
+  let wtr = config.build_writer(output_path)?;
 
   match mode {
     SliceMode::Left => {
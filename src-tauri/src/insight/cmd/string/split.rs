@@ -1,6 +1,5 @@
 use std::{
-  fs::File,
-  io::{BufReader, BufWriter, Read},
+  io::{BufReader, Read},
   path::Path,
   sync::{
     Arc,
@@ -10,11 +9,14 @@ use std::{
 };
 
 use anyhow::{Result, anyhow};
-use csv::{Reader, ReaderBuilder, Writer, WriterBuilder};
+use csv::{Reader, Writer};
 use tokio::sync::oneshot;
 
 use crate::{
-  io::csv::{options::CsvOptions, selection::Selection},
+  io::{
+    csv::{config::CsvConfigBuilder, options::CsvOptions, selection::Selection},
+    writer::{Finish, OutputWriter},
+  },
   utils::EventEmitter,
 };
 
@@ -36,7 +38,7 @@ impl From<&str> for SplitMode {
 
 pub async fn split_n<E>(
   mut rdr: Reader<BufReader<Box<dyn Read + Send>>>,
-  mut wtr: Writer<BufWriter<File>>,
+  mut wtr: Writer<OutputWriter>,
   column: &str,
   n: usize,
   by: String,
@@ -106,7 +108,7 @@ where
 
     let final_rows = rows.load(Ordering::Relaxed);
     let _ = done_tx.send(final_rows);
-    Ok::<_, anyhow::Error>(wtr.flush()?)
+    wtr.finish()
   });
 
   counter_task.await??;
@@ -120,7 +122,7 @@ where
 
 pub async fn split_max<E>(
   mut rdr: Reader<BufReader<Box<dyn Read + Send>>>,
-  mut wtr: Writer<BufWriter<File>>,
+  mut wtr: Writer<OutputWriter>,
   column: String,
   n: usize,
   by: String,
@@ -196,7 +198,7 @@ where
 
     let final_rows = rows.load(Ordering::Relaxed);
     let _ = done_tx.send(final_rows);
-    Ok::<_, anyhow::Error>(wtr.flush()?)
+    wtr.finish()
   });
 
   counter_task.await??;
@@ -235,7 +237,7 @@ where
 
   let mut opts = CsvOptions::new(&path);
   opts.set_skiprows(skiprows);
-  let (sep, reader) = opts.skiprows_and_delimiter()?;
+  let (dialect, reader) = opts.skiprows_and_dialect()?;
   let output_path = opts.output_path(Some("split"), None)?;
 
   let total_rows = match progress {
@@ -244,13 +246,12 @@ where
   };
   emitter.emit_total_rows(total_rows).await?;
 
-  let rdr = ReaderBuilder::new()
-    .delimiter(sep)
+  let config = CsvConfigBuilder::new()
+    .dialect(&dialect)
     .quoting(quoting)
-    .from_reader(reader);
-
-  let buf_writer = BufWriter::with_capacity(256_000, File::create(output_path)?);
-  let wtr = WriterBuilder::new().delimiter(sep).from_writer(buf_writer);
+    .build();
+  let rdr = config.build_reader(reader);
+  let wtr = config.build_writer(output_path)?;
 
   match mode {
     SplitMode::Nth => split_n(rdr, wtr, &column, num, by, progress, emitter).await?,
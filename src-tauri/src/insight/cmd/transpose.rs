@@ -1,44 +1,57 @@
-use std::{fs::File, io::BufWriter, path::Path, time::Instant};
-
-use anyhow::Result;
-use csv::{ByteRecord, ReaderBuilder, WriterBuilder};
+use std::{
+  fs::File,
+  io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+  path::Path,
+  time::Instant,
+};
+
+use anyhow::{Result, anyhow};
+use csv::ByteRecord;
 use memmap2::MmapOptions;
 
-use crate::{io::csv::options::CsvOptions, utils::WTR_BUFFER_SIZE};
+use crate::io::{
+  csv::{config::CsvConfigBuilder, options::CsvOptions},
+  writer::Finish,
+};
 
 pub async fn in_memory_transpose<P: AsRef<Path> + Send + Sync>(
   path: P,
   quoting: bool,
-  skiprows: usize
+  skiprows: usize,
 ) -> Result<()> {
   let mut opts = CsvOptions::new(&path);
   opts.set_skiprows(skiprows);
-  let (sep, reader) = opts.skiprows_and_delimiter()?;
+  let (dialect, reader) = opts.skiprows_and_dialect()?;
   let output_path = opts.output_path(Some("transpose"), None)?;
-
-  let nrows = ReaderBuilder::new()
-    .delimiter(sep)
+  let config = CsvConfigBuilder::new()
+    .dialect(&dialect)
     .quoting(quoting)
-    .from_reader(reader)
-    .byte_headers()?
-    .len();
-
-  let file = File::open(path)?;
-  let mmap = unsafe { MmapOptions::new().populate().map(&file)? };
-
-  let output_file = File::create(output_path)?;
-  let buf_wtr = BufWriter::with_capacity(WTR_BUFFER_SIZE, output_file);
-  let mut wtr = WriterBuilder::new().delimiter(sep).from_writer(buf_wtr);
+    .build();
+
+  let nrows = config.build_reader(reader).byte_headers()?.len();
+
+  // 压缩文件先解压到内存, 否则直接mmap
+  let mmap;
+  let decompressed;
+  let data: &[u8] = if opts.is_compressed() {
+    let mut buf = Vec::new();
+    opts.open_reader()?.read_to_end(&mut buf)?;
+    decompressed = buf;
+    &decompressed
+  } else {
+    let file = File::open(path)?;
+    mmap = unsafe { MmapOptions::new().populate().map(&file)? };
+    &mmap
+  };
+
+  let mut wtr = config.build_writer(output_path)?;
 
   let mut record = ByteRecord::with_capacity(1024, nrows);
 
   for i in 0..nrows {
     record.clear();
 
-    let mut rdr = ReaderBuilder::new()
-      .delimiter(sep)
-      .quoting(quoting)
-      .from_reader(&mmap[..]);
+    let mut rdr = config.build_reader(data);
 
     for row in rdr.byte_records() {
       let row = row?;
@@ -49,22 +62,22 @@ pub async fn in_memory_transpose<P: AsRef<Path> + Send + Sync>(
     wtr.write_byte_record(&record)?;
   }
 
-  Ok(wtr.flush()?)
+  wtr.finish()
 }
 
-pub async fn multipass_transpose<P: AsRef<Path> + Send + Sync>(path: P, quoting: bool) -> Result<()> {
+pub async fn multipass_transpose<P: AsRef<Path> + Send + Sync>(
+  path: P,
+  quoting: bool,
+) -> Result<()> {
   let opts = CsvOptions::new(&path);
-  let sep = opts.get_delimiter()?;
   let output_path = opts.output_path(Some("transpose"), None)?;
-
-  let mut rdr = ReaderBuilder::new()
-    .delimiter(sep)
+  let config = CsvConfigBuilder::new()
+    .dialect(&opts.dialect()?)
     .quoting(quoting)
-    .from_reader(File::open(&path)?);
+    .build();
 
-  let output_file = File::create(output_path)?;
-  let buf_wtr = BufWriter::with_capacity(WTR_BUFFER_SIZE, output_file);
-  let mut wtr = WriterBuilder::new().delimiter(sep).from_writer(buf_wtr);
+  let mut rdr = config.build_reader(opts.open_reader()?);
+  let mut wtr = config.build_writer(output_path)?;
 
   let nrows = rdr.byte_headers()?.len();
 
@@ -72,9 +85,7 @@ pub async fn multipass_transpose<P: AsRef<Path> + Send + Sync>(path: P, quoting:
 
   for i in 0..nrows {
     record.clear();
-    let mut rdr = ReaderBuilder::new()
-      .delimiter(sep)
-      .from_reader(File::open(&path)?);
+    let mut rdr = config.build_reader(opts.open_reader()?);
 
     let mut record = ByteRecord::new();
     for row in rdr.byte_records() {
@@ -83,11 +94,130 @@ pub async fn multipass_transpose<P: AsRef<Path> + Send + Sync>(path: P, quoting:
     wtr.write_byte_record(&record)?;
   }
 
-  Ok(wtr.flush()?)
+  wtr.finish()
+}
+
+/// Bytes of fields buffered in memory before they are spilled to disk
+pub const TRANSPOSE_MEMORY_BUDGET: usize = 256 * 1024 * 1024;
+
+/// LEB128 长度前缀
+fn write_len(buf: &mut Vec<u8>, mut len: usize) {
+  while len >= 0x80 {
+    buf.push((len as u8) | 0x80);
+    len >>= 7;
+  }
+  buf.push(len as u8);
+}
+
+/// Split a block of length-prefixed values
+fn for_each_value(mut block: &[u8], mut f: impl FnMut(&[u8]) -> Result<()>) -> Result<()> {
+  while !block.is_empty() {
+    let (mut len, mut shift, mut n) = (0usize, 0, 0);
+    loop {
+      let b = *block
+        .get(n)
+        .ok_or_else(|| anyhow!("corrupted transpose block"))?;
+      len |= ((b & 0x7f) as usize) << shift;
+      shift += 7;
+      n += 1;
+      if b < 0x80 {
+        break;
+      }
+    }
+    let value = block
+      .get(n..n + len)
+      .ok_or_else(|| anyhow!("corrupted transpose block"))?;
+    f(value)?;
+    block = &block[n + len..];
+  }
+  Ok(())
+}
+
+/// Transpose with a single parse of the input.
+///
+/// Fields are appended to one buffer per column, when the buffers exceed `budget` bytes
+/// they are written to a temp file as a run of column blocks. Output row `i` is then
+/// assembled from block `i` of every run, so memory stays bounded by `budget`.
+pub async fn streaming_transpose<P: AsRef<Path> + Send + Sync>(
+  path: P,
+  quoting: bool,
+  skiprows: usize,
+  budget: usize,
+) -> Result<()> {
+  let mut opts = CsvOptions::new(&path);
+  opts.set_skiprows(skiprows);
+  let (dialect, reader) = opts.skiprows_and_dialect()?;
+  let output_path = opts.output_path(Some("transpose"), None)?;
+  let config = CsvConfigBuilder::new()
+    .dialect(&dialect)
+    .quoting(quoting)
+    .flexible(true)
+    .build();
+
+  let mut rdr = config.build_reader(reader);
+  let ncols = rdr.byte_headers()?.len();
+
+  let mut columns: Vec<Vec<u8>> = vec![Vec::new(); ncols];
+  let mut buffered = 0;
+  // 每次溢写的各列块在临时文件中的 (offset, len)
+  let mut runs: Vec<Vec<(u64, usize)>> = Vec::new();
+  let mut spill = BufWriter::new(tempfile::tempfile()?);
+  let mut offset = 0;
+
+  let mut row = ByteRecord::new();
+  while rdr.read_byte_record(&mut row)? {
+    if row.len() > ncols {
+      let line = row.position().map_or(0, |p| p.line());
+      return Err(anyhow!(
+        "line {line} has {} fields, the header has {ncols}",
+        row.len()
+      ));
+    }
+    // 短行缺失的列写空值, 保证每个输出行的字段数一致
+    for (i, column) in columns.iter_mut().enumerate() {
+      let field = row.get(i).unwrap_or_default();
+      write_len(column, field.len());
+      column.extend_from_slice(field);
+      buffered += field.len() + 1;
+    }
+    if buffered >= budget {
+      let mut blocks = Vec::with_capacity(ncols);
+      for column in columns.iter_mut() {
+        spill.write_all(column)?;
+        blocks.push((offset, column.len()));
+        offset += column.len() as u64;
+        column.clear();
+      }
+      runs.push(blocks);
+      buffered = 0;
+    }
+  }
+
+  let mut wtr = config.build_writer(output_path)?;
+  let mut spill = BufReader::new(spill.into_inner().map_err(|e| e.into_error())?);
+  let mut block = Vec::new();
+  for (i, column) in columns.iter().enumerate() {
+    for blocks in &runs {
+      let (start, len) = blocks[i];
+      block.resize(len, 0);
+      spill.seek(SeekFrom::Start(start))?;
+      spill.read_exact(&mut block)?;
+      for_each_value(&block, |value| Ok(wtr.write_field(value)?))?;
+    }
+    for_each_value(column, |value| Ok(wtr.write_field(value)?))?;
+    wtr.write_record(None::<&[u8]>)?;
+  }
+
+  wtr.finish()
 }
 
 #[tauri::command]
-pub async fn transpose(path: String, mode: String, quoting: bool, skiprows: usize) -> Result<String, String> {
+pub async fn transpose(
+  path: String,
+  mode: String,
+  quoting: bool,
+  skiprows: usize,
+) -> Result<String, String> {
   let start_time = Instant::now();
 
   match mode.as_str() {
@@ -107,6 +237,15 @@ pub async fn transpose(path: String, mode: String, quoting: bool, skiprows: usiz
       }
       Err(err) => Err(format!("{err}")),
     },
+    "streaming" => {
+      match streaming_transpose(path, quoting, skiprows, TRANSPOSE_MEMORY_BUDGET).await {
+        Ok(()) => {
+          let elapsed_time = start_time.elapsed().as_secs_f64();
+          Ok(format!("{elapsed_time:.2}"))
+        }
+        Err(err) => Err(format!("{err}")),
+      }
+    }
     _ => Err(format!("Unknown transpose mode")),
   }
 }
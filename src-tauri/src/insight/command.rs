@@ -1,6 +1,5 @@
 use std::{
   collections::{HashMap, HashSet},
-  fs::File,
   io::{BufRead, BufReader},
   path::Path,
 };
@@ -8,7 +7,10 @@ use std::{
 use anyhow::Result;
 use tauri::{Emitter, Window};
 
-use crate::io::csv::options::CsvOptions;
+use crate::io::{
+  compress,
+  csv::{options::CsvOptions, sniff::Dialect},
+};
 use crate::tojson;
 
 #[tauri::command]
@@ -96,8 +98,8 @@ pub async fn to_json(path: String, skiprows: usize) -> Result<String, String> {
 
 #[tauri::command]
 pub async fn preview_n_lines(path: String, n: usize) -> Result<Vec<String>, String> {
-  let file = File::open(&path).map_err(|e| e.to_string())?;
-  let reader = BufReader::new(file);
+  let opts = CsvOptions::new(&path);
+  let reader = BufReader::new(opts.open_reader().map_err(|e| e.to_string())?);
   let mut lines = Vec::with_capacity(n);
 
   for line in reader.lines().take(n) {
@@ -120,3 +122,25 @@ pub async fn detect_separator(path: String, skiprows: usize) -> Result<String, S
     Err(err) => Err(format!("{err}")),
   }
 }
+
+/// Sniff delimiter, quoting, header and preamble rows, `preamble_rows` can be used as `skiprows`
+#[tauri::command]
+pub async fn sniff_dialect(path: String, skiprows: usize) -> Result<Dialect, String> {
+  let mut opts = CsvOptions::new(path);
+  opts.set_skiprows(skiprows);
+
+  async { opts.dialect().map_err(|e| e.to_string()) }.await
+}
+
+/// List the members of a zip archive as selectable inputs (`archive.zip::member`)
+#[tauri::command]
+pub async fn zip_members(path: String) -> Result<Vec<String>, String> {
+  let members = compress::zip_members(&path).map_err(|e| e.to_string())?;
+
+  Ok(
+    members
+      .into_iter()
+      .map(|m| format!("{path}{}{m}", compress::ZIP_MEMBER_SEP))
+      .collect(),
+  )
+}
@@ -1,7 +1,16 @@
-use std::{io, ops};
+use std::{
+  fs::{self, File},
+  io::{self, Read, Seek, SeekFrom},
+  ops,
+  path::{Path, PathBuf},
+  time::UNIX_EPOCH,
+};
 
 use anyhow::Result;
 use csv_index::RandomAccessSimple;
+use serde::{Deserialize, Serialize};
+
+use crate::io::csv::sniff::Dialect;
 
 /// Indexed composes a CSV reader with a simple random access index.
 pub struct Indexed<R, I> {
@@ -23,12 +32,16 @@ impl<R, I> ops::DerefMut for Indexed<R, I> {
 }
 
 impl<R: io::Read + io::Seek, I: io::Read + io::Seek> Indexed<R, I> {
-  /// Opens an index.
-  pub fn open(csv_rdr: csv::Reader<R>, idx_rdr: I) -> Result<Indexed<R, I>> {
-    Ok(Indexed {
-      csv_rdr: csv_rdr,
-      idx: RandomAccessSimple::open(idx_rdr)?,
-    })
+  /// Opens an index, `csv_rdr` must not have been read yet.
+  ///
+  /// The reader is moved to the first indexed record, so skipped rows are not taken as the header.
+  pub fn open(mut csv_rdr: csv::Reader<R>, idx_rdr: I) -> Result<Indexed<R, I>> {
+    let mut idx = RandomAccessSimple::open(idx_rdr)?;
+    if !idx.is_empty() {
+      let pos = idx.get(0)?;
+      csv_rdr.get_mut().seek(SeekFrom::Start(pos.byte()))?;
+    }
+    Ok(Indexed { csv_rdr, idx })
   }
 
   /// Return the number of records (not including the header record) in this index.
@@ -59,3 +72,180 @@ impl<R: io::Read + io::Seek, I: io::Read + io::Seek> Indexed<R, I> {
     Ok(())
   }
 }
+
+/// Bump when the layout of the `.idx` or of the metadata changes
+pub const INDEX_VERSION: u32 = 3;
+
+/// Bytes hashed at the head and at the end of the indexed range
+const HASH_BLOCK: u64 = 64 * 1024;
+
+/// Blocks of `SAMPLE_BLOCK` bytes hashed across the indexed range
+const SAMPLES: u64 = 64;
+const SAMPLE_BLOCK: u64 = 4 * 1024;
+
+/// `<file>.idx.meta`, what the index was built from.
+///
+/// The `.idx` keeps the `csv-index` layout (big-endian record offsets and a count),
+/// offsets are absolute positions in the file, so `skiprows` rows are never indexed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexMeta {
+  pub version: u32,
+  /// Size of the file when it was indexed
+  pub size: u64,
+  /// Modification time of the file, milliseconds since the Unix epoch
+  pub mtime: u64,
+  /// FNV-1a of the first bytes of the file
+  pub head_hash: String,
+  /// FNV-1a of the last bytes before `size`
+  pub tail_hash: String,
+  /// FNV-1a of blocks spread over the first `size` bytes, checked before an index is extended
+  #[serde(default)]
+  pub samples: Vec<String>,
+  pub skiprows: usize,
+  pub delimiter: char,
+  pub quote: char,
+  pub escape: Option<char>,
+  pub comment: Option<char>,
+  pub quoting: bool,
+  pub flexible: bool,
+  /// Indexed records, header included
+  pub records: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", content = "reason", rename_all = "snake_case")]
+pub enum IndexStatus {
+  /// No `.idx` next to the file
+  Missing,
+  /// The index matches the file
+  Fresh,
+  /// `.idx` without metadata (older versions), it cannot be validated
+  Unversioned,
+  /// The file was modified since it was indexed, the index is rebuilt on use
+  Stale(String),
+  /// Data was only appended since it was indexed, the index is extended on use
+  Appended(String),
+  /// Built with another `skiprows` or dialect, ignored
+  Mismatch(String),
+}
+
+/// 64-bit FNV-1a, stable across platforms and Rust versions
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+  bytes.iter().fold(0xcbf29ce484222325, |hash, &b| {
+    (hash ^ b as u64).wrapping_mul(0x100000001b3)
+  })
+}
+
+/// Hash `len` bytes of `file` starting at `start`
+pub fn hash_range(file: &mut File, start: u64, len: u64) -> Result<String> {
+  let mut buf = Vec::with_capacity(len as usize);
+  file.seek(SeekFrom::Start(start))?;
+  file.take(len).read_to_end(&mut buf)?;
+  Ok(format!("{:016x}", fnv1a(&buf)))
+}
+
+/// Hashes of `SAMPLES` blocks spread evenly over the first `size` bytes of `file`,
+/// at most 256 KiB are read whatever the size
+pub fn sample_hashes(file: &mut File, size: u64) -> Result<Vec<String>> {
+  (0..SAMPLES)
+    .map(|i| {
+      let start = size / SAMPLES * i;
+      hash_range(file, start, SAMPLE_BLOCK.min(size - start))
+    })
+    .collect()
+}
+
+/// Size, mtime and head/tail hashes of the first `size` bytes of `file`
+pub fn fingerprint(file: &mut File, size: u64) -> Result<(u64, String, String)> {
+  let mtime = file
+    .metadata()?
+    .modified()?
+    .duration_since(UNIX_EPOCH)
+    .map_or(0, |d| d.as_millis() as u64);
+  let block = size.min(HASH_BLOCK);
+  let head_hash = hash_range(file, 0, block)?;
+  let tail_hash = hash_range(file, size - block, block)?;
+  Ok((mtime, head_hash, tail_hash))
+}
+
+/// `<file>.idx` -> `<file>.idx.meta`
+pub fn meta_path(idx_path: &Path) -> PathBuf {
+  let mut p = idx_path.to_path_buf();
+  p.as_mut_os_string().push(".meta");
+  p
+}
+
+impl IndexMeta {
+  pub fn read(idx_path: &Path) -> Result<Option<IndexMeta>> {
+    match fs::read(meta_path(idx_path)) {
+      Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+      Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+      Err(e) => Err(e.into()),
+    }
+  }
+
+  pub fn write(&self, idx_path: &Path) -> Result<()> {
+    fs::write(meta_path(idx_path), serde_json::to_vec_pretty(self)?)?;
+    Ok(())
+  }
+
+  /// Compare with the file at `csv_path` read with `skiprows` and `dialect`
+  pub fn status(&self, csv_path: &Path, skiprows: usize, dialect: &Dialect) -> Result<IndexStatus> {
+    if self.version != INDEX_VERSION {
+      return Ok(IndexStatus::Stale(format!(
+        "index version {} is not {INDEX_VERSION}",
+        self.version
+      )));
+    }
+    if self.skiprows != skiprows {
+      return Ok(IndexStatus::Mismatch(format!(
+        "built with skiprows {}, requested {skiprows}",
+        self.skiprows
+      )));
+    }
+    if self.delimiter != dialect.delimiter as char
+      || self.quote != dialect.quote as char
+      || self.escape != dialect.escape.map(char::from)
+      || self.comment != dialect.comment.map(char::from)
+    {
+      return Ok(IndexStatus::Mismatch(
+        "built with another delimiter, quote, escape or comment".to_string(),
+      ));
+    }
+
+    let mut file = File::open(csv_path)?;
+    let size = file.metadata()?.len();
+    if size < self.size {
+      return Ok(IndexStatus::Stale(format!(
+        "file shrank from {} to {size} bytes",
+        self.size
+      )));
+    }
+    // 只比较建索引时的那部分内容
+    let (mtime, head_hash, tail_hash) = fingerprint(&mut file, self.size)?;
+    if head_hash != self.head_hash || tail_hash != self.tail_hash {
+      return Ok(IndexStatus::Stale("file content changed".to_string()));
+    }
+    if size > self.size {
+      // 只比较首尾, 抽样的块在扩展索引前检查, 见 `verify_samples`
+      return Ok(IndexStatus::Appended(format!(
+        "file grew from {} to {size} bytes",
+        self.size
+      )));
+    }
+    if mtime != self.mtime {
+      return Ok(IndexStatus::Stale(
+        "file modification time changed".to_string(),
+      ));
+    }
+
+    Ok(IndexStatus::Fresh)
+  }
+
+  /// Whether the blocks sampled over the indexed range are unchanged.
+  ///
+  /// Checked once before the index is extended, `status` only compares the head and the tail.
+  pub fn verify_samples(&self, file: &mut File) -> Result<bool> {
+    Ok(sample_hashes(file, self.size)? == self.samples)
+  }
+}
@@ -0,0 +1,204 @@
+use std::{
+  fs::File,
+  io::{BufReader, Read, Seek, SeekFrom},
+  path::Path,
+};
+
+use anyhow::{Result, anyhow};
+use serde::Serialize;
+
+use crate::utils::RDR_BUFFER_SIZE;
+
+/// Separator between a zip archive and one of its members,
+/// e.g. `C:/data/extract.zip::2024/orders.csv`
+pub const ZIP_MEMBER_SEP: &str = "::";
+
+/// Data file extensions that are preferred when a zip member is not specified
+const DATA_EXTENSIONS: [&str; 4] = ["csv", "tsv", "txt", "dat"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+  None,
+  Gzip,
+  Zstd,
+  Bzip2,
+  Xz,
+  Zip,
+}
+
+impl Compression {
+  /// Guess the compression from the file extension
+  pub fn from_extension<P: AsRef<Path>>(path: P) -> Compression {
+    let ext = path
+      .as_ref()
+      .extension()
+      .and_then(|e| e.to_str())
+      .unwrap_or_default()
+      .to_ascii_lowercase();
+
+    match ext.as_str() {
+      "gz" | "gzip" => Compression::Gzip,
+      "zst" | "zstd" => Compression::Zstd,
+      "bz2" | "bzip2" => Compression::Bzip2,
+      "xz" => Compression::Xz,
+      "zip" => Compression::Zip,
+      _ => Compression::None,
+    }
+  }
+
+  /// Detect the compression by magic bytes, fall back to the file extension
+  pub fn detect<P: AsRef<Path>>(path: P) -> Result<Compression> {
+    let (archive, member) = split_member(path.as_ref());
+    if member.is_some() {
+      return Ok(Compression::Zip);
+    }
+
+    let mut magic = [0u8; 6];
+    let mut file = File::open(archive)?;
+    let mut n = 0;
+    while n < magic.len() {
+      let read = file.read(&mut magic[n..])?;
+      if read == 0 {
+        break;
+      }
+      n += read;
+    }
+
+    let compression = match &magic[..n] {
+      [0x1F, 0x8B, ..] => Compression::Gzip,
+      [0x28, 0xB5, 0x2F, 0xFD, ..] => Compression::Zstd,
+      [b'B', b'Z', b'h', ..] => Compression::Bzip2,
+      [0xFD, b'7', b'z', b'X', b'Z', 0x00] => Compression::Xz,
+      [b'P', b'K', 0x03, 0x04, ..] | [b'P', b'K', 0x05, 0x06, ..] => Compression::Zip,
+      _ => Compression::from_extension(archive),
+    };
+
+    Ok(compression)
+  }
+
+  /// The file extension used for this compression
+  pub fn extension(&self) -> Option<&'static str> {
+    match self {
+      Compression::None => None,
+      Compression::Gzip => Some("gz"),
+      Compression::Zstd => Some("zst"),
+      Compression::Bzip2 => Some("bz2"),
+      Compression::Xz => Some("xz"),
+      Compression::Zip => Some("zip"),
+    }
+  }
+
+  /// Not a plain file, i.e. cannot be memory-mapped or indexed
+  pub fn is_compressed(&self) -> bool {
+    *self != Compression::None
+  }
+}
+
+/// Split `archive.zip::member` into the archive path and the member name
+pub fn split_member(path: &Path) -> (&Path, Option<&str>) {
+  match path.to_str().and_then(|s| s.split_once(ZIP_MEMBER_SEP)) {
+    Some((archive, member)) if !member.is_empty() => (Path::new(archive), Some(member)),
+    Some((archive, _)) => (Path::new(archive), None),
+    None => (path, None),
+  }
+}
+
+/// Open a (possibly compressed) file and return a reader over the decompressed bytes
+pub fn open_reader<P: AsRef<Path>>(path: P) -> Result<Box<dyn Read + Send>> {
+  let (archive, member) = split_member(path.as_ref());
+  let compression = Compression::detect(path.as_ref())?;
+
+  let file = File::open(archive)?;
+  let reader: Box<dyn Read + Send> = match compression {
+    Compression::None => Box::new(file),
+    Compression::Gzip => Box::new(flate2::read::MultiGzDecoder::new(BufReader::with_capacity(
+      RDR_BUFFER_SIZE,
+      file,
+    ))),
+    Compression::Zstd => Box::new(zstd::stream::read::Decoder::new(file)?),
+    Compression::Bzip2 => Box::new(bzip2::read::MultiBzDecoder::new(BufReader::with_capacity(
+      RDR_BUFFER_SIZE,
+      file,
+    ))),
+    Compression::Xz => Box::new(xz2::read::XzDecoder::new_multi_decoder(
+      BufReader::with_capacity(RDR_BUFFER_SIZE, file),
+    )),
+    Compression::Zip => open_zip_member(file, member)?,
+  };
+
+  Ok(reader)
+}
+
+/// List the file members of a zip archive
+pub fn zip_members<P: AsRef<Path>>(path: P) -> Result<Vec<String>> {
+  let (archive, _) = split_member(path.as_ref());
+  let zip = zip::ZipArchive::new(File::open(archive)?)?;
+
+  Ok(
+    zip
+      .file_names()
+      .filter(|name| !name.ends_with('/'))
+      .map(|name| name.to_string())
+      .collect(),
+  )
+}
+
+/// Pick the default member of a zip archive: the first data file, else the first file
+fn default_member(names: &[String]) -> Option<&String> {
+  names
+    .iter()
+    .find(|name| {
+      Path::new(name)
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| DATA_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+    })
+    .or_else(|| names.first())
+}
+
+/// Stream a zip member straight from its data offset,
+/// so the returned reader does not borrow the archive
+fn open_zip_member(file: File, member: Option<&str>) -> Result<Box<dyn Read + Send>> {
+  let mut zip = zip::ZipArchive::new(file.try_clone()?)?;
+  let names: Vec<String> = zip
+    .file_names()
+    .filter(|name| !name.ends_with('/'))
+    .map(|name| name.to_string())
+    .collect();
+
+  let name = match member {
+    Some(m) => m.to_string(),
+    None => default_member(&names)
+      .cloned()
+      .ok_or_else(|| anyhow!("zip archive is empty"))?,
+  };
+  let index = zip
+    .index_for_name(&name)
+    .ok_or_else(|| anyhow!("member `{name}` not found in zip archive"))?;
+
+  let entry = zip.by_index_raw(index)?;
+  if entry.encrypted() {
+    return Err(anyhow!("encrypted zip member `{name}` is not supported"));
+  }
+  let method = entry.compression();
+  let data_start = entry.data_start();
+  let compressed_size = entry.compressed_size();
+  drop(entry);
+
+  let mut file = file;
+  file.seek(SeekFrom::Start(data_start))?;
+  let raw = BufReader::with_capacity(RDR_BUFFER_SIZE, file.take(compressed_size));
+
+  let reader: Box<dyn Read + Send> = match method {
+    zip::CompressionMethod::Stored => Box::new(raw),
+    zip::CompressionMethod::Deflated => Box::new(flate2::read::DeflateDecoder::new(raw)),
+    other => {
+      return Err(anyhow!(
+        "zip member `{name}` uses unsupported compression method {other}"
+      ));
+    }
+  };
+
+  Ok(reader)
+}
@@ -1,13 +1,64 @@
 use std::{
-  fs::File,
-  io::{BufWriter, Read},
-  path::PathBuf,
+  io::{Read, Write},
+  path::Path,
 };
 
 use anyhow::Result;
-use csv::{QuoteStyle, ReaderBuilder, WriterBuilder};
+use csv::{QuoteStyle, ReaderBuilder, Terminator, Trim, WriterBuilder};
 
-use crate::utils::WTR_BUFFER_SIZE;
+use crate::io::{
+  compress::Compression,
+  csv::sniff::Dialect,
+  writer::{OutputOptions, OutputWriter},
+};
+
+/// Line ending of the output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineEnding {
+  #[default]
+  Lf,
+  CrLf,
+}
+
+impl From<&str> for LineEnding {
+  fn from(s: &str) -> Self {
+    match s.to_ascii_lowercase().as_str() {
+      "crlf" | "\r\n" | "windows" => LineEnding::CrLf,
+      _ => LineEnding::Lf,
+    }
+  }
+}
+
+impl LineEnding {
+  fn terminator(&self) -> Terminator {
+    match self {
+      LineEnding::Lf => Terminator::Any(b'\n'),
+      LineEnding::CrLf => Terminator::CRLF,
+    }
+  }
+}
+
+/// `headers`, `fields` or `all`, anything else means no trimming
+pub fn parse_trim(s: &str) -> Trim {
+  match s.to_ascii_lowercase().as_str() {
+    "headers" => Trim::Headers,
+    "fields" => Trim::Fields,
+    "all" => Trim::All,
+    _ => Trim::None,
+  }
+}
+
+/// First byte of an optional single char argument, `\t` `\r` `\n` `\\` are unescaped
+pub fn parse_byte(s: Option<&str>) -> Option<u8> {
+  match s? {
+    "" => None,
+    "\\t" => Some(b'\t'),
+    "\\r" => Some(b'\r'),
+    "\\n" => Some(b'\n'),
+    "\\\\" => Some(b'\\'),
+    s => s.as_bytes().first().copied(),
+  }
+}
 
 #[derive(Debug, Clone)]
 pub struct CsvConfig {
@@ -18,11 +69,20 @@ pub struct CsvConfig {
 
   // Reader
   pub quoting: bool,
+  pub read_quote: u8,
+  pub escape: Option<u8>,
+  pub double_quote: bool,
+  pub comment: Option<u8>,
+  /// `None` treats `\r`, `\n` and `\r\n` as record terminators
+  pub terminator: Option<u8>,
+  pub trim: Trim,
 
   // Writer
   pub write_delim: u8,
   pub quote: u8,
   pub quote_style: QuoteStyle,
+  pub line_ending: LineEnding,
+  pub output: OutputOptions,
 }
 
 impl Default for CsvConfig {
@@ -32,9 +92,17 @@ impl Default for CsvConfig {
       flexible: false,
       has_headers: true,
       quoting: true,
+      read_quote: b'"',
+      escape: None,
+      double_quote: true,
+      comment: None,
+      terminator: None,
+      trim: Trim::None,
       write_delim: b',',
       quote: b'"',
       quote_style: QuoteStyle::Necessary,
+      line_ending: LineEnding::Lf,
+      output: OutputOptions::default(),
     }
   }
 }
@@ -46,19 +114,30 @@ impl CsvConfig {
       .has_headers(self.has_headers)
       .flexible(self.flexible)
       .quoting(self.quoting)
+      .quote(self.read_quote)
+      .escape(self.escape)
+      .double_quote(self.double_quote)
+      .comment(self.comment)
+      .terminator(self.terminator.map_or(Terminator::CRLF, Terminator::Any))
+      .trim(self.trim)
       .from_reader(reader)
   }
 
-  pub fn build_writer(&self, path: &PathBuf) -> Result<csv::Writer<BufWriter<File>>> {
-    let buf_wtr = BufWriter::with_capacity(WTR_BUFFER_SIZE, File::create(path)?);
+  /// 输出需要调用 `Finish::finish` 才会落盘, `-` 表示写到 stdout
+  pub fn build_writer<P: AsRef<Path>>(&self, path: P) -> Result<csv::Writer<OutputWriter>> {
+    let output = OutputWriter::create(path, self.output)?;
+    Ok(self.build_writer_from(output))
+  }
 
-    let wtr = WriterBuilder::new()
+  /// Same settings as `build_writer` on any writer, e.g. for a library that consumes it
+  pub fn build_writer_from<W: Write>(&self, wtr: W) -> csv::Writer<W> {
+    WriterBuilder::new()
       .delimiter(self.write_delim)
+      .flexible(self.flexible)
       .quote(self.quote)
       .quote_style(self.quote_style)
-      .from_writer(buf_wtr);
-
-    Ok(wtr)
+      .terminator(self.line_ending.terminator())
+      .from_writer(wtr)
   }
 }
 
@@ -123,6 +202,59 @@ impl CsvConfigBuilder {
     self
   }
 
+  /// 使用 sniff 出的 dialect 设置 reader 的引号/注释规则与 reader 和 writer 的分隔符 (has_headers 由调用方决定)
+  pub fn dialect(mut self, dialect: &Dialect) -> Self {
+    self.inner.delimiter = dialect.delimiter;
+    self.inner.write_delim = dialect.delimiter;
+    self.inner.read_quote = dialect.quote;
+    self.inner.escape = dialect.escape;
+    self.inner.double_quote = dialect.double_quote;
+    self.inner.comment = dialect.comment;
+    self
+  }
+
+  /// 设置 reader 的引号字符
+  pub fn read_quote(mut self, quote: u8) -> Self {
+    self.inner.read_quote = quote;
+    self
+  }
+
+  /// 设置 reader 引号内的转义字符, 如 `\"`
+  pub fn escape(mut self, escape: Option<u8>) -> Self {
+    self.inner.escape = escape;
+    self
+  }
+
+  /// 设置 reader 是否把 `""` 解析为 `"`
+  pub fn double_quote(mut self, double_quote: bool) -> Self {
+    self.inner.double_quote = double_quote;
+    self
+  }
+
+  /// 设置注释行前缀, 如 `#`
+  pub fn comment(mut self, comment: Option<u8>) -> Self {
+    self.inner.comment = comment;
+    self
+  }
+
+  /// 设置 reader 的行结束符, 如只用 `\r` 的文件
+  pub fn terminator(mut self, terminator: Option<u8>) -> Self {
+    self.inner.terminator = terminator;
+    self
+  }
+
+  /// 设置 reader 是否去除首尾空白
+  pub fn trim(mut self, trim: Trim) -> Self {
+    self.inner.trim = trim;
+    self
+  }
+
+  /// 设置 writer 的换行符 (LF/CRLF)
+  pub fn line_ending(mut self, line_ending: LineEnding) -> Self {
+    self.inner.line_ending = line_ending;
+    self
+  }
+
   /// 设置 writer 使用的引号字符
   pub fn quote(mut self, quote: u8) -> Self {
     self.inner.quote = quote;
@@ -135,6 +267,24 @@ impl CsvConfigBuilder {
     self
   }
 
+  /// 设置输出压缩格式 (gzip/zstd)
+  pub fn compression(mut self, compression: Compression) -> Self {
+    self.inner.output.compression = compression;
+    self
+  }
+
+  /// 设置是否先写临时文件, 成功后再重命名
+  pub fn atomic(mut self, atomic: bool) -> Self {
+    self.inner.output.atomic = atomic;
+    self
+  }
+
+  /// 设置输出方式 (压缩格式, 临时文件, stdout), 见 `OutputOptions::from_arg`
+  pub fn output(mut self, output: OutputOptions) -> Self {
+    self.inner.output = output;
+    self
+  }
+
   /// 构建最终的 CsvConfig
   pub fn build(self) -> CsvConfig {
     self.inner
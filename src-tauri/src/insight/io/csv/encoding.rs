@@ -1,11 +1,13 @@
-use std::fs::File;
 use std::io::Read;
+use std::path::Path;
 
 use anyhow::Result;
 use chardetng::{EncodingDetector, Iso2022JpDetection, Utf8Detection};
-use encoding_rs::{Encoding, GBK, UTF_8, UTF_16BE, UTF_16LE};
+use encoding_rs::{Encoding, GB18030, GBK, UTF_8, UTF_16BE, UTF_16LE};
 use serde::Serialize;
 
+use crate::io::{compress, csv::options::CsvOptions};
+
 #[derive(Debug, Clone, Serialize)]
 pub struct EncodingResult {
   pub encoding: String,
@@ -25,17 +27,22 @@ impl EncodingResult {
 
 pub fn detect_encoding(path: &str, check_bom: bool) -> Result<EncodingResult> {
   let mut sample = Vec::new();
-  let file = File::open(path)?;
+  let (archive, _) = compress::split_member(Path::new(path));
+  let file_size = std::fs::metadata(archive)?.len();
 
-  // 小文件读取全部,大文件读取 512KB
-  let file_size = file.metadata()?.len();
-  let read_size = if file_size < 1024 * 1024 {
+  // 小文件读取全部,大文件读取 512KB; 压缩文件按解压后的内容读取 512KB
+  let opts = CsvOptions::new(path);
+  let compressed = opts.is_compressed();
+  let read_size = if file_size < 1024 * 1024 && !compressed {
     file_size as u64
   } else {
     512 * 1024
   };
 
-  file.take(read_size).read_to_end(&mut sample)?;
+  opts
+    .open_reader()?
+    .take(read_size)
+    .read_to_end(&mut sample)?;
 
   if sample.is_empty() {
     return Ok(EncodingResult::new(UTF_8, 0.5, false));
@@ -108,13 +115,13 @@ pub fn detect_encoding(path: &str, check_bom: bool) -> Result<EncodingResult> {
 }
 
 /// GBK模式检测结果
-struct GbkPattern {
-  count: usize,
-  is_valid_utf8_sequence: bool, // 这些字节是否也是有效的 UTF-8 序列
+pub(crate) struct GbkPattern {
+  pub(crate) count: usize,
+  pub(crate) is_valid_utf8_sequence: bool, // 这些字节是否也是有效的 UTF-8 序列
 }
 
 /// 统计GBK特征字节对(2字节中文)
-fn count_gbk_pattern(sample: &[u8]) -> GbkPattern {
+pub(crate) fn count_gbk_pattern(sample: &[u8]) -> GbkPattern {
   let mut gbk_count = 0;
   let mut i = 0;
   let valid_utf8_seq = true;
@@ -156,7 +163,7 @@ fn count_gbk_pattern(sample: &[u8]) -> GbkPattern {
 }
 
 /// 统计 UTF-8 中文特征（3 字节）
-fn count_utf8_chinese_pattern(sample: &[u8]) -> usize {
+pub(crate) fn count_utf8_chinese_pattern(sample: &[u8]) -> usize {
   let mut utf8_count = 0;
   let mut i = 0;
 
@@ -256,7 +263,8 @@ fn calculate_confidence(
 pub fn encoding_from_name(name: &str) -> Option<&'static Encoding> {
   match name {
     "UTF-8" => Some(UTF_8),
-    "GBK" | "GB18030" => Some(GBK),
+    "GBK" => Some(GBK),
+    "GB18030" => Some(GB18030),
     "UTF-16LE" => Some(UTF_16LE),
     "UTF-16BE" => Some(UTF_16BE),
     _ => Encoding::for_label(name.as_bytes()),
@@ -1,4 +1,7 @@
 pub mod config;
 pub mod encoding;
 pub mod options;
+pub mod parallel;
 pub mod selection;
+pub mod sniff;
+pub mod spill;
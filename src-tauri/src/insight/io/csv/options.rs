@@ -6,12 +6,19 @@ use std::{
 };
 
 use anyhow::{Result, anyhow};
-use csv::ReaderBuilder;
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 
 use crate::{
-  index::Indexed,
-  io::excel::excel_reader::{self, FastExcelReader},
+  index::{IndexStatus, Indexed},
+  io::{
+    compress::{self, Compression},
+    csv::{
+      config::CsvConfigBuilder,
+      sniff::{self, Dialect},
+    },
+    excel::excel_reader::{self, FastExcelReader},
+    ipc::{self, IpcCsvReader},
+  },
   utils::RDR_BUFFER_SIZE,
 };
 
@@ -55,26 +62,34 @@ impl<P: AsRef<Path> + Send + Sync> CsvOptions<P> {
     )
   }
 
-  /// return parent path
+  /// return parent path (the directory of the archive for `archive.zip::member`)
   pub fn parent_path(&self) -> Result<&str> {
-    self
-      .path
-      .as_ref()
+    let (archive, _) = compress::split_member(self.path.as_ref());
+    archive
       .parent()
       .ok_or(anyhow!("get parent path failed"))?
       .to_str()
       .ok_or(anyhow!("parent path is null"))
   }
 
-  /// return file stem
+  /// return file stem, without the compression extension (`a.csv.gz` -> `a`)
   pub fn file_stem(&self) -> Result<&str> {
-    self
-      .path
-      .as_ref()
+    let (archive, member) = compress::split_member(self.path.as_ref());
+    let path = member.map(Path::new).unwrap_or(archive);
+    let stem = path
       .file_stem()
       .ok_or(anyhow!("get file stem failed"))?
       .to_str()
-      .ok_or(anyhow!("file stem is null"))
+      .ok_or(anyhow!("file stem is null"))?;
+
+    // `data.csv.gz` -> `data`
+    if member.is_none() && Compression::from_extension(archive).is_compressed() {
+      if let Some(inner) = Path::new(stem).file_stem().and_then(|s| s.to_str()) {
+        return Ok(inner);
+      }
+    }
+
+    Ok(stem)
   }
 
   /// return file name
@@ -98,43 +113,46 @@ impl<P: AsRef<Path> + Send + Sync> CsvOptions<P> {
     Ok(output_path)
   }
 
-  /// Check the delimiter of CSV
-  pub fn detect_separator(&self) -> Result<u8> {
-    let file = File::open(&self.path)?;
-    let reader = BufReader::new(file);
+  /// Detect the compression of the file (by magic bytes, then by extension)
+  pub fn compression(&self) -> Result<Compression> {
+    Compression::detect(&self.path)
+  }
 
-    let mut lines_iter = reader.lines();
+  /// Whether the input is an Arrow IPC file (Feather v2) rather than CSV text
+  pub fn is_arrow(&self) -> bool {
+    ipc::is_ipc(&self.path).unwrap_or(false)
+  }
 
-    // Skip the first `skiprows` lines
-    for _ in 0..self.skiprows {
-      if let Some(Ok(_)) = lines_iter.next() {
-        // Line skipped
-      } else {
-        // If there are not enough lines to skip, return Err
-        return Err(anyhow!("there are not enough lines to skip"));
-      }
+  /// Whether the input is compressed, a zip member or an Arrow file,
+  /// i.e. cannot be memory-mapped or indexed
+  pub fn is_compressed(&self) -> bool {
+    self
+      .compression()
+      .map(|c| c.is_compressed())
+      .unwrap_or(false)
+      || self.is_arrow()
+  }
+
+  /// Open the file, transparently decompressing gzip, zstd, bzip2, xz and zip members
+  /// and converting Arrow IPC files to CSV
+  pub fn open_reader(&self) -> Result<Box<dyn Read + Send>> {
+    if self.is_arrow() {
+      let file = BufReader::with_capacity(RDR_BUFFER_SIZE, File::open(self.path.as_ref())?);
+      return Ok(Box::new(IpcCsvReader::new(file)?));
     }
+    compress::open_reader(&self.path)
+  }
 
-    let seg_symbols = [b';', b',', b'\t', b'|', b'^'];
-    let mut separators_count: HashMap<u8, usize> = seg_symbols.iter().map(|&c| (c, 0)).collect();
-    let mut max_count = 0;
-    let mut separator = b',';
+  /// Check the delimiter of CSV
+  pub fn detect_separator(&self) -> Result<u8> {
+    Ok(self.dialect()?.delimiter)
+  }
 
-    // read next line after skipping
-    if let Some(next_line) = lines_iter.next() {
-      let line = next_line?;
-      for c in line.as_bytes() {
-        if let Some(count) = separators_count.get_mut(c) {
-          *count += 1;
-          if *count > max_count {
-            max_count = *count;
-            separator = *c;
-          }
-        }
-      }
-    }
+  /// Sniff the dialect (delimiter, quoting, header, preamble) of the rows after `skiprows`
+  pub fn dialect(&self) -> Result<Dialect> {
+    let (dialect, _) = self.skiprows_and_dialect()?;
 
-    Ok(separator)
+    Ok(dialect)
   }
 
   /// Count the rows of file
@@ -149,7 +167,7 @@ impl<P: AsRef<Path> + Send + Sync> CsvOptions<P> {
 
   /// Count the lines of file
   pub fn count_lines(&self) -> Result<usize> {
-    let reader = BufReader::new(File::open(&self.path)?);
+    let reader = BufReader::new(self.open_reader()?);
     let line_count = if self.decrease {
       reader.lines().count().saturating_sub(1)
     } else {
@@ -165,8 +183,15 @@ impl<P: AsRef<Path> + Send + Sync> CsvOptions<P> {
   /// - `Ok((delimiter, reader))`: 成功时返回delimiter和 reader
   /// - `Err(...)`: 文件不存在,I/O错误或跳行过程中文件提前结束
   pub fn skiprows_and_delimiter(&self) -> Result<(u8, BufReader<Box<dyn Read + Send>>)> {
-    let file = File::open(&self.path)?;
-    let mut reader = BufReader::with_capacity(RDR_BUFFER_SIZE, file);
+    let (dialect, reader) = self.skiprows_and_dialect()?;
+
+    Ok((dialect.delimiter, reader))
+  }
+
+  /// 跳过CSV文件开头的指定行数, 采样后续内容识别 dialect,
+  /// 返回 dialect 与从跳行位置开始读取的 reader
+  pub fn skiprows_and_dialect(&self) -> Result<(Dialect, BufReader<Box<dyn Read + Send>>)> {
+    let mut reader = BufReader::with_capacity(RDR_BUFFER_SIZE, self.open_reader()?);
 
     // 跳过前skiprows行
     for i in 0..self.skiprows {
@@ -182,42 +207,32 @@ impl<P: AsRef<Path> + Send + Sync> CsvOptions<P> {
       log::debug!("Skipped line {}: {:?}", i, line.trim_end());
     }
 
-    let mut header_line = String::new();
-    let n = reader.read_line(&mut header_line)?;
-    if n == 0 {
-      // 文件在跳行后无数据: 返回默认分隔符+空reader
+    let mut sample = Vec::with_capacity(sniff::SAMPLE_BYTES);
+    (&mut reader)
+      .take(sniff::SAMPLE_BYTES as u64)
+      .read_to_end(&mut sample)?;
+    if sample.is_empty() {
+      // 文件在跳行后无数据: 返回默认dialect+空reader
       let empty: Box<dyn Read + Send> = Box::new(io::empty());
-      return Ok((b',', BufReader::new(empty)));
+      return Ok((Dialect::default(), BufReader::new(empty)));
     }
 
-    let candidates = [b';', b',', b'\t', b'|', b'^'];
-    let mut counts: HashMap<u8, usize> = candidates.iter().map(|&c| (c, 0)).collect();
-    let mut max_count = 0;
-    let mut best_sep = b',';
-
-    for &byte in header_line.as_bytes() {
-      if let Some(cnt) = counts.get_mut(&byte) {
-        *cnt += 1;
-        if *cnt > max_count {
-          max_count = *cnt;
-          best_sep = byte;
-        }
-      }
-    }
+    let dialect = sniff::sniff(&sample, sample.len() < sniff::SAMPLE_BYTES);
+    log::debug!("Sniffed dialect: {dialect:?}");
 
-    // 把header_line放回reader前面
-    let chained: Box<dyn Read + Send> =
-      Box::new(Cursor::new(header_line.into_bytes()).chain(reader));
+    // 把采样内容放回reader前面
+    let chained: Box<dyn Read + Send> = Box::new(Cursor::new(sample).chain(reader));
     let final_reader = BufReader::new(chained);
 
-    Ok((best_sep, final_reader))
+    Ok((dialect, final_reader))
   }
 
   pub fn skiprows_reader(&self) -> Result<csv::Reader<BufReader<Box<dyn Read + Send>>>> {
-    let (delimiter, reader) = self.skiprows_and_delimiter()?;
-    let rdr = ReaderBuilder::new()
-      .delimiter(delimiter)
-      .from_reader(reader);
+    let (dialect, reader) = self.skiprows_and_dialect()?;
+    let rdr = CsvConfigBuilder::new()
+      .dialect(&dialect)
+      .build()
+      .build_reader(reader);
 
     Ok(rdr)
   }
@@ -288,11 +303,7 @@ impl<P: AsRef<Path> + Send + Sync> CsvOptions<P> {
           // use `csv` to get the headers
           let mut opts = CsvOptions::new(f);
           opts.set_skiprows(self.skiprows);
-          let (sep, reader) = opts.skiprows_and_delimiter().ok()?;
-          let mut rdr = ReaderBuilder::new()
-            .delimiter(sep)
-            // .has_headers(false)
-            .from_reader(reader);
+          let mut rdr = opts.skiprows_reader().ok()?;
 
           rdr
             .headers()
@@ -316,11 +327,7 @@ impl<P: AsRef<Path> + Send + Sync> CsvOptions<P> {
 
   /// Get csv headers {key: label, value: value}
   pub fn map_headers(&self) -> Result<Vec<HashMap<String, String>>> {
-    let (sep, reader) = self.skiprows_and_delimiter()?;
-    let mut rdr = ReaderBuilder::new()
-      .delimiter(sep)
-      // .has_headers(false)
-      .from_reader(reader);
+    let mut rdr = self.skiprows_reader()?;
 
     let headers: Vec<HashMap<String, String>> = rdr
       .headers()?
@@ -342,8 +349,7 @@ impl<P: AsRef<Path> + Send + Sync> CsvOptions<P> {
     let mut duplicate_headers: HashSet<String> = HashSet::new();
     let mut unique_headers: HashSet<String> = HashSet::new();
 
-    let (sep, reader) = self.skiprows_and_delimiter()?;
-    let mut rdr = ReaderBuilder::new().delimiter(sep).from_reader(reader);
+    let mut rdr = self.skiprows_reader()?;
 
     match rdr.headers() {
       Ok(headers) => {
@@ -368,9 +374,10 @@ impl<P: AsRef<Path> + Send + Sync> CsvOptions<P> {
   }
 
   pub fn from_reader<R: Read>(&self, rdr: R) -> csv::Reader<R> {
-    ReaderBuilder::new()
-      .delimiter(self.detect_separator().expect("no detect separator"))
-      .from_reader(rdr)
+    CsvConfigBuilder::new()
+      .dialect(&self.dialect().expect("no detect dialect"))
+      .build()
+      .build_reader(rdr)
   }
 
   pub fn idx_path(&self) -> PathBuf {
@@ -379,26 +386,52 @@ impl<P: AsRef<Path> + Send + Sync> CsvOptions<P> {
     p
   }
 
-  pub fn index_files(&self) -> Result<Option<(csv::Reader<File>, File)>> {
-    let csv_file_result = File::open(&self.path);
-    let idx_file_result = File::open(&self.idx_path());
-
-    match (csv_file_result, idx_file_result) {
-      (Ok(csv_file), Ok(idx_file)) => {
-        let data_modified = csv_file.metadata()?.modified()?;
-        let idx_modified = idx_file.metadata()?.modified()?;
-        if data_modified > idx_modified {
-          return Err(anyhow!(
-            "The CSV file was modified after the index file. Please re-create the index."
-          ));
-        }
-        let csv_rdr = self.from_reader(csv_file);
-        Ok(Some((csv_rdr, idx_file)))
+  /// Bring the `.idx` up to date, returns whether a usable index exists afterwards.
+  ///
+  /// A stale index (file modified since) is rebuilt, the index of a file that only grew
+  /// is extended, an index built with other `skiprows` or dialect, or without metadata, is ignored.
+  /// Call it once before the work, not from parallel tasks.
+  pub fn ensure_index(&self) -> Result<bool> {
+    // compressed input cannot be seeked, an index would be meaningless
+    if self.is_compressed() {
+      return Ok(false);
+    }
+
+    let health = crate::cmd::idx::index_health(self)?;
+    match (health.status, health.meta) {
+      (IndexStatus::Fresh, _) => {}
+      (IndexStatus::Stale(reason), Some(meta)) => {
+        log::info!("rebuild index of {}: {reason}", self.file_name()?);
+        crate::cmd::idx::build_index(self, meta.quoting, meta.flexible)?;
+      }
+      (IndexStatus::Appended(reason), Some(meta)) => {
+        log::info!("extend index of {}: {reason}", self.file_name()?);
+        crate::cmd::idx::append_index(self, &meta)?;
       }
-      (Err(_), Err(_)) => Ok(None),
-      (Ok(_), Err(_)) => Ok(None),
-      _ => Ok(None),
+      (status, _) => {
+        log::debug!("index of {} not used: {status:?}", self.file_name()?);
+        return Ok(false);
+      }
+    }
+    Ok(true)
+  }
+
+  /// Open the file and its `.idx` if the index is fresh, never writes the index.
+  ///
+  /// See [`CsvOptions::ensure_index`] to rebuild or extend it first.
+  pub fn index_files(&self) -> Result<Option<(csv::Reader<File>, File)>> {
+    if self.is_compressed() {
+      return Ok(None);
     }
+
+    let status = crate::cmd::idx::index_health(self)?.status;
+    if status != IndexStatus::Fresh {
+      log::debug!("index of {} not used: {status:?}", self.file_name()?);
+      return Ok(None);
+    }
+
+    let csv_rdr = self.from_reader(File::open(&self.path)?);
+    Ok(Some((csv_rdr, File::open(self.idx_path())?)))
   }
 
   pub fn indexed(&self) -> Result<Option<Indexed<File, File>>> {
@@ -0,0 +1,246 @@
+use std::{fs::File, ops::Range, path::Path};
+
+use anyhow::{Result, anyhow};
+use csv::ByteRecord;
+use memmap2::Mmap;
+use rayon::{
+  ThreadPoolBuilder,
+  iter::{IntoParallelIterator, ParallelIterator},
+};
+
+use crate::{
+  io::csv::{
+    config::{CsvConfig, CsvConfigBuilder},
+    options::CsvOptions,
+  },
+  utils,
+};
+
+/// Bytes handed to one worker at a time
+pub const CHUNK_SIZE: usize = 32 * 1024 * 1024;
+
+/// Reads a plain CSV file on several threads without an `.idx`.
+///
+/// The file is memory-mapped and cut into chunks of about `chunk_size` bytes,
+/// every cut is moved forward to the next record boundary, chunks are parsed on rayon
+/// and their results are handed back in file order.
+pub struct ParallelReader {
+  mmap: Mmap,
+  config: CsvConfig,
+  headers: ByteRecord,
+  /// 表头之后第一条记录的偏移
+  body_start: usize,
+  chunk_size: usize,
+}
+
+/// The records of one chunk
+pub struct Chunk<'a> {
+  /// Position of the chunk in the file, starting at 0
+  pub index: usize,
+  rdr: csv::Reader<&'a [u8]>,
+  /// 非 flexible 时每条记录应有的字段数 (表头字段数)
+  fields: Option<usize>,
+}
+
+impl Chunk<'_> {
+  /// Same as `csv::Reader::read_byte_record`, field counts are checked against the header
+  pub fn read_byte_record(&mut self, record: &mut ByteRecord) -> Result<bool> {
+    if !self.rdr.read_byte_record(record)? {
+      return Ok(false);
+    }
+    if let Some(fields) = self.fields
+      && record.len() != fields
+    {
+      let line = record.position().map_or(0, |p| p.line());
+      return Err(anyhow!(
+        "found record with {} fields, but the header has {fields} fields (chunk {}, line {line})",
+        record.len(),
+        self.index
+      ));
+    }
+    Ok(true)
+  }
+}
+
+impl ParallelReader {
+  /// Map the file behind `opts`, skip `skiprows` lines and read the header with `config`
+  pub fn from_options<P>(opts: &CsvOptions<P>, config: &CsvConfig) -> Result<Self>
+  where
+    P: AsRef<Path> + Send + Sync,
+  {
+    if opts.is_compressed() {
+      return Err(anyhow!("compressed file cannot be memory-mapped"));
+    }
+
+    let file = File::open(opts.file_path()?)?;
+    let mmap = unsafe { Mmap::map(&file)? };
+
+    // 跳过前skiprows行
+    let mut start = 0;
+    for i in 0..opts.get_skip_rows() {
+      match mmap[start..].iter().position(|&b| b == b'\n') {
+        Some(pos) => start += pos + 1,
+        None => {
+          return Err(anyhow!(
+            "File ended at line {i} while skipping {} rows",
+            opts.get_skip_rows()
+          ));
+        }
+      }
+    }
+
+    let config = CsvConfigBuilder::from_config(config.clone())
+      .has_headers(false)
+      .build();
+    let mut rdr = CsvConfigBuilder::from_config(config.clone())
+      .flexible(true)
+      .build()
+      .build_reader(&mmap[start..]);
+    let mut headers = ByteRecord::new();
+    rdr.read_byte_record(&mut headers)?;
+    let body_start = start + rdr.position().byte() as usize;
+    drop(rdr);
+
+    Ok(Self {
+      mmap,
+      config,
+      headers: utils::clean_header(&headers),
+      body_start,
+      chunk_size: CHUNK_SIZE,
+    })
+  }
+
+  pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+    self.chunk_size = chunk_size.max(1);
+    self
+  }
+
+  /// Header with the UTF-8 BOM removed
+  pub fn byte_headers(&self) -> &ByteRecord {
+    &self.headers
+  }
+
+  /// 引号内的换行不是记录边界, 按引号个数的奇偶判断切点是否位于引号内.
+  /// 转义字符与注释行会打乱奇偶, 这两种 dialect 只切一块
+  fn splittable(&self) -> bool {
+    self.config.escape.is_none()
+      && self.config.comment.is_none()
+      && self.config.terminator.is_none_or(|t| t == b'\n')
+  }
+
+  /// Byte ranges of the chunks, every range starts at a record boundary
+  pub fn chunks(&self) -> Vec<Range<usize>> {
+    let (start, end) = (self.body_start, self.mmap.len());
+    if start >= end {
+      return Vec::new();
+    }
+    let n = (end - start).div_ceil(self.chunk_size);
+    let mut bounds = vec![start];
+    if n > 1 && self.splittable() {
+      let data = &self.mmap[..];
+      let quote = self.config.read_quote;
+      let quoting = self.config.quoting;
+      let cuts: Vec<usize> = (1..n).map(|i| start + i * self.chunk_size).collect();
+
+      // 每段内的引号个数
+      let quotes: Vec<usize> = match quoting {
+        true => (0..cuts.len())
+          .into_par_iter()
+          .map(|i| {
+            let lo = if i == 0 { start } else { cuts[i - 1] };
+            data[lo..cuts[i]].iter().filter(|&&b| b == quote).count()
+          })
+          .collect(),
+        false => vec![0; cuts.len()],
+      };
+
+      let mut in_quotes = false;
+      for (&cut, &count) in cuts.iter().zip(&quotes) {
+        in_quotes ^= count % 2 == 1;
+        // 从切点向后找第一个不在引号内的换行
+        let mut quoted = in_quotes;
+        let next = data[cut..]
+          .iter()
+          .position(|&b| {
+            if quoting && b == quote {
+              quoted = !quoted;
+            }
+            b == b'\n' && !quoted
+          })
+          .map_or(end, |pos| cut + pos + 1);
+        if next > *bounds.last().unwrap_or(&start) && next < end {
+          bounds.push(next);
+        }
+      }
+    }
+    bounds.push(end);
+
+    bounds.windows(2).map(|w| w[0]..w[1]).collect()
+  }
+
+  /// Run `f` on every chunk with `jobs` threads, `sink` receives the results in file order.
+  ///
+  /// At most `jobs` chunks are in flight, so memory stays bounded whatever the file size.
+  pub fn for_each_ordered<T, F, S>(&self, jobs: usize, f: F, mut sink: S) -> Result<()>
+  where
+    T: Send,
+    F: Fn(&mut Chunk) -> Result<T> + Send + Sync,
+    S: FnMut(T) -> Result<()>,
+  {
+    let njobs = utils::njobs(Some(jobs));
+    let pool = ThreadPoolBuilder::new()
+      .num_threads(njobs)
+      .build()
+      .map_err(|e| anyhow!("Failed to create thread pool: {e}"))?;
+
+    let fields = (!self.config.flexible).then_some(self.headers.len());
+    let chunks = self.chunks();
+    for (wave, ranges) in chunks.chunks(njobs).enumerate() {
+      let results: Vec<Result<T>> = pool.install(|| {
+        (0..ranges.len())
+          .into_par_iter()
+          .map(|i| {
+            let mut chunk = Chunk {
+              index: wave * njobs + i,
+              rdr: self.config.build_reader(&self.mmap[ranges[i].clone()]),
+              fields,
+            };
+            f(&mut chunk)
+          })
+          .collect()
+      });
+      for result in results {
+        sink(result?)?;
+      }
+    }
+
+    Ok(())
+  }
+
+  /// Run `f` on every chunk with `jobs` threads, results are in file order
+  pub fn map_chunks<T, F>(&self, jobs: usize, f: F) -> Result<Vec<T>>
+  where
+    T: Send,
+    F: Fn(&mut Chunk) -> Result<T> + Send + Sync,
+  {
+    let mut results = Vec::new();
+    self.for_each_ordered(jobs, f, |t| {
+      results.push(t);
+      Ok(())
+    })?;
+    Ok(results)
+  }
+
+  /// Number of data rows
+  pub fn count_records(&self, jobs: usize) -> Result<u64> {
+    let counts = self.map_chunks(jobs, |chunk| {
+      let mut record = ByteRecord::new();
+      let mut count: u64 = 0;
+      while chunk.read_byte_record(&mut record)? {
+        count += 1;
+      }
+      Ok(count)
+    })?;
+    Ok(counts.into_iter().sum())
+  }
+}
@@ -0,0 +1,362 @@
+use serde::{Serialize, Serializer};
+
+/// Candidate delimiters, earlier ones win when the scores tie
+const DELIMITERS: [u8; 5] = [b',', b';', b'\t', b'|', b'^'];
+const QUOTES: [u8; 2] = [b'"', b'\''];
+
+/// Number of bytes read from the file for sniffing
+pub const SAMPLE_BYTES: usize = 64 * 1024;
+/// Max number of records looked at
+const SAMPLE_ROWS: usize = 200;
+/// Rows after the header that must agree on the field count
+const HEADER_LOOKAHEAD: usize = 3;
+
+/// The dialect of a CSV file, as guessed by [`sniff`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Dialect {
+  #[serde(serialize_with = "as_char")]
+  pub delimiter: u8,
+  #[serde(serialize_with = "as_char")]
+  pub quote: u8,
+  /// Escape character inside quoted fields, `None` when quotes are escaped by doubling
+  #[serde(serialize_with = "as_opt_char")]
+  pub escape: Option<u8>,
+  pub double_quote: bool,
+  /// Comment line prefix, set when `#` lines are found that do not fit the rows
+  #[serde(serialize_with = "as_opt_char")]
+  pub comment: Option<u8>,
+  /// Whether the first row after the preamble looks like a header
+  pub has_header: bool,
+  /// Title/comment lines before the header, to be used as `skiprows`
+  pub preamble_rows: usize,
+  /// Field count of a typical row
+  pub fields: usize,
+}
+
+impl Default for Dialect {
+  fn default() -> Self {
+    Self {
+      delimiter: b',',
+      quote: b'"',
+      escape: None,
+      double_quote: true,
+      comment: None,
+      has_header: true,
+      preamble_rows: 0,
+      fields: 0,
+    }
+  }
+}
+
+fn as_char<S: Serializer>(b: &u8, s: S) -> Result<S::Ok, S::Error> {
+  s.serialize_char(*b as char)
+}
+
+fn as_opt_char<S: Serializer>(b: &Option<u8>, s: S) -> Result<S::Ok, S::Error> {
+  match b {
+    Some(b) => s.serialize_some(&(*b as char)),
+    None => s.serialize_none(),
+  }
+}
+
+/// A parsed record and the (0-based) line it starts on
+struct Record {
+  line: usize,
+  fields: Vec<String>,
+}
+
+impl Record {
+  fn is_blank(&self) -> bool {
+    self.fields.len() == 1 && self.fields[0].trim().is_empty()
+  }
+
+  fn is_comment(&self) -> bool {
+    self.fields[0].starts_with('#')
+  }
+}
+
+fn is_boundary(b: Option<&u8>) -> bool {
+  match b {
+    None => true,
+    Some(b) => matches!(b, b'\r' | b'\n') || DELIMITERS.contains(b),
+  }
+}
+
+/// Quote char that most often opens or closes a field
+fn detect_quote(sample: &[u8]) -> u8 {
+  let mut best = (b'"', 0);
+  for quote in QUOTES {
+    let count = sample
+      .iter()
+      .enumerate()
+      .filter(|(i, b)| {
+        **b == quote
+          && (is_boundary(i.checked_sub(1).and_then(|p| sample.get(p)))
+            || is_boundary(sample.get(i + 1)))
+      })
+      .count();
+    if count > best.1 {
+      best = (quote, count);
+    }
+  }
+  best.0
+}
+
+/// `\"` vs `""` inside quoted fields
+fn detect_escape(sample: &[u8], quote: u8) -> Option<u8> {
+  let mut backslash = 0;
+  let mut doubled = 0;
+  for i in 1..sample.len().saturating_sub(1) {
+    if sample[i] != quote {
+      continue;
+    }
+    if sample[i - 1] == b'\\' && !is_boundary(sample.get(i + 1)) {
+      backslash += 1;
+    } else if sample[i + 1] == quote && !is_boundary(sample.get(i - 1)) && sample[i - 1] != quote {
+      doubled += 1;
+    }
+  }
+
+  (backslash > doubled).then_some(b'\\')
+}
+
+/// Quote-aware split of the sample into records,
+/// the last record is dropped when the sample was cut in the middle of the file
+fn parse(sample: &[u8], delimiter: u8, quote: u8, escape: Option<u8>, eof: bool) -> Vec<Record> {
+  let mut records = Vec::new();
+  let mut fields = Vec::new();
+  let mut field = Vec::new();
+  let mut quoted = false;
+  let mut in_quotes = false;
+  let mut line = 0;
+  let mut start_line = 0;
+  let mut i = 0;
+
+  let end_field = |field: &mut Vec<u8>, fields: &mut Vec<String>| {
+    fields.push(String::from_utf8_lossy(field).into_owned());
+    field.clear();
+  };
+
+  while i < sample.len() && records.len() < SAMPLE_ROWS {
+    let b = sample[i];
+    if in_quotes {
+      if b == b'\n' {
+        line += 1;
+      }
+      if Some(b) == escape && i + 1 < sample.len() {
+        field.push(sample[i + 1]);
+        i += 2;
+        continue;
+      }
+      if b == quote {
+        if sample.get(i + 1) == Some(&quote) {
+          field.push(quote);
+          i += 2;
+          continue;
+        }
+        in_quotes = false;
+      } else {
+        field.push(b);
+      }
+    } else if b == quote && field.is_empty() && !quoted {
+      in_quotes = true;
+      quoted = true;
+    } else if b == delimiter {
+      end_field(&mut field, &mut fields);
+      quoted = false;
+    } else if b == b'\n' {
+      if field.last() == Some(&b'\r') {
+        field.pop();
+      }
+      end_field(&mut field, &mut fields);
+      records.push(Record {
+        line: start_line,
+        fields: std::mem::take(&mut fields),
+      });
+      quoted = false;
+      line += 1;
+      start_line = line;
+    } else {
+      field.push(b);
+    }
+    i += 1;
+  }
+
+  if eof && i >= sample.len() && (!field.is_empty() || !fields.is_empty()) {
+    end_field(&mut field, &mut fields);
+    records.push(Record {
+      line: start_line,
+      fields,
+    });
+  }
+
+  records
+}
+
+/// Most common field count among non-blank records, ties go to the larger count
+fn mode(records: &[Record]) -> usize {
+  let mut counts: Vec<(usize, usize)> = Vec::new();
+  for record in records.iter().filter(|r| !r.is_blank()) {
+    let n = record.fields.len();
+    match counts.iter_mut().find(|(len, _)| *len == n) {
+      Some((_, c)) => *c += 1,
+      None => counts.push((n, 1)),
+    }
+  }
+  counts
+    .into_iter()
+    .max_by_key(|(len, c)| (*c, *len))
+    .map(|(len, _)| len)
+    .unwrap_or(0)
+}
+
+/// Index of the first record that starts a consistent run of `fields` wide records
+fn header_index(records: &[Record], fields: usize) -> usize {
+  let candidates = || {
+    records
+      .iter()
+      .enumerate()
+      .filter(|(_, r)| r.fields.len() == fields)
+  };
+  candidates()
+    .find(|(idx, _)| {
+      records[idx + 1..]
+        .iter()
+        .filter(|r| !r.is_blank() && !r.is_comment())
+        .take(HEADER_LOOKAHEAD)
+        .all(|r| r.fields.len() == fields)
+    })
+    .or_else(|| candidates().next())
+    .map(|(idx, _)| idx)
+    .unwrap_or(0)
+}
+
+/// Share of consistent rows after the preamble, field count, header index
+type Score = (f64, usize, usize);
+
+/// Score a delimiter, `None` when it does not split the rows at all
+fn score(records: &[Record]) -> Option<Score> {
+  let fields = mode(records);
+  if fields <= 1 {
+    return None;
+  }
+  let header = header_index(records, fields);
+  let body: Vec<&Record> = std::iter::once(&records[header])
+    .chain(
+      records[header + 1..]
+        .iter()
+        .filter(|r| !r.is_blank() && !r.is_comment()),
+    )
+    .collect();
+  let consistent = body.iter().filter(|r| r.fields.len() == fields).count();
+
+  Some((consistent as f64 / body.len() as f64, fields, header))
+}
+
+fn is_numeric(s: &str) -> bool {
+  let s = s.trim();
+  !s.is_empty() && s.replace([',', '%'], "").parse::<f64>().is_ok()
+}
+
+/// Compare the candidate header with the rows below it, column by column:
+/// numeric columns with a text header, or fixed width columns with a header of another width vote for a header
+fn detect_header(header: &Record, rows: &[Record]) -> bool {
+  let rows: Vec<&Record> = rows
+    .iter()
+    .filter(|r| r.fields.len() == header.fields.len())
+    .take(50)
+    .collect();
+  if rows.is_empty() {
+    return true;
+  }
+
+  let mut votes = 0i32;
+  for (col, name) in header.fields.iter().enumerate() {
+    let values: Vec<&str> = rows
+      .iter()
+      .map(|r| r.fields[col].as_str())
+      .filter(|v| !v.trim().is_empty())
+      .collect();
+    if values.is_empty() {
+      continue;
+    }
+
+    if values.iter().all(|v| is_numeric(v)) {
+      votes += if is_numeric(name) { -1 } else { 1 };
+      continue;
+    }
+
+    let width = values[0].chars().count();
+    if values.iter().all(|v| v.chars().count() == width) {
+      votes += if name.chars().count() == width { -1 } else { 1 };
+    }
+  }
+
+  votes >= 0
+}
+
+/// `#` when every `#` line (other than the header) has another field count than the rows
+fn detect_comment(records: &[Record], fields: usize, header: usize) -> Option<u8> {
+  let mut found = false;
+  for (_, record) in records
+    .iter()
+    .enumerate()
+    .filter(|(idx, r)| *idx != header && r.is_comment())
+  {
+    if record.fields.len() == fields {
+      return None;
+    }
+    found = true;
+  }
+
+  found.then_some(b'#')
+}
+
+/// Guess the dialect from the first bytes of a file,
+/// `eof` tells whether the sample holds the whole file
+pub fn sniff(sample: &[u8], eof: bool) -> Dialect {
+  let sample = sample.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(sample);
+  if sample.is_empty() {
+    return Dialect::default();
+  }
+
+  let quote = detect_quote(sample);
+  let escape = detect_escape(sample, quote);
+
+  let mut best: Option<(u8, Score, Vec<Record>)> = None;
+  for delimiter in DELIMITERS {
+    let records = parse(sample, delimiter, quote, escape, eof);
+    let Some(s) = score(&records) else {
+      continue;
+    };
+    let better = match &best {
+      None => true,
+      Some((_, b, _)) => s.0 > b.0 || (s.0 == b.0 && (s.1 > b.1 || (s.1 == b.1 && s.2 < b.2))),
+    };
+    if better {
+      best = Some((delimiter, s, records));
+    }
+  }
+
+  let Some((delimiter, (_, fields, header), records)) = best else {
+    // 单列文件
+    return Dialect {
+      quote,
+      escape,
+      double_quote: escape.is_none(),
+      fields: 1,
+      ..Dialect::default()
+    };
+  };
+
+  Dialect {
+    delimiter,
+    quote,
+    escape,
+    double_quote: escape.is_none(),
+    comment: detect_comment(&records, fields, header),
+    has_header: detect_header(&records[header], &records[header + 1..]),
+    preamble_rows: records[header].line,
+    fields,
+  }
+}
@@ -0,0 +1,50 @@
+use std::{
+  fs::File,
+  io::{Seek, SeekFrom},
+};
+
+use anyhow::Result;
+use csv::{ByteRecord, ReaderBuilder, WriterBuilder};
+
+/// Approximate heap size of a parsed record
+pub fn record_size(record: &ByteRecord) -> usize {
+  record.as_slice().len() + record.len() * std::mem::size_of::<usize>() + 64
+}
+
+/// A writer on an anonymous temp file, removed when the file is dropped.
+///
+/// Only the csv buffer sits in front of the file, so many runs can be open at once.
+pub fn run_writer() -> Result<csv::Writer<File>> {
+  Ok(
+    WriterBuilder::new()
+      .flexible(true)
+      .from_writer(tempfile::tempfile()?),
+  )
+}
+
+/// Flush a writer from `run_writer` and rewind its file for `read_run`
+pub fn finish_run(wtr: csv::Writer<File>) -> Result<File> {
+  let mut file = wtr.into_inner().map_err(|e| e.into_error())?;
+  file.seek(SeekFrom::Start(0))?;
+  Ok(file)
+}
+
+/// Write `records` to a temp file
+pub fn write_run<'a, I>(records: I) -> Result<File>
+where
+  I: IntoIterator<Item = &'a ByteRecord>,
+{
+  let mut wtr = run_writer()?;
+  for record in records {
+    wtr.write_byte_record(record)?;
+  }
+  finish_run(wtr)
+}
+
+/// Read back a run, in the order it was written
+pub fn read_run(file: File) -> csv::Reader<File> {
+  ReaderBuilder::new()
+    .has_headers(false)
+    .flexible(true)
+    .from_reader(file)
+}
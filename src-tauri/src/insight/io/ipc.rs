@@ -0,0 +1,106 @@
+use std::{
+  fs::File,
+  io::{self, Read, Seek},
+  path::Path,
+};
+
+use anyhow::Result;
+use arrow::{csv::WriterBuilder, ipc::reader::FileReader, record_batch::RecordBatch};
+
+use crate::io::compress;
+
+/// Magic bytes at the start (and end) of an Arrow IPC file, a.k.a. Feather v2
+pub const MAGIC: &[u8; 6] = b"ARROW1";
+
+/// Extensions of Arrow IPC files, used when the magic bytes are missing
+const EXTENSIONS: [&str; 3] = ["arrow", "feather", "ipc"];
+
+/// Whether the file is an Arrow IPC file, by magic bytes then by extension.
+/// Zip members are never Arrow, the IPC reader needs a seekable file
+pub fn is_ipc<P: AsRef<Path>>(path: P) -> Result<bool> {
+  let (archive, member) = compress::split_member(path.as_ref());
+  if member.is_some() {
+    return Ok(false);
+  }
+
+  let mut magic = [0u8; 6];
+  let mut file = File::open(archive)?;
+  let mut n = 0;
+  while n < magic.len() {
+    let read = file.read(&mut magic[n..])?;
+    if read == 0 {
+      break;
+    }
+    n += read;
+  }
+  if &magic[..n] == MAGIC {
+    return Ok(true);
+  }
+
+  let ext = archive
+    .extension()
+    .and_then(|e| e.to_str())
+    .unwrap_or_default()
+    .to_ascii_lowercase();
+  Ok(EXTENSIONS.contains(&ext.as_str()))
+}
+
+/// Streams the record batches of an Arrow IPC file as CSV text,
+/// so every command that reads CSV can read Arrow directly
+pub struct IpcCsvReader<R: Read + Seek> {
+  batches: FileReader<R>,
+  buf: Vec<u8>,
+  pos: usize,
+  header_written: bool,
+  done: bool,
+}
+
+impl<R: Read + Seek> IpcCsvReader<R> {
+  pub fn new(reader: R) -> Result<Self> {
+    Ok(Self {
+      batches: FileReader::try_new(reader, None)?,
+      buf: Vec::new(),
+      pos: 0,
+      header_written: false,
+      done: false,
+    })
+  }
+
+  /// 一次转换一个 batch, 第一个 batch 带表头
+  fn fill(&mut self, batch: &RecordBatch) -> io::Result<()> {
+    self.buf.clear();
+    self.pos = 0;
+    let mut wtr = WriterBuilder::new()
+      .with_header(!self.header_written)
+      .build(&mut self.buf);
+    wtr.write(batch).map_err(io::Error::other)?;
+    self.header_written = true;
+    Ok(())
+  }
+}
+
+impl<R: Read + Seek> Read for IpcCsvReader<R> {
+  fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+    while self.pos >= self.buf.len() {
+      if self.done {
+        return Ok(0);
+      }
+      match self.batches.next() {
+        Some(batch) => self.fill(&batch.map_err(io::Error::other)?)?,
+        None => {
+          self.done = true;
+          // 没有任何 batch 时仍输出表头
+          if !self.header_written {
+            let empty = RecordBatch::new_empty(self.batches.schema());
+            self.fill(&empty)?;
+          }
+        }
+      }
+    }
+
+    let n = out.len().min(self.buf.len() - self.pos);
+    out[..n].copy_from_slice(&self.buf[self.pos..self.pos + n]);
+    self.pos += n;
+    Ok(n)
+  }
+}
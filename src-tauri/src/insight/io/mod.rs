@@ -1,2 +1,5 @@
+pub mod compress;
 pub mod csv;
 pub mod excel;
+pub mod ipc;
+pub mod writer;
@@ -0,0 +1,241 @@
+use std::{
+  ffi::OsString,
+  fs::File,
+  io::{self, BufWriter, Stdout, Write},
+  path::{Path, PathBuf},
+};
+
+use anyhow::{Result, anyhow};
+use tempfile::TempPath;
+
+use crate::{io::compress::Compression, utils::WTR_BUFFER_SIZE};
+
+/// Output path that means "write to stdout"
+pub const STDOUT: &str = "-";
+
+/// How an output file is written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputOptions {
+  /// gzip or zstd, the extension is appended to the output path when missing
+  pub compression: Compression,
+  /// Write into a temp file next to the output, renamed on `finish`
+  pub atomic: bool,
+  /// Write to stdout whatever the output path
+  pub stdout: bool,
+}
+
+impl Default for OutputOptions {
+  fn default() -> Self {
+    Self {
+      compression: Compression::None,
+      atomic: true,
+      stdout: false,
+    }
+  }
+}
+
+impl OutputOptions {
+  /// The `output_compression` argument of a command: `gzip`, `zstd`, `none` or empty,
+  /// `-` writes to stdout
+  pub fn from_arg(arg: Option<&str>) -> Result<Self> {
+    let compression = match arg.unwrap_or_default().to_ascii_lowercase().as_str() {
+      "" | "none" => Compression::None,
+      "gzip" | "gz" => Compression::Gzip,
+      "zstd" | "zst" => Compression::Zstd,
+      STDOUT => {
+        return Ok(Self {
+          stdout: true,
+          ..Self::default()
+        });
+      }
+      other => {
+        return Err(anyhow!(
+          "unknown output compression `{other}`, use gzip, zstd or - for stdout"
+        ));
+      }
+    };
+    Ok(Self {
+      compression,
+      ..Self::default()
+    })
+  }
+
+  /// Error for commands that write several files, stdout would interleave them
+  pub fn require_files(&self) -> Result<()> {
+    match self.stdout {
+      true => Err(anyhow!(
+        "this command writes several files, it cannot write to stdout"
+      )),
+      false => Ok(()),
+    }
+  }
+}
+
+enum Sink {
+  Plain(BufWriter<File>),
+  Gzip(flate2::write::GzEncoder<BufWriter<File>>),
+  Zstd(zstd::stream::write::Encoder<'static, BufWriter<File>>),
+  Stdout(BufWriter<Stdout>),
+}
+
+/// The single writer used by every command output.
+///
+/// Nothing is visible at the output path until `finish` succeeds when writing atomically,
+/// dropping the writer (e.g. on error) removes the temp file.
+pub struct OutputWriter {
+  sink: Sink,
+  path: Option<PathBuf>,
+  temp: Option<TempPath>,
+}
+
+impl OutputWriter {
+  /// Create a writer for `path`, `-` writes to stdout
+  pub fn create<P: AsRef<Path>>(path: P, opts: OutputOptions) -> Result<Self> {
+    if opts.stdout || path.as_ref() == Path::new(STDOUT) {
+      return Ok(Self::stdout());
+    }
+
+    let path = output_file(path, opts.compression);
+    let (file, temp) = if opts.atomic {
+      let (file, temp) = temp_file(&path)?.into_parts();
+      (file, Some(temp))
+    } else {
+      (File::create(&path)?, None)
+    };
+
+    let buf_wtr = BufWriter::with_capacity(WTR_BUFFER_SIZE, file);
+    let sink = match opts.compression {
+      Compression::None => Sink::Plain(buf_wtr),
+      Compression::Gzip => Sink::Gzip(flate2::write::GzEncoder::new(
+        buf_wtr,
+        flate2::Compression::default(),
+      )),
+      Compression::Zstd => Sink::Zstd(zstd::stream::write::Encoder::new(buf_wtr, 0)?),
+      other => {
+        return Err(anyhow!(
+          "{other:?} output is not supported, use gzip or zstd"
+        ));
+      }
+    };
+
+    Ok(Self {
+      sink,
+      path: Some(path),
+      temp,
+    })
+  }
+
+  pub fn stdout() -> Self {
+    Self {
+      sink: Sink::Stdout(BufWriter::with_capacity(WTR_BUFFER_SIZE, io::stdout())),
+      path: None,
+      temp: None,
+    }
+  }
+
+  /// The final output path, `None` for stdout
+  pub fn path(&self) -> Option<&Path> {
+    self.path.as_deref()
+  }
+
+  /// Finish the compressed stream, flush, and move the temp file into place
+  pub fn finish(self) -> Result<()> {
+    let file = match self.sink {
+      Sink::Plain(wtr) => wtr.into_inner().map_err(|e| e.into_error())?,
+      Sink::Gzip(wtr) => wtr.finish()?.into_inner().map_err(|e| e.into_error())?,
+      Sink::Zstd(wtr) => wtr.finish()?.into_inner().map_err(|e| e.into_error())?,
+      Sink::Stdout(mut wtr) => return Ok(wtr.flush()?),
+    };
+
+    if let (Some(temp), Some(path)) = (self.temp, self.path) {
+      file.sync_all()?;
+      drop(file);
+      temp.persist(&path)?;
+    }
+
+    Ok(())
+  }
+}
+
+/// Hidden temp file next to `path` (`.<name>.XXXX.tmp`), for writers that need a real file
+pub fn temp_file<P: AsRef<Path>>(path: P) -> Result<tempfile::NamedTempFile> {
+  let path = path.as_ref();
+  let parent = match path.parent() {
+    Some(p) if !p.as_os_str().is_empty() => p,
+    _ => Path::new("."),
+  };
+  let file_name = path
+    .file_name()
+    .ok_or_else(|| anyhow!("invalid output path: {}", path.display()))?;
+  let mut prefix = OsString::from(".");
+  prefix.push(file_name);
+  prefix.push(".");
+  Ok(
+    tempfile::Builder::new()
+      .prefix(&prefix)
+      .suffix(".tmp")
+      .tempfile_in(parent)?,
+  )
+}
+
+impl Write for OutputWriter {
+  fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+    match &mut self.sink {
+      Sink::Plain(w) => w.write(buf),
+      Sink::Gzip(w) => w.write(buf),
+      Sink::Zstd(w) => w.write(buf),
+      Sink::Stdout(w) => w.write(buf),
+    }
+  }
+
+  fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+    match &mut self.sink {
+      Sink::Plain(w) => w.write_all(buf),
+      Sink::Gzip(w) => w.write_all(buf),
+      Sink::Zstd(w) => w.write_all(buf),
+      Sink::Stdout(w) => w.write_all(buf),
+    }
+  }
+
+  fn flush(&mut self) -> io::Result<()> {
+    match &mut self.sink {
+      Sink::Plain(w) => w.flush(),
+      Sink::Gzip(w) => w.flush(),
+      Sink::Zstd(w) => w.flush(),
+      Sink::Stdout(w) => w.flush(),
+    }
+  }
+}
+
+/// Finish an output, see [`OutputWriter::finish`]
+pub trait Finish {
+  fn finish(self) -> Result<()>;
+}
+
+impl Finish for csv::Writer<OutputWriter> {
+  fn finish(self) -> Result<()> {
+    self
+      .into_inner()
+      .map_err(|e| anyhow!("{}", e.error()))?
+      .finish()
+  }
+}
+
+/// Append the compression extension to `path` if it is not there yet
+pub fn output_file<P: AsRef<Path>>(path: P, compression: Compression) -> PathBuf {
+  let path = path.as_ref();
+  match compression.extension() {
+    Some(ext) if Compression::from_extension(path) != compression => {
+      let mut name = path.as_os_str().to_owned();
+      name.push(".");
+      name.push(ext);
+      PathBuf::from(name)
+    }
+    _ => path.to_path_buf(),
+  }
+}
+
+/// Shorthand for an atomic, uncompressed output file
+pub fn create<P: AsRef<Path>>(path: P) -> Result<OutputWriter> {
+  OutputWriter::create(path, OutputOptions::default())
+}
@@ -3,5 +3,6 @@ pub mod io;
 
 pub mod command;
 pub mod index;
+pub mod similarity;
 pub mod tojson;
 pub mod utils;
@@ -0,0 +1,245 @@
+use std::borrow::Cow;
+
+use anyhow::{Result, anyhow};
+
+use crate::io::csv::selection::Selection;
+
+/// How values are normalised before they are compared
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Normalize {
+  pub trim: bool,
+  pub case_fold: bool,
+  pub collapse_whitespace: bool,
+  /// 全角字母, 数字, 符号与空格转为半角
+  pub half_width: bool,
+  pub remove_punctuation: bool,
+}
+
+impl Normalize {
+  /// Parse `trim|lower|space|width|punct`, `all` turns every step on
+  pub fn parse(steps: &str) -> Result<Self> {
+    let mut normalize = Normalize::default();
+    for step in steps.split('|').map(str::trim).filter(|s| !s.is_empty()) {
+      match step {
+        "trim" => normalize.trim = true,
+        "lower" => normalize.case_fold = true,
+        "space" => normalize.collapse_whitespace = true,
+        "width" => normalize.half_width = true,
+        "punct" => normalize.remove_punctuation = true,
+        "all" => {
+          normalize = Normalize {
+            trim: true,
+            case_fold: true,
+            collapse_whitespace: true,
+            half_width: true,
+            remove_punctuation: true,
+          }
+        }
+        _ => return Err(anyhow!("unknown normalization `{step}`")),
+      }
+    }
+    Ok(normalize)
+  }
+
+  pub fn is_identity(&self) -> bool {
+    *self == Normalize::default()
+  }
+
+  pub fn apply<'a>(&self, value: &'a [u8]) -> Cow<'a, [u8]> {
+    if self.is_identity() {
+      return Cow::Borrowed(value);
+    }
+
+    // 非 UTF-8 (如 GBK) 不能按字符处理, 转换有损会让不同的值变成同一个键
+    let Ok(s) = std::str::from_utf8(value) else {
+      return Cow::Owned(self.apply_bytes(value));
+    };
+
+    let mut s: String = s
+      .chars()
+      .map(|c| match self.half_width {
+        true => to_half_width(c),
+        false => c,
+      })
+      .filter(|&c| !(self.remove_punctuation && is_punctuation(c)))
+      .collect();
+    if self.case_fold {
+      s = s.to_lowercase();
+    }
+    if self.collapse_whitespace {
+      s = s.split_whitespace().collect::<Vec<_>>().join(" ");
+    }
+    if self.trim {
+      s = s.trim().to_string();
+    }
+    Cow::Owned(s.into_bytes())
+  }
+
+  /// Byte level normalisation of values that are not UTF-8, only ASCII is changed.
+  ///
+  /// A byte >= 0x80 and the byte after it are kept as they are, the trail byte of a
+  /// double-byte encoding may look like an ASCII letter or punctuation.
+  fn apply_bytes(&self, value: &[u8]) -> Vec<u8> {
+    let mut units: Vec<&[u8]> = Vec::with_capacity(value.len());
+    let mut i = 0;
+    while i < value.len() {
+      let len = match value[i] >= 0x80 {
+        true => 2.min(value.len() - i),
+        false => 1,
+      };
+      units.push(&value[i..i + len]);
+      i += len;
+    }
+    let is_space = |u: &[u8]| u.len() == 1 && u[0].is_ascii_whitespace();
+
+    let mut out = Vec::with_capacity(value.len());
+    let mut pending_space = false;
+    for unit in units {
+      if self.remove_punctuation && unit.len() == 1 && unit[0].is_ascii_punctuation() {
+        continue;
+      }
+      if self.collapse_whitespace && is_space(unit) {
+        pending_space = true;
+        continue;
+      }
+      // 连续空白合并为一个空格, 首尾的空白去掉
+      if pending_space && !out.is_empty() {
+        out.push(b' ');
+      }
+      pending_space = false;
+      match self.case_fold && unit.len() == 1 {
+        true => out.push(unit[0].to_ascii_lowercase()),
+        false => out.extend_from_slice(unit),
+      }
+    }
+    if self.trim {
+      let start = out.iter().take_while(|b| b.is_ascii_whitespace()).count();
+      let end = out.len()
+        - out[start..]
+          .iter()
+          .rev()
+          .take_while(|b| b.is_ascii_whitespace())
+          .count();
+      out = out[start..end].to_vec();
+    }
+    out
+  }
+
+  /// Same as `Selection::get_composite_key` on normalised values
+  pub fn composite_key(&self, selection: &Selection, row: &csv::ByteRecord) -> Vec<u8> {
+    if self.is_identity() {
+      return selection.get_composite_key(row);
+    }
+    let mut key = Vec::new();
+    for (i, &idx) in selection.get_indices().iter().enumerate() {
+      if i > 0 {
+        key.push(b'\0');
+      }
+      if let Some(field) = row.get(idx) {
+        key.extend_from_slice(&self.apply(field));
+      }
+    }
+    key
+  }
+}
+
+fn to_half_width(c: char) -> char {
+  match c {
+    '\u{3000}' => ' ',
+    '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+    _ => c,
+  }
+}
+
+/// ASCII 标点及常见的中文标点
+fn is_punctuation(c: char) -> bool {
+  c.is_ascii_punctuation()
+    || matches!(c,
+      '\u{00B7}' | '\u{2010}'..='\u{2027}' | '\u{3001}'..='\u{303F}' | '\u{FF01}'..='\u{FF0F}'
+      | '\u{FF1A}'..='\u{FF20}' | '\u{FF3B}'..='\u{FF40}' | '\u{FF5B}'..='\u{FF65}')
+}
+
+/// Jaro similarity in `[0, 1]`
+pub fn jaro(a: &[char], b: &[char]) -> f64 {
+  if a.is_empty() && b.is_empty() {
+    return 1.0;
+  }
+  if a.is_empty() || b.is_empty() {
+    return 0.0;
+  }
+
+  let window = (a.len().max(b.len()) / 2).saturating_sub(1);
+  let mut b_matched = vec![false; b.len()];
+  let mut a_matches = Vec::new();
+  for (i, ca) in a.iter().enumerate() {
+    let lo = i.saturating_sub(window);
+    let hi = (i + window + 1).min(b.len());
+    for j in lo..hi {
+      if !b_matched[j] && b[j] == *ca {
+        b_matched[j] = true;
+        a_matches.push(*ca);
+        break;
+      }
+    }
+  }
+  if a_matches.is_empty() {
+    return 0.0;
+  }
+
+  let b_matches = b
+    .iter()
+    .zip(&b_matched)
+    .filter(|(_, m)| **m)
+    .map(|(c, _)| c);
+  let half_transpositions = a_matches
+    .iter()
+    .zip(b_matches)
+    .filter(|(x, y)| x != y)
+    .count();
+  let m = a_matches.len() as f64;
+  let t = half_transpositions as f64 / 2.0;
+  (m / a.len() as f64 + m / b.len() as f64 + (m - t) / m) / 3.0
+}
+
+/// Jaro-Winkler similarity, a common prefix of up to 4 chars raises the Jaro score
+pub fn jaro_winkler(a: &[char], b: &[char]) -> f64 {
+  let j = jaro(a, b);
+  let prefix = a.iter().zip(b).take(4).take_while(|(x, y)| x == y).count();
+  j + prefix as f64 * 0.1 * (1.0 - j)
+}
+
+/// Levenshtein distance, the number of inserted, deleted or substituted chars
+pub fn levenshtein(a: &[char], b: &[char]) -> usize {
+  let mut prev: Vec<usize> = (0..=b.len()).collect();
+  let mut cur = vec![0; b.len() + 1];
+  for (i, ca) in a.iter().enumerate() {
+    cur[0] = i + 1;
+    for (j, cb) in b.iter().enumerate() {
+      let cost = usize::from(ca != cb);
+      cur[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(cur[j] + 1);
+    }
+    std::mem::swap(&mut prev, &mut cur);
+  }
+  prev[b.len()]
+}
+
+/// Damerau-Levenshtein distance (optimal string alignment), swapping two adjacent chars is one edit
+pub fn damerau_levenshtein(a: &[char], b: &[char]) -> usize {
+  let mut prev2 = vec![0; b.len() + 1];
+  let mut prev: Vec<usize> = (0..=b.len()).collect();
+  let mut cur = vec![0; b.len() + 1];
+  for i in 1..=a.len() {
+    cur[0] = i;
+    for j in 1..=b.len() {
+      let cost = usize::from(a[i - 1] != b[j - 1]);
+      let mut d = (prev[j - 1] + cost).min(prev[j] + 1).min(cur[j - 1] + 1);
+      if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+        d = d.min(prev2[j - 2] + 1);
+      }
+      cur[j] = d;
+    }
+    std::mem::swap(&mut prev2, &mut prev);
+    std::mem::swap(&mut prev, &mut cur);
+  }
+  prev[b.len()]
+}
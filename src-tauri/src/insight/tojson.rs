@@ -1,16 +1,18 @@
 use anyhow::Result;
-use csv::ReaderBuilder;
 use serde_json::{Value, json};
 
-use crate::io::csv::options::CsvOptions;
+use crate::io::csv::{config::CsvConfigBuilder, options::CsvOptions};
 
 pub fn csv_to_json(path: String, skiprows: usize) -> Result<String> {
   let n_rows = 20 + skiprows;
   let mut opts = CsvOptions::new(&path);
   opts.set_skiprows(skiprows);
-  let (sep, reader) = opts.skiprows_and_delimiter()?;
+  let (dialect, reader) = opts.skiprows_and_dialect()?;
 
-  let mut rdr = ReaderBuilder::new().delimiter(sep).from_reader(reader);
+  let mut rdr = CsvConfigBuilder::new()
+    .dialect(&dialect)
+    .build()
+    .build_reader(reader);
 
   let headers = rdr.headers()?.clone();
 
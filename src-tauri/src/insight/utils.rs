@@ -1,19 +1,69 @@
+use std::collections::HashSet;
 use std::fs::File;
 use std::future::Future;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 
 use anyhow::{Result, anyhow};
 use csv::ByteRecord;
 use memmap2::Mmap;
+use sysinfo::System;
 use tauri::{AppHandle, Emitter};
 
-use crate::io::csv::options::CsvOptions;
+use crate::cmd::search::generic::MatchSink;
+use crate::io::{compress::Compression, csv::options::CsvOptions};
 
 pub const EXCEL_MAX_ROW: usize = 104_8575; // no headers
 pub const RDR_BUFFER_SIZE: usize = 1 * 1024 * 1024;
 pub const WTR_BUFFER_SIZE: usize = 1 * 1024 * 1024;
 pub const DEFAULT_BATCH_SIZE: usize = 50_000;
+/// 解析后的记录约占文件大小的倍数
+pub const MEMORY_OVERHEAD: u64 = 3;
+/// 压缩文件解压后约为文件大小的倍数
+const COMPRESSION_RATIO: u64 = 5;
+/// Bounds of the records held in memory by a disk-backed command
+const MIN_SPILL_BUDGET: u64 = 16 * 1024 * 1024;
+const MAX_SPILL_BUDGET: u64 = 512 * 1024 * 1024;
+
+/// File size, estimated after decompression for compressed files
+pub fn uncompressed_size<P: AsRef<Path> + Send + Sync>(opts: &CsvOptions<P>) -> Result<u64> {
+  let size = std::fs::metadata(opts.file_path()?)?.len();
+  Ok(match opts.is_compressed() {
+    true => size.saturating_mul(COMPRESSION_RATIO),
+    false => size,
+  })
+}
+
+/// Where a command that needs every row at once keeps them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryPlan {
+  InMemory,
+  /// Spill to temp files, holding at most this many bytes of records at a time
+  OnDisk(usize),
+}
+
+/// Compare the estimated size of the parsed file against the available memory.
+///
+/// Loading is allowed while it takes less than half of what is available,
+/// otherwise records go through temp files so a big file cannot OOM the app.
+pub fn memory_plan<P: AsRef<Path> + Send + Sync>(opts: &CsvOptions<P>) -> Result<MemoryPlan> {
+  let size = uncompressed_size(opts)?;
+
+  let mut sys = System::new();
+  sys.refresh_memory();
+  let mut available = sys.available_memory();
+  if let Some(limits) = sys.cgroup_limits() {
+    available = available.min(limits.free_memory);
+  }
+
+  // 无法获取内存信息时按原来的方式处理
+  if available == 0 || size.saturating_mul(MEMORY_OVERHEAD) < available / 2 {
+    return Ok(MemoryPlan::InMemory);
+  }
+  let budget = (available / 8).clamp(MIN_SPILL_BUDGET, MAX_SPILL_BUDGET);
+  log::info!("{size} bytes to load with {available} bytes available, spilling to disk");
+  Ok(MemoryPlan::OnDisk(budget as usize))
+}
 
 #[inline]
 pub fn num_cpus() -> usize {
@@ -135,6 +185,128 @@ pub fn clean_header(header: &ByteRecord) -> ByteRecord {
   cleaned
 }
 
+/// Extensions of the files a folder expands to, also when gzip, zstd, bzip2, xz or zip compressed
+const FOLDER_DATA_EXTENSIONS: [&str; 5] = ["csv", "tsv", "arrow", "feather", "ipc"];
+
+/// Index sidecars (`.idx`, `.idx.meta`, `.cidx`) are never searched
+fn is_index_sidecar(name: &str) -> bool {
+  let name = name.to_ascii_lowercase();
+  name.ends_with(".idx") || name.ends_with(".idx.meta") || name.ends_with(".cidx")
+}
+
+/// Names of the search outputs of `files`, see `MatchSink::output_names`
+fn search_outputs(files: &[PathBuf]) -> HashSet<String> {
+  files
+    .iter()
+    .filter_map(|f| {
+      CsvOptions::new(f)
+        .file_stem()
+        .ok()
+        .map(MatchSink::output_names)
+    })
+    .flatten()
+    .collect()
+}
+
+/// A data file a folder expands to, see `FOLDER_DATA_EXTENSIONS`
+fn is_folder_data_file(path: &Path) -> bool {
+  let path = match Compression::from_extension(path) {
+    Compression::Gzip | Compression::Zstd | Compression::Bzip2 | Compression::Xz => {
+      Path::new(path.file_stem().unwrap_or_default())
+    }
+    _ => path,
+  };
+  path.extension().and_then(|e| e.to_str()).is_some_and(|e| {
+    let e = e.to_ascii_lowercase();
+    e == "zip" || FOLDER_DATA_EXTENSIONS.contains(&e.as_str())
+  })
+}
+
+/// Expand `|` separated files, folders and file name patterns (`*`, `?`) into files.
+///
+/// A folder stands for the data files directly in it (csv, tsv, arrow, maybe compressed),
+/// folders and patterns skip index sidecars and the search outputs of the other files
+/// in the folder (e.g. `a_search.csv` next to `a.csv`). Matches are sorted by path.
+pub fn expand_paths(spec: &str) -> Result<Vec<PathBuf>> {
+  let mut paths = Vec::new();
+  for part in spec.split('|').map(str::trim).filter(|s| !s.is_empty()) {
+    let path = Path::new(part);
+    let pattern = path
+      .file_name()
+      .map(|n| n.to_string_lossy().to_string())
+      .unwrap_or_default();
+
+    let (dir, pattern) = if pattern.contains(['*', '?']) {
+      let dir = match path.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p,
+        _ => Path::new("."),
+      };
+      (dir, Some(pattern.chars().collect::<Vec<_>>()))
+    } else if path.is_dir() {
+      (path, None)
+    } else {
+      paths.push(path.to_path_buf());
+      continue;
+    };
+
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+      let entry_path = entry?.path();
+      if entry_path.is_file() {
+        files.push(entry_path);
+      }
+    }
+    let outputs = search_outputs(&files);
+
+    let mut matched = Vec::new();
+    for entry_path in files {
+      let name = entry_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+      if is_index_sidecar(&name) || outputs.contains(&name) {
+        continue;
+      }
+      let name: Vec<char> = name.chars().collect();
+      let matches = match &pattern {
+        Some(p) => wildcard_match(p, &name),
+        None => is_folder_data_file(&entry_path),
+      };
+      if matches {
+        matched.push(entry_path);
+      }
+    }
+    if matched.is_empty() {
+      return Err(anyhow!("No file matches {part}"));
+    }
+    matched.sort();
+    paths.extend(matched);
+  }
+  Ok(paths)
+}
+
+/// `*` matches any run of chars, `?` matches exactly one
+fn wildcard_match(pattern: &[char], name: &[char]) -> bool {
+  let (mut p, mut n) = (0, 0);
+  let mut star: Option<(usize, usize)> = None;
+  while n < name.len() {
+    if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+      p += 1;
+      n += 1;
+    } else if p < pattern.len() && pattern[p] == '*' {
+      star = Some((p, n));
+      p += 1;
+    } else if let Some((sp, sn)) = star {
+      p = sp + 1;
+      n = sn + 1;
+      star = Some((sp, sn + 1));
+    } else {
+      return false;
+    }
+  }
+  pattern[p..].iter().all(|&c| c == '*')
+}
+
 pub trait EventEmitter {
   fn emit_total_rows(&self, count: usize) -> impl Future<Output = Result<()>> + Send;
   fn emit_update_rows(&self, count: usize) -> impl Future<Output = Result<()>> + Send;
@@ -132,18 +132,29 @@ fn main() {
       command::to_json,
       command::preview_n_lines,
       command::detect_separator,
+      command::sniff_dialect,
+      command::zip_members,
       apply::apply,
       cat::cat_csv,
       cat::cat_excel,
       convert::excel_to_csv::map_excel_sheets,
       convert::perform::csv2csv,
       convert::perform::encoding2utf8,
+      convert::perform::transcode,
+      convert::perform::fix_mojibake,
       convert::perform::detect_file_encoding,
       convert::perform::csv2xlsx,
       convert::perform::dbf2csv,
+      convert::perform::csv2dbf,
+      convert::perform::csv2html,
+      convert::perform::csv2md,
       convert::perform::excel2csv,
       convert::perform::json2csv,
       convert::perform::jsonl2csv,
+      convert::perform::xml2csv,
+      convert::perform::csv2xml,
+      convert::perform::csv2arrow,
+      convert::perform::arrow2csv,
       count::count,
       datefmt::datefmt,
       dedup::dedup,
@@ -152,12 +163,16 @@ fn main() {
       extsort::extsort,
       fill::fill,
       idx::csv_idx,
+      idx::idx_health,
       insert::insert,
       join::join,
       pinyin::pinyin,
       rename::rename,
       replace::replace,
       reverse::reverse,
+      search::column_index::column_idx,
+      search::column_index::column_idx_health,
+      search::column_index::drop_column_idx,
       search::perform::search,
       search::perform::search_chain,
       select::select,
@@ -0,0 +1,63 @@
+use insight::{
+  cmd::{
+    convert::{arrow_to_csv::arrow_to_csv, csv_to_arrow::csv_to_arrow},
+    count::count_rows,
+  },
+  io::csv::options::CsvOptions,
+};
+
+#[tokio::test]
+async fn test_csv_arrow_roundtrip() -> anyhow::Result<()> {
+  let temp_dir = tempfile::TempDir::new()?;
+  let file_path = temp_dir.path().join("input.csv");
+  std::fs::write(
+    &file_path,
+    "id;name;age;vip\n007;Tom;18;true\n008;\"Jer;ry\";;false\n009;Spike;6.5;true\n",
+  )?;
+
+  let report = csv_to_arrow(&file_path, 0, 2, true, 0).await?;
+  assert_eq!(report.rows, 3);
+  assert_eq!(report.batches, 2);
+  let types: Vec<&str> = report
+    .columns
+    .iter()
+    .map(|c| c.data_type.as_str())
+    .collect();
+  assert_eq!(types, vec!["Int64", "Utf8", "Float64", "Boolean"]);
+
+  // 不推断类型时保留前导零
+  let report = csv_to_arrow(&file_path, 0, 1024, false, 0).await?;
+  assert_eq!(report.batches, 1);
+  assert!(report.columns.iter().all(|c| c.data_type == "Utf8"));
+
+  let arrow_path = temp_dir.path().join("input_arrow.arrow");
+  let rows = arrow_to_csv(arrow_path.to_str().unwrap(), ",".to_string()).await?;
+  assert_eq!(rows, 3);
+  assert_eq!(
+    std::fs::read_to_string(temp_dir.path().join("input_arrow_arrow.csv"))?,
+    "id,name,age,vip\n007,Tom,18,true\n008,Jer;ry,,false\n009,Spike,6.5,true\n"
+  );
+
+  Ok(temp_dir.close()?)
+}
+
+#[tokio::test]
+async fn test_read_arrow_as_csv() -> anyhow::Result<()> {
+  let temp_dir = tempfile::TempDir::new()?;
+  let file_path = temp_dir.path().join("input.csv");
+  std::fs::write(&file_path, "name,age\nTom,18\nJerry,19\nSpike,6\n")?;
+  csv_to_arrow(&file_path, 0, 2, true, 0).await?;
+
+  let arrow_path = temp_dir.path().join("input_arrow.arrow");
+  let opts = CsvOptions::new(&arrow_path);
+  assert!(opts.is_compressed());
+  assert_eq!(opts.file_stem()?, "input_arrow");
+  assert_eq!(opts.from_headers()?, vec!["name", "age"]);
+  assert_eq!(count_rows(&arrow_path, 0).await?, 3);
+
+  let mut rdr = opts.skiprows_reader()?;
+  let rows: Vec<csv::StringRecord> = rdr.records().collect::<Result<_, _>>()?;
+  assert_eq!(rows[1], vec!["Jerry", "19"]);
+
+  Ok(temp_dir.close()?)
+}
@@ -29,6 +29,7 @@ async fn test_cat_csv() -> anyhow::Result<()> {
     output_path.to_string_lossy().to_string(),
     true,
     2,
+    insight::io::writer::OutputOptions::default(),
   )
   .await?;
   let binding = std::fs::read_to_string(&output_path)?;
@@ -0,0 +1,181 @@
+use insight::{
+  cmd::search::{
+    column_index::{self, ColumnIndex, IndexQuery},
+    generic::SearchOutput,
+  },
+  index::IndexStatus,
+  io::csv::options::CsvOptions,
+  utils::MemoryPlan,
+};
+
+fn create_input(dir: &std::path::Path) -> anyhow::Result<std::path::PathBuf> {
+  let path = dir.join("input.csv");
+  std::fs::write(
+    &path,
+    "\n\
+     id,name,city\n\
+     1,Tom,\"Paris, FR\"\n\
+     2,Jerry,Rome\n\
+     3,Tom,Rome\n\
+     4,,Oslo\n\
+     5,Jenny,Paris\n\
+     6,Sandy,Rome\n",
+  )?;
+  Ok(path)
+}
+
+#[tokio::test]
+async fn test_column_index_query() -> anyhow::Result<()> {
+  let temp_dir = tempfile::TempDir::new()?;
+  let path = create_input(temp_dir.path())?;
+  let mut opts = CsvOptions::new(&path);
+  opts.set_skiprows(1);
+
+  let metas = column_index::create_column_index(
+    &path,
+    &["name", "city"],
+    true,
+    false,
+    1,
+    Some(MemoryPlan::InMemory),
+  )
+  .await?;
+  assert_eq!(metas[0].values, 5);
+  assert_eq!(metas[1].values, 4);
+  assert!(opts.idx_path().exists());
+
+  // 分块写入临时文件后归并, 结果与内存中排序相同
+  let index_file = |column: &str| -> anyhow::Result<Vec<u8>> {
+    let dir = column_index::column_index_dir(&opts);
+    for entry in std::fs::read_dir(dir)? {
+      let path = entry?.path();
+      if path.extension().is_some_and(|e| e == "cidx")
+        && path
+          .file_name()
+          .unwrap()
+          .to_string_lossy()
+          .starts_with(column)
+      {
+        return Ok(std::fs::read(path)?);
+      }
+    }
+    Err(anyhow::anyhow!("no index of {column}"))
+  };
+  let in_memory = index_file("name")?;
+  column_index::build_column_index(&opts, "name", true, false, Some(MemoryPlan::OnDisk(100)))?;
+  assert_eq!(index_file("name")?, in_memory);
+  // 重建写入临时文件后替换, 目录中不留下临时文件
+  assert_eq!(
+    std::fs::read_dir(column_index::column_index_dir(&opts))?.count(),
+    4
+  );
+
+  let mut index = ColumnIndex::open(&opts, "name", true, false)?.unwrap();
+  let equal = |values: &[&str]| IndexQuery::Equal(values.iter().map(|s| s.to_string()).collect());
+  assert_eq!(
+    index.query(&equal(&["Tom", "Sandy", "Nobody"]))?,
+    vec![0, 2, 5]
+  );
+  assert_eq!(index.query(&equal(&[""]))?, vec![3]);
+  assert_eq!(
+    index.query(&IndexQuery::StartsWith(vec![
+      "Je".to_string(),
+      "T".to_string()
+    ]))?,
+    vec![0, 1, 2, 4]
+  );
+  let mut index = ColumnIndex::open(&opts, "city", true, false)?.unwrap();
+  assert_eq!(index.query(&equal(&["Paris, FR", "Oslo"]))?, vec![0, 3]);
+  assert!(ColumnIndex::open(&opts, "id", true, false)?.is_none());
+  // quoting 与建索引时不同, 回退为扫描
+  assert!(ColumnIndex::open(&opts, "name", false, false)?.is_none());
+  assert!(ColumnIndex::open(&opts, "name", true, true)?.is_none());
+  assert_eq!(IndexQuery::new("contains", vec!["T".to_string()]), None);
+  assert_eq!(
+    IndexQuery::new("equal_multi", vec!["Tom".to_string()]),
+    Some(equal(&["Tom"]))
+  );
+  assert_eq!(
+    IndexQuery::new("starts_with_multi", vec!["T".to_string()]),
+    Some(IndexQuery::StartsWith(vec!["T".to_string()]))
+  );
+
+  Ok(temp_dir.close()?)
+}
+
+#[tokio::test]
+async fn test_column_index_search() -> anyhow::Result<()> {
+  let temp_dir = tempfile::TempDir::new()?;
+  let path = create_input(temp_dir.path())?;
+  let mut opts = CsvOptions::new(&path);
+  opts.set_skiprows(1);
+  column_index::create_column_index(&path, &["city"], true, false, 1, None).await?;
+
+  let query = IndexQuery::new("starts_with", vec!["Paris".to_string()]).unwrap();
+  let matches =
+    column_index::search_indexed(&opts, "city", &query, true, false, SearchOutput::Rows)?;
+  assert_eq!(matches.as_deref(), Some("2"));
+  assert_eq!(
+    std::fs::read_to_string(temp_dir.path().join("input_search.csv"))?,
+    "id,name,city\n1,Tom,\"Paris, FR\"\n5,Jenny,Paris\n"
+  );
+
+  let query = IndexQuery::new("equal", vec!["Rome".to_string()]).unwrap();
+  let matches =
+    column_index::search_indexed(&opts, "city", &query, true, false, SearchOutput::RowNumbers)?;
+  assert_eq!(matches.as_deref(), Some("3"));
+  assert_eq!(
+    std::fs::read_to_string(temp_dir.path().join("input_search_rows.csv"))?,
+    "row\n2\n3\n6\n"
+  );
+  let matches =
+    column_index::search_indexed(&opts, "city", &query, true, false, SearchOutput::Count)?;
+  assert_eq!(matches.as_deref(), Some("3"));
+  // 未匹配的行需要扫描文件
+  assert!(
+    column_index::search_indexed(&opts, "city", &query, true, false, SearchOutput::Split)?
+      .is_none()
+  );
+  assert!(
+    column_index::search_indexed(&opts, "name", &query, true, false, SearchOutput::Rows)?.is_none()
+  );
+
+  Ok(temp_dir.close()?)
+}
+
+#[tokio::test]
+async fn test_column_index_health_and_drop() -> anyhow::Result<()> {
+  let temp_dir = tempfile::TempDir::new()?;
+  let path = create_input(temp_dir.path())?;
+  let mut opts = CsvOptions::new(&path);
+  opts.set_skiprows(1);
+  column_index::create_column_index(&path, &["name", "city"], true, false, 1, None).await?;
+
+  let health = column_index::column_index_health(&opts)?;
+  assert_eq!(health.len(), 2);
+  assert!(health.iter().all(|h| h.status == IndexStatus::Fresh));
+  let columns: Vec<&str> = health
+    .iter()
+    .map(|h| h.meta.as_ref().unwrap().column.as_str())
+    .collect();
+  assert_eq!(columns, vec!["city", "name"]);
+
+  // 追加数据后索引不再使用, 回退为扫描
+  std::fs::write(&path, std::fs::read_to_string(&path)? + "7,Tom,Oslo\n")?;
+  let health = column_index::column_index_health(&opts)?;
+  assert!(matches!(health[0].status, IndexStatus::Appended(_)));
+  assert!(ColumnIndex::open(&opts, "name", true, false)?.is_none());
+  let query = IndexQuery::new("equal", vec!["Tom".to_string()]).unwrap();
+  assert!(
+    column_index::search_indexed(&opts, "name", &query, true, false, SearchOutput::Rows)?.is_none()
+  );
+
+  assert_eq!(column_index::drop_column_index(&opts, Some("city"))?, 1);
+  assert_eq!(column_index::drop_column_index(&opts, Some("city"))?, 0);
+  assert_eq!(column_index::column_index_health(&opts)?.len(), 1);
+  assert_eq!(column_index::drop_column_index(&opts, None)?, 1);
+  assert!(!column_index::column_index_dir(&opts).exists());
+  assert!(column_index::column_index_health(&opts)?.is_empty());
+
+  Ok(temp_dir.close()?)
+}
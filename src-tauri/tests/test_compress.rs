@@ -0,0 +1,126 @@
+use std::io::Write;
+
+use insight::io::{compress::Compression, csv::options::CsvOptions};
+
+const DATA: &str = "\nname,age,gender\nPatrick,4,male\n汤姆,18,男\n杰瑞,19,male\nSandy,24,female\n";
+
+fn write_zip(path: &std::path::Path, members: &[(&str, &str)]) -> anyhow::Result<()> {
+  let mut zip = zip::ZipWriter::new(std::fs::File::create(path)?);
+  let options =
+    zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+  for (name, content) in members {
+    zip.start_file(*name, options)?;
+    zip.write_all(content.as_bytes())?;
+  }
+  zip.finish()?;
+
+  Ok(())
+}
+
+#[tokio::test]
+async fn test_count_gzip_and_zstd() -> anyhow::Result<()> {
+  let temp_dir = tempfile::TempDir::new()?;
+
+  let gz_path = temp_dir.path().join("input.csv.gz");
+  let mut gz = flate2::write::GzEncoder::new(
+    std::fs::File::create(&gz_path)?,
+    flate2::Compression::default(),
+  );
+  gz.write_all(DATA.as_bytes())?;
+  gz.finish()?;
+
+  let zst_path = temp_dir.path().join("input.csv.zst");
+  std::fs::write(&zst_path, zstd::encode_all(DATA.as_bytes(), 3)?)?;
+
+  for path in [&gz_path, &zst_path] {
+    let row_count = insight::cmd::count::count_rows(path.to_str().unwrap(), 1).await?;
+    assert_eq!(row_count, 4);
+  }
+
+  let mut opts = CsvOptions::new(&gz_path);
+  opts.set_skiprows(1);
+  assert_eq!(opts.compression()?, Compression::Gzip);
+  assert_eq!(opts.from_headers()?, vec!["name", "age", "gender"]);
+  assert_eq!(
+    opts.output_path(Some("search"), None)?,
+    temp_dir.path().join("input_search.csv")
+  );
+
+  Ok(temp_dir.close()?)
+}
+
+#[tokio::test]
+async fn test_count_bzip2_and_xz() -> anyhow::Result<()> {
+  let temp_dir = tempfile::TempDir::new()?;
+
+  let bz2_path = temp_dir.path().join("input.csv.bz2");
+  let mut bz = bzip2::write::BzEncoder::new(
+    std::fs::File::create(&bz2_path)?,
+    bzip2::Compression::default(),
+  );
+  bz.write_all(DATA.as_bytes())?;
+  bz.finish()?;
+
+  let xz_path = temp_dir.path().join("input.csv.xz");
+  let mut xz = xz2::write::XzEncoder::new(std::fs::File::create(&xz_path)?, 6);
+  xz.write_all(DATA.as_bytes())?;
+  xz.finish()?;
+
+  for path in [&bz2_path, &xz_path] {
+    let row_count = insight::cmd::count::count_rows(path.to_str().unwrap(), 1).await?;
+    assert_eq!(row_count, 4);
+  }
+
+  Ok(temp_dir.close()?)
+}
+
+#[tokio::test]
+async fn test_zip_members() -> anyhow::Result<()> {
+  let temp_dir = tempfile::TempDir::new()?;
+
+  let zip_path = temp_dir.path().join("archive.zip");
+  write_zip(
+    &zip_path,
+    &[
+      ("readme.md", "# not a csv"),
+      ("2024/orders.csv", "id,amount\n1,10\n2,20\n3,30\n"),
+      ("people.csv", DATA),
+    ],
+  )?;
+
+  let members = insight::io::compress::zip_members(&zip_path)?;
+  assert_eq!(members, vec!["readme.md", "2024/orders.csv", "people.csv"]);
+
+  // 未指定成员时, 取第一个数据文件
+  let row_count = insight::cmd::count::count_rows(zip_path.to_str().unwrap(), 0).await?;
+  assert_eq!(row_count, 3);
+
+  let member_path = format!("{}::people.csv", zip_path.display());
+  let row_count = insight::cmd::count::count_rows(&member_path, 1).await?;
+  assert_eq!(row_count, 4);
+
+  let opts = CsvOptions::new(&member_path);
+  assert_eq!(
+    opts.output_path(Some("search"), None)?,
+    temp_dir.path().join("people_search.csv")
+  );
+
+  let missing = format!("{}::missing.csv", zip_path.display());
+  assert!(insight::cmd::count::count_rows(&missing, 0).await.is_err());
+
+  Ok(temp_dir.close()?)
+}
+
+#[tokio::test]
+async fn test_create_index_rejects_compressed() -> anyhow::Result<()> {
+  let temp_dir = tempfile::TempDir::new()?;
+
+  let zst_path = temp_dir.path().join("input.csv.zst");
+  std::fs::write(&zst_path, zstd::encode_all(DATA.as_bytes(), 3)?)?;
+
+  let result = insight::cmd::idx::create_index(&zst_path, true, false, 1).await;
+  assert!(result.is_err());
+  assert!(!temp_dir.path().join("input.csv.zst.idx").exists());
+
+  Ok(temp_dir.close()?)
+}
@@ -0,0 +1,95 @@
+use insight::io::{
+  csv::config::{CsvConfigBuilder, LineEnding, parse_trim},
+  writer::Finish,
+};
+
+#[test]
+fn test_config_reader_dialect() -> anyhow::Result<()> {
+  // 注释行、反斜杠转义和多余空白
+  let data = "# exported 2024\nname,note\n# skip me\nTom,\"say \\\"hi\\\"\"\n  Jerry ,  a b  \n";
+  let config = CsvConfigBuilder::new()
+    .comment(Some(b'#'))
+    .escape(Some(b'\\'))
+    .double_quote(false)
+    .trim(parse_trim("all"))
+    .build();
+  let mut rdr = config.build_reader(data.as_bytes());
+  assert_eq!(rdr.headers()?, vec!["name", "note"]);
+  let rows: Vec<csv::StringRecord> = rdr.records().collect::<Result<_, _>>()?;
+  assert_eq!(rows.len(), 2);
+  assert_eq!(rows[0], vec!["Tom", "say \"hi\""]);
+  assert_eq!(rows[1], vec!["Jerry", "a b"]);
+
+  // CR-only 行尾
+  let config = CsvConfigBuilder::new().terminator(Some(b'\r')).build();
+  let mut rdr = config.build_reader("name,note\rTom,a\nb\rJerry,c\r".as_bytes());
+  assert_eq!(rdr.headers()?, vec!["name", "note"]);
+  let rows: Vec<csv::StringRecord> = rdr.records().collect::<Result<_, _>>()?;
+  assert_eq!(rows[0], vec!["Tom", "a\nb"]);
+  assert_eq!(rows[1], vec!["Jerry", "c"]);
+
+  Ok(())
+}
+
+#[test]
+fn test_config_writer_line_ending() -> anyhow::Result<()> {
+  let temp_dir = tempfile::TempDir::new()?;
+  let lf_path = temp_dir.path().join("lf.csv");
+  let crlf_path = temp_dir.path().join("crlf.csv");
+
+  let mut wtr = CsvConfigBuilder::new().build().build_writer(&lf_path)?;
+  wtr.write_record(["a", "b"])?;
+  wtr.write_record(["1", "2"])?;
+  wtr.finish()?;
+
+  let mut wtr = CsvConfigBuilder::new()
+    .line_ending(LineEnding::from("crlf"))
+    .write_delimiter(b';')
+    .build()
+    .build_writer(&crlf_path)?;
+  wtr.write_record(["a", "b"])?;
+  wtr.write_record(["1", "2"])?;
+  wtr.finish()?;
+
+  assert_eq!(std::fs::read_to_string(lf_path)?, "a,b\n1,2\n");
+  assert_eq!(std::fs::read_to_string(crlf_path)?, "a;b\r\n1;2\r\n");
+
+  Ok(temp_dir.close()?)
+}
+
+#[tokio::test]
+async fn test_csv_to_csv_comment_and_escape() -> anyhow::Result<()> {
+  let temp_dir = tempfile::TempDir::new()?;
+  let file_path = temp_dir.path().join("input.csv");
+  std::fs::write(
+    &file_path,
+    "name|note\n# generated by system\nTom|\"it\\\"s\"\nJerry|\" padded \"\n",
+  )?;
+
+  insight::cmd::convert::csv_to_csv::csv_to_csv(
+    &file_path,
+    ",",
+    "\"",
+    "necessary",
+    true,
+    "input.csv".to_string(),
+    false,
+    0,
+    false,
+    "crlf",
+    "fields",
+    Some("#"),
+    Some("\\"),
+    None,
+    insight::utils::MockEmitter::default(),
+  )
+  .await?;
+
+  let output_path = temp_dir.path().join("input_fmt.csv");
+  assert_eq!(
+    std::fs::read_to_string(output_path)?,
+    "name,note\r\nTom,\"it\"\"s\"\r\nJerry,padded\r\n"
+  );
+
+  Ok(temp_dir.close()?)
+}
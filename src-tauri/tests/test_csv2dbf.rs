@@ -0,0 +1,86 @@
+use insight::cmd::convert::csv_to_dbf::{DbfType, Overflow, csv_to_dbf, parse_field_types};
+
+#[test]
+fn test_parse_field_types() -> anyhow::Result<()> {
+  let types = parse_field_types("name:C(20)|amount:n(12,2)|birthday:D|vip:L|note:C")?;
+  assert_eq!(types["name"], DbfType::Character(20));
+  assert_eq!(types["amount"], DbfType::Numeric(12, 2));
+  assert_eq!(types["birthday"], DbfType::Date);
+  assert_eq!(types["vip"], DbfType::Logical);
+  assert_eq!(types["note"], DbfType::Character(0));
+
+  assert!(parse_field_types("name:C(300)").is_err());
+  assert!(parse_field_types("amount:N(4,4)").is_err());
+  assert!(parse_field_types("amount").is_err());
+
+  assert_eq!("error".parse::<Overflow>()?, Overflow::Error);
+  let err = "eror".parse::<Overflow>().unwrap_err();
+  assert!(err.to_string().contains("unknown overflow policy"));
+
+  Ok(())
+}
+
+#[tokio::test]
+async fn test_csv2dbf_infer() -> anyhow::Result<()> {
+  let temp_dir = tempfile::TempDir::new()?;
+  let file_path = temp_dir.path().join("input.csv");
+  std::fs::write(
+    &file_path,
+    "name,amount,birthday,vip,customer_number\n汤姆,-12.5,2024-01-31,true,1\nJerry,300,2023/12/01,F,\nSandy,7.25,,yes,3\n",
+  )?;
+
+  let report = csv_to_dbf(&file_path, 0, "", Overflow::Error, "gbk").await?;
+  assert_eq!(report.rows, 3);
+  assert_eq!(report.codepage, "GBK");
+  let fields: Vec<(&str, DbfType)> = report
+    .fields
+    .iter()
+    .map(|f| (f.name.as_str(), f.kind))
+    .collect();
+  assert_eq!(
+    fields,
+    vec![
+      ("name", DbfType::Character(5)),
+      ("amount", DbfType::Numeric(6, 2)),
+      ("birthday", DbfType::Date),
+      ("vip", DbfType::Logical),
+      ("customer_n", DbfType::Numeric(1, 0)),
+    ]
+  );
+  assert!(temp_dir.path().join("input_csv2dbf.dbf").exists());
+
+  Ok(temp_dir.close()?)
+}
+
+#[tokio::test]
+async fn test_csv2dbf_overflow() -> anyhow::Result<()> {
+  let temp_dir = tempfile::TempDir::new()?;
+  let file_path = temp_dir.path().join("input.csv");
+  std::fs::write(&file_path, "name,amount\nPatrick,1.234\nSandy,2\n")?;
+
+  let err = csv_to_dbf(&file_path, 0, "name:C(5)", Overflow::Error, "utf-8")
+    .await
+    .unwrap_err();
+  assert_eq!(
+    err.to_string(),
+    "row 1, column `name`: `Patrick` is longer than C(5)"
+  );
+  assert!(!temp_dir.path().join("input_csv2dbf.dbf").exists());
+
+  let report = csv_to_dbf(
+    &file_path,
+    0,
+    "name:C(5)|amount:N(6,2)",
+    Overflow::Truncate,
+    "utf-8",
+  )
+  .await?;
+  assert_eq!(report.truncated, 2);
+
+  let err = csv_to_dbf(&file_path, 0, "amount:N(3,2)", Overflow::Truncate, "utf-8")
+    .await
+    .unwrap_err();
+  assert!(err.to_string().contains("does not fit N(3,2)"));
+
+  Ok(temp_dir.close()?)
+}
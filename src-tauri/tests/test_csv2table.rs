@@ -0,0 +1,44 @@
+use insight::cmd::convert::csv_to_table::{
+  TableFormat, csv_to_table, escape_markdown, render_markdown,
+};
+
+#[test]
+fn test_render_markdown() {
+  let headers = vec!["name".to_string(), "amount".to_string()];
+  let rows = vec![
+    vec!["Tom|Jerry".to_string(), "1,200.5".to_string()],
+    vec!["line1\r\nline2".to_string(), "".to_string()],
+    vec!["<b>".to_string(), "-3".to_string()],
+  ];
+  assert_eq!(
+    render_markdown(&headers, &rows),
+    "| name | amount |\n| --- | ---: |\n| Tom\\|Jerry | 1,200.5 |\n| line1<br>line2 |  |\n| &lt;b&gt; | -3 |\n"
+  );
+  assert_eq!(escape_markdown("a\\b"), "a\\\\b");
+}
+
+#[tokio::test]
+async fn test_csv2html_limit_and_columns() -> anyhow::Result<()> {
+  let temp_dir = tempfile::TempDir::new()?;
+  let file_path = temp_dir.path().join("input.csv");
+  std::fs::write(
+    &file_path,
+    "name,age,note\nTom,4,\"a & b\"\nJerry,3,<i>\nSpike,6,x\n",
+  )?;
+
+  let rows = csv_to_table(&file_path, 0, "note|age", 2, TableFormat::Html).await?;
+  assert_eq!(rows, 2);
+
+  let html = std::fs::read_to_string(temp_dir.path().join("input_table.html"))?;
+  assert!(html.starts_with("<!DOCTYPE html>"));
+  assert!(html.contains("<tr><th>note</th><th class=\"num\">age</th></tr>"));
+  assert!(html.contains("<tr><td>a &amp; b</td><td class=\"num\">4</td></tr>"));
+  assert!(html.contains("<tr><td>&lt;i&gt;</td><td class=\"num\">3</td></tr>"));
+  assert!(!html.contains("Spike"));
+
+  csv_to_table(&file_path, 0, "", 0, TableFormat::Markdown).await?;
+  let md = std::fs::read_to_string(temp_dir.path().join("input_table.md"))?;
+  assert_eq!(md.lines().count(), 5);
+
+  Ok(temp_dir.close()?)
+}
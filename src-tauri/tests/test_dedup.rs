@@ -0,0 +1,405 @@
+use std::sync::atomic::AtomicUsize;
+
+use insight::{
+  cmd::dedup::{self, DedupMode, DedupOptions, FuzzyOptions, KeepBy, KeepByColumn, KeepValue},
+  io::csv::selection::Selection,
+  similarity::{Normalize, jaro_winkler},
+};
+
+fn sample() -> String {
+  let mut data = String::from("id,name,city\n");
+  for i in 0..500 {
+    let name = match i % 7 {
+      0 => "\"Tom, Jr\"".to_string(),
+      1 => "\"multi\nline\"".to_string(),
+      n => format!("name{}", (i * n) % 23),
+    };
+    data.push_str(&format!("{i},{name},city{}\n", i % 3));
+  }
+  data
+}
+
+fn run(
+  data: &str,
+  normalize: &Normalize,
+  mode: DedupMode,
+  partitions: Option<usize>,
+) -> anyhow::Result<String> {
+  let mut rdr = csv::ReaderBuilder::new().from_reader(data.as_bytes());
+  let mut wtr = csv::WriterBuilder::new().from_writer(Vec::new());
+  let sel = Selection::from_headers(rdr.byte_headers()?, &["name", "city"])?;
+  let rows = AtomicUsize::new(0);
+  match partitions {
+    Some(n) => dedup::partitioned_dedup(&mut rdr, &mut wtr, &sel, normalize, mode, n, &rows, None)?,
+    None => dedup::dedup_csv_rows(&mut rdr, &mut wtr, &sel, normalize, mode, false, None)?,
+  };
+  Ok(String::from_utf8(wtr.into_inner()?)?)
+}
+
+#[test]
+fn test_partitioned_dedup_matches_in_memory() -> anyhow::Result<()> {
+  let data = sample();
+  let normalize = Normalize::default();
+  for mode in [
+    DedupMode::KeepFirst,
+    DedupMode::KeepLast,
+    DedupMode::KeepDuplicates,
+    DedupMode::Unique,
+  ] {
+    let expected = run(&data, &normalize, mode, None)?;
+    for partitions in [1, 3, 16] {
+      assert_eq!(
+        run(&data, &normalize, mode, Some(partitions))?,
+        expected,
+        "{mode:?} with {partitions} partitions"
+      );
+    }
+  }
+  Ok(())
+}
+
+#[tokio::test]
+async fn test_dedup_keep_order() -> anyhow::Result<()> {
+  let temp_dir = tempfile::TempDir::new()?;
+
+  let file_path = temp_dir.path().join("input.csv");
+  std::fs::write(
+    &file_path,
+    "name,age\nTom,1\nJerry,2\nTom,3\nSandy,4\nJerry,5\nPat,6\n",
+  )?;
+  let output_path = temp_dir.path().join("input_dedup.csv");
+
+  let cases = [
+    (
+      DedupMode::KeepFirst,
+      "name,age\nTom,1\nJerry,2\nSandy,4\nPat,6\n",
+    ),
+    (
+      DedupMode::KeepLast,
+      "name,age\nTom,3\nSandy,4\nJerry,5\nPat,6\n",
+    ),
+  ];
+  // 1 字节的内存预算强制走分区
+  for budget in [None, Some(1)] {
+    for (mode, expected) in cases {
+      let emitter = insight::utils::MockEmitter::default();
+      let update_rows = emitter.update_rows.clone();
+      let options = DedupOptions {
+        mode,
+        sorted: false,
+        normalize: Normalize::default(),
+        keep_by: None,
+        audit: false,
+        memory_budget: budget,
+        progress: true,
+      };
+      let output_rows = dedup::dedup_csv(
+        &file_path,
+        &["name".to_string()],
+        &options,
+        0,
+        false,
+        true,
+        emitter,
+      )
+      .await?;
+      assert_eq!(output_rows, 4);
+      assert_eq!(std::fs::read_to_string(&output_path)?, expected);
+      if budget.is_some() {
+        assert_eq!(update_rows.lock().unwrap().last(), Some(&6));
+      }
+    }
+  }
+
+  Ok(temp_dir.close()?)
+}
+
+#[test]
+fn test_dedup_normalized() -> anyhow::Result<()> {
+  let data = "name,city\n\"  Tom   Smith \",NY\n\u{ff34}\u{ff4f}\u{ff4d} smith,NY\n\"TOM, SMITH\",NY\nTom Smith,LA\n";
+  let normalize = Normalize::parse("trim|lower|space|width|punct")?;
+
+  for partitions in [None, Some(2)] {
+    let output = run_columns(data, &normalize, &["name", "city"], partitions)?;
+    assert_eq!(output, "name,city\n  Tom   Smith ,NY\nTom Smith,LA\n");
+  }
+  // 不做规范化时都不相同
+  let output = run_columns(data, &Normalize::default(), &["name", "city"], None)?;
+  assert_eq!(output.lines().count(), 5);
+
+  assert!(Normalize::parse("trim|upper").is_err());
+
+  // GBK 的 "张三" 与 "李四" 不是 UTF-8, 规范化后仍不相同
+  let trim = Normalize::parse("trim")?;
+  let (a, b) = (b" \xd5\xc5\xc8\xfd ", b"\xc0\xee\xcb\xc4");
+  assert_eq!(&*trim.apply(a), b"\xd5\xc5\xc8\xfd");
+  assert_ne!(trim.apply(a), trim.apply(b));
+  // 双字节编码的尾字节保持不变
+  let normalize = Normalize::parse("all")?;
+  assert_eq!(&*normalize.apply(b"  A\x81B,  C\x81 "), b"a\x81B c\x81");
+  Ok(())
+}
+
+fn run_columns(
+  data: &str,
+  normalize: &Normalize,
+  columns: &[&str],
+  partitions: Option<usize>,
+) -> anyhow::Result<String> {
+  let mut rdr = csv::ReaderBuilder::new().from_reader(data.as_bytes());
+  let mut wtr = csv::WriterBuilder::new().from_writer(Vec::new());
+  let sel = Selection::from_headers(rdr.byte_headers()?, columns)?;
+  let rows = AtomicUsize::new(0);
+  let mode = DedupMode::KeepFirst;
+  match partitions {
+    Some(n) => dedup::partitioned_dedup(&mut rdr, &mut wtr, &sel, normalize, mode, n, &rows, None)?,
+    None => dedup::dedup_csv_rows(&mut rdr, &mut wtr, &sel, normalize, mode, false, None)?,
+  };
+  Ok(String::from_utf8(wtr.into_inner()?)?)
+}
+
+#[test]
+fn test_jaro_winkler() {
+  let chars = |s: &str| s.chars().collect::<Vec<_>>();
+  let jw = |a: &str, b: &str| jaro_winkler(&chars(a), &chars(b));
+
+  assert!((jw("MARTHA", "MARHTA") - 0.9611).abs() < 1e-4);
+  assert!((jw("DWAYNE", "DUANE") - 0.84).abs() < 1e-4);
+  assert!((jw("DIXON", "DICKSONX") - 0.8133).abs() < 1e-4);
+  assert_eq!(jw("", ""), 1.0);
+  assert_eq!(jw("abc", ""), 0.0);
+  assert_eq!(jw("张三丰", "张三丰"), 1.0);
+}
+
+#[tokio::test]
+async fn test_dedup_cluster() -> anyhow::Result<()> {
+  let temp_dir = tempfile::TempDir::new()?;
+
+  let file_path = temp_dir.path().join("input.csv");
+  std::fs::write(
+    &file_path,
+    "name,phone\n\
+     Jonathan Smith,13800001111\n\
+     Mary Jones,13900002222\n\
+     jonathon smith,13800001234\n\
+     Jonathan Smith,15000001111\n\
+     ,13800001111\n\
+     Mary  Jones,13900002299\n",
+  )?;
+  let output_path = temp_dir.path().join("input_dedup.csv");
+
+  let fuzzy = FuzzyOptions {
+    column: "name".to_string(),
+    threshold: 0.9,
+    block_prefix: 7,
+  };
+  let rows = dedup::cluster_csv(
+    &file_path,
+    &["phone".to_string()],
+    Normalize::parse("lower|space")?,
+    &fuzzy,
+    0,
+    false,
+    true,
+  )
+  .await?;
+  assert_eq!(rows, 6);
+
+  // 电话前缀不同的行不比较, 空名称单独成簇
+  assert_eq!(
+    std::fs::read_to_string(&output_path)?,
+    "name,phone,cluster_id\n\
+     Jonathan Smith,13800001111,1\n\
+     Mary Jones,13900002222,2\n\
+     jonathon smith,13800001234,1\n\
+     Jonathan Smith,15000001111,3\n\
+     ,13800001111,4\n\
+     Mary  Jones,13900002299,2\n"
+  );
+
+  // 没有 block 列时需要两两比较所有行
+  let no_block = dedup::cluster_csv(
+    &file_path,
+    &[],
+    Normalize::default(),
+    &fuzzy,
+    0,
+    false,
+    true,
+  );
+  assert!(no_block.await.is_err());
+
+  // 一个 block 内不同的值过多
+  let mut data = String::from("name,phone\n");
+  for i in 0..=dedup::MAX_BLOCK_VALUES {
+    data.push_str(&format!("n{i},1\n"));
+  }
+  std::fs::write(&file_path, data)?;
+  let err = dedup::cluster_csv(
+    &file_path,
+    &["phone".to_string()],
+    Normalize::default(),
+    &fuzzy,
+    0,
+    false,
+    true,
+  )
+  .await
+  .unwrap_err();
+  assert!(err.to_string().contains("distinct values"));
+
+  Ok(temp_dir.close()?)
+}
+
+fn keep_by(
+  data: &str,
+  keep_by: KeepBy,
+  sorted: bool,
+  partitions: Option<usize>,
+) -> anyhow::Result<(String, String)> {
+  let mut rdr = csv::ReaderBuilder::new().from_reader(data.as_bytes());
+  let mut wtr = csv::WriterBuilder::new().from_writer(Vec::new());
+  let mut audit = csv::WriterBuilder::new().from_writer(Vec::new());
+  let sel = Selection::from_headers(rdr.byte_headers()?, &["id"])?;
+  let normalize = Normalize::default();
+  let mode = DedupMode::KeepBy(keep_by);
+  let rows = AtomicUsize::new(0);
+  match partitions {
+    Some(n) => dedup::partitioned_dedup(
+      &mut rdr,
+      &mut wtr,
+      &sel,
+      &normalize,
+      mode,
+      n,
+      &rows,
+      Some(&mut audit),
+    )?,
+    None => dedup::dedup_csv_rows(
+      &mut rdr,
+      &mut wtr,
+      &sel,
+      &normalize,
+      mode,
+      sorted,
+      Some(&mut audit),
+    )?,
+  };
+  Ok((
+    String::from_utf8(wtr.into_inner()?)?,
+    String::from_utf8(audit.into_inner()?)?,
+  ))
+}
+
+#[test]
+fn test_dedup_keep_by() -> anyhow::Result<()> {
+  // 按 id 排好序, 排序与未排序的结果一致
+  let data = "id,amount,updated_at\n\
+    a,10,2024-01-05\n\
+    a,30,2023/12/31\n\
+    a,x,2024-03-01 08:00:00\n\
+    a,30,2024-02-01\n\
+    b,5,bad\n\
+    c,-1,20240101\n\
+    c,2.5,2023-06-01\n";
+
+  let cases = [
+    (
+      KeepBy {
+        column: 1,
+        max: true,
+        value: KeepValue::Numeric,
+      },
+      "id,amount,updated_at\na,30,2023/12/31\nb,5,bad\nc,2.5,2023-06-01\n",
+      "id,amount,updated_at\na,10,2024-01-05\na,x,2024-03-01 08:00:00\na,30,2024-02-01\nc,-1,20240101\n",
+    ),
+    (
+      KeepBy {
+        column: 1,
+        max: false,
+        value: KeepValue::Numeric,
+      },
+      "id,amount,updated_at\na,10,2024-01-05\nb,5,bad\nc,-1,20240101\n",
+      "id,amount,updated_at\na,30,2023/12/31\na,x,2024-03-01 08:00:00\na,30,2024-02-01\nc,2.5,2023-06-01\n",
+    ),
+    (
+      KeepBy {
+        column: 2,
+        max: true,
+        value: KeepValue::Date,
+      },
+      "id,amount,updated_at\na,x,2024-03-01 08:00:00\nb,5,bad\nc,-1,20240101\n",
+      "id,amount,updated_at\na,10,2024-01-05\na,30,2023/12/31\na,30,2024-02-01\nc,2.5,2023-06-01\n",
+    ),
+    (
+      KeepBy {
+        column: 1,
+        max: true,
+        value: KeepValue::Lexical,
+      },
+      "id,amount,updated_at\na,x,2024-03-01 08:00:00\nb,5,bad\nc,2.5,2023-06-01\n",
+      "id,amount,updated_at\na,10,2024-01-05\na,30,2023/12/31\na,30,2024-02-01\nc,-1,20240101\n",
+    ),
+  ];
+  for (rule, kept, dropped) in cases {
+    for (sorted, partitions) in [(true, None), (false, None), (false, Some(3))] {
+      let (output, audit) = keep_by(data, rule, sorted, partitions)?;
+      assert_eq!(
+        output, kept,
+        "{rule:?} sorted {sorted} partitions {partitions:?}"
+      );
+      assert_eq!(
+        audit, dropped,
+        "{rule:?} sorted {sorted} partitions {partitions:?}"
+      );
+    }
+  }
+
+  Ok(())
+}
+
+#[tokio::test]
+async fn test_dedup_audit_file() -> anyhow::Result<()> {
+  let temp_dir = tempfile::TempDir::new()?;
+
+  let file_path = temp_dir.path().join("input.csv");
+  std::fs::write(
+    &file_path,
+    "id,updated_at\n1,2024-01-01\n2,2024-01-01\n1,2024-05-01\n1,2024-02-01\n",
+  )?;
+  let rule = KeepByColumn {
+    column: "updated_at".to_string(),
+    max: true,
+    value: KeepValue::Date,
+  };
+  let options = DedupOptions {
+    mode: DedupMode::KeepFirst,
+    sorted: false,
+    normalize: Normalize::default(),
+    keep_by: Some(rule),
+    audit: true,
+    memory_budget: None,
+    progress: false,
+  };
+  dedup::dedup_csv(
+    &file_path,
+    &["id".to_string()],
+    &options,
+    0,
+    false,
+    true,
+    insight::utils::MockEmitter::default(),
+  )
+  .await?;
+
+  assert_eq!(
+    std::fs::read_to_string(temp_dir.path().join("input_dedup.csv"))?,
+    "id,updated_at\n2,2024-01-01\n1,2024-05-01\n"
+  );
+  assert_eq!(
+    std::fs::read_to_string(temp_dir.path().join("input_dedup_discarded.csv"))?,
+    "id,updated_at\n1,2024-01-01\n1,2024-02-01\n"
+  );
+
+  Ok(temp_dir.close()?)
+}
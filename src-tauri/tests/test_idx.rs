@@ -30,3 +30,144 @@ async fn test_idx() -> anyhow::Result<()> {
 
   Ok(temp_dir.close()?)
 }
+
+#[tokio::test]
+async fn test_idx_health() -> anyhow::Result<()> {
+  use insight::{
+    cmd::idx::{create_index, index_health},
+    index::IndexStatus,
+    io::csv::options::CsvOptions,
+  };
+
+  let temp_dir = tempfile::TempDir::new()?;
+  let file_path = temp_dir.path().join("input.csv");
+  std::fs::write(&file_path, "title\nindex,name\n1,AC\n2,AD\n3,AA\n")?;
+
+  let mut opts = CsvOptions::new(file_path.to_str().unwrap());
+  opts.set_skiprows(1);
+  assert_eq!(index_health(&opts)?.status, IndexStatus::Missing);
+
+  create_index(&file_path, true, false, 1).await?;
+  let health = index_health(&opts)?;
+  assert_eq!(health.status, IndexStatus::Fresh);
+  assert_eq!(health.meta.unwrap().records, 4);
+
+  // offsets skip the title row
+  let mut idx = opts.indexed()?.unwrap();
+  assert_eq!(
+    idx.byte_headers()?,
+    &csv::ByteRecord::from(vec!["index", "name"])
+  );
+  idx.seek(1)?;
+  let row = idx.byte_records().next().unwrap()?;
+  assert_eq!(row, csv::ByteRecord::from(vec!["2", "AD"]));
+
+  // built for skiprows 1, ignored for skiprows 0
+  let plain = CsvOptions::new(file_path.to_str().unwrap());
+  assert!(matches!(
+    index_health(&plain)?.status,
+    IndexStatus::Mismatch(_)
+  ));
+  assert!(plain.indexed()?.is_none());
+
+  // same size, other content: stale, not used until rebuilt
+  std::fs::write(&file_path, "title\nindex,name\n1,AC\n2,AD\n3,AB\n")?;
+  assert!(matches!(index_health(&opts)?.status, IndexStatus::Stale(_)));
+  assert!(opts.indexed()?.is_none());
+  assert!(matches!(index_health(&opts)?.status, IndexStatus::Stale(_)));
+  assert!(opts.ensure_index()?);
+  let mut idx = opts.indexed()?.unwrap();
+  assert_eq!(idx.count(), 3);
+  idx.seek(2)?;
+  let row = idx.byte_records().next().unwrap()?;
+  assert_eq!(row, csv::ByteRecord::from(vec!["3", "AB"]));
+  assert_eq!(index_health(&opts)?.status, IndexStatus::Fresh);
+
+  let json = insight::cmd::idx::idx_health(file_path.to_string_lossy().to_string(), 1)
+    .await
+    .map_err(anyhow::Error::msg)?;
+  assert!(json.contains("\"status\":\"fresh\""));
+
+  // 没有 .idx.meta 的旧索引不会被使用
+  std::fs::remove_file(temp_dir.path().join("input.csv.idx.meta"))?;
+  assert_eq!(index_health(&opts)?.status, IndexStatus::Unversioned);
+  assert!(opts.indexed()?.is_none());
+
+  Ok(temp_dir.close()?)
+}
+
+#[tokio::test]
+async fn test_idx_append() -> anyhow::Result<()> {
+  use std::io::Write;
+
+  use insight::{
+    cmd::idx::{create_index, index_health},
+    index::IndexStatus,
+    io::csv::options::CsvOptions,
+  };
+
+  let temp_dir = tempfile::TempDir::new()?;
+  let file_path = temp_dir.path().join("log.csv");
+  let idx_path = temp_dir.path().join("log.csv.idx");
+  // 最后一行没有换行, 追加后会接在同一行上
+  std::fs::write(&file_path, "day,msg\n1,\"a\nb\"\n2,c")?;
+  create_index(&file_path, true, false, 0).await?;
+
+  let mut file = std::fs::OpenOptions::new().append(true).open(&file_path)?;
+  write!(file, "d\n3,e\n4,\"f,g\"\n")?;
+  drop(file);
+
+  let opts = CsvOptions::new(file_path.to_str().unwrap());
+  assert!(matches!(
+    index_health(&opts)?.status,
+    IndexStatus::Appended(_)
+  ));
+  assert_eq!(insight::cmd::count::count_rows(&file_path, 0).await?, 4);
+  let health = index_health(&opts)?;
+  assert_eq!(health.status, IndexStatus::Fresh);
+  assert_eq!(health.meta.unwrap().records, 5);
+
+  let mut idx = opts.indexed()?.unwrap();
+  idx.seek(1)?;
+  let row = idx.byte_records().next().unwrap()?;
+  assert_eq!(row, csv::ByteRecord::from(vec!["2", "cd"]));
+
+  // 与完整重建的索引一致
+  let appended = std::fs::read(&idx_path)?;
+  std::fs::remove_file(&idx_path)?;
+  create_index(&file_path, true, false, 0).await?;
+  assert_eq!(appended, std::fs::read(&idx_path)?);
+
+  // 追加前的内容被修改时完整重建
+  std::fs::write(
+    &file_path,
+    "day,msg\n9,\"a\nb\"\n2,cd\n3,e\n4,\"f,g\"\n5,h\n",
+  )?;
+  assert!(matches!(index_health(&opts)?.status, IndexStatus::Stale(_)));
+  assert_eq!(insight::cmd::count::count_rows(&file_path, 0).await?, 5);
+
+  // 修改在首尾的哈希块之外, 长度不变, 再追加数据
+  let mut data = String::from("day,msg\n");
+  for i in 0..20_000 {
+    data.push_str(&format!("{i},m\n"));
+  }
+  std::fs::write(&file_path, &data)?;
+  create_index(&file_path, true, false, 0).await?;
+  let data = data.replacen("10000,m\n", "10000,\"\n", 1) + "a,b\"\n";
+  std::fs::write(&file_path, data)?;
+  // 只比较首尾时看起来是追加, 扩展前检查抽样的块后重建
+  assert!(matches!(
+    index_health(&opts)?.status,
+    IndexStatus::Appended(_)
+  ));
+  assert_eq!(
+    insight::cmd::count::count_rows(&file_path, 0).await?,
+    10_001
+  );
+  let rebuilt = std::fs::read(&idx_path)?;
+  std::fs::remove_file(&idx_path)?;
+  create_index(&file_path, true, false, 0).await?;
+  assert_eq!(rebuilt, std::fs::read(&idx_path)?);
+
+  Ok(temp_dir.close()?)
+}
@@ -0,0 +1,62 @@
+use insight::cmd::convert::mojibake::{MojibakeKind, fix_mojibake, repair_cell};
+
+#[test]
+fn test_repair_cell() {
+  // UTF-8 被当作 Windows-1252 解码
+  let repair = repair_cell("æ±¤å§†").unwrap();
+  assert_eq!(repair.kind, MojibakeKind::Utf8AsLatin1);
+  assert_eq!(repair.text, "汤姆");
+
+  // GBK 被当作 Latin-1 解码
+  let (gbk, _, _) = encoding_rs::GBK.encode("汤姆");
+  let garbled: String = gbk.iter().map(|b| *b as char).collect();
+  let repair = repair_cell(&garbled).unwrap();
+  assert_eq!(repair.kind, MojibakeKind::GbkAsLatin1);
+  assert_eq!(repair.text, "汤姆");
+
+  // UTF-8 被当作 GBK 解码
+  let repair = repair_cell("浣犲ソ").unwrap();
+  assert_eq!(repair.kind, MojibakeKind::Utf8AsGbk);
+  assert_eq!(repair.text, "你好");
+
+  for clean in ["Müller", "Ñandú", "汤姆", "你好", "Patrick"] {
+    assert_eq!(repair_cell(clean), None, "{clean}");
+  }
+}
+
+#[tokio::test]
+async fn test_fix_mojibake() -> anyhow::Result<()> {
+  let temp_dir = tempfile::TempDir::new()?;
+  let file_path = temp_dir.path().join("input.csv");
+  std::fs::write(
+    &file_path,
+    "name,city\nPatrick,Müller\næ±¤å§†,浣犲ソ\n杰瑞,Berlin\n",
+  )?;
+
+  let report = fix_mojibake(&file_path, 0, 0.6).await?;
+  assert_eq!(report.rows, 3);
+  assert_eq!(report.repaired_cells, 2);
+  assert_eq!(report.repaired_rows, 1);
+  assert_eq!(
+    report
+      .columns
+      .iter()
+      .map(|c| (c.column.as_str(), c.cells))
+      .collect::<Vec<_>>(),
+    vec![("name", 1), ("city", 1)]
+  );
+
+  let output = std::fs::read_to_string(temp_dir.path().join("input_mojibake.csv"))?;
+  assert_eq!(
+    output,
+    "name,city\nPatrick,Müller\n汤姆,你好\n杰瑞,Berlin\n"
+  );
+
+  let audit = std::fs::read_to_string(temp_dir.path().join("input_mojibake_report.csv"))?;
+  let lines: Vec<&str> = audit.lines().collect();
+  assert_eq!(lines[0], "row,column,kind,confidence,original,repaired");
+  assert_eq!(lines[1], "2,name,utf8_as_latin1,0.80,æ±¤å§†,汤姆");
+  assert_eq!(lines[2], "2,city,utf8_as_gbk,0.75,浣犲ソ,你好");
+
+  Ok(temp_dir.close()?)
+}
@@ -0,0 +1,81 @@
+use insight::io::csv::{config::CsvConfigBuilder, options::CsvOptions, parallel::ParallelReader};
+
+#[tokio::test]
+async fn test_parallel_reader_quoted_newlines() -> anyhow::Result<()> {
+  let temp_dir = tempfile::TempDir::new()?;
+
+  let mut data = String::from("title\n\u{feff}id,note,city\n");
+  for i in 0..200 {
+    match i % 4 {
+      0 => data.push_str(&format!("{i},\"multi\nline \"\"{i}\"\"\n\",x\r\n")),
+      1 => data.push_str(&format!("{i},\"a,b\",\"y\"\n")),
+      2 => data.push_str(&format!("{i},,\"\"\"\"\n")),
+      _ => data.push_str(&format!("{i},plain,z\n")),
+    }
+  }
+  let file_path = temp_dir.path().join("input.csv");
+  std::fs::write(&file_path, &data)?;
+
+  let mut opts = CsvOptions::new(file_path.to_str().unwrap());
+  opts.set_skiprows(1);
+  let mut expected = opts.skiprows_reader()?;
+  let expected: Vec<csv::ByteRecord> = expected.byte_records().collect::<Result<_, _>>()?;
+  assert_eq!(expected.len(), 200);
+
+  let config = CsvConfigBuilder::new().dialect(&opts.dialect()?).build();
+  for chunk_size in [1, 7, 64, 1 << 20] {
+    let rdr = ParallelReader::from_options(&opts, &config)?.chunk_size(chunk_size);
+    assert_eq!(
+      rdr.byte_headers(),
+      &csv::ByteRecord::from(vec!["id", "note", "city"])
+    );
+
+    let chunks = rdr.map_chunks(4, |chunk| {
+      let mut records = Vec::new();
+      let mut record = csv::ByteRecord::new();
+      while chunk.read_byte_record(&mut record)? {
+        records.push(record.clone());
+      }
+      Ok(records)
+    })?;
+    let records: Vec<csv::ByteRecord> = chunks.into_iter().flatten().collect();
+    assert_eq!(records, expected, "chunk size {chunk_size}");
+    assert_eq!(rdr.count_records(3)?, 200);
+  }
+
+  assert_eq!(insight::cmd::count::count_rows(&file_path, 1).await?, 200);
+
+  Ok(temp_dir.close()?)
+}
+
+#[tokio::test]
+async fn test_parallel_replace_without_index() -> anyhow::Result<()> {
+  let temp_dir = tempfile::TempDir::new()?;
+
+  let file_path = temp_dir.path().join("input.csv");
+  std::fs::write(
+    &file_path,
+    "name,age\nJerry,19\n\"Pat\nrick\",4\nSandy,24\nTom,x\n",
+  )?;
+  let output_path = temp_dir.path().join("input_replace.csv");
+
+  let opts = CsvOptions::new(file_path.to_string_lossy().to_string());
+  let wtr = CsvConfigBuilder::new().build().build_writer(&output_path)?;
+  let matched = insight::cmd::replace::regex_replace_parallel(
+    wtr,
+    opts,
+    "age".to_string(),
+    r"^\d+$".to_string(),
+    "XX".to_string(),
+    4,
+  )?;
+  assert_eq!(matched, "3");
+
+  let context = std::fs::read_to_string(&output_path)?;
+  assert_eq!(
+    context,
+    "name,age\nJerry,XX\n\"Pat\nrick\",XX\nSandy,XX\nTom,x\n"
+  );
+
+  Ok(temp_dir.close()?)
+}
@@ -46,3 +46,29 @@ async fn test_pinyin() -> anyhow::Result<()> {
 
   Ok(temp_dir.close()?)
 }
+
+#[test]
+fn test_fuzzy_pinyin() -> anyhow::Result<()> {
+  use insight::cmd::search::fuzzy::{FuzzyMatcher, FuzzyOptions};
+
+  // 拼音相同的汉字视为相同
+  let strict = FuzzyMatcher::new(
+    "张三",
+    FuzzyOptions {
+      max_distance: 0,
+      ..Default::default()
+    },
+  )?;
+  let pinyin = FuzzyMatcher::new(
+    "张三",
+    FuzzyOptions {
+      max_distance: 0,
+      pinyin: true,
+      ..Default::default()
+    },
+  )?;
+  assert!(strict.is_match("张三") && !strict.is_match("章三"));
+  assert!(pinyin.is_match("章三") && !pinyin.is_match("李四"));
+
+  Ok(())
+}
@@ -36,3 +36,39 @@ async fn test_reverse() -> anyhow::Result<()> {
 
   Ok(temp_dir.close()?)
 }
+
+#[tokio::test]
+async fn test_reverse_on_disk() -> anyhow::Result<()> {
+  let temp_dir = tempfile::TempDir::new()?;
+
+  let mut data = String::from("id,note\n");
+  for i in 0..100 {
+    data.push_str(&format!("{i},\"line\n{i}\"\n"));
+  }
+  let file_path = temp_dir.path().join("input.csv");
+  std::fs::write(&file_path, &data)?;
+  let output_path = temp_dir.path().join("input_reverse.csv");
+
+  for budget in [1, 200, 1 << 20] {
+    insight::cmd::reverse::reverse_csv_with(
+      &file_path,
+      true,
+      0,
+      false,
+      Some(insight::utils::MemoryPlan::OnDisk(budget)),
+    )
+    .await?;
+
+    let mut expected = String::from("id,note\n");
+    for i in (0..100).rev() {
+      expected.push_str(&format!("{i},\"line\n{i}\"\n"));
+    }
+    assert_eq!(
+      std::fs::read_to_string(&output_path)?,
+      expected,
+      "budget {budget}"
+    );
+  }
+
+  Ok(temp_dir.close()?)
+}
@@ -3,15 +3,24 @@ use std::io::Write;
 use insight::{
   cmd::{
     idx::create_index,
-    search::{filters, filters_multi},
+    search::{
+      date::{DateMatcher, Unparsable},
+      filters, filters_chain,
+      filters_columns::{self, ColumnScope},
+      filters_multi,
+      fuzzy::{self, FuzzyMatcher, FuzzyMetric, FuzzyOptions},
+      generic::{MatchSink, SearchInput, SearchOutput},
+      lookup::{LookupMatcher, read_lookup_values},
+    },
   },
   io::csv::options::CsvOptions,
+  similarity::{damerau_levenshtein, levenshtein},
 };
 
 async fn create_temp_csv() -> anyhow::Result<(
   tempfile::TempDir,
   csv::Reader<std::io::BufReader<Box<dyn std::io::Read + Send>>>,
-  csv::Writer<std::io::BufWriter<std::fs::File>>,
+  csv::Writer<insight::io::writer::OutputWriter>,
   String,
   String,
 )> {
@@ -58,12 +67,10 @@ async fn test_equal() -> anyhow::Result<()> {
   let column = "name".to_string();
   let conditions = vec!["Tom".to_string()];
   let opts = CsvOptions::new(path);
-  let idx = opts.indexed().unwrap().unwrap();
   let match_rows = filters::contains(
     rdr,
     wtr,
     opts,
-    Some(idx),
     column,
     conditions,
     true,
@@ -88,12 +95,10 @@ async fn test_equal_parallel() -> anyhow::Result<()> {
   let column = "name".to_string();
   let conditions = vec!["Tom".to_string()];
   let opts = CsvOptions::new(path);
-  let idx = opts.indexed().unwrap().unwrap();
   let match_rows = filters::contains(
     rdr,
     wtr,
     opts,
-    Some(idx),
     column,
     conditions,
     true,
@@ -107,6 +112,45 @@ async fn test_equal_parallel() -> anyhow::Result<()> {
   Ok(temp_dir.close()?)
 }
 
+#[tokio::test]
+async fn test_parallel_search_reader_settings() -> anyhow::Result<()> {
+  let temp_dir = tempfile::TempDir::new()?;
+  let path = temp_dir.path().join("input.csv");
+  std::fs::write(&path, "name,age\n\"Tom,18\nJerry\n\"Tom,6\n")?;
+  let path = path.to_string_lossy().to_string();
+
+  // quoting off and ragged rows, the thread count must not change the result
+  for threads in [1, 2] {
+    let opts = CsvOptions::new(path.clone());
+    let config = insight::io::csv::config::CsvConfigBuilder::new()
+      .quoting(false)
+      .flexible(true)
+      .build();
+    let (_, reader) = opts.skiprows_and_dialect()?;
+    let rdr = config.build_reader(reader);
+    let wtr = config.build_writer(temp_dir.path().join("input_search.csv"))?;
+    let input = SearchInput {
+      opts,
+      quoting: false,
+      flexible: true,
+    };
+    let match_rows = filters::equal(
+      rdr,
+      wtr,
+      input,
+      "name".to_string(),
+      vec!["\"Tom".to_string()],
+      false,
+      Some(threads),
+      insight::utils::MockEmitter::default(),
+    )
+    .await?;
+    assert_eq!(match_rows, "2", "threads {threads}");
+  }
+
+  Ok(temp_dir.close()?)
+}
+
 #[tokio::test]
 async fn test_not_equal() -> anyhow::Result<()> {
   let (temp_dir, rdr, wtr, output_path, path) = create_temp_csv().await?;
@@ -114,12 +158,10 @@ async fn test_not_equal() -> anyhow::Result<()> {
   let column = "name".to_string();
   let conditions = vec!["Tom".to_string()];
   let opts = CsvOptions::new(path);
-  let idx = opts.indexed().unwrap().unwrap();
   let match_rows = filters::not_equal(
     rdr,
     wtr,
     opts,
-    Some(idx),
     column,
     conditions,
     true,
@@ -150,12 +192,10 @@ async fn test_not_equal_parallel() -> anyhow::Result<()> {
   let column = "name".to_string();
   let conditions = vec!["Tom".to_string()];
   let opts = CsvOptions::new(path);
-  let idx = opts.indexed().unwrap().unwrap();
   let match_rows = filters::not_equal(
     rdr,
     wtr,
     opts,
-    Some(idx),
     column,
     conditions,
     true,
@@ -176,12 +216,10 @@ async fn test_contains() -> anyhow::Result<()> {
   let column = "name".to_string();
   let conditions = vec!["at".to_string()];
   let opts = CsvOptions::new(path);
-  let idx = opts.indexed().unwrap().unwrap();
   let match_rows = filters::contains(
     rdr,
     wtr,
     opts,
-    Some(idx),
     column,
     conditions,
     true,
@@ -207,12 +245,10 @@ async fn test_contains_parallel() -> anyhow::Result<()> {
   let column = "name".to_string();
   let conditions = vec!["at".to_string()];
   let opts = CsvOptions::new(path);
-  let idx = opts.indexed().unwrap().unwrap();
   let match_rows = filters::contains(
     rdr,
     wtr,
     opts,
-    Some(idx),
     column,
     conditions,
     true,
@@ -232,12 +268,10 @@ async fn test_not_contains() -> anyhow::Result<()> {
   let column = "name".to_string();
   let conditions = vec!["at".to_string()];
   let opts = CsvOptions::new(path);
-  let idx = opts.indexed().unwrap().unwrap();
   let match_rows = filters::not_contains(
     rdr,
     wtr,
     opts,
-    Some(idx),
     column,
     conditions,
     true,
@@ -267,12 +301,10 @@ async fn test_not_contains_parallel() -> anyhow::Result<()> {
   let column = "name".to_string();
   let conditions = vec!["at".to_string()];
   let opts = CsvOptions::new(path);
-  let idx = opts.indexed().unwrap().unwrap();
   let match_rows = filters::not_contains(
     rdr,
     wtr,
     opts,
-    Some(idx),
     column,
     conditions,
     true,
@@ -293,12 +325,10 @@ async fn test_starts_with() -> anyhow::Result<()> {
   let column = "name".to_string();
   let conditions = vec!["Pa".to_string()];
   let opts = CsvOptions::new(path);
-  let idx = opts.indexed().unwrap().unwrap();
   let match_rows = filters::starts_with(
     rdr,
     wtr,
     opts,
-    Some(idx),
     column,
     conditions,
     true,
@@ -324,12 +354,10 @@ async fn test_starts_with_parallel() -> anyhow::Result<()> {
   let column = "name".to_string();
   let conditions = vec!["Pa".to_string()];
   let opts = CsvOptions::new(path);
-  let idx = opts.indexed().unwrap().unwrap();
   let match_rows = filters::starts_with(
     rdr,
     wtr,
     opts,
-    Some(idx),
     column,
     conditions,
     true,
@@ -350,12 +378,10 @@ async fn test_not_starts_with() -> anyhow::Result<()> {
   let column = "name".to_string();
   let conditions = vec!["Pa".to_string()];
   let opts = CsvOptions::new(path);
-  let idx = opts.indexed().unwrap().unwrap();
   let match_rows = filters::not_starts_with(
     rdr,
     wtr,
     opts,
-    Some(idx),
     column,
     conditions,
     true,
@@ -386,12 +412,10 @@ async fn test_not_starts_with_parallel() -> anyhow::Result<()> {
   let column = "name".to_string();
   let conditions = vec!["Pa".to_string()];
   let opts = CsvOptions::new(path);
-  let idx = opts.indexed().unwrap().unwrap();
   let match_rows = filters::not_starts_with(
     rdr,
     wtr,
     opts,
-    Some(idx),
     column,
     conditions,
     true,
@@ -411,12 +435,10 @@ async fn test_ends_with() -> anyhow::Result<()> {
   let column = "name".to_string();
   let conditions = vec!["ick".to_string()];
   let opts = CsvOptions::new(path);
-  let idx = opts.indexed().unwrap().unwrap();
   let match_rows = filters::ends_with(
     rdr,
     wtr,
     opts,
-    Some(idx),
     column,
     conditions,
     true,
@@ -441,12 +463,10 @@ async fn test_ends_with_parallel() -> anyhow::Result<()> {
   let column = "name".to_string();
   let conditions = vec!["ick".to_string()];
   let opts = CsvOptions::new(path);
-  let idx = opts.indexed().unwrap().unwrap();
   let match_rows = filters::ends_with(
     rdr,
     wtr,
     opts,
-    Some(idx),
     column,
     conditions,
     true,
@@ -466,12 +486,10 @@ async fn test_not_ends_with() -> anyhow::Result<()> {
   let column = "name".to_string();
   let conditions = vec!["ick".to_string()];
   let opts = CsvOptions::new(path);
-  let idx = opts.indexed().unwrap().unwrap();
   let match_rows = filters::not_ends_with(
     rdr,
     wtr,
     opts,
-    Some(idx),
     column,
     conditions,
     true,
@@ -501,12 +519,10 @@ async fn test_not_ends_with_parallel() -> anyhow::Result<()> {
   let column = "name".to_string();
   let conditions = vec!["ick".to_string()];
   let opts = CsvOptions::new(path);
-  let idx = opts.indexed().unwrap().unwrap();
   let match_rows = filters::not_ends_with(
     rdr,
     wtr,
     opts,
-    Some(idx),
     column,
     conditions,
     true,
@@ -526,12 +542,10 @@ async fn test_regex() -> anyhow::Result<()> {
   let column = "name".to_string();
   let regex_char = r"^J.*".to_string(); // Matches any string that starts with 'J'
   let opts = CsvOptions::new(path);
-  let idx = opts.indexed().unwrap().unwrap();
   let match_rows = filters::regex_search(
     rdr,
     wtr,
     opts,
-    Some(idx),
     column,
     regex_char,
     true,
@@ -556,12 +570,10 @@ async fn test_regex_parallel() -> anyhow::Result<()> {
   let column = "name".to_string();
   let regex_char = r"^J.*".to_string(); // Matches any string that starts with 'J'
   let opts = CsvOptions::new(path);
-  let idx = opts.indexed().unwrap().unwrap();
   let match_rows = filters::regex_search(
     rdr,
     wtr,
     opts,
-    Some(idx),
     column,
     regex_char,
     true,
@@ -581,12 +593,10 @@ async fn test_is_null() -> anyhow::Result<()> {
   let column = "name".to_string();
   let conditions = vec!["".to_string()];
   let opts = CsvOptions::new(path);
-  let idx = opts.indexed().unwrap().unwrap();
   let match_rows = filters::is_null(
     rdr,
     wtr,
     opts,
-    Some(idx),
     column,
     conditions,
     true,
@@ -611,12 +621,10 @@ async fn test_is_null_parallel() -> anyhow::Result<()> {
   let column = "name".to_string();
   let conditions = vec!["".to_string()];
   let opts = CsvOptions::new(path);
-  let idx = opts.indexed().unwrap().unwrap();
   let match_rows = filters::is_null(
     rdr,
     wtr,
     opts,
-    Some(idx),
     column,
     conditions,
     true,
@@ -636,12 +644,10 @@ async fn test_is_not_null() -> anyhow::Result<()> {
   let column = "name".to_string();
   let conditions = vec!["".to_string()];
   let opts = CsvOptions::new(path);
-  let idx = opts.indexed().unwrap().unwrap();
   let match_rows = filters::is_not_null(
     rdr,
     wtr,
     opts,
-    Some(idx),
     column,
     conditions,
     true,
@@ -672,12 +678,10 @@ async fn test_is_not_null_parallel() -> anyhow::Result<()> {
   let column = "name".to_string();
   let conditions = vec!["".to_string()];
   let opts = CsvOptions::new(path);
-  let idx = opts.indexed().unwrap().unwrap();
   let match_rows = filters::is_not_null(
     rdr,
     wtr,
     opts,
-    Some(idx),
     column,
     conditions,
     true,
@@ -697,12 +701,10 @@ async fn test_gt() -> anyhow::Result<()> {
   let column = "age".to_string();
   let conditions = "18".to_string();
   let opts = CsvOptions::new(path);
-  let idx = opts.indexed().unwrap().unwrap();
   let match_rows = filters::greater_than(
     rdr,
     wtr,
     opts,
-    Some(idx),
     column,
     conditions,
     true,
@@ -727,12 +729,10 @@ async fn test_gt_parallel() -> anyhow::Result<()> {
   let column = "age".to_string();
   let conditions = "18".to_string();
   let opts = CsvOptions::new(path);
-  let idx = opts.indexed().unwrap().unwrap();
   let match_rows = filters::greater_than(
     rdr,
     wtr,
     opts,
-    Some(idx),
     column,
     conditions,
     true,
@@ -752,12 +752,10 @@ async fn test_ge() -> anyhow::Result<()> {
   let column = "age".to_string();
   let conditions = "18".to_string();
   let opts = CsvOptions::new(path);
-  let idx = opts.indexed().unwrap().unwrap();
   let match_rows = filters::greater_than_or_equal(
     rdr,
     wtr,
     opts,
-    Some(idx),
     column,
     conditions,
     true,
@@ -787,12 +785,10 @@ async fn test_ge_parallel() -> anyhow::Result<()> {
   let column = "age".to_string();
   let conditions = "18".to_string();
   let opts = CsvOptions::new(path);
-  let idx = opts.indexed().unwrap().unwrap();
   let match_rows = filters::greater_than_or_equal(
     rdr,
     wtr,
     opts,
-    Some(idx),
     column,
     conditions,
     true,
@@ -812,12 +808,10 @@ async fn test_lt() -> anyhow::Result<()> {
   let column = "age".to_string();
   let conditions = "18".to_string();
   let opts = CsvOptions::new(path);
-  let idx = opts.indexed().unwrap().unwrap();
   let match_rows = filters::less_than(
     rdr,
     wtr,
     opts,
-    Some(idx),
     column,
     conditions,
     true,
@@ -842,12 +836,10 @@ async fn test_lt_parallel() -> anyhow::Result<()> {
   let column = "age".to_string();
   let conditions = "18".to_string();
   let opts = CsvOptions::new(path);
-  let idx = opts.indexed().unwrap().unwrap();
   let match_rows = filters::less_than(
     rdr,
     wtr,
     opts,
-    Some(idx),
     column,
     conditions,
     true,
@@ -867,12 +859,10 @@ async fn test_le() -> anyhow::Result<()> {
   let column = "age".to_string();
   let conditions = "18".to_string();
   let opts = CsvOptions::new(path);
-  let idx = opts.indexed().unwrap().unwrap();
   let match_rows = filters::less_than_or_equal(
     rdr,
     wtr,
     opts,
-    Some(idx),
     column,
     conditions,
     true,
@@ -897,12 +887,10 @@ async fn test_le_parallel() -> anyhow::Result<()> {
   let column = "age".to_string();
   let conditions = "18".to_string();
   let opts = CsvOptions::new(path);
-  let idx = opts.indexed().unwrap().unwrap();
   let match_rows = filters::less_than_or_equal(
     rdr,
     wtr,
     opts,
-    Some(idx),
     column,
     conditions,
     true,
@@ -922,12 +910,10 @@ async fn test_between() -> anyhow::Result<()> {
   let column = "age".to_string();
   let conditions = vec!["18".to_string(), "19".to_string()];
   let opts = CsvOptions::new(path);
-  let idx = opts.indexed().unwrap().unwrap();
   let match_rows = filters::between(
     rdr,
     wtr,
     opts,
-    Some(idx),
     column,
     conditions,
     true,
@@ -952,12 +938,10 @@ async fn test_between_parallel() -> anyhow::Result<()> {
   let column = "age".to_string();
   let conditions = vec!["18".to_string(), "19".to_string()];
   let opts = CsvOptions::new(path);
-  let idx = opts.indexed().unwrap().unwrap();
   let match_rows = filters::between(
     rdr,
     wtr,
     opts,
-    Some(idx),
     column,
     conditions,
     true,
@@ -997,6 +981,7 @@ async fn test_equal_multi() -> anyhow::Result<()> {
     true,
     true,
     false,
+    SearchOutput::Rows,
     insight::utils::MockEmitter::default(),
   )
   .await?
@@ -1071,6 +1056,7 @@ async fn test_contains_multi() -> anyhow::Result<()> {
     true,
     true,
     false,
+    SearchOutput::Rows,
     insight::utils::MockEmitter::default(),
   )
   .await?
@@ -1145,6 +1131,7 @@ async fn test_starts_with_multi() -> anyhow::Result<()> {
     true,
     true,
     false,
+    SearchOutput::Rows,
     insight::utils::MockEmitter::default(),
   )
   .await?
@@ -1219,6 +1206,7 @@ async fn test_ends_with_multi() -> anyhow::Result<()> {
     true,
     true,
     false,
+    SearchOutput::Rows,
     insight::utils::MockEmitter::default(),
   )
   .await?
@@ -1277,3 +1265,608 @@ async fn test_ends_with_multi() -> anyhow::Result<()> {
 
   Ok(temp_dir.close()?)
 }
+
+#[tokio::test]
+async fn test_search_output_modes() -> anyhow::Result<()> {
+  for threads in [1, 2] {
+    let (temp_dir, rdr, _, _, path) = create_temp_csv().await?;
+    let mut opts = CsvOptions::new(path.clone());
+    opts.set_skiprows(1);
+    let config = insight::io::csv::config::CsvConfigBuilder::new().build();
+    let sink = MatchSink::for_search(&opts, &config, SearchOutput::Split)?;
+    let match_rows = filters::greater_than(
+      rdr,
+      sink,
+      opts,
+      "age".to_string(),
+      "18".to_string(),
+      false,
+      Some(threads),
+      insight::utils::MockEmitter::default(),
+    )
+    .await?;
+    assert_eq!(match_rows, "2");
+    let read = |name: &str| std::fs::read_to_string(temp_dir.path().join(name));
+    assert_eq!(
+      read("input_search.csv")?,
+      "name,age,gender\nJerry,19,male\nSandy,24,female\n"
+    );
+    assert_eq!(
+      read("input_search_unmatched.csv")?,
+      "name,age,gender\nTom,18,male\nPatrick,4,male\n"
+    );
+    temp_dir.close()?;
+
+    let (temp_dir, rdr, _, _, path) = create_temp_csv().await?;
+    let mut opts = CsvOptions::new(path.clone());
+    opts.set_skiprows(1);
+    let sink = MatchSink::for_search(&opts, &config, SearchOutput::RowNumbers)?;
+    let match_rows = filters::greater_than(
+      rdr,
+      sink,
+      opts,
+      "age".to_string(),
+      "18".to_string(),
+      false,
+      Some(threads),
+      insight::utils::MockEmitter::default(),
+    )
+    .await?;
+    assert_eq!(match_rows, "2");
+    assert_eq!(
+      std::fs::read_to_string(temp_dir.path().join("input_search_rows.csv"))?,
+      "row\n2\n4\n"
+    );
+    assert!(!temp_dir.path().join("input_search.csv").exists());
+    temp_dir.close()?;
+
+    // count only: nothing is written
+    let (temp_dir, rdr, _, _, path) = create_temp_csv().await?;
+    let mut opts = CsvOptions::new(path.clone());
+    opts.set_skiprows(1);
+    let sink = MatchSink::for_search(&opts, &config, SearchOutput::Count)?;
+    let match_rows = filters::is_not_null(
+      rdr,
+      sink,
+      opts,
+      "gender".to_string(),
+      vec![],
+      false,
+      Some(threads),
+      insight::utils::MockEmitter::default(),
+    )
+    .await?;
+    assert_eq!(match_rows, "4");
+    for entry in std::fs::read_dir(temp_dir.path())? {
+      assert!(!entry?.file_name().to_string_lossy().contains("_search"));
+    }
+    temp_dir.close()?;
+  }
+
+  Ok(())
+}
+
+#[tokio::test]
+async fn test_search_chain_output_modes() -> anyhow::Result<()> {
+  for threads in [1, 2] {
+    let (temp_dir, _, _, _, path) = create_temp_csv().await?;
+    let configs: Vec<insight::cmd::search::perform::ColumnConfig> =
+      serde_json::from_value(serde_json::json!([
+        { "column": "gender", "mode": "equal", "condition": "male" },
+        { "column": "age", "mode": "lt", "condition": "19" },
+      ]))?;
+    let match_rows = filters_chain::search_with_chain(
+      path.clone(),
+      configs,
+      vec!["and".to_string()],
+      1,
+      true,
+      false,
+      false,
+      threads,
+      SearchOutput::RowNumbers,
+      insight::utils::MockEmitter::default(),
+    )
+    .await?;
+    assert_eq!(match_rows, "2");
+    assert_eq!(
+      std::fs::read_to_string(temp_dir.path().join("input_search_rows.csv"))?,
+      "row\n1\n3\n"
+    );
+    temp_dir.close()?;
+  }
+
+  Ok(())
+}
+
+#[tokio::test]
+async fn test_equal_multi_split() -> anyhow::Result<()> {
+  let (temp_dir, _, _, _, path) = create_temp_csv().await?;
+
+  let match_rows = filters_multi::equal_multi(
+    path,
+    "name".to_string(),
+    vec!["Tom".to_string(), "Jerry".to_string()],
+    1,
+    true,
+    false,
+    false,
+    SearchOutput::Split,
+    insight::utils::MockEmitter::default(),
+  )
+  .await?;
+  assert_eq!(match_rows, "2");
+
+  let read = |name: &str| std::fs::read_to_string(temp_dir.path().join(name));
+  assert_eq!(read("input_Tom.csv")?, "name,age,gender\nTom,18,male\n");
+  assert_eq!(read("input_Jerry.csv")?, "name,age,gender\nJerry,19,male\n");
+  assert_eq!(
+    read("input_search_unmatched.csv")?,
+    "name,age,gender\nPatrick,4,male\nSandy,24,female\n"
+  );
+
+  Ok(temp_dir.close()?)
+}
+
+#[tokio::test]
+async fn test_search_any_and_all_columns() -> anyhow::Result<()> {
+  for threads in [1, 2] {
+    let temp_dir = tempfile::TempDir::new()?;
+    let path = temp_dir.path().join("phones.csv");
+    std::fs::write(
+      &path,
+      "id,phone,backup\n1,13800001111,\n2,13900002222,13800001111\n3,,13700003333\n",
+    )?;
+    let output_path = temp_dir.path().join("phones_search.csv");
+
+    let match_rows = filters_columns::search_columns(
+      &path,
+      "equal",
+      "13800001111",
+      ColumnScope::Any,
+      true,
+      0,
+      true,
+      false,
+      false,
+      threads,
+      SearchOutput::Split,
+      insight::utils::MockEmitter::default(),
+    )
+    .await?;
+    assert_eq!(match_rows, "2");
+    assert_eq!(
+      std::fs::read_to_string(&output_path)?,
+      "id,phone,backup,_matched_columns\n\
+       1,13800001111,,phone\n\
+       2,13900002222,13800001111,backup\n"
+    );
+    assert_eq!(
+      std::fs::read_to_string(temp_dir.path().join("phones_search_unmatched.csv"))?,
+      "id,phone,backup,_matched_columns\n3,,13700003333,\n"
+    );
+
+    let match_rows = filters_columns::search_columns(
+      &path,
+      "regex",
+      r"^\d+$",
+      ColumnScope::All,
+      false,
+      0,
+      true,
+      false,
+      false,
+      threads,
+      SearchOutput::Rows,
+      insight::utils::MockEmitter::default(),
+    )
+    .await?;
+    assert_eq!(match_rows, "1");
+    assert_eq!(
+      std::fs::read_to_string(&output_path)?,
+      "id,phone,backup\n2,13900002222,13800001111\n"
+    );
+
+    temp_dir.close()?;
+  }
+
+  let temp_dir = tempfile::TempDir::new()?;
+  let path = temp_dir.path().join("phones.csv");
+  std::fs::write(&path, "id,phone\n1,2\n")?;
+  let err = filters_columns::search_columns(
+    &path,
+    "gt",
+    "1",
+    ColumnScope::Any,
+    false,
+    0,
+    true,
+    false,
+    false,
+    1,
+    SearchOutput::Rows,
+    insight::utils::MockEmitter::default(),
+  )
+  .await;
+  assert!(err.is_err());
+
+  Ok(temp_dir.close()?)
+}
+
+#[tokio::test]
+async fn test_lookup_search() -> anyhow::Result<()> {
+  let temp_dir = tempfile::TempDir::new()?;
+  let lookup_path = temp_dir.path().join("ids.csv");
+  std::fs::write(&lookup_path, "id,note\n Tom ,a\nSandy,b\nTom,c\n,d\n")?;
+  let values = read_lookup_values(&lookup_path, "id", 0)?;
+  assert_eq!(values, vec!["Tom", "Sandy"]);
+  let titled_path = temp_dir.path().join("titled.csv");
+  std::fs::write(&titled_path, "exported ids\nid,note\nJerry,a\n")?;
+  assert_eq!(read_lookup_values(&titled_path, "id", 1)?, vec!["Jerry"]);
+
+  let xlsx_path = temp_dir.path().join("ids.xlsx");
+  let mut workbook = rust_xlsxwriter::Workbook::new();
+  let sheet = workbook.add_worksheet();
+  sheet.write_string(0, 0, "code")?;
+  sheet.write_string(1, 0, "Pat")?;
+  sheet.write_number(2, 0, 4)?;
+  workbook.save(&xlsx_path)?;
+  assert_eq!(read_lookup_values(&xlsx_path, "code", 0)?, vec!["Pat", "4"]);
+
+  let cases = [
+    (
+      "equal",
+      values.clone(),
+      vec!["Tom,18,male", "Sandy,24,female"],
+    ),
+    (
+      "not_equal",
+      values.clone(),
+      vec!["Jerry,19,male", "Patrick,4,male"],
+    ),
+    (
+      "contains",
+      vec!["at".to_string(), "erry".to_string()],
+      vec!["Jerry,19,male", "Patrick,4,male"],
+    ),
+    (
+      "starts_with",
+      vec!["at".to_string(), "Sa".to_string()],
+      vec!["Sandy,24,female"],
+    ),
+    (
+      "not_starts_with",
+      vec!["T".to_string(), "J".to_string()],
+      vec!["Patrick,4,male", "Sandy,24,female"],
+    ),
+  ];
+  for threads in [1, 2] {
+    for (mode, values, expected) in &cases {
+      let (temp_dir, rdr, wtr, output_path, path) = create_temp_csv().await?;
+      let mut opts = CsvOptions::new(path);
+      opts.set_skiprows(1);
+      let match_rows = filters::lookup(
+        rdr,
+        wtr,
+        opts,
+        "name".to_string(),
+        LookupMatcher::new(mode, values.clone())?,
+        false,
+        Some(threads),
+        insight::utils::MockEmitter::default(),
+      )
+      .await?;
+      assert_eq!(match_rows, expected.len().to_string(), "{mode}");
+      let context = std::fs::read_to_string(output_path)?;
+      let result: Vec<&str> = context.trim().split('\n').skip(1).collect();
+      assert_eq!(&result, expected, "{mode}");
+      temp_dir.close()?;
+    }
+  }
+  assert!(LookupMatcher::new("regex", values).is_err());
+
+  Ok(temp_dir.close()?)
+}
+
+#[tokio::test]
+async fn test_date_search() -> anyhow::Result<()> {
+  let cases = [
+    ("date_before", "2024-03-10", Unparsable::Exclude, vec!["a"]),
+    (
+      "date_after",
+      "2024-03-10",
+      Unparsable::Exclude,
+      vec!["b", "c"],
+    ),
+    (
+      "date_between",
+      "2024-06-30|2024-03-05",
+      Unparsable::Exclude,
+      vec!["a", "b", "c"],
+    ),
+    (
+      "date_same_month",
+      "2024-03-01",
+      Unparsable::Exclude,
+      vec!["a", "b"],
+    ),
+    (
+      "date_same_quarter",
+      "2024/5/1",
+      Unparsable::Exclude,
+      vec!["c"],
+    ),
+    (
+      "date_same_year",
+      "2024-01-01",
+      Unparsable::Include,
+      vec!["a", "b", "c", "d"],
+    ),
+    (
+      "date_before",
+      "2024-03-10",
+      Unparsable::Include,
+      vec!["a", "d"],
+    ),
+  ];
+  for threads in [1, 2] {
+    for (mode, condition, unparsable, expected) in &cases {
+      let temp_dir = tempfile::TempDir::new()?;
+      let path = temp_dir.path().join("dates.csv");
+      std::fs::write(
+        &path,
+        "name,date\na,2024/3/5\nb,2024-03-15\nc,2024-06-30 10:00:00\nd,n/a\n",
+      )?;
+      let opts = CsvOptions::new(path.to_string_lossy().to_string());
+      let (_sep, reader) = opts.skiprows_and_delimiter()?;
+      let config = insight::io::csv::config::CsvConfigBuilder::new().build();
+      let output_path = temp_dir.path().join("dates_search.csv");
+      let match_rows = filters::date_search(
+        config.build_reader(reader),
+        config.build_writer(&output_path)?,
+        opts,
+        "date".to_string(),
+        DateMatcher::new(mode, condition, None, *unparsable)?,
+        false,
+        Some(threads),
+        insight::utils::MockEmitter::default(),
+      )
+      .await?;
+      assert_eq!(match_rows, expected.len().to_string(), "{mode} {condition}");
+      let context = std::fs::read_to_string(output_path)?;
+      let names: Vec<&str> = context
+        .trim()
+        .split('\n')
+        .skip(1)
+        .map(|line| line.split(',').next().unwrap())
+        .collect();
+      assert_eq!(&names, expected, "{mode} {condition}");
+      temp_dir.close()?;
+    }
+  }
+
+  // 指定格式时不再自动识别
+  let matcher = DateMatcher::new(
+    "date_after",
+    "05/03/2024",
+    Some("%d/%m/%Y"),
+    Unparsable::Exclude,
+  )?;
+  assert!(matcher.is_match("06/03/2024"));
+  assert!(!matcher.is_match("04/03/2024"));
+  assert!(!matcher.is_match("2024-03-06"));
+
+  let today = chrono::Local::now().date_naive();
+  let day = |n: i64| {
+    (today - chrono::Duration::days(n))
+      .format("%Y-%m-%d")
+      .to_string()
+  };
+  let matcher = DateMatcher::new("date_last_days", "7", None, Unparsable::Exclude)?;
+  assert!(matcher.is_match(&day(0)));
+  assert!(matcher.is_match(&day(7)));
+  assert!(!matcher.is_match(&day(8)));
+  assert!(!matcher.is_match(&day(-1)));
+
+  assert!(DateMatcher::new("date_between", "2024-01-01", None, Unparsable::Exclude).is_err());
+  assert!(DateMatcher::new("date_before", "soon", None, Unparsable::Exclude).is_err());
+
+  // 条件链中的日期格式和无法解析的值
+  let temp_dir = tempfile::TempDir::new()?;
+  let path = temp_dir.path().join("dates.csv");
+  std::fs::write(
+    &path,
+    "name,date\na,05/03/2024\nb,20/03/2024\nc,n/a\nd,01/04/2024\n",
+  )?;
+  let configs: Vec<insight::cmd::search::perform::ColumnConfig> =
+    serde_json::from_value(serde_json::json!([
+      {
+        "column": "date",
+        "mode": "date_before",
+        "condition": "15/03/2024",
+        "date_format": "%d/%m/%Y",
+        "unparsable": "include",
+      },
+      { "column": "name", "mode": "not_equal", "condition": "d" },
+    ]))?;
+  let match_rows = filters_chain::search_with_chain(
+    path.to_string_lossy().to_string(),
+    configs,
+    vec!["and".to_string()],
+    0,
+    true,
+    false,
+    false,
+    1,
+    SearchOutput::Rows,
+    insight::utils::MockEmitter::default(),
+  )
+  .await?;
+  assert_eq!(match_rows, "2");
+  assert_eq!(
+    std::fs::read_to_string(temp_dir.path().join("dates_search.csv"))?,
+    "name,date\na,05/03/2024\nc,n/a\n"
+  );
+  temp_dir.close()?;
+
+  Ok(())
+}
+
+#[tokio::test]
+async fn test_search_chain_matchers() -> anyhow::Result<()> {
+  let temp_dir = tempfile::TempDir::new()?;
+  let path = temp_dir.path().join("chain.csv");
+  std::fs::write(&path, "name,age\nab1,9\nab2,30\nxy3,40\n")?;
+  let search = |configs: serde_json::Value| {
+    let path = path.clone();
+    async move {
+      let configs: Vec<insight::cmd::search::perform::ColumnConfig> =
+        serde_json::from_value(configs)?;
+      filters_chain::search_with_chain(
+        path.to_string_lossy().to_string(),
+        configs,
+        vec!["and".to_string()],
+        0,
+        true,
+        false,
+        false,
+        1,
+        SearchOutput::Rows,
+        insight::utils::MockEmitter::default(),
+      )
+      .await
+    }
+  };
+
+  let match_rows = search(serde_json::json!([
+    { "column": "name", "mode": "regex", "condition": "^ab\\d$" },
+    { "column": "age", "mode": "gt", "condition": "10" },
+  ]))
+  .await?;
+  assert_eq!(match_rows, "1");
+
+  // 无效的正则在读取前报错, 而不是每行都不匹配
+  let err = search(serde_json::json!([
+    { "column": "name", "mode": "regex", "condition": "(ab" },
+    { "column": "age", "mode": "gt", "condition": "10" },
+  ]))
+  .await;
+  assert!(err.is_err());
+  temp_dir.close()?;
+
+  Ok(())
+}
+
+#[test]
+fn test_edit_distance() {
+  let chars = |s: &str| s.chars().collect::<Vec<_>>();
+  let lev = |a: &str, b: &str| levenshtein(&chars(a), &chars(b));
+  let dl = |a: &str, b: &str| damerau_levenshtein(&chars(a), &chars(b));
+
+  assert_eq!(lev("kitten", "sitting"), 3);
+  assert_eq!(lev("", "abc"), 3);
+  assert_eq!(lev("张三", "张三"), 0);
+  assert_eq!(lev("ca", "ac"), 2);
+  assert_eq!(dl("ca", "ac"), 1);
+  assert_eq!(dl("Patirck", "Patrick"), 1);
+  assert_eq!(dl("kitten", "sitting"), 3);
+  assert_eq!(dl("abc", ""), 3);
+}
+
+#[tokio::test]
+async fn test_fuzzy_search() -> anyhow::Result<()> {
+  let options = |metric, max_distance, threshold| FuzzyOptions {
+    metric,
+    max_distance,
+    threshold,
+    ..Default::default()
+  };
+  let cases = [
+    (
+      "Jery|Sandi",
+      options(FuzzyMetric::Levenshtein, 1, 0.0),
+      vec!["Jerry,19,male,0.8000", "Sandy,24,female,0.8000"],
+    ),
+    ("Patirck", options(FuzzyMetric::Levenshtein, 1, 0.0), vec![]),
+    (
+      "Patirck",
+      options(FuzzyMetric::Damerau, 1, 0.0),
+      vec!["Patrick,4,male,0.8571"],
+    ),
+    (
+      "Patrik",
+      options(FuzzyMetric::JaroWinkler, 0, 0.9),
+      vec!["Patrick,4,male,0.9714"],
+    ),
+    (
+      "tom",
+      FuzzyOptions {
+        max_distance: 0,
+        ignore_case: true,
+        ..Default::default()
+      },
+      vec!["Tom,18,male,1.0000"],
+    ),
+  ];
+  for threads in [1, 2] {
+    for (condition, options, expected) in &cases {
+      let (temp_dir, _, _, output_path, path) = create_temp_csv().await?;
+      let match_rows = fuzzy::search_fuzzy(
+        path,
+        "name".to_string(),
+        FuzzyMatcher::new(condition, *options)?,
+        true,
+        1,
+        true,
+        false,
+        false,
+        threads,
+        SearchOutput::Rows,
+        insight::utils::MockEmitter::default(),
+      )
+      .await?;
+      assert_eq!(match_rows, expected.len().to_string(), "{condition}");
+      let context = std::fs::read_to_string(output_path)?;
+      let mut lines = context.trim().split('\n');
+      assert_eq!(lines.next(), Some("name,age,gender,_fuzzy_score"));
+      assert_eq!(&lines.collect::<Vec<_>>(), expected, "{condition}");
+      temp_dir.close()?;
+    }
+  }
+
+  assert!(FuzzyMatcher::new("Tom", options(FuzzyMetric::JaroWinkler, 0, 1.5)).is_err());
+  assert!(FuzzyMatcher::new(" | ", FuzzyOptions::default()).is_err());
+
+  // search_chain 中的 fuzzy 选项
+  let (temp_dir, _, _, output_path, path) = create_temp_csv().await?;
+  let configs: Vec<insight::cmd::search::perform::ColumnConfig> =
+    serde_json::from_value(serde_json::json!([
+      {
+        "column": "name",
+        "mode": "fuzzy",
+        "condition": "Patirck|Tomm",
+        "fuzzy": { "metric": "damerau" },
+      },
+      { "column": "age", "mode": "gt", "condition": "10" },
+    ]))?;
+  let match_rows = filters_chain::search_with_chain(
+    path,
+    configs,
+    vec!["and".to_string()],
+    1,
+    true,
+    false,
+    false,
+    1,
+    SearchOutput::Rows,
+    insight::utils::MockEmitter::default(),
+  )
+  .await?;
+  assert_eq!(match_rows, "1");
+  assert_eq!(
+    std::fs::read_to_string(output_path)?,
+    "name,age,gender\nTom,18,male\n"
+  );
+
+  Ok(temp_dir.close()?)
+}
@@ -0,0 +1,235 @@
+use insight::{
+  cmd::search::{
+    generic::SearchOutput,
+    multi_file::{self, FilesOutput},
+    perform::ColumnConfig,
+  },
+  utils::{MockEmitter, expand_paths},
+};
+
+fn create_inputs(dir: &std::path::Path) -> anyhow::Result<()> {
+  std::fs::write(
+    dir.join("a.csv"),
+    "name,age,gender\nTom,18,male\nJerry,19,male\nSandy,24,female\n",
+  )?;
+  std::fs::write(
+    dir.join("b.csv"),
+    "\u{feff}name,age,gender\nPatrick,4,male\nTom,30,male\n",
+  )?;
+  std::fs::write(dir.join("c.csv"), "id,city\n1,x\n")?;
+  std::fs::write(dir.join("notes.txt"), "nothing")?;
+  Ok(())
+}
+
+#[tokio::test]
+async fn test_expand_paths() -> anyhow::Result<()> {
+  let temp_dir = tempfile::TempDir::new()?;
+  create_inputs(temp_dir.path())?;
+  let dir = temp_dir.path().to_string_lossy().to_string();
+
+  let names = |paths: Vec<std::path::PathBuf>| -> Vec<String> {
+    paths
+      .iter()
+      .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+      .collect()
+  };
+  assert_eq!(
+    names(expand_paths(&format!("{dir}/*.csv"))?),
+    vec!["a.csv", "b.csv", "c.csv"]
+  );
+  assert_eq!(names(expand_paths(&format!("{dir}/?.csv"))?).len(), 3);
+  assert_eq!(
+    names(expand_paths(&format!("{dir}/b.csv|{dir}/a*"))?),
+    vec!["b.csv", "a.csv"]
+  );
+  // 文件夹只包含数据文件, 不包含索引和搜索结果
+  std::fs::write(temp_dir.path().join("a.csv.idx"), "")?;
+  std::fs::write(temp_dir.path().join("a.csv.idx.meta"), "")?;
+  std::fs::write(temp_dir.path().join("a_search.csv"), "")?;
+  std::fs::write(temp_dir.path().join("b_search_rows.csv"), "")?;
+  std::fs::write(temp_dir.path().join("a_search_combined.csv"), "")?;
+  std::fs::write(temp_dir.path().join("d.csv.gz"), "")?;
+  // 只跳过同目录文件的搜索结果, 名字里带 _search 的数据文件照常搜索
+  std::fs::write(temp_dir.path().join("user_search_log.csv"), "")?;
+  std::fs::write(temp_dir.path().join("x_search.csv"), "")?;
+  assert_eq!(
+    names(expand_paths(&dir)?),
+    vec![
+      "a.csv",
+      "b.csv",
+      "c.csv",
+      "d.csv.gz",
+      "user_search_log.csv",
+      "x_search.csv"
+    ]
+  );
+  assert_eq!(names(expand_paths(&format!("{dir}/a*"))?), vec!["a.csv"]);
+  assert_eq!(
+    names(expand_paths(&format!("{dir}/*.txt"))?),
+    vec!["notes.txt"]
+  );
+  assert!(expand_paths(&format!("{dir}/*.xlsx")).is_err());
+
+  Ok(temp_dir.close()?)
+}
+
+#[tokio::test]
+async fn test_search_files_per_file() -> anyhow::Result<()> {
+  let temp_dir = tempfile::TempDir::new()?;
+  create_inputs(temp_dir.path())?;
+  let dir = temp_dir.path().to_string_lossy().to_string();
+
+  let emitter = MockEmitter::default();
+  let (success, err) = (emitter.success.clone(), emitter.err.clone());
+  let paths = expand_paths(&format!("{dir}/*.csv"))?;
+  let filter = multi_file::column_filter("name".to_string(), "equal", "Tom|Sandy")?;
+  let total = multi_file::search_files(
+    paths,
+    filter,
+    FilesOutput::PerFile(SearchOutput::Rows),
+    0,
+    true,
+    false,
+    2,
+    emitter,
+  )
+  .await?;
+  assert_eq!(total, "3");
+
+  assert_eq!(
+    std::fs::read_to_string(temp_dir.path().join("a_search.csv"))?,
+    "name,age,gender\nTom,18,male\nSandy,24,female\n"
+  );
+  assert_eq!(
+    std::fs::read_to_string(temp_dir.path().join("b_search.csv"))?,
+    "name,age,gender\nTom,30,male\n"
+  );
+  // c.csv has no name column, the other files are still searched
+  assert!(!temp_dir.path().join("c_search.csv").exists());
+  let success = success.lock().unwrap().clone();
+  assert!(success.contains("a.csv|2"));
+  assert!(success.contains("b.csv|1"));
+  assert!(err.lock().unwrap().starts_with("c.csv|"));
+
+  Ok(temp_dir.close()?)
+}
+
+#[tokio::test]
+async fn test_search_files_combined() -> anyhow::Result<()> {
+  let temp_dir = tempfile::TempDir::new()?;
+  create_inputs(temp_dir.path())?;
+  let dir = temp_dir.path().to_string_lossy().to_string();
+
+  let configs: Vec<ColumnConfig> = serde_json::from_value(serde_json::json!([
+    { "column": "gender", "mode": "equal", "condition": "male" },
+    { "column": "age", "mode": "gt", "condition": "10" },
+  ]))?;
+  let filter = multi_file::chain_filter(configs, vec!["and".to_string()])?;
+  let emitter = MockEmitter::default();
+  let err = emitter.err.clone();
+  let total = multi_file::search_files(
+    expand_paths(&format!("{dir}/a.csv|{dir}/c.csv|{dir}/b.csv"))?,
+    filter,
+    FilesOutput::Combined { row_number: true },
+    0,
+    true,
+    false,
+    4,
+    emitter,
+  )
+  .await?;
+  assert_eq!(total, "3");
+
+  assert_eq!(
+    std::fs::read_to_string(temp_dir.path().join("a_search_combined.csv"))?,
+    "_source_file,_source_row,name,age,gender\n\
+     a.csv,1,Tom,18,male\n\
+     a.csv,2,Jerry,19,male\n\
+     b.csv,2,Tom,30,male\n"
+  );
+  assert!(err.lock().unwrap().starts_with("c.csv|"));
+
+  // 表头不一致的文件不合并
+  std::fs::write(temp_dir.path().join("d.csv"), "name,age\nTom,40\n")?;
+  let filter = multi_file::column_filter("name".to_string(), "equal", "Tom")?;
+  let emitter = MockEmitter::default();
+  let err = emitter.err.clone();
+  let total = multi_file::search_files(
+    expand_paths(&format!("{dir}/b.csv|{dir}/d.csv"))?,
+    filter,
+    FilesOutput::Combined { row_number: false },
+    0,
+    true,
+    false,
+    1,
+    emitter,
+  )
+  .await?;
+  assert_eq!(total, "1");
+  assert_eq!(
+    std::fs::read_to_string(temp_dir.path().join("b_search_combined.csv"))?,
+    "_source_file,name,age,gender\nb.csv,Tom,30,male\n"
+  );
+  assert!(err.lock().unwrap().contains("d.csv|headers differ"));
+
+  Ok(temp_dir.close()?)
+}
+
+#[tokio::test]
+async fn test_search_files_frontend_modes() -> anyhow::Result<()> {
+  let temp_dir = tempfile::TempDir::new()?;
+  create_inputs(temp_dir.path())?;
+  let dir = temp_dir.path().to_string_lossy().to_string();
+
+  // *_multi 模式在多文件搜索中使用相同的谓词
+  for (mode, condition) in [
+    ("equal_multi", "Tom|Sandy"),
+    ("starts_with_multi", "To|San"),
+    ("contains_multi", "om|and"),
+    ("ends_with_multi", "om|dy"),
+  ] {
+    let filter = multi_file::column_filter("name".to_string(), mode, condition)?;
+    let total = multi_file::search_files(
+      expand_paths(&format!("{dir}/a.csv|{dir}/b.csv"))?,
+      filter,
+      FilesOutput::PerFile(SearchOutput::Count),
+      0,
+      true,
+      false,
+      2,
+      MockEmitter::default(),
+    )
+    .await?;
+    assert_eq!(total, "3", "{mode}");
+  }
+
+  let emitter = MockEmitter::default();
+  let success = emitter.success.clone();
+  let total = multi_file::search_files_lines(
+    expand_paths(&format!("{dir}/a.csv|{dir}/b.csv"))?,
+    r"^Tom,\d+".to_string(),
+    FilesOutput::PerFile(SearchOutput::Rows),
+    0,
+    emitter,
+  )
+  .await?;
+  assert_eq!(total, "2");
+  assert_eq!(
+    std::fs::read_to_string(temp_dir.path().join("a_search.csv"))?,
+    "Tom,18,male\n"
+  );
+  assert!(success.lock().unwrap().contains("b.csv|1"));
+  assert!(
+    multi_file::search_files_lines(
+      expand_paths(&format!("{dir}/a.csv"))?,
+      "Tom".to_string(),
+      FilesOutput::Combined { row_number: false },
+      0,
+      MockEmitter::default(),
+    )
+    .await
+    .is_err()
+  );
+
+  Ok(temp_dir.close()?)
+}
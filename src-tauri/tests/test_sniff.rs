@@ -0,0 +1,75 @@
+use insight::io::csv::{options::CsvOptions, sniff::sniff};
+
+#[test]
+fn test_sniff_delimiter_quote_aware() {
+  let dialect = sniff(b"\"a,b\";c;d\n1;2;3\n4;5;6\n", true);
+  assert_eq!(dialect.delimiter, b';');
+  assert_eq!(dialect.fields, 3);
+  assert_eq!(dialect.preamble_rows, 0);
+  assert!(dialect.has_header);
+
+  let dialect = sniff(b"name\tage\nPatrick\t4\nSandy\t24\n", true);
+  assert_eq!(dialect.delimiter, b'\t');
+
+  // 纯数字首行不是 header
+  let dialect = sniff(b"1,2,3\n4,5,6\n7,8,9\n", true);
+  assert!(!dialect.has_header);
+}
+
+#[test]
+fn test_sniff_quote_and_escape() {
+  let dialect = sniff(b"'name','note'\n'Tom','it\\'s ok'\n'Jerry','a, b'\n", true);
+  assert_eq!(dialect.delimiter, b',');
+  assert_eq!(dialect.quote, b'\'');
+  assert_eq!(dialect.escape, Some(b'\\'));
+  assert!(!dialect.double_quote);
+
+  let dialect = sniff(b"name,note\nTom,\"say \"\"hi\"\"\"\nJerry,\"a, b\"\n", true);
+  assert_eq!(dialect.quote, b'"');
+  assert_eq!(dialect.escape, None);
+  assert!(dialect.double_quote);
+}
+
+#[tokio::test]
+async fn test_sniff_preamble() -> anyhow::Result<()> {
+  let temp_dir = tempfile::TempDir::new()?;
+  let file_path = temp_dir.path().join("input.csv");
+  std::fs::write(
+    &file_path,
+    "Sales report, 2024\nGenerated by system\n\nid;name;amount\n1;Tom;10\n2;Jerry;20\n3;Sandy;30\n",
+  )?;
+
+  let opts = CsvOptions::new(&file_path);
+  let dialect = opts.dialect()?;
+  assert_eq!(dialect.delimiter, b';');
+  assert_eq!(dialect.preamble_rows, 3);
+  assert!(dialect.has_header);
+
+  let mut opts = CsvOptions::new(&file_path);
+  opts.set_skiprows(dialect.preamble_rows);
+  assert_eq!(opts.from_headers()?, vec!["id", "name", "amount"]);
+  assert_eq!(opts.dialect()?.preamble_rows, 0);
+
+  let mut rdr = opts.skiprows_reader()?;
+  assert_eq!(rdr.records().count(), 3);
+
+  Ok(temp_dir.close()?)
+}
+
+#[tokio::test]
+async fn test_skiprows_reader_uses_sniffed_quote() -> anyhow::Result<()> {
+  let temp_dir = tempfile::TempDir::new()?;
+  let file_path = temp_dir.path().join("input.csv");
+  std::fs::write(&file_path, "'a|b'|c\n'1|2'|3\n'4'|'5|6'\n")?;
+
+  let opts = CsvOptions::new(&file_path);
+  assert_eq!(opts.detect_separator()?, b'|');
+
+  let mut rdr = opts.skiprows_reader()?;
+  assert_eq!(rdr.headers()?, vec!["a|b", "c"]);
+  let rows: Vec<csv::StringRecord> = rdr.records().collect::<Result<_, _>>()?;
+  assert_eq!(rows[0], vec!["1|2", "3"]);
+  assert_eq!(rows[1], vec!["4", "5|6"]);
+
+  Ok(temp_dir.close()?)
+}
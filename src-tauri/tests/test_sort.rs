@@ -56,3 +56,45 @@ async fn test_sort() -> anyhow::Result<()> {
 
   Ok(temp_dir.close()?)
 }
+
+#[tokio::test]
+async fn test_sort_on_disk() -> anyhow::Result<()> {
+  let temp_dir = tempfile::TempDir::new()?;
+
+  // 相同的 key 保持原来的顺序
+  let mut rows: Vec<(i64, usize)> = (0..300).map(|i| ((i * 37 % 50) - 20, i as usize)).collect();
+  let mut data = String::from("key,seq\n");
+  for (key, seq) in &rows {
+    data.push_str(&format!("{key},{seq}\n"));
+  }
+  let file_path = temp_dir.path().join("input.csv");
+  std::fs::write(&file_path, &data)?;
+  let output_path = temp_dir.path().join("input_sort.csv");
+
+  rows.sort_by_key(|(key, _)| *key);
+  let ascending: Vec<String> = rows.iter().map(|(k, s)| format!("{k},{s}")).collect();
+  rows.sort_by_key(|(key, _)| std::cmp::Reverse(*key));
+  let descending: Vec<String> = rows.iter().map(|(k, s)| format!("{k},{s}")).collect();
+
+  for budget in [1, 500, 1 << 20] {
+    for (reverse, expected) in [(false, &ascending), (true, &descending)] {
+      insight::cmd::sort::sort_csv_with(
+        &file_path,
+        "key".to_string(),
+        true,
+        reverse,
+        true,
+        false,
+        0,
+        Some(insight::utils::MemoryPlan::OnDisk(budget)),
+      )
+      .await?;
+
+      let context = std::fs::read_to_string(&output_path)?;
+      let result: Vec<&str> = context.lines().skip(1).collect();
+      assert_eq!(&result, expected, "budget {budget}, reverse {reverse}");
+    }
+  }
+
+  Ok(temp_dir.close()?)
+}
@@ -25,7 +25,13 @@ async fn test_split_rows() -> anyhow::Result<()> {
   let file_stem = file_path.file_stem().unwrap().to_str().unwrap();
   let output_path = format!("{parent_path}/{file_stem}");
 
-  insight::cmd::split::sequential_split_rows(csv_options, size.try_into()?, &output_path).await?;
+  insight::cmd::split::sequential_split_rows(
+    csv_options,
+    size.try_into()?,
+    &output_path,
+    insight::io::writer::OutputOptions::default(),
+  )
+  .await?;
 
   let output_files: Vec<_> = std::fs::read_dir(temp_dir.path())?
     .filter_map(Result::ok)
@@ -82,8 +88,13 @@ async fn test_split_lines() -> anyhow::Result<()> {
   let file_stem = file_path.file_stem().unwrap().to_str().unwrap();
   let output_path = format!("{parent_path}/{file_stem}");
 
-  insight::cmd::split::split_lines(file_path.to_string_lossy().to_string(), 2, &output_path)
-    .await?;
+  insight::cmd::split::split_lines(
+    file_path.to_string_lossy().to_string(),
+    2,
+    &output_path,
+    insight::io::writer::OutputOptions::default(),
+  )
+  .await?;
 
   let output_files: Vec<_> = std::fs::read_dir(temp_dir.path())?
     .filter_map(Result::ok)
@@ -1,7 +1,7 @@
 fn create_temp_csv() -> anyhow::Result<(
   tempfile::TempDir,
   csv::Reader<std::io::BufReader<Box<dyn std::io::Read + Send>>>,
-  csv::Writer<std::io::BufWriter<std::fs::File>>,
+  csv::Writer<insight::io::writer::OutputWriter>,
   String,
 )> {
   use std::io::Write;
@@ -35,9 +35,7 @@ fn create_temp_csv() -> anyhow::Result<(
   csv_options.set_skiprows(2);
   let rdr = csv_options.skiprows_reader()?;
 
-  let output_file = std::fs::File::create(&output_path)?;
-  let buf_writer = std::io::BufWriter::with_capacity(256_000, output_file);
-  let wtr = csv::WriterBuilder::new().from_writer(buf_writer);
+  let wtr = csv::WriterBuilder::new().from_writer(insight::io::writer::create(&output_path)?);
 
   Ok((temp_dir, rdr, wtr, output_path))
 }
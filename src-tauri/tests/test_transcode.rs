@@ -0,0 +1,73 @@
+use insight::cmd::convert::transcode::{Unmappable, transcode};
+
+#[tokio::test]
+async fn test_transcode_to_gbk_and_utf16() -> anyhow::Result<()> {
+  let temp_dir = tempfile::TempDir::new()?;
+  let file_path = temp_dir.path().join("input.csv");
+  std::fs::write(&file_path, "name,age\n汤姆,18\n杰瑞,19\n")?;
+
+  let report = transcode(
+    &file_path,
+    Some("UTF-8"),
+    "GB18030",
+    false,
+    Unmappable::Error,
+  )
+  .await?;
+  assert_eq!(report.lines, 3);
+  assert_eq!(report.lossy, 0);
+  let output = std::fs::read(temp_dir.path().join("input_gb18030.csv"))?;
+  let (decoded, _, had_errors) = encoding_rs::GB18030.decode(&output);
+  assert!(!had_errors);
+  assert_eq!(decoded, "name,age\n汤姆,18\n杰瑞,19\n");
+
+  transcode(&file_path, None, "UTF-16LE", true, Unmappable::Error).await?;
+  let output = std::fs::read(temp_dir.path().join("input_utf-16le.csv"))?;
+  assert_eq!(&output[..4], b"\xFF\xFEn\x00");
+  let (decoded, _, _) = encoding_rs::UTF_16LE.decode(&output);
+  assert_eq!(decoded, "name,age\n汤姆,18\n杰瑞,19\n");
+
+  Ok(temp_dir.close()?)
+}
+
+#[tokio::test]
+async fn test_transcode_unmappable() -> anyhow::Result<()> {
+  let temp_dir = tempfile::TempDir::new()?;
+  let file_path = temp_dir.path().join("input.csv");
+  std::fs::write(&file_path, "name,note\nTom,ok😀\nJerry,東京\n")?;
+  let output_path = temp_dir.path().join("input_shift_jis.csv");
+
+  let err = transcode(&file_path, None, "Shift_JIS", false, Unmappable::Error).await;
+  assert!(err.unwrap_err().to_string().contains("line 2"));
+  assert!(!output_path.exists());
+
+  let report = transcode(&file_path, None, "Shift_JIS", false, Unmappable::Replace).await?;
+  assert_eq!(report.lossy, 1);
+  let output = std::fs::read(&output_path)?;
+  let (decoded, _, _) = encoding_rs::SHIFT_JIS.decode(&output);
+  assert_eq!(decoded, "name,note\nTom,ok?\nJerry,東京\n");
+
+  let report = transcode(&file_path, None, "Shift_JIS", false, Unmappable::HtmlEscape).await?;
+  assert_eq!(report.lossy, 1);
+  let output = std::fs::read(&output_path)?;
+  let (decoded, _, _) = encoding_rs::SHIFT_JIS.decode(&output);
+  assert_eq!(decoded, "name,note\nTom,ok&#128512;\nJerry,東京\n");
+
+  assert!(
+    transcode(
+      &file_path,
+      None,
+      "not-an-encoding",
+      false,
+      Unmappable::Error
+    )
+    .await
+    .is_err()
+  );
+
+  assert_eq!("html_escape".parse::<Unmappable>()?, Unmappable::HtmlEscape);
+  let err = "ignore".parse::<Unmappable>().unwrap_err();
+  assert!(err.to_string().contains("unknown unmappable policy"));
+
+  Ok(temp_dir.close()?)
+}
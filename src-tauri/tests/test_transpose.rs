@@ -52,3 +52,30 @@ async fn test_multipass() -> anyhow::Result<()> {
 
   Ok(temp_dir.close()?)
 }
+
+#[tokio::test]
+async fn test_streaming() -> anyhow::Result<()> {
+  let (temp_dir, path, output_path) = create_temp_csv()?;
+
+  // budget 1: every row is spilled to disk
+  for budget in [1, 1 << 20] {
+    insight::cmd::transpose::streaming_transpose(&path, true, 0, budget).await?;
+    let context = std::fs::read_to_string(&output_path)?;
+    let result = context.trim().split('\n').collect::<Vec<_>>();
+    let expected = vec!["Tom,Jerry,Patrick", "18,19,4", "female,male,male"];
+    assert_eq!(expected, result);
+  }
+
+  std::fs::write(&path, "a,b\n\"x,1\",\"multi\nline\"\n2\n3,\"\"\n")?;
+  insight::cmd::transpose::streaming_transpose(&path, true, 0, 4).await?;
+  let context = std::fs::read_to_string(&output_path)?;
+  assert_eq!(context, "\"x,1\",2,3\n\"multi\nline\",,\n");
+
+  std::fs::write(&path, "a,b\n1,2\n3,4,5\n")?;
+  let err = insight::cmd::transpose::streaming_transpose(&path, true, 0, 4)
+    .await
+    .unwrap_err();
+  assert!(err.to_string().contains("3 fields"), "{err}");
+
+  Ok(temp_dir.close()?)
+}
@@ -0,0 +1,85 @@
+use std::io::{Read, Write};
+
+use insight::io::{
+  compress::Compression,
+  csv::config::CsvConfigBuilder,
+  writer::{self, Finish, OutputOptions, OutputWriter},
+};
+
+#[test]
+fn test_atomic_output_only_visible_after_finish() -> anyhow::Result<()> {
+  let temp_dir = tempfile::TempDir::new()?;
+  let output_path = temp_dir.path().join("input_search.csv");
+
+  let mut wtr = writer::create(&output_path)?;
+  writeln!(wtr, "name,age")?;
+  assert!(!output_path.exists());
+  wtr.finish()?;
+  assert_eq!(std::fs::read_to_string(&output_path)?, "name,age\n");
+
+  // 未调用finish (例如中途出错) 时不留下半成品, 也不覆盖已有输出
+  let mut wtr = writer::create(&output_path)?;
+  writeln!(wtr, "half written")?;
+  drop(wtr);
+  assert_eq!(std::fs::read_to_string(&output_path)?, "name,age\n");
+  assert_eq!(std::fs::read_dir(temp_dir.path())?.count(), 1);
+
+  Ok(temp_dir.close()?)
+}
+
+#[test]
+fn test_compressed_output() -> anyhow::Result<()> {
+  let temp_dir = tempfile::TempDir::new()?;
+  let output_path = temp_dir.path().join("input_select.csv");
+
+  let config = CsvConfigBuilder::new()
+    .compression(Compression::Gzip)
+    .build();
+  let mut wtr = config.build_writer(&output_path)?;
+  wtr.write_record(["name", "age"])?;
+  wtr.write_record(["汤姆", "18"])?;
+  wtr.finish()?;
+
+  let gz_path = temp_dir.path().join("input_select.csv.gz");
+  assert!(!output_path.exists());
+  let mut content = String::new();
+  flate2::read::GzDecoder::new(std::fs::File::open(&gz_path)?).read_to_string(&mut content)?;
+  assert_eq!(content, "name,age\n汤姆,18\n");
+
+  let opts = OutputOptions {
+    compression: Compression::Zstd,
+    atomic: false,
+    stdout: false,
+  };
+  let mut wtr = OutputWriter::create(temp_dir.path().join("out.csv.zst"), opts)?;
+  wtr.write_all(b"a,b\n1,2\n")?;
+  assert_eq!(
+    wtr.path(),
+    Some(temp_dir.path().join("out.csv.zst").as_path())
+  );
+  wtr.finish()?;
+  let data = zstd::decode_all(std::fs::File::open(temp_dir.path().join("out.csv.zst"))?)?;
+  assert_eq!(data, b"a,b\n1,2\n");
+
+  assert!(
+    OutputWriter::create(
+      temp_dir.path().join("out.csv"),
+      OutputOptions {
+        compression: Compression::Zip,
+        ..OutputOptions::default()
+      }
+    )
+    .is_err()
+  );
+
+  assert_eq!(
+    OutputOptions::from_arg(Some("gzip"))?.compression,
+    Compression::Gzip
+  );
+  assert_eq!(OutputOptions::from_arg(None)?, OutputOptions::default());
+  assert!(OutputOptions::from_arg(Some("-"))?.stdout);
+  assert!(OutputOptions::from_arg(Some("-"))?.require_files().is_err());
+  assert!(OutputOptions::from_arg(Some("bz2")).is_err());
+
+  Ok(temp_dir.close()?)
+}
@@ -0,0 +1,105 @@
+use insight::cmd::convert::{csv_to_xml::csv_to_xml, xml_to_csv::xml_to_csv};
+
+#[tokio::test]
+async fn test_xml2csv() -> anyhow::Result<()> {
+  let temp_dir = tempfile::TempDir::new()?;
+  let file_path = temp_dir.path().join("input.xml");
+  std::fs::write(
+    &file_path,
+    r#"<?xml version="1.0" encoding="UTF-8"?>
+<ns:Declaration xmlns:ns="urn:customs">
+  <Header><Id>H1</Id></Header>
+  <ns:Invoices>
+    <ns:Invoice no="001" type="VAT">
+      <Buyer code="B1"><Name>Tom &amp; Jerry</Name></Buyer>
+      <Item><Price>1.5</Price></Item>
+      <Item><Price>2</Price></Item>
+      <Note><![CDATA[a < b]]></Note>
+    </ns:Invoice>
+    <ns:Invoice no="002">
+      <Buyer code="B2"><Name>Spike</Name></Buyer>
+      <Item><Price>3</Price></Item>
+      <Remark/>
+    </ns:Invoice>
+  </ns:Invoices>
+</ns:Declaration>
+"#,
+  )?;
+
+  let rows = xml_to_csv(
+    file_path.to_str().unwrap(),
+    "Invoices/Invoice",
+    ",".to_string(),
+  )
+  .await?;
+  assert_eq!(rows, 2);
+
+  let output = std::fs::read_to_string(temp_dir.path().join("input_xml.csv"))?;
+  assert_eq!(
+    output,
+    "@no,@type,Buyer/@code,Buyer/Name,Item/Price,Item_2/Price,Note,Remark\n\
+     001,VAT,B1,Tom & Jerry,1.5,2,a < b,\n\
+     002,,B2,Spike,3,,,\n"
+  );
+
+  assert!(
+    xml_to_csv(
+      file_path.to_str().unwrap(),
+      "/Invoices/Invoice",
+      ",".to_string()
+    )
+    .await
+    .is_err()
+  );
+
+  Ok(temp_dir.close()?)
+}
+
+#[tokio::test]
+async fn test_csv2xml() -> anyhow::Result<()> {
+  let temp_dir = tempfile::TempDir::new()?;
+  let file_path = temp_dir.path().join("input.csv");
+  std::fs::write(
+    &file_path,
+    "id,name,unit price,note\n1,Tom & Jerry,1.5,\n2,<Spike>,3,\"a\"\"b\"\n",
+  )?;
+
+  let rows = csv_to_xml(&file_path, 0, "Invoices", "Invoice", "id").await?;
+  assert_eq!(rows, 2);
+
+  let output = std::fs::read_to_string(temp_dir.path().join("input_xml.xml"))?;
+  assert_eq!(
+    output,
+    r#"<?xml version="1.0" encoding="UTF-8"?>
+<Invoices>
+  <Invoice id="1">
+    <name>Tom &amp; Jerry</name>
+    <unit_price>1.5</unit_price>
+    <note/>
+  </Invoice>
+  <Invoice id="2">
+    <name>&lt;Spike&gt;</name>
+    <unit_price>3</unit_price>
+    <note>a&quot;b</note>
+  </Invoice>
+</Invoices>
+"#
+  );
+
+  // 输出可以再转回 CSV
+  let xml_path = temp_dir.path().join("input_xml.xml");
+  xml_to_csv(xml_path.to_str().unwrap(), "Invoice", ",".to_string()).await?;
+  let output = std::fs::read_to_string(temp_dir.path().join("input_xml_xml.csv"))?;
+  assert_eq!(
+    output,
+    "@id,name,unit_price,note\n1,Tom & Jerry,1.5,\n2,<Spike>,3,\"a\"\"b\"\n"
+  );
+
+  assert!(
+    csv_to_xml(&file_path, 0, "r", "row", "missing")
+      .await
+      .is_err()
+  );
+
+  Ok(temp_dir.close()?)
+}
@@ -494,6 +494,88 @@ export const i18nMapping = {
     char: "CHAR",
     insertLeft: "Left",
     insertRight: "Right",
+    transcodeCsv: "Transcode",
+    fromEncoding: "FROM ENCODING",
+    toEncoding: "TO ENCODING",
+    unmappable: "UNMAPPABLE CHARACTERS",
+    replaceChar: "Replace (?)",
+    htmlEscape: "HTML Escape",
+    lossyChars: "characters replaced",
+    abort: "Abort",
+    fixMojibake: "Mojibake",
+    minConfidence: "MIN CONFIDENCE",
+    repairedCells: "cells repaired",
+    csv2Dbf: "Csv2Dbf",
+    csvToDbf: "CSV to DBF",
+    fieldTypes: "FIELD TYPES",
+    fieldTypesPlaceholder: "empty infers, e.g. name:C(20)|amount:N(12,2)",
+    overflow: "OVERFLOW",
+    truncate: "Truncate",
+    codepage: "CODEPAGE",
+    truncatedCells: "cells truncated",
+    csv2Html: "Csv2Html",
+    csv2Md: "Csv2Md",
+    csvToHtml: "CSV to HTML",
+    csvToMd: "CSV to Markdown",
+    tableColumns: "COLUMNS",
+    tableColumnsPlaceholder: "empty keeps all, e.g. name|age",
+    rowLimit: "ROW LIMIT (0 = ALL)",
+    xml2Csv: "Xml2Csv",
+    csv2Xml: "Csv2Xml",
+    xmlToCsv: "XML to CSV",
+    csvToXml: "CSV to XML",
+    recordPath: "RECORD PATH",
+    recordPathPlaceholder: "e.g. Invoices/Invoice",
+    rootElement: "ROOT ELEMENT",
+    rowElement: "ROW ELEMENT",
+    attributeColumns: "ATTRIBUTE COLUMNS",
+    attributeColumnsPlaceholder: "e.g. id|type, * for all",
+    csv2Arrow: "Csv2Arrow",
+    arrow2Csv: "Arrow2Csv",
+    csvToArrow: "CSV to Arrow",
+    arrowToCsv: "Arrow to CSV",
+    batchSize: "BATCH SIZE",
+    inferTypes: "INFER TYPES",
+    inferRows: "INFER ROWS (0 = ALL)",
+    indexHealth: "Health",
+    indexStatus: "index",
+    indexHealthFailed: "Index health check failed:",
+    columnIndex: "COLUMN INDEX (speeds up equal and starts_with searches)",
+    createColumnIndexDone: "Create column index done, elapsed time:",
+    dropColumnIndex: "Drop",
+    droppedColumnIndexes: "Dropped column indexes:",
+    dropColumnIndexFailed: "Drop column index failed:",
+    searchOutput: "OUTPUT",
+    matchedRows: "Matched rows",
+    countOnly: "Count only",
+    rowNumbers: "Row numbers",
+    splitMatched: "Matched + unmatched",
+    lookupList: "LOOKUP LIST (values from a column of another CSV or Excel file)",
+    lookupNone: "No lookup file, the condition below is used",
+    lookupColumnPlaceholder: "Lookup column, empty uses the search column",
+    lookupModeUnsupported: "A lookup list supports equal, contains and starts_with (and their not_ modes)",
+    lookupSkiprows: "skiprows",
+    dateBefore: "date_before",
+    dateAfter: "date_after",
+    dateBetween: "date_between",
+    dateLastDays: "date_last_days",
+    dateSameMonth: "date_same_month",
+    dateSameQuarter: "date_same_quarter",
+    dateSameYear: "date_same_year",
+    dateOptions: "DATE FORMAT & UNPARSABLE CELLS",
+    dateFormatPlaceholder: "Date format, empty auto-detects, e.g. %d/%m/%Y",
+    excludeUnparsable: "Exclude",
+    includeUnparsable: "Include",
+    fuzzy: "fuzzy",
+    fuzzyOptions: "FUZZY MATCH",
+    levenshtein: "Levenshtein",
+    damerau: "Damerau-Levenshtein",
+    jaroWinkler: "Jaro-Winkler",
+    maxDistance: "Max distance",
+    minSimilarity: "Min similarity",
+    comparePinyin: "Pinyin",
+    ignoreCase: "Ignore case",
+    writeFuzzyScore: "Score column",
   },
   zh: {
     locale: "语言",
@@ -969,6 +1051,88 @@ export const i18nMapping = {
     char: "字符",
     insertLeft: "左",
     insertRight: "右",
+    transcodeCsv: "转换编码",
+    fromEncoding: "源编码",
+    toEncoding: "目标编码",
+    unmappable: "无法映射的字符",
+    replaceChar: "替换为 ?",
+    htmlEscape: "HTML 转义",
+    lossyChars: "个字符被替换",
+    abort: "中止",
+    fixMojibake: "修复乱码",
+    minConfidence: "最低置信度",
+    repairedCells: "个单元格已修复",
+    csv2Dbf: "CSV转DBF",
+    csvToDbf: "CSV转DBF",
+    fieldTypes: "字段类型",
+    fieldTypesPlaceholder: "留空自动推断, 例如: name:C(20)|amount:N(12,2)",
+    overflow: "超长处理",
+    truncate: "截断",
+    codepage: "代码页",
+    truncatedCells: "个单元格被截断",
+    csv2Html: "CSV转HTML",
+    csv2Md: "CSV转Markdown",
+    csvToHtml: "CSV转HTML",
+    csvToMd: "CSV转Markdown",
+    tableColumns: "列",
+    tableColumnsPlaceholder: "留空保留所有列, 例如: name|age",
+    rowLimit: "行数上限 (0 为全部)",
+    xml2Csv: "XML转CSV",
+    csv2Xml: "CSV转XML",
+    xmlToCsv: "XML转CSV",
+    csvToXml: "CSV转XML",
+    recordPath: "记录路径",
+    recordPathPlaceholder: "例如: Invoices/Invoice",
+    rootElement: "根元素",
+    rowElement: "行元素",
+    attributeColumns: "属性列",
+    attributeColumnsPlaceholder: "例如: id|type, * 为全部",
+    csv2Arrow: "CSV转Arrow",
+    arrow2Csv: "Arrow转CSV",
+    csvToArrow: "CSV转Arrow",
+    arrowToCsv: "Arrow转CSV",
+    batchSize: "批大小",
+    inferTypes: "推断类型",
+    inferRows: "推断行数 (0 为全部)",
+    indexHealth: "检查",
+    indexStatus: "索引",
+    indexHealthFailed: "索引检查失败:",
+    columnIndex: "列索引 (加速 equal 和 starts_with 搜索)",
+    createColumnIndexDone: "创建列索引完成, 耗时:",
+    dropColumnIndex: "删除",
+    droppedColumnIndexes: "已删除列索引:",
+    dropColumnIndexFailed: "删除列索引失败:",
+    searchOutput: "输出",
+    matchedRows: "匹配行",
+    countOnly: "仅计数",
+    rowNumbers: "行号",
+    splitMatched: "匹配 + 未匹配",
+    lookupList: "查找列表 (取另一个 CSV 或 Excel 文件的一列作为条件)",
+    lookupNone: "未选择查找文件, 使用下方条件",
+    lookupColumnPlaceholder: "查找列, 留空使用搜索列",
+    lookupModeUnsupported: "查找列表仅支持 equal, contains 和 starts_with (及其 not_ 模式)",
+    lookupSkiprows: "跳过行数",
+    dateBefore: "早于日期",
+    dateAfter: "晚于日期",
+    dateBetween: "日期区间",
+    dateLastDays: "最近 N 天",
+    dateSameMonth: "同月",
+    dateSameQuarter: "同季度",
+    dateSameYear: "同年",
+    dateOptions: "日期格式 & 无法解析的值",
+    dateFormatPlaceholder: "日期格式, 留空自动识别, 例如: %d/%m/%Y",
+    excludeUnparsable: "排除",
+    includeUnparsable: "包含",
+    fuzzy: "模糊匹配",
+    fuzzyOptions: "模糊匹配选项",
+    levenshtein: "编辑距离",
+    damerau: "编辑距离 (含换位)",
+    jaroWinkler: "Jaro-Winkler 相似度",
+    maxDistance: "最大距离",
+    minSimilarity: "最低相似度",
+    comparePinyin: "拼音",
+    ignoreCase: "忽略大小写",
+    writeFuzzyScore: "相似度列",
   }
 } as const;
 